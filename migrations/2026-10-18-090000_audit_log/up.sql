@@ -0,0 +1,16 @@
+-- Your SQL goes here
+
+CREATE TABLE IF NOT EXISTS audit_log
+(
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    actor TEXT NOT NULL,
+    action TEXT NOT NULL,
+    entity_type TEXT NOT NULL,
+    entity_uuid TEXT NOT NULL,
+    changes TEXT,
+    ip TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_entity ON audit_log (entity_type, entity_uuid);
+CREATE INDEX IF NOT EXISTS idx_audit_log_created_at ON audit_log (created_at);
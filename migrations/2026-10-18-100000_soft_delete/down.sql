@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+drop index if exists idx_albums_deleted_at;
+drop index if exists idx_episodes_deleted_at;
+drop index if exists idx_contents_deleted_at;
+alter table albums drop column deleted_at;
+alter table episodes drop column deleted_at;
+alter table contents drop column deleted_at;
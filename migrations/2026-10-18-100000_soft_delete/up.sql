@@ -0,0 +1,9 @@
+-- Your SQL goes here
+
+ALTER TABLE albums ADD COLUMN deleted_at TIMESTAMP;
+ALTER TABLE episodes ADD COLUMN deleted_at TIMESTAMP;
+ALTER TABLE contents ADD COLUMN deleted_at TIMESTAMP;
+
+CREATE INDEX IF NOT EXISTS idx_albums_deleted_at ON albums (deleted_at);
+CREATE INDEX IF NOT EXISTS idx_episodes_deleted_at ON episodes (deleted_at);
+CREATE INDEX IF NOT EXISTS idx_contents_deleted_at ON contents (deleted_at);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+alter table episodes drop column poster_url;
+alter table episodes drop column bitrate;
+alter table episodes drop column video_codec;
+alter table episodes drop column duration_ms;
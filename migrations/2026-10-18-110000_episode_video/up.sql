@@ -0,0 +1,6 @@
+-- Your SQL goes here
+
+ALTER TABLE episodes ADD COLUMN duration_ms INTEGER;
+ALTER TABLE episodes ADD COLUMN video_codec TEXT;
+ALTER TABLE episodes ADD COLUMN bitrate INTEGER;
+ALTER TABLE episodes ADD COLUMN poster_url TEXT;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+alter table episodes drop column hls_error;
+alter table episodes drop column hls_url;
+alter table episodes drop column hls_status;
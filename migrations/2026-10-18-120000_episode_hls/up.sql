@@ -0,0 +1,5 @@
+-- Your SQL goes here
+
+ALTER TABLE episodes ADD COLUMN hls_status TEXT;
+ALTER TABLE episodes ADD COLUMN hls_url TEXT;
+ALTER TABLE episodes ADD COLUMN hls_error TEXT;
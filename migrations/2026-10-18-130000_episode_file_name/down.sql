@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+alter table episodes drop column file_name;
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+
+ALTER TABLE episodes ADD COLUMN file_name TEXT;
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS episode_daily_views;
+DROP TABLE IF EXISTS album_daily_views;
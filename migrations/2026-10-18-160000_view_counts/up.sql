@@ -0,0 +1,26 @@
+-- Your SQL goes here
+
+CREATE TABLE IF NOT EXISTS album_daily_views
+(
+    album_id INTEGER NOT NULL,
+    day TEXT NOT NULL,
+    views INTEGER NOT NULL DEFAULT 0,
+    reads INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (album_id, day),
+    FOREIGN KEY (album_id) REFERENCES albums(id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS episode_daily_views
+(
+    episode_id INTEGER NOT NULL,
+    day TEXT NOT NULL,
+    reads INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (episode_id, day),
+    FOREIGN KEY (episode_id) REFERENCES episodes(id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_album_daily_views_day ON album_daily_views (day);
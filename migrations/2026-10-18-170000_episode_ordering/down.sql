@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_episodes_album_sort_order;
+alter table episodes drop column sort_order;
+alter table episodes drop column volume;
+alter table episodes drop column number;
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+
+ALTER TABLE episodes ADD COLUMN number REAL;
+ALTER TABLE episodes ADD COLUMN volume INTEGER;
+ALTER TABLE episodes ADD COLUMN sort_order INTEGER NOT NULL DEFAULT 0;
+
+-- Keep the current creation order until an album is reordered
+UPDATE episodes SET sort_order = id;
+
+CREATE INDEX IF NOT EXISTS idx_episodes_album_sort_order ON episodes (album_id, sort_order);
@@ -0,0 +1,18 @@
+-- Your SQL goes here
+
+-- Number the pages of every episode 0, 1, 2... keeping their current order
+CREATE TEMP TABLE content_positions AS
+SELECT id,
+       ROW_NUMBER() OVER (PARTITION BY episode_id ORDER BY index_no, id) - 1 AS position
+FROM contents
+WHERE deleted_at IS NULL;
+
+UPDATE contents
+SET index_no = (SELECT position FROM content_positions WHERE content_positions.id = contents.id)
+WHERE deleted_at IS NULL;
+
+DROP TABLE content_positions;
+
+-- Trashed pages keep their old index to be put back there on restore
+CREATE UNIQUE INDEX IF NOT EXISTS idx_contents_episode_index_no
+    ON contents (episode_id, index_no) WHERE deleted_at IS NULL;
@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_category_parent_id;
+DROP INDEX IF EXISTS idx_category_slug;
+alter table category drop column sort_order;
+alter table category drop column icon_content_type;
+alter table category drop column icon_url;
+alter table category drop column description;
+alter table category drop column slug;
+alter table category drop column parent_id;
@@ -0,0 +1,18 @@
+-- Your SQL goes here
+
+ALTER TABLE category ADD COLUMN parent_id INTEGER REFERENCES category (id);
+ALTER TABLE category ADD COLUMN slug TEXT NOT NULL DEFAULT '';
+ALTER TABLE category ADD COLUMN description TEXT NOT NULL DEFAULT '';
+ALTER TABLE category ADD COLUMN icon_url TEXT;
+ALTER TABLE category ADD COLUMN icon_content_type TEXT;
+ALTER TABLE category ADD COLUMN sort_order INTEGER NOT NULL DEFAULT 0;
+
+-- Slugs of existing categories from their names, the id keeps empty and repeated ones unique
+UPDATE category SET slug = lower(replace(trim(name), ' ', '-'));
+UPDATE category SET slug = 'category-' || id
+WHERE slug = ''
+   OR EXISTS (SELECT 1 FROM category other WHERE other.slug = category.slug AND other.id < category.id);
+UPDATE category SET sort_order = id;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_category_slug ON category (slug);
+CREATE INDEX IF NOT EXISTS idx_category_parent_id ON category (parent_id);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_album_categories_category_id;
+DROP TABLE IF EXISTS album_categories;
@@ -0,0 +1,21 @@
+-- Your SQL goes here
+
+CREATE TABLE IF NOT EXISTS album_categories
+(
+    album_id INTEGER NOT NULL,
+    category_id INTEGER NOT NULL,
+    -- Order of the album categories, the first one is also kept in albums.category_id
+    position INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (album_id, category_id),
+    FOREIGN KEY (album_id) REFERENCES albums(id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE,
+    FOREIGN KEY (category_id) REFERENCES category(id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+INSERT OR IGNORE INTO album_categories (album_id, category_id, position)
+SELECT id, category_id, 0 FROM albums;
+
+CREATE INDEX IF NOT EXISTS idx_album_categories_category_id ON album_categories (category_id);
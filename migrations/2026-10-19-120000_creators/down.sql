@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_album_credits_creator_id;
+DROP TABLE IF EXISTS album_credits;
+DROP TABLE IF EXISTS creators;
@@ -0,0 +1,34 @@
+-- Your SQL goes here
+
+CREATE TABLE IF NOT EXISTS creators
+(
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    uuid TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    -- Comma separated pen names and other spellings, searched together with the name
+    aliases TEXT NOT NULL DEFAULT '',
+    bio TEXT NOT NULL DEFAULT '',
+    avatar_url TEXT,
+    avatar_content_type TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS album_credits
+(
+    album_id INTEGER NOT NULL,
+    creator_id INTEGER NOT NULL,
+    -- author, artist, translator or publisher
+    role TEXT NOT NULL,
+    -- Order of the credits on the album
+    position INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (album_id, creator_id, role),
+    FOREIGN KEY (album_id) REFERENCES albums(id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE,
+    FOREIGN KEY (creator_id) REFERENCES creators(id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_album_credits_creator_id ON album_credits (creator_id);
@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_contents_episode_locale_index_no;
+DELETE FROM contents WHERE locale <> '';
+alter table contents drop column locale;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_contents_episode_index_no
+    ON contents (episode_id, index_no) WHERE deleted_at IS NULL;
+DROP TABLE IF EXISTS episode_translations;
+DROP TABLE IF EXISTS album_translations;
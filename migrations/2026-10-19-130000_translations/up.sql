@@ -0,0 +1,37 @@
+-- Your SQL goes here
+
+CREATE TABLE IF NOT EXISTS album_translations
+(
+    album_id INTEGER NOT NULL,
+    -- Lowercase language tag such as pt-br, never the default locale
+    locale TEXT NOT NULL,
+    title TEXT NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (album_id, locale),
+    FOREIGN KEY (album_id) REFERENCES albums(id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS episode_translations
+(
+    episode_id INTEGER NOT NULL,
+    locale TEXT NOT NULL,
+    title TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (episode_id, locale),
+    FOREIGN KEY (episode_id) REFERENCES episodes(id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+-- Pages of a translated edition of the episode, empty for the pages in the default locale
+ALTER TABLE contents ADD COLUMN locale TEXT NOT NULL DEFAULT '';
+
+-- Every edition is numbered from 0 on its own
+DROP INDEX IF EXISTS idx_contents_episode_index_no;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_contents_episode_locale_index_no
+    ON contents (episode_id, locale, index_no) WHERE deleted_at IS NULL;
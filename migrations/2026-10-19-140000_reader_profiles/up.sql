@@ -0,0 +1,16 @@
+-- Your SQL goes here
+
+CREATE TABLE IF NOT EXISTS reader_profiles
+(
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    uuid TEXT NOT NULL UNIQUE,
+    display_name TEXT NOT NULL DEFAULT '',
+    -- Declared by the reader, YYYY-MM-DD
+    birth_date DATE,
+    -- Set by an admin after checking the reader's age: under_13, 13_to_15, 16_to_17 or 18_plus.
+    -- Takes precedence over the birth date.
+    age_bracket TEXT,
+    age_verified_at TIMESTAMP,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
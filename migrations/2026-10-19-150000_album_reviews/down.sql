@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+alter table albums drop column rating_count;
+alter table albums drop column rating_average;
+DROP INDEX IF EXISTS idx_album_reviews_reader_profile_id;
+DROP INDEX IF EXISTS idx_album_reviews_status;
+DROP TABLE IF EXISTS album_reviews;
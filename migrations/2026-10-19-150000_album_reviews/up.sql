@@ -0,0 +1,32 @@
+-- Your SQL goes here
+
+CREATE TABLE IF NOT EXISTS album_reviews
+(
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    uuid TEXT NOT NULL UNIQUE,
+    album_id INTEGER NOT NULL,
+    reader_profile_id INTEGER NOT NULL,
+    -- Stars from 1 to 5
+    rating INTEGER NOT NULL CHECK (rating BETWEEN 1 AND 5),
+    body TEXT NOT NULL DEFAULT '',
+    -- pending, approved or rejected. Written reviews wait for moderation, bare ratings are approved.
+    status TEXT NOT NULL DEFAULT 'pending',
+    moderated_at TIMESTAMP,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    -- One review per reader and album
+    UNIQUE (album_id, reader_profile_id),
+    FOREIGN KEY (album_id) REFERENCES albums(id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE,
+    FOREIGN KEY (reader_profile_id) REFERENCES reader_profiles(id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_album_reviews_status ON album_reviews (status);
+CREATE INDEX IF NOT EXISTS idx_album_reviews_reader_profile_id ON album_reviews (reader_profile_id);
+
+-- Average and count of the ratings of reviews not rejected, refreshed on every review change
+ALTER TABLE albums ADD COLUMN rating_average REAL NOT NULL DEFAULT 0;
+ALTER TABLE albums ADD COLUMN rating_count INTEGER NOT NULL DEFAULT 0;
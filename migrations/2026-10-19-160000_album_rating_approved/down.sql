@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+-- Nothing to undo, the ratings are refreshed on the next review change
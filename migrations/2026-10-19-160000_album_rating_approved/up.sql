@@ -0,0 +1,12 @@
+-- Your SQL goes here
+
+-- Only approved reviews count toward the album rating, pending reviews were counted before
+UPDATE albums
+SET rating_count   = (SELECT COUNT(*)
+                      FROM album_reviews
+                      WHERE album_reviews.album_id = albums.id
+                        AND album_reviews.status = 'approved'),
+    rating_average = COALESCE((SELECT ROUND(AVG(rating), 2)
+                               FROM album_reviews
+                               WHERE album_reviews.album_id = albums.id
+                                 AND album_reviews.status = 'approved'), 0);
@@ -0,0 +1,282 @@
+//! Maintenance CLI sharing the services of the HTTP API
+
+use std::env;
+use std::fs;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use clap::{Parser, Subcommand};
+use dotenvy::dotenv;
+use serde::Serialize;
+use uuid::Uuid;
+
+use nextera_entmt::common::config::{self, Config};
+use nextera_entmt::common::database::{sqlite_connection, DbPool};
+use nextera_entmt::features::category::models::AddCategoryRequest;
+use nextera_entmt::features::{audit, category, integrity, maintenance};
+
+#[derive(Parser)]
+#[command(
+    name = "entmt-admin",
+    about = "Maintenance tasks for the entertainment API"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Manage categories
+    Category {
+        #[command(subcommand)]
+        command: CategoryCommand,
+    },
+    /// Manage albums
+    Album {
+        #[command(subcommand)]
+        command: AlbumCommand,
+    },
+    /// Re-read content type, size and dimensions of every stored file
+    Reindex,
+    /// Check stored files and external links, update broken_at and list orphaned files
+    Integrity,
+    /// Remove stale upload staging entries from the tmp directory
+    PurgeTmp {
+        /// Only remove entries not modified for this many minutes
+        #[arg(long, default_value_t = 60)]
+        older_than_minutes: u64,
+    },
+    /// Generate new API keys and write them to the env file
+    RotateKeys {
+        /// Rotate the admin key
+        #[arg(long)]
+        admin: bool,
+        /// Rotate the reader key
+        #[arg(long)]
+        user: bool,
+        #[arg(long, default_value = ".env")]
+        env_file: String,
+    },
+    /// Print counts of stored items
+    Stats,
+}
+
+#[derive(Subcommand)]
+enum CategoryCommand {
+    /// Create a category
+    Create {
+        name: String,
+        /// Unique URL name, generated from the name when empty
+        #[arg(long)]
+        slug: Option<String>,
+        /// Parent category ID
+        #[arg(long)]
+        parent: Option<i32>,
+    },
+}
+
+#[derive(Subcommand)]
+enum AlbumCommand {
+    /// Import an album from a directory: cover and images at the top, one sub directory per episode
+    Import {
+        dir: String,
+        /// Category ID of the new album
+        #[arg(long)]
+        category: i32,
+        /// Album title, defaults to the directory name
+        #[arg(long)]
+        title: Option<String>,
+        #[arg(long)]
+        description: Option<String>,
+    },
+}
+
+#[actix_web::main]
+async fn main() -> ExitCode {
+    dotenv().ok();
+    let cli = Cli::parse();
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    config::init(config.clone());
+    let pool = sqlite_connection::connect(config.database.url.clone());
+    match run(&pool, cli.command).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(pool: &DbPool, command: Command) -> Result<(), String> {
+    match command {
+        Command::Category {
+            command: CategoryCommand::Create { name, slug, parent },
+        } => {
+            let created = category::services::Service::add_category(
+                pool,
+                AddCategoryRequest {
+                    name,
+                    slug,
+                    description: None,
+                    parent_id: parent,
+                    sort_order: None,
+                    icon: None,
+                },
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+            record(
+                pool,
+                "create",
+                "category",
+                created.id.unwrap_or_default().to_string(),
+                serde_json::to_value(&created).ok(),
+            )
+            .await;
+            print_json(&created)
+        }
+        Command::Album {
+            command:
+                AlbumCommand::Import {
+                    dir,
+                    category,
+                    title,
+                    description,
+                },
+        } => {
+            let album = maintenance::services::Service::import_album(
+                pool,
+                &dir,
+                category,
+                title,
+                description,
+            )
+            .await?;
+            record(
+                pool,
+                "create",
+                "album",
+                album.uuid.clone(),
+                serde_json::to_value(&album).ok(),
+            )
+            .await;
+            print_json(&album)
+        }
+        Command::Reindex => {
+            let report = maintenance::services::Service::reindex_metadata(pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            print_json(&report)
+        }
+        Command::Integrity => {
+            let report = integrity::services::Service::scan(pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            print_json(&report)
+        }
+        Command::PurgeTmp { older_than_minutes } => {
+            let purged = maintenance::services::Service::purge_tmp(Duration::from_secs(
+                older_than_minutes * 60,
+            ))
+            .map_err(|e| e.to_string())?;
+            println!(
+                "Removed {} entries from {}",
+                purged,
+                config::get().storage.tmp_dir
+            );
+            Ok(())
+        }
+        Command::RotateKeys {
+            admin,
+            user,
+            env_file,
+        } => rotate_keys(admin, user, &env_file),
+        Command::Stats => {
+            let stats = maintenance::services::Service::get_stats(pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            print_json(&stats)
+        }
+    }
+}
+
+/// Replace `ADMIN_API_KEY`/`USER_API_KEY` in the env file, which overrides the config file keys
+fn rotate_keys(admin: bool, user: bool, env_file: &str) -> Result<(), String> {
+    if !admin && !user {
+        return Err(String::from("Pass --admin, --user or both"));
+    }
+    let mut lines: Vec<String> = match fs::read_to_string(env_file) {
+        Ok(raw) => raw.lines().map(String::from).collect(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => vec![],
+        Err(e) => return Err(format!("{}: {}", env_file, e)),
+    };
+    let mut rotated = vec![];
+    if admin {
+        rotated.push(("ADMIN_API_KEY", new_api_key()));
+    }
+    if user {
+        rotated.push(("USER_API_KEY", new_api_key()));
+    }
+    for (key, value) in &rotated {
+        let prefix = format!("{}=", key);
+        let line = format!("{}{}", prefix, value);
+        match lines
+            .iter_mut()
+            .find(|l| l.trim_start().starts_with(&prefix))
+        {
+            Some(existing) => *existing = line,
+            None => lines.push(line),
+        }
+    }
+    fs::write(env_file, format!("{}\n", lines.join("\n")))
+        .map_err(|e| format!("{}: {}", env_file, e))?;
+    for (key, value) in rotated {
+        println!("{}={}", key, value);
+    }
+    println!(
+        "Written to {}, restart the API server to apply the new keys",
+        env_file
+    );
+    Ok(())
+}
+
+fn new_api_key() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+async fn record(
+    pool: &DbPool,
+    action: &str,
+    entity_type: &str,
+    entity_uuid: String,
+    after: Option<serde_json::Value>,
+) {
+    let actor = format!(
+        "cli:{}",
+        env::var("USER").unwrap_or_else(|_| String::from("unknown"))
+    );
+    audit::services::Service::record(
+        pool,
+        actor,
+        None,
+        action,
+        entity_type,
+        entity_uuid,
+        None,
+        after,
+    )
+    .await;
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    println!("{}", json);
+    Ok(())
+}
@@ -0,0 +1,924 @@
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub auth: AuthConfig,
+    pub storage: StorageConfig,
+    pub cors: CorsConfig,
+    pub upload: UploadConfig,
+    pub rate_limit: RateLimitConfig,
+    pub integrity: IntegrityConfig,
+    pub video: VideoConfig,
+    pub jobs: JobsConfig,
+    pub webhooks: WebhooksConfig,
+    pub events: EventsConfig,
+    pub analytics: AnalyticsConfig,
+    pub i18n: I18nConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// Address the HTTP server binds to
+    pub host: String,
+    pub port: u16,
+    /// Number of actix workers, 0 means one per CPU core
+    pub workers: usize,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            host: String::from("127.0.0.1"),
+            port: 8010,
+            workers: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AuthConfig {
+    pub admin_api_key: String,
+    pub user_api_key: String,
+    /// Route prefixes which can be requested without an API key
+    pub public_routes: Vec<String>,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        AuthConfig {
+            admin_api_key: String::new(),
+            user_api_key: String::new(),
+            public_routes: vec![String::from("/health")],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// Directory where album, episode and content files are stored
+    pub data_dir: String,
+    /// Directory where multipart uploads are staged before being moved to `data_dir`,
+    /// must be on the same filesystem as `data_dir`
+    pub tmp_dir: String,
+    /// Minutes after which a leftover tmp entry is removed by the periodic sweep
+    pub tmp_max_age_minutes: u64,
+    /// Minutes between sweeps of the tmp directory, 0 only sweeps on startup
+    pub tmp_sweep_interval_minutes: u64,
+    /// Directory where files of deleted albums, episodes and contents are kept until purged
+    pub trash_dir: String,
+    /// Days a deleted item stays in the trash before it is purged automatically
+    pub trash_retention_days: i64,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        StorageConfig {
+            data_dir: String::from("data"),
+            tmp_dir: String::from("tmp"),
+            tmp_max_age_minutes: 60,
+            tmp_sweep_interval_minutes: 30,
+            trash_dir: String::from("trash"),
+            trash_retention_days: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CorsConfig {
+    /// Policy for the admin API (every route not listed in `reader_routes`)
+    pub admin: CorsPolicy,
+    /// Policy for the public reader API
+    pub reader: CorsPolicy,
+    /// Route prefixes served with the reader policy
+    pub reader_routes: Vec<String>,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        CorsConfig {
+            admin: CorsPolicy::default(),
+            reader: CorsPolicy {
+                allowed_methods: vec![String::from("GET"), String::from("HEAD")],
+                ..CorsPolicy::default()
+            },
+            reader_routes: vec![String::from("/static")],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CorsPolicy {
+    /// Allowed origins, `"*"` allows any origin, empty blocks cross-origin requests
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+    pub expose_headers: Vec<String>,
+    /// Seconds a browser may cache a preflight response
+    pub max_age: Option<usize>,
+    pub supports_credentials: bool,
+}
+
+impl Default for CorsPolicy {
+    fn default() -> Self {
+        CorsPolicy {
+            allowed_origins: vec![],
+            allowed_methods: ["GET", "POST", "PUT", "DELETE"]
+                .iter()
+                .map(|m| m.to_string())
+                .collect(),
+            allowed_headers: ["X-API-KEY", "X-Reader-Profile", "Content-Type", "Accept"]
+                .iter()
+                .map(|h| h.to_string())
+                .collect(),
+            expose_headers: vec![String::from("X-Response-Time")],
+            max_age: Some(3600),
+            supports_credentials: false,
+        }
+    }
+}
+
+impl CorsPolicy {
+    fn validate(&self, name: &str) -> Result<(), ConfigError> {
+        let any_origin = self.allowed_origins.iter().any(|origin| origin == "*");
+        if any_origin && self.supports_credentials {
+            return Err(ConfigError::Invalid(format!(
+                "cors.{}: allowed_origins \"*\" cannot be combined with supports_credentials",
+                name
+            )));
+        }
+        for origin in &self.allowed_origins {
+            if origin != "*" && !(origin.starts_with("http://") || origin.starts_with("https://")) {
+                return Err(ConfigError::Invalid(format!(
+                    "cors.{}: origin {:?} must start with http:// or https://",
+                    name, origin
+                )));
+            }
+            if origin.ends_with('/') {
+                return Err(ConfigError::Invalid(format!(
+                    "cors.{}: origin {:?} must not end with '/'",
+                    name, origin
+                )));
+            }
+        }
+        for method in &self.allowed_methods {
+            if actix_web::http::Method::from_bytes(method.as_bytes()).is_err() {
+                return Err(ConfigError::Invalid(format!(
+                    "cors.{}: invalid method {:?}",
+                    name, method
+                )));
+            }
+        }
+        for header in self
+            .allowed_headers
+            .iter()
+            .chain(self.expose_headers.iter())
+        {
+            if actix_web::http::header::HeaderName::from_bytes(header.as_bytes()).is_err() {
+                return Err(ConfigError::Invalid(format!(
+                    "cors.{}: invalid header {:?}",
+                    name, header
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct UploadConfig {
+    /// Maximum size of a single uploaded file in bytes
+    pub max_file_bytes: u64,
+    /// Maximum size of a whole multipart request in bytes
+    pub max_request_bytes: u64,
+    /// Maximum number of files in a single multipart request
+    pub max_files: usize,
+    /// Maximum size of a JSON request body in bytes
+    pub max_json_bytes: usize,
+}
+
+impl Default for UploadConfig {
+    fn default() -> Self {
+        UploadConfig {
+            max_file_bytes: 50 * 1024 * 1024,
+            max_request_bytes: 500 * 1024 * 1024,
+            max_files: 200,
+            max_json_bytes: 256 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    /// Use `Forwarded`/`X-Forwarded-For` for the client IP, only enable behind a trusted proxy
+    pub trust_forwarded_for: bool,
+    /// Token bucket rules, the first rule matching the role and route is applied
+    pub rules: Vec<RateLimitRule>,
+    /// Route prefixes counted against the daily download quota
+    pub download_routes: Vec<String>,
+    /// Daily download bytes per reader key and client IP, 0 disables the quota
+    pub reader_daily_download_bytes: u64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            enabled: true,
+            trust_forwarded_for: false,
+            rules: vec![
+                RateLimitRule {
+                    role: String::from("user"),
+                    route: String::new(),
+                    capacity: 120,
+                    refill_per_second: 2.0,
+                },
+                RateLimitRule {
+                    role: String::from("anonymous"),
+                    route: String::new(),
+                    capacity: 60,
+                    refill_per_second: 1.0,
+                },
+            ],
+            download_routes: vec![String::from("/static")],
+            reader_daily_download_bytes: 2 * 1024 * 1024 * 1024,
+        }
+    }
+}
+
+/// Limits are kept per API key and client IP. There is one key per role, so a rule for the
+/// `admin` or `user` role is the limit of that key, `anonymous` covers requests without a key.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RateLimitRule {
+    /// `admin`, `user`, `anonymous` or `any`
+    pub role: String,
+    /// Route prefix, empty matches every route
+    #[serde(default)]
+    pub route: String,
+    /// Maximum burst of requests
+    pub capacity: u32,
+    /// Tokens added back per second
+    pub refill_per_second: f64,
+}
+
+impl RateLimitConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        for rule in &self.rules {
+            if !["admin", "user", "anonymous", "any"].contains(&rule.role.as_str()) {
+                return Err(ConfigError::Invalid(format!(
+                    "rate_limit.rules: unknown role {:?}",
+                    rule.role
+                )));
+            }
+            if rule.capacity == 0 || rule.refill_per_second <= 0.0 {
+                return Err(ConfigError::Invalid(format!(
+                    "rate_limit.rules: capacity and refill_per_second of {:?} {:?} must be greater than 0",
+                    rule.role, rule.route
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct IntegrityConfig {
+    /// Minutes between background integrity scans, 0 disables the background scan
+    pub scan_interval_minutes: u64,
+    /// Send a HEAD request to every external episode `file_url`
+    pub probe_external: bool,
+    /// Seconds before a HEAD probe is considered failed
+    pub probe_timeout_seconds: u64,
+    /// Replace the scheme and host of probed URLs, e.g. a local stand-in server
+    pub probe_base_url: Option<String>,
+}
+
+impl Default for IntegrityConfig {
+    fn default() -> Self {
+        IntegrityConfig {
+            scan_interval_minutes: 24 * 60,
+            probe_external: true,
+            probe_timeout_seconds: 10,
+            probe_base_url: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct JobsConfig {
+    /// Number of background job workers, 0 leaves queued jobs untouched
+    pub workers: usize,
+    /// Seconds an idle worker waits before polling the jobs table again
+    pub poll_interval_seconds: u64,
+    /// Attempts before a job is marked failed
+    pub max_attempts: i32,
+    /// Delay before the first retry, doubled on every further attempt
+    pub retry_backoff_seconds: u64,
+    pub retry_backoff_max_seconds: u64,
+    /// Days succeeded and cancelled jobs are kept, 0 keeps them forever
+    pub retention_days: i64,
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        JobsConfig {
+            workers: 2,
+            poll_interval_seconds: 5,
+            max_attempts: 5,
+            retry_backoff_seconds: 30,
+            retry_backoff_max_seconds: 60 * 60,
+            retention_days: 7,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct WebhooksConfig {
+    /// Seconds before a delivery attempt is considered failed
+    pub timeout_seconds: u64,
+    /// Bytes of the endpoint response kept in the delivery log
+    pub max_response_body_bytes: usize,
+}
+
+impl Default for WebhooksConfig {
+    fn default() -> Self {
+        WebhooksConfig {
+            timeout_seconds: 10,
+            max_response_body_bytes: 2048,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct EventsConfig {
+    /// Events kept in memory for `Last-Event-ID` replay
+    pub log_capacity: usize,
+    /// Events buffered per `/events` client before a slow client starts missing events
+    pub client_buffer: usize,
+    /// Seconds between keep-alive comments on idle streams
+    pub keep_alive_seconds: u64,
+}
+
+impl Default for EventsConfig {
+    fn default() -> Self {
+        EventsConfig {
+            log_capacity: 1000,
+            client_buffer: 256,
+            keep_alive_seconds: 15,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AnalyticsConfig {
+    /// Days of views counted by the `trending` album sort when the request gives none
+    pub trending_window_days: i64,
+}
+
+impl Default for AnalyticsConfig {
+    fn default() -> Self {
+        AnalyticsConfig {
+            trending_window_days: 7,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct I18nConfig {
+    /// Language of album and episode metadata and pages without a locale, served when no
+    /// translation matches `Accept-Language` or `?lang=`
+    pub default_locale: String,
+}
+
+impl Default for I18nConfig {
+    fn default() -> Self {
+        I18nConfig {
+            default_locale: String::from("en"),
+        }
+    }
+}
+
+/// Used when the `video` cargo feature is enabled
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct VideoConfig {
+    pub ffprobe_path: String,
+    pub ffmpeg_path: String,
+    /// Position of the poster frame, clamped to the video duration
+    pub poster_at_seconds: f64,
+    /// Queue HLS packaging automatically when a video episode is uploaded
+    pub hls_enabled: bool,
+    pub hls_segment_seconds: u32,
+    /// Renditions taller than the source video are skipped
+    pub hls_renditions: Vec<HlsRendition>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HlsRendition {
+    /// Name of the rendition playlist, e.g. `720p`
+    pub name: String,
+    pub height: u32,
+    pub video_bitrate_kbps: u32,
+    pub audio_bitrate_kbps: u32,
+}
+
+impl Default for VideoConfig {
+    fn default() -> Self {
+        VideoConfig {
+            ffprobe_path: String::from("ffprobe"),
+            ffmpeg_path: String::from("ffmpeg"),
+            poster_at_seconds: 1.0,
+            hls_enabled: false,
+            hls_segment_seconds: 6,
+            hls_renditions: vec![
+                HlsRendition {
+                    name: String::from("360p"),
+                    height: 360,
+                    video_bitrate_kbps: 800,
+                    audio_bitrate_kbps: 96,
+                },
+                HlsRendition {
+                    name: String::from("720p"),
+                    height: 720,
+                    video_bitrate_kbps: 2800,
+                    audio_bitrate_kbps: 128,
+                },
+                HlsRendition {
+                    name: String::from("1080p"),
+                    height: 1080,
+                    video_bitrate_kbps: 5000,
+                    audio_bitrate_kbps: 192,
+                },
+            ],
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(String, std::io::Error),
+    Parse(String, toml::de::Error),
+    InvalidEnv(String, String),
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, e) => write!(f, "failed to read config file {}: {}", path, e),
+            ConfigError::Parse(path, e) => write!(f, "failed to parse config file {}: {}", path, e),
+            ConfigError::InvalidEnv(key, value) => {
+                write!(
+                    f,
+                    "invalid value {:?} for environment variable {}",
+                    value, key
+                )
+            }
+            ConfigError::Invalid(message) => write!(f, "invalid configuration: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Load the configuration from `ENTMT_CONFIG` (default `config.toml`), apply environment
+    /// overrides and validate the result. A missing default config file falls back to defaults.
+    pub fn load() -> Result<Self, ConfigError> {
+        let explicit_path = env::var("ENTMT_CONFIG").ok();
+        let path = explicit_path
+            .clone()
+            .unwrap_or_else(|| String::from(DEFAULT_CONFIG_PATH));
+        let mut config = if explicit_path.is_some() || Path::new(&path).exists() {
+            Self::from_file(&path)?
+        } else {
+            Config::default()
+        };
+        config.apply_env_overrides()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_file(path: &str) -> Result<Self, ConfigError> {
+        let raw = fs::read_to_string(path).map_err(|e| ConfigError::Read(path.to_string(), e))?;
+        toml::from_str(&raw).map_err(|e| ConfigError::Parse(path.to_string(), e))
+    }
+
+    fn apply_env_overrides(&mut self) -> Result<(), ConfigError> {
+        if let Ok(value) = env::var("DATABASE_URL") {
+            self.database.url = value;
+        }
+        if let Ok(value) = env::var("ADMIN_API_KEY") {
+            self.auth.admin_api_key = value;
+        }
+        if let Ok(value) = env::var("USER_API_KEY") {
+            self.auth.user_api_key = value;
+        }
+        if let Ok(value) = env::var("ENTMT_HOST") {
+            self.server.host = value;
+        }
+        if let Some(value) = parse_env("ENTMT_PORT")? {
+            self.server.port = value;
+        }
+        if let Some(value) = parse_env("ENTMT_WORKERS")? {
+            self.server.workers = value;
+        }
+        if let Ok(value) = env::var("ENTMT_DATA_DIR") {
+            self.storage.data_dir = value;
+        }
+        if let Ok(value) = env::var("ENTMT_TMP_DIR") {
+            self.storage.tmp_dir = value;
+        }
+        if let Some(value) = parse_env("ENTMT_TMP_MAX_AGE_MINUTES")? {
+            self.storage.tmp_max_age_minutes = value;
+        }
+        if let Some(value) = parse_env("ENTMT_TMP_SWEEP_INTERVAL_MINUTES")? {
+            self.storage.tmp_sweep_interval_minutes = value;
+        }
+        if let Ok(value) = env::var("ENTMT_TRASH_DIR") {
+            self.storage.trash_dir = value;
+        }
+        if let Some(value) = parse_env("ENTMT_TRASH_RETENTION_DAYS")? {
+            self.storage.trash_retention_days = value;
+        }
+        if let Ok(value) = env::var("ENTMT_CORS_ADMIN_ORIGINS") {
+            self.cors.admin.allowed_origins = split_list(&value);
+        }
+        if let Ok(value) = env::var("ENTMT_CORS_READER_ORIGINS") {
+            self.cors.reader.allowed_origins = split_list(&value);
+        }
+        if let Ok(value) = env::var("ENTMT_PUBLIC_ROUTES") {
+            self.auth.public_routes = split_list(&value);
+        }
+        if let Some(value) = parse_env("ENTMT_MAX_FILE_BYTES")? {
+            self.upload.max_file_bytes = value;
+        }
+        if let Some(value) = parse_env("ENTMT_MAX_REQUEST_BYTES")? {
+            self.upload.max_request_bytes = value;
+        }
+        if let Some(value) = parse_env("ENTMT_MAX_FILES")? {
+            self.upload.max_files = value;
+        }
+        if let Some(value) = parse_env("ENTMT_MAX_JSON_BYTES")? {
+            self.upload.max_json_bytes = value;
+        }
+        if let Some(value) = parse_env("ENTMT_RATE_LIMIT_ENABLED")? {
+            self.rate_limit.enabled = value;
+        }
+        if let Some(value) = parse_env("ENTMT_READER_DAILY_DOWNLOAD_BYTES")? {
+            self.rate_limit.reader_daily_download_bytes = value;
+        }
+        if let Some(value) = parse_env("ENTMT_INTEGRITY_SCAN_INTERVAL_MINUTES")? {
+            self.integrity.scan_interval_minutes = value;
+        }
+        if let Some(value) = parse_env("ENTMT_INTEGRITY_PROBE_EXTERNAL")? {
+            self.integrity.probe_external = value;
+        }
+        if let Ok(value) = env::var("ENTMT_FFPROBE_PATH") {
+            self.video.ffprobe_path = value;
+        }
+        if let Ok(value) = env::var("ENTMT_FFMPEG_PATH") {
+            self.video.ffmpeg_path = value;
+        }
+        if let Ok(value) = env::var("ENTMT_DEFAULT_LOCALE") {
+            self.i18n.default_locale = value;
+        }
+        if let Some(value) = parse_env("ENTMT_JOB_WORKERS")? {
+            self.jobs.workers = value;
+        }
+        if let Some(value) = parse_env("ENTMT_JOB_POLL_INTERVAL_SECONDS")? {
+            self.jobs.poll_interval_seconds = value;
+        }
+        if let Ok(value) = env::var("ENTMT_INTEGRITY_PROBE_BASE_URL") {
+            self.integrity.probe_base_url = Some(value).filter(|s| !s.trim().is_empty());
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.database.url.trim().is_empty() {
+            return Err(ConfigError::Invalid(String::from(
+                "database.url (DATABASE_URL) must be set",
+            )));
+        }
+        if self.auth.admin_api_key.trim().is_empty() {
+            return Err(ConfigError::Invalid(String::from(
+                "auth.admin_api_key (ADMIN_API_KEY) must be set",
+            )));
+        }
+        if self.auth.user_api_key.trim().is_empty() {
+            return Err(ConfigError::Invalid(String::from(
+                "auth.user_api_key (USER_API_KEY) must be set",
+            )));
+        }
+        if self.auth.admin_api_key == self.auth.user_api_key {
+            return Err(ConfigError::Invalid(String::from(
+                "auth.admin_api_key and auth.user_api_key must be different",
+            )));
+        }
+        if self.server.host.trim().is_empty() {
+            return Err(ConfigError::Invalid(String::from(
+                "server.host cannot be empty",
+            )));
+        }
+        if self.server.port == 0 {
+            return Err(ConfigError::Invalid(String::from(
+                "server.port cannot be 0",
+            )));
+        }
+        if self.storage.data_dir.trim().is_empty() {
+            return Err(ConfigError::Invalid(String::from(
+                "storage.data_dir cannot be empty",
+            )));
+        }
+        if self.storage.tmp_dir.trim().is_empty() {
+            return Err(ConfigError::Invalid(String::from(
+                "storage.tmp_dir cannot be empty",
+            )));
+        }
+        if self.storage.tmp_max_age_minutes == 0 {
+            return Err(ConfigError::Invalid(String::from(
+                "storage.tmp_max_age_minutes must be greater than 0",
+            )));
+        }
+        if self.storage.trash_dir.trim().is_empty() {
+            return Err(ConfigError::Invalid(String::from(
+                "storage.trash_dir cannot be empty",
+            )));
+        }
+        if self.storage.trash_retention_days < 1 {
+            return Err(ConfigError::Invalid(String::from(
+                "storage.trash_retention_days must be at least 1",
+            )));
+        }
+        if self.storage.data_dir.trim_end_matches('/') == self.storage.tmp_dir.trim_end_matches('/')
+        {
+            return Err(ConfigError::Invalid(String::from(
+                "storage.data_dir and storage.tmp_dir must be different directories",
+            )));
+        }
+        if self.upload.max_file_bytes == 0
+            || self.upload.max_request_bytes == 0
+            || self.upload.max_files == 0
+            || self.upload.max_json_bytes == 0
+        {
+            return Err(ConfigError::Invalid(String::from(
+                "upload limits must be greater than 0",
+            )));
+        }
+        if self.upload.max_file_bytes > self.upload.max_request_bytes {
+            return Err(ConfigError::Invalid(String::from(
+                "upload.max_file_bytes cannot be greater than upload.max_request_bytes",
+            )));
+        }
+        self.cors.admin.validate("admin")?;
+        self.cors.reader.validate("reader")?;
+        self.rate_limit.validate()?;
+        if self.video.poster_at_seconds < 0.0 {
+            return Err(ConfigError::Invalid(String::from(
+                "video.poster_at_seconds cannot be negative",
+            )));
+        }
+        if self.video.hls_segment_seconds == 0 {
+            return Err(ConfigError::Invalid(String::from(
+                "video.hls_segment_seconds must be greater than 0",
+            )));
+        }
+        if self.video.hls_enabled && self.video.hls_renditions.is_empty() {
+            return Err(ConfigError::Invalid(String::from(
+                "video.hls_renditions cannot be empty when video.hls_enabled is set",
+            )));
+        }
+        for rendition in &self.video.hls_renditions {
+            if rendition.name.is_empty()
+                || !rendition
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            {
+                return Err(ConfigError::Invalid(format!(
+                    "video.hls_renditions: name {:?} may only contain letters, digits, '_' and '-'",
+                    rendition.name
+                )));
+            }
+            if rendition.height == 0 || rendition.video_bitrate_kbps == 0 {
+                return Err(ConfigError::Invalid(format!(
+                    "video.hls_renditions: height and video_bitrate_kbps of {} must be greater than 0",
+                    rendition.name
+                )));
+            }
+        }
+        if self.integrity.probe_timeout_seconds == 0 {
+            return Err(ConfigError::Invalid(String::from(
+                "integrity.probe_timeout_seconds must be greater than 0",
+            )));
+        }
+        if let Some(base_url) = &self.integrity.probe_base_url {
+            if !(base_url.starts_with("http://") || base_url.starts_with("https://")) {
+                return Err(ConfigError::Invalid(format!(
+                    "integrity.probe_base_url {:?} must start with http:// or https://",
+                    base_url
+                )));
+            }
+        }
+        if self.analytics.trending_window_days < 1 {
+            return Err(ConfigError::Invalid(String::from(
+                "analytics.trending_window_days must be at least 1",
+            )));
+        }
+        if crate::common::i18n::normalize_locale(&self.i18n.default_locale).is_none() {
+            return Err(ConfigError::Invalid(format!(
+                "i18n.default_locale {:?} is not a language tag such as en or pt-BR",
+                self.i18n.default_locale
+            )));
+        }
+        if self.events.client_buffer == 0 || self.events.keep_alive_seconds == 0 {
+            return Err(ConfigError::Invalid(String::from(
+                "events.client_buffer and events.keep_alive_seconds must be greater than 0",
+            )));
+        }
+        if self.webhooks.timeout_seconds == 0 {
+            return Err(ConfigError::Invalid(String::from(
+                "webhooks.timeout_seconds must be greater than 0",
+            )));
+        }
+        if self.jobs.poll_interval_seconds == 0 {
+            return Err(ConfigError::Invalid(String::from(
+                "jobs.poll_interval_seconds must be greater than 0",
+            )));
+        }
+        if self.jobs.max_attempts < 1 {
+            return Err(ConfigError::Invalid(String::from(
+                "jobs.max_attempts must be at least 1",
+            )));
+        }
+        if self.jobs.retry_backoff_max_seconds < self.jobs.retry_backoff_seconds {
+            return Err(ConfigError::Invalid(String::from(
+                "jobs.retry_backoff_max_seconds cannot be less than jobs.retry_backoff_seconds",
+            )));
+        }
+        if self.jobs.retention_days < 0 {
+            return Err(ConfigError::Invalid(String::from(
+                "jobs.retention_days cannot be negative",
+            )));
+        }
+        for route in &self.auth.public_routes {
+            if !route.starts_with('/') {
+                return Err(ConfigError::Invalid(format!(
+                    "public route {:?} must start with '/'",
+                    route
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn bind_address(&self) -> (String, u16) {
+        (self.server.host.clone(), self.server.port)
+    }
+}
+
+/// Store the loaded configuration for the rest of the process
+pub fn init(config: Config) {
+    if CONFIG.set(config).is_err() {
+        eprintln!("Configuration was already initialized");
+    }
+}
+
+/// Get the process configuration, falling back to defaults when `init` was never called
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+fn parse_env<T: std::str::FromStr>(key: &str) -> Result<Option<T>, ConfigError> {
+    match env::var(key) {
+        Ok(value) => value
+            .trim()
+            .parse::<T>()
+            .map(Some)
+            .map_err(|_| ConfigError::InvalidEnv(key.to_string(), value)),
+        Err(_) => Ok(None),
+    }
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Defaults with the settings `validate` requires
+    fn valid_config() -> Config {
+        let mut config = Config::default();
+        config.database.url = String::from("entmt.db");
+        config.auth.admin_api_key = String::from("admin-key");
+        config.auth.user_api_key = String::from("user-key");
+        config
+    }
+
+    #[test]
+    fn environment_overrides_file_which_overrides_defaults() {
+        let path = env::temp_dir().join(format!("entmt-config-{}.toml", uuid::Uuid::new_v4()));
+        fs::write(
+            &path,
+            "[server]\nport = 9000\n\n[database]\nurl = \"entmt.db\"\n\n[storage]\ndata_dir = \"toml-data\"\n\n\
+             [auth]\nadmin_api_key = \"admin-key\"\nuser_api_key = \"user-key\"\n",
+        )
+        .unwrap();
+        env::set_var("ENTMT_CONFIG", &path);
+        env::set_var("ENTMT_PORT", "9100");
+        env::set_var(
+            "ENTMT_CORS_READER_ORIGINS",
+            "https://a.example, https://b.example",
+        );
+        let loaded = Config::load();
+        env::set_var("ENTMT_PORT", "not a port");
+        let invalid = Config::load();
+        for key in ["ENTMT_CONFIG", "ENTMT_PORT", "ENTMT_CORS_READER_ORIGINS"] {
+            env::remove_var(key);
+        }
+        let _ = fs::remove_file(&path);
+
+        let loaded = loaded.unwrap();
+        // Not in the file nor the environment
+        assert_eq!(loaded.server.host, ServerConfig::default().host);
+        assert_eq!(loaded.storage.data_dir, "toml-data");
+        assert_eq!(loaded.server.port, 9100);
+        assert_eq!(
+            loaded.cors.reader.allowed_origins,
+            vec!["https://a.example", "https://b.example"]
+        );
+        assert!(matches!(
+            invalid,
+            Err(ConfigError::InvalidEnv(key, value)) if key == "ENTMT_PORT" && value == "not a port"
+        ));
+    }
+
+    #[test]
+    fn lists_are_split_on_commas_and_trimmed() {
+        assert_eq!(
+            split_list(" /health, /static ,,/api-docs "),
+            vec!["/health", "/static", "/api-docs"]
+        );
+        assert!(split_list("").is_empty());
+        assert!(split_list(" , ").is_empty());
+    }
+
+    #[test]
+    fn validate_rejects_missing_keys_and_empty_rate_limits() {
+        assert!(valid_config().validate().is_ok());
+
+        let mut config = valid_config();
+        config.auth.admin_api_key = String::from("  ");
+        assert!(matches!(config.validate(), Err(ConfigError::Invalid(_))));
+
+        let mut config = valid_config();
+        config.auth.user_api_key = String::new();
+        assert!(matches!(config.validate(), Err(ConfigError::Invalid(_))));
+
+        let mut config = valid_config();
+        config.rate_limit.rules[0].capacity = 0;
+        assert!(matches!(config.validate(), Err(ConfigError::Invalid(_))));
+
+        let mut config = valid_config();
+        config.rate_limit.rules[0].refill_per_second = 0.0;
+        assert!(matches!(config.validate(), Err(ConfigError::Invalid(_))));
+    }
+}
@@ -0,0 +1,96 @@
+use actix_web::http::header::{HeaderValue, ACCEPT_LANGUAGE, CONTENT_LANGUAGE, VARY};
+use actix_web::{web, HttpRequest, HttpResponseBuilder};
+use serde::Deserialize;
+
+use crate::common::config;
+
+#[derive(Debug, Deserialize)]
+struct LangQuery {
+    lang: Option<String>,
+}
+
+/// Language of untranslated metadata and pages
+pub fn default_locale() -> String {
+    normalize_locale(&config::get().i18n.default_locale).unwrap_or_else(|| String::from("en"))
+}
+
+/// Lowercase language tag with `-` separators, e.g. `pt_BR` becomes `pt-br`. `None` when the
+/// value is not a language tag.
+pub fn normalize_locale(value: &str) -> Option<String> {
+    let locale = value.trim().replace('_', "-").to_lowercase();
+    let mut subtags = locale.split('-');
+    let language = subtags.next()?;
+    if !(2..=3).contains(&language.len()) || !language.chars().all(|c| c.is_ascii_lowercase()) {
+        return None;
+    }
+    let valid_subtags = subtags.all(|subtag| {
+        (1..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric())
+    });
+    valid_subtags.then_some(locale)
+}
+
+/// Locales asked for by the request in order of preference, `?lang=` before `Accept-Language`
+pub fn requested_locales(http_request: &HttpRequest) -> Vec<String> {
+    let mut locales = vec![];
+    if let Ok(query) = web::Query::<LangQuery>::from_query(http_request.query_string()) {
+        if let Some(lang) = &query.lang {
+            locales.extend(lang.split(',').filter_map(normalize_locale));
+        }
+    }
+    let accept_language = http_request
+        .headers()
+        .get(ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let mut weighted = accept_language
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let locale = normalize_locale(parts.next()?)?;
+            let quality = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map(|q| q.trim().parse::<f32>().unwrap_or(0.0))
+                .unwrap_or(1.0);
+            (quality > 0.0).then_some((locale, quality))
+        })
+        .collect::<Vec<(String, f32)>>();
+    // Stable, entries of equal quality keep the order of the header
+    weighted.sort_by(|a, b| b.1.total_cmp(&a.1));
+    locales.extend(weighted.into_iter().map(|(locale, _)| locale));
+    locales
+}
+
+/// Best of `available` for the requested locales. A requested `pt-br` falls back to `pt` and a
+/// requested `pt` accepts any `pt-*`. `None` when nothing matches.
+pub fn negotiate(requested: &[String], available: &[String]) -> Option<String> {
+    for locale in requested {
+        if available.contains(locale) {
+            return Some(locale.clone());
+        }
+        let language = primary_language(locale);
+        if let Some(found) = available
+            .iter()
+            .find(|candidate| primary_language(candidate) == language)
+        {
+            return Some(found.clone());
+        }
+    }
+    None
+}
+
+/// Mark a negotiated response with its language
+pub fn set_language_headers(response: &mut HttpResponseBuilder, locale: &str) {
+    if let Ok(value) = HeaderValue::from_str(locale) {
+        response.insert_header((CONTENT_LANGUAGE, value));
+    }
+    vary_on_language(response);
+}
+
+/// Caches must key responses localized per item on `Accept-Language`
+pub fn vary_on_language(response: &mut HttpResponseBuilder) {
+    response.insert_header((VARY, "Accept-Language"));
+}
+
+fn primary_language(locale: &str) -> &str {
+    locale.split('-').next().unwrap_or(locale)
+}
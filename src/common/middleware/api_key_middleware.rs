@@ -71,6 +71,20 @@ where
         let admin_key = self.admin_key.clone();
         let user_key = self.user_key.clone();
 
+        let auth_header = req.headers().get("X-API-KEY").and_then(|h| h.to_str().ok());
+
+        let role = match auth_header {
+            Some(key) if key == admin_key => Some("admin"),
+            Some(key) if key == user_key => Some("user"),
+            _ => None,
+        };
+
+        // Public routes need no key, a valid one still sets the role, e.g. so admins are not
+        // age checked on /static
+        if let Some(role) = role {
+            req.extensions_mut().insert(role.to_string());
+        }
+
         // Skip middleware for /swagger route or /data route or /file route
         for public_route in self.public_routes.clone() {
             if req.path().starts_with(public_route.as_str()) {
@@ -93,16 +107,7 @@ where
             });
         }
 
-        let auth_header = req.headers().get("X-API-KEY").and_then(|h| h.to_str().ok());
-
-        let role = match auth_header {
-            Some(key) if key == admin_key => Some("admin"),
-            Some(key) if key == user_key => Some("user"),
-            _ => None,
-        };
-
-        if let Some(role) = role {
-            req.extensions_mut().insert(role.to_string());
+        if role.is_some() {
             let fut = self.service.call(req);
             Box::pin(async move {
                 let res = fut.await?;
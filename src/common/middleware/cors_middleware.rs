@@ -0,0 +1,134 @@
+use std::rc::Rc;
+
+use actix_cors::{Cors, CorsMiddleware};
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::Error;
+use futures_util::future::LocalBoxFuture;
+
+use crate::common::config::{CorsConfig, CorsPolicy};
+
+/// Build an actix `Cors` transform from a configured policy
+pub fn build_cors(policy: &CorsPolicy) -> Cors {
+    let mut cors = Cors::default();
+    if policy.allowed_origins.iter().any(|origin| origin == "*") {
+        cors = cors.allow_any_origin();
+    } else {
+        for origin in &policy.allowed_origins {
+            cors = cors.allowed_origin(origin);
+        }
+    }
+    cors = cors.allowed_methods(policy.allowed_methods.iter().map(|m| m.as_str()));
+    cors = cors.allowed_headers(policy.allowed_headers.iter().map(|h| h.as_str()));
+    if !policy.expose_headers.is_empty() {
+        cors = cors.expose_headers(policy.expose_headers.iter().map(|h| h.as_str()));
+    }
+    cors = cors.max_age(policy.max_age);
+    if policy.supports_credentials {
+        cors = cors.supports_credentials();
+    }
+    cors
+}
+
+/// Apply the reader CORS policy to reader routes and the admin policy to everything else
+pub struct CorsPolicies {
+    admin: Cors,
+    reader: Cors,
+    reader_routes: Vec<String>,
+}
+
+impl CorsPolicies {
+    pub fn new(config: &CorsConfig) -> Self {
+        Self {
+            admin: build_cors(&config.admin),
+            reader: build_cors(&config.reader),
+            reader_routes: config.reader_routes.clone(),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for CorsPolicies
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = CorsPoliciesService<S>;
+    type InitError = ();
+    type Future = LocalBoxFuture<'static, Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        let service = Rc::new(service);
+        let admin = self.admin.new_transform(SharedService(Rc::clone(&service)));
+        let reader = self.reader.new_transform(SharedService(service));
+        let reader_routes = self.reader_routes.clone();
+        Box::pin(async move {
+            Ok(CorsPoliciesService {
+                admin: admin.await?,
+                reader: reader.await?,
+                reader_routes,
+            })
+        })
+    }
+}
+
+pub struct CorsPoliciesService<S> {
+    admin: CorsMiddleware<SharedService<S>>,
+    reader: CorsMiddleware<SharedService<S>>,
+    reader_routes: Vec<String>,
+}
+
+impl<S, B> Service<ServiceRequest> for CorsPoliciesService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.admin.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let is_reader_route = self
+            .reader_routes
+            .iter()
+            .any(|route| req.path().starts_with(route.as_str()));
+        if is_reader_route {
+            self.reader.call(req)
+        } else {
+            self.admin.call(req)
+        }
+    }
+}
+
+/// Lets both CORS policies wrap the same inner service
+pub struct SharedService<S>(Rc<S>);
+
+impl<S, B> Service<ServiceRequest> for SharedService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = S::Future;
+
+    fn poll_ready(
+        &self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.0.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        self.0.call(req)
+    }
+}
@@ -1,2 +1,4 @@
 pub mod api_key_middleware;
+pub mod cors_middleware;
+pub mod rate_limit_middleware;
 pub mod response_time_middleware;
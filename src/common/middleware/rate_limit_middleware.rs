@@ -0,0 +1,497 @@
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use actix_web::body::{BodySize, BoxBody, EitherBody, MessageBody};
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderName, HeaderValue, RETRY_AFTER};
+use actix_web::web::Bytes;
+use actix_web::{Error, HttpResponse};
+use chrono::{NaiveDate, Utc};
+use futures_util::future::{ok, LocalBoxFuture, Ready};
+
+use crate::common::config::{AuthConfig, RateLimitConfig, RateLimitRule};
+use crate::common::models::response_message::ResponseMessage;
+
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+const IDLE_CLIENT_TTL: Duration = Duration::from_secs(3600);
+
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+struct DownloadQuota {
+    day: NaiveDate,
+    bytes: u64,
+}
+
+/// Shared rate limit state, create it once and clone it into every worker
+#[derive(Clone, Default)]
+pub struct RateLimitState {
+    buckets: Arc<Mutex<HashMap<String, TokenBucket>>>,
+    quotas: Arc<Mutex<HashMap<String, DownloadQuota>>>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Decision {
+    Allowed {
+        limit: u32,
+        remaining: u32,
+        reset: u64,
+    },
+    Limited {
+        limit: u32,
+        retry_after: u64,
+    },
+}
+
+impl RateLimitState {
+    fn take_token(&self, key: &str, rule: &RateLimitRule, now: Instant) -> Decision {
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() > MAX_TRACKED_CLIENTS {
+            buckets.retain(|_, bucket| now.duration_since(bucket.updated_at) < IDLE_CLIENT_TTL);
+        }
+        let capacity = rule.capacity as f64;
+        let bucket = buckets.entry(key.to_string()).or_insert(TokenBucket {
+            tokens: capacity,
+            updated_at: now,
+        });
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rule.refill_per_second).min(capacity);
+        bucket.updated_at = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Decision::Allowed {
+                limit: rule.capacity,
+                remaining: bucket.tokens.floor() as u32,
+                reset: ((capacity - bucket.tokens) / rule.refill_per_second).ceil() as u64,
+            }
+        } else {
+            Decision::Limited {
+                limit: rule.capacity,
+                retry_after: ((1.0 - bucket.tokens) / rule.refill_per_second).ceil() as u64,
+            }
+        }
+    }
+
+    fn downloaded_on(&self, key: &str, today: NaiveDate) -> u64 {
+        let quotas = self.quotas.lock().unwrap();
+        match quotas.get(key) {
+            Some(quota) if quota.day == today => quota.bytes,
+            _ => 0,
+        }
+    }
+
+    fn add_download(&self, key: &str, bytes: u64, today: NaiveDate) {
+        let mut quotas = self.quotas.lock().unwrap();
+        quotas.retain(|_, quota| quota.day == today);
+        let quota = quotas.entry(key.to_string()).or_insert(DownloadQuota {
+            day: today,
+            bytes: 0,
+        });
+        quota.bytes += bytes;
+    }
+}
+
+/// Response body that charges every chunk sent to the client's daily download quota, so
+/// streamed and chunked downloads are counted as well
+struct CountedBody {
+    body: BoxBody,
+    state: RateLimitState,
+    client_key: String,
+}
+
+impl MessageBody for CountedBody {
+    type Error = Box<dyn std::error::Error>;
+
+    fn size(&self) -> BodySize {
+        self.body.size()
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.get_mut();
+        let chunk = Pin::new(&mut this.body).poll_next(cx);
+        if let Poll::Ready(Some(Ok(bytes))) = &chunk {
+            this.state.add_download(
+                &this.client_key,
+                bytes.len() as u64,
+                Utc::now().date_naive(),
+            );
+        }
+        chunk
+    }
+}
+
+pub struct RateLimitMiddleware {
+    state: RateLimitState,
+    config: RateLimitConfig,
+    auth: AuthConfig,
+}
+
+impl RateLimitMiddleware {
+    pub fn new(state: RateLimitState, config: RateLimitConfig, auth: AuthConfig) -> Self {
+        Self {
+            state,
+            config,
+            auth,
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimitMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = RateLimitMiddlewareService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RateLimitMiddlewareService {
+            service: Rc::new(service),
+            state: self.state.clone(),
+            config: Rc::new(self.config.clone()),
+            auth: Rc::new(self.auth.clone()),
+        })
+    }
+}
+
+pub struct RateLimitMiddlewareService<S> {
+    service: Rc<S>,
+    state: RateLimitState,
+    config: Rc<RateLimitConfig>,
+    auth: Rc<AuthConfig>,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if !self.config.enabled {
+            let fut = self.service.call(req);
+            return Box::pin(async move { Ok(fut.await?.map_into_left_body()) });
+        }
+
+        let role = match req.headers().get("X-API-KEY").and_then(|h| h.to_str().ok()) {
+            Some(key) if key == self.auth.admin_api_key => "admin",
+            Some(key) if key == self.auth.user_api_key => "user",
+            _ => "anonymous",
+        };
+        let client_ip = if self.config.trust_forwarded_for {
+            req.connection_info()
+                .realip_remote_addr()
+                .unwrap_or("unknown")
+                .to_string()
+        } else {
+            req.peer_addr()
+                .map(|addr| addr.ip().to_string())
+                .unwrap_or_else(|| String::from("unknown"))
+        };
+        let client_key = format!("{}:{}", role, client_ip);
+        let path = req.path().to_string();
+
+        let mut rate_headers: Option<(u32, u32, u64)> = None;
+        if let Some((index, rule)) = self.config.rules.iter().enumerate().find(|(_, rule)| {
+            (rule.role == role || rule.role == "any") && path.starts_with(rule.route.as_str())
+        }) {
+            match self
+                .state
+                .take_token(&format!("{}:{}", index, client_key), rule, Instant::now())
+            {
+                Decision::Allowed {
+                    limit,
+                    remaining,
+                    reset,
+                } => rate_headers = Some((limit, remaining, reset)),
+                Decision::Limited { limit, retry_after } => {
+                    let response = HttpResponse::TooManyRequests()
+                        .insert_header((RETRY_AFTER, retry_after.to_string()))
+                        .insert_header(("X-RateLimit-Limit", limit.to_string()))
+                        .insert_header(("X-RateLimit-Remaining", "0"))
+                        .insert_header(("X-RateLimit-Reset", retry_after.to_string()))
+                        .json(ResponseMessage {
+                            message: String::from("Too Many Requests"),
+                        });
+                    return Box::pin(async move {
+                        Ok(req.into_response(response).map_into_right_body())
+                    });
+                }
+            }
+        }
+
+        let quota = self.config.reader_daily_download_bytes;
+        let is_download = role != "admin"
+            && quota > 0
+            && self
+                .config
+                .download_routes
+                .iter()
+                .any(|route| path.starts_with(route.as_str()));
+        if is_download
+            && self
+                .state
+                .downloaded_on(&client_key, Utc::now().date_naive())
+                >= quota
+        {
+            let response = quota_exceeded_response();
+            return Box::pin(async move { Ok(req.into_response(response).map_into_right_body()) });
+        }
+
+        let state = self.state.clone();
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let mut res = fut.await?;
+            if let Some((limit, remaining, reset)) = rate_headers {
+                let headers = res.headers_mut();
+                headers.insert(
+                    HeaderName::from_static("x-ratelimit-limit"),
+                    HeaderValue::from(limit),
+                );
+                headers.insert(
+                    HeaderName::from_static("x-ratelimit-remaining"),
+                    HeaderValue::from(remaining),
+                );
+                headers.insert(
+                    HeaderName::from_static("x-ratelimit-reset"),
+                    HeaderValue::from(reset),
+                );
+            }
+            if !is_download || !res.status().is_success() {
+                return Ok(res.map_into_left_body());
+            }
+            // Refuse a response that would take the client past the quota before sending it
+            if let BodySize::Sized(size) = res.response().body().size() {
+                if state.downloaded_on(&client_key, Utc::now().date_naive()) + size > quota {
+                    let (req, _) = res.into_parts();
+                    return Ok(
+                        ServiceResponse::new(req, quota_exceeded_response()).map_into_right_body()
+                    );
+                }
+            }
+            let res = res.map_body(|_, body| {
+                BoxBody::new(CountedBody {
+                    body: body.boxed(),
+                    state,
+                    client_key,
+                })
+            });
+            Ok(res.map_into_right_body())
+        })
+    }
+}
+
+fn quota_exceeded_response() -> HttpResponse {
+    HttpResponse::TooManyRequests()
+        .insert_header((RETRY_AFTER, seconds_until_tomorrow().to_string()))
+        .json(ResponseMessage {
+            message: String::from("Daily download quota exceeded"),
+        })
+}
+
+fn seconds_until_tomorrow() -> u64 {
+    let now = Utc::now();
+    let tomorrow = now
+        .date_naive()
+        .succ_opt()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|d| d.and_utc())
+        .unwrap_or(now);
+    (tomorrow - now).num_seconds().max(1) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::{call_service, init_service, read_body, TestRequest};
+    use actix_web::{web, App};
+
+    use super::*;
+
+    fn rule(role: &str, capacity: u32, refill_per_second: f64) -> RateLimitRule {
+        RateLimitRule {
+            role: role.to_string(),
+            route: String::new(),
+            capacity,
+            refill_per_second,
+        }
+    }
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn buckets_refill_at_the_rule_rate_up_to_capacity() {
+        let state = RateLimitState::default();
+        let rule = rule("user", 2, 0.5);
+        let start = Instant::now();
+        let allowed = |remaining, reset| Decision::Allowed {
+            limit: 2,
+            remaining,
+            reset,
+        };
+        assert_eq!(state.take_token("key", &rule, start), allowed(1, 2));
+        assert_eq!(state.take_token("key", &rule, start), allowed(0, 4));
+        assert_eq!(
+            state.take_token("key", &rule, start),
+            Decision::Limited {
+                limit: 2,
+                retry_after: 2,
+            }
+        );
+        // Half a token after one second
+        assert_eq!(
+            state.take_token("key", &rule, start + Duration::from_secs(1)),
+            Decision::Limited {
+                limit: 2,
+                retry_after: 1,
+            }
+        );
+        assert_eq!(
+            state.take_token("key", &rule, start + Duration::from_secs(2)),
+            allowed(0, 4)
+        );
+        // Never more than the capacity however long the client was idle
+        let later = start + Duration::from_secs(600);
+        assert_eq!(state.take_token("key", &rule, later), allowed(1, 2));
+        // Other keys have their own bucket
+        assert_eq!(state.take_token("other", &rule, start), allowed(1, 2));
+    }
+
+    #[test]
+    fn download_quota_resets_every_day() {
+        let state = RateLimitState::default();
+        let (today, tomorrow) = (date("2026-10-19"), date("2026-10-20"));
+        state.add_download("key", 100, today);
+        state.add_download("key", 50, today);
+        assert_eq!(state.downloaded_on("key", today), 150);
+        assert_eq!(state.downloaded_on("other", today), 0);
+        assert_eq!(state.downloaded_on("key", tomorrow), 0);
+        state.add_download("key", 5, tomorrow);
+        assert_eq!(state.downloaded_on("key", tomorrow), 5);
+        // Counts of past days are dropped
+        assert_eq!(state.downloaded_on("key", today), 0);
+    }
+
+    fn middleware(config: RateLimitConfig) -> RateLimitMiddleware {
+        let auth = AuthConfig {
+            admin_api_key: String::from("admin-key"),
+            user_api_key: String::from("user-key"),
+            ..AuthConfig::default()
+        };
+        RateLimitMiddleware::new(RateLimitState::default(), config, auth)
+    }
+
+    #[actix_web::test]
+    async fn limited_requests_get_retry_after_and_rate_limit_headers() {
+        let config = RateLimitConfig {
+            rules: vec![rule("user", 2, 0.5)],
+            ..RateLimitConfig::default()
+        };
+        let app = init_service(
+            App::new()
+                .wrap(middleware(config))
+                .route("/albums", web::get().to(|| async { "ok" })),
+        )
+        .await;
+        let request = || {
+            TestRequest::get()
+                .uri("/albums")
+                .insert_header(("X-API-KEY", "user-key"))
+                .to_request()
+        };
+        let header = |response: &ServiceResponse<EitherBody<BoxBody>>, name: &str| {
+            response
+                .headers()
+                .get(name)
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .to_string()
+        };
+
+        let response = call_service(&app, request()).await;
+        assert_eq!(response.status(), 200);
+        assert_eq!(header(&response, "X-RateLimit-Limit"), "2");
+        assert_eq!(header(&response, "X-RateLimit-Remaining"), "1");
+        assert_eq!(header(&response, "X-RateLimit-Reset"), "2");
+        let response = call_service(&app, request()).await;
+        assert_eq!(header(&response, "X-RateLimit-Remaining"), "0");
+        assert_eq!(header(&response, "X-RateLimit-Reset"), "4");
+
+        let response = call_service(&app, request()).await;
+        assert_eq!(response.status(), 429);
+        assert_eq!(header(&response, "Retry-After"), "2");
+        assert_eq!(header(&response, "X-RateLimit-Limit"), "2");
+        assert_eq!(header(&response, "X-RateLimit-Remaining"), "0");
+        assert_eq!(header(&response, "X-RateLimit-Reset"), "2");
+
+        // No rule for anonymous requests here, so no headers either
+        let response = call_service(&app, TestRequest::get().uri("/albums").to_request()).await;
+        assert_eq!(response.status(), 200);
+        assert!(response.headers().get("X-RateLimit-Limit").is_none());
+    }
+
+    #[actix_web::test]
+    async fn sized_downloads_over_the_quota_are_refused() {
+        let config = RateLimitConfig {
+            rules: vec![],
+            reader_daily_download_bytes: 15,
+            ..RateLimitConfig::default()
+        };
+        let app = init_service(
+            App::new()
+                .wrap(middleware(config))
+                .route("/static/small", web::get().to(|| async { "0123456789" }))
+                .route(
+                    "/static/large",
+                    web::get().to(|| async { "0123456789abcdefghij" }),
+                ),
+        )
+        .await;
+        let request = |uri: &str, api_key: &str| {
+            TestRequest::get()
+                .uri(uri)
+                .insert_header(("X-API-KEY", api_key))
+                .to_request()
+        };
+
+        // Larger than the whole quota, refused before anything is sent
+        let response = call_service(&app, request("/static/large", "user-key")).await;
+        assert_eq!(response.status(), 429);
+        assert!(response.headers().get(RETRY_AFTER).is_some());
+        let response = call_service(&app, request("/static/small", "user-key")).await;
+        assert_eq!(response.status(), 200);
+        assert_eq!(read_body(response).await, "0123456789");
+        // 10 bytes counted, another 10 would pass the quota
+        let response = call_service(&app, request("/static/small", "user-key")).await;
+        assert_eq!(response.status(), 429);
+        // Admin downloads are not counted
+        let response = call_service(&app, request("/static/large", "admin-key")).await;
+        assert_eq!(response.status(), 200);
+    }
+}
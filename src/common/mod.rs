@@ -1,6 +1,13 @@
+pub mod config;
 pub mod database;
 pub mod enums;
+pub mod i18n;
 pub mod middleware;
 pub mod models;
 pub mod ne_parse;
+pub mod pagination;
+#[cfg(test)]
+pub mod test_support;
+pub mod tmp_dir;
 pub mod utils;
+pub mod video;
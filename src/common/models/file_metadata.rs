@@ -26,7 +26,12 @@ impl ImageMetadata {
 
 #[derive(Debug, Clone, Serialize)]
 pub struct VideoMetadata {
-    pub thumbnail: String,
+    /// Poster frame generated next to the video file
+    pub thumbnail: Option<String>,
     pub width: u32,
     pub height: u32,
-}
\ No newline at end of file
+    pub duration_ms: u64,
+    pub codec: String,
+    /// Bits per second
+    pub bitrate: u64,
+}
@@ -1,7 +1,13 @@
-use crate::features::album::models::AlbumResponse;
-use crate::features::episode::models::EpisodeResponse;
-use crate::features::content::models::ContentResponse;
+use crate::features::album::models::{AlbumResponse, TrendingAlbumResponse};
+use crate::features::audit::models::AuditLogResponse;
 use crate::features::category::models::CategoryResponse;
+use crate::features::content::models::ContentResponse;
+use crate::features::creator::models::CreatorResponse;
+use crate::features::episode::models::EpisodeResponse;
+use crate::features::jobs::models::JobResponse;
+use crate::features::review::models::ReviewResponse;
+use crate::features::trash::models::TrashItemResponse;
+use crate::features::webhooks::models::{WebhookDeliveryResponse, WebhookResponse};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
@@ -9,10 +15,36 @@ use utoipa::ToSchema;
 #[aliases(
     ResponseDataCategory = ResponseData<CategoryResponse>,
     ResponseDataAlbum = ResponseData<AlbumResponse>,
+    ResponseDataTrendingAlbum = ResponseData<TrendingAlbumResponse>,
+    ResponseDataCreator = ResponseData<CreatorResponse>,
+    ResponseDataReview = ResponseData<ReviewResponse>,
     ResponseDataEpisode = ResponseData<EpisodeResponse>,
     ResponseDataContent = ResponseData<ContentResponse>,
+    ResponseDataAuditLog = ResponseData<AuditLogResponse>,
+    ResponseDataTrashItem = ResponseData<TrashItemResponse>,
+    ResponseDataJob = ResponseData<JobResponse>,
+    ResponseDataWebhook = ResponseData<WebhookResponse>,
+    ResponseDataWebhookDelivery = ResponseData<WebhookDeliveryResponse>,
 )]
 pub struct ResponseData<T> {
     pub data: Vec<T>,
     pub total: i64,
+    /// Pass as `cursor` to get the next page, empty on the last page
+    #[serde(default)]
+    pub next_cursor: Option<String>,
+    #[serde(default)]
+    pub has_more: bool,
+}
+
+impl<T> ResponseData<T> {
+    /// Complete list or offset page, `has_more` is derived from `total`
+    pub fn new(data: Vec<T>, total: i64, offset: i64) -> Self {
+        let has_more = offset + (data.len() as i64) < total;
+        ResponseData {
+            data,
+            total,
+            next_cursor: None,
+            has_more,
+        }
+    }
 }
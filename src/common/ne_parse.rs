@@ -15,6 +15,19 @@ impl NEParse {
         }
     }
 
+    pub fn opt_immut_str_to_opt_f64(s: Option<&str>) -> Option<f64> {
+        s?.trim().parse::<f64>().ok().filter(|d| d.is_finite())
+    }
+
+    /// Comma separated numbers such as `1,4,7`, `None` when empty or any number is invalid
+    pub fn opt_immut_str_to_opt_vec_i32(s: Option<&str>) -> Option<Vec<i32>> {
+        let s = s?.trim();
+        if s.is_empty() {
+            return None;
+        }
+        s.split(',').map(|d| d.trim().parse::<i32>().ok()).collect()
+    }
+
     pub fn opt_immut_str_to_option_string(s: Option<&str>) -> Option<String> {
         if s != None {
             Some(s?.to_string())
@@ -27,9 +40,9 @@ impl NEParse {
         if s != None {
             if s?.eq("true") {
                 Some(true)
-            }else if s?.eq("false"){
+            } else if s?.eq("false") {
                 Some(false)
-            }else{
+            } else {
                 None
             }
         } else {
@@ -0,0 +1,513 @@
+use std::fmt;
+
+use diesel::dsl::sql;
+use diesel::expression::BoxableExpression;
+use diesel::sql_types::{BigInt, Bool, Double, Text};
+use diesel::sqlite::Sqlite;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::common::models::response_data::ResponseData;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    fn as_sql(self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+/// How sort values are compared when resuming from a cursor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKind {
+    Integer,
+    Real,
+    Text,
+}
+
+/// A sort option of a list endpoint
+#[derive(Debug, Clone)]
+pub struct SortField {
+    pub name: &'static str,
+    /// SQL expression of the sort value, must never be NULL
+    pub expression: String,
+    pub kind: SortKind,
+    /// Order used when the request gives none
+    pub default_order: SortOrder,
+}
+
+impl SortField {
+    pub fn new(
+        name: &'static str,
+        expression: impl Into<String>,
+        kind: SortKind,
+        default_order: SortOrder,
+    ) -> Self {
+        SortField {
+            name,
+            expression: expression.into(),
+            kind,
+            default_order,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PageQuery {
+    /// Sort field, the accepted values are listed on each endpoint
+    pub sort: Option<String>,
+    /// Sort order (asc, desc), each sort field has its own default
+    pub order: Option<SortOrder>,
+    /// `next_cursor` of the previous page, the sort and order must not change between pages
+    pub cursor: Option<String>,
+    /// Page size (Get all for 0)
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug)]
+pub enum PaginationError {
+    UnknownSort {
+        sort: String,
+        allowed: Vec<&'static str>,
+    },
+    InvalidCursor,
+    InvalidLimit,
+    Database(diesel::result::Error),
+}
+
+impl fmt::Display for PaginationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaginationError::UnknownSort { sort, allowed } => write!(
+                f,
+                "Unknown sort {:?}, expected one of: {}",
+                sort,
+                allowed.join(", ")
+            ),
+            PaginationError::InvalidCursor => {
+                write!(f, "Invalid cursor or cursor used with another sort")
+            }
+            PaginationError::InvalidLimit => write!(f, "limit must not be negative"),
+            PaginationError::Database(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<diesel::result::Error> for PaginationError {
+    fn from(e: diesel::result::Error) -> Self {
+        PaginationError::Database(e)
+    }
+}
+
+/// Decoded form of the opaque `next_cursor` token
+#[derive(Debug, Serialize, Deserialize)]
+struct Cursor {
+    sort: String,
+    order: SortOrder,
+    /// Sort value of the last row of the previous page
+    value: String,
+    /// Id of the last row of the previous page, breaks ties between equal sort values
+    id: i64,
+}
+
+impl Cursor {
+    fn encode(&self) -> String {
+        hex::encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    fn decode(token: &str) -> Option<Cursor> {
+        let bytes = hex::decode(token).ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+}
+
+/// Validated sort, order and position of a list request
+#[derive(Debug)]
+pub struct Page {
+    field: SortField,
+    order: SortOrder,
+    after: Option<Cursor>,
+    limit: Option<i64>,
+    offset: i64,
+}
+
+impl Page {
+    /// Check the request against the sort fields of the entity, the first field is the default.
+    /// `default_limit` applies when the request gives no limit, `None` returns every row.
+    pub fn new(
+        query: PageQuery,
+        offset: Option<i64>,
+        fields: Vec<SortField>,
+        default_limit: Option<i64>,
+    ) -> Result<Page, PaginationError> {
+        let allowed = fields.iter().map(|f| f.name).collect::<Vec<&'static str>>();
+        let field = match query.sort.as_deref().filter(|sort| !sort.is_empty()) {
+            Some(sort) => fields.into_iter().find(|f| f.name == sort).ok_or_else(|| {
+                PaginationError::UnknownSort {
+                    sort: sort.to_string(),
+                    allowed,
+                }
+            })?,
+            None => fields
+                .into_iter()
+                .next()
+                .expect("an entity needs at least one sort field"),
+        };
+        let order = query.order.unwrap_or(field.default_order);
+
+        let after = match query.cursor.as_deref().filter(|cursor| !cursor.is_empty()) {
+            Some(token) => {
+                let cursor = Cursor::decode(token).ok_or(PaginationError::InvalidCursor)?;
+                if cursor.sort != field.name
+                    || cursor.order != order
+                    || (field.kind == SortKind::Integer && cursor.value.parse::<i64>().is_err())
+                    || (field.kind == SortKind::Real && cursor.value.parse::<f64>().is_err())
+                {
+                    return Err(PaginationError::InvalidCursor);
+                }
+                Some(cursor)
+            }
+            None => None,
+        };
+
+        let limit = match query.limit {
+            Some(limit) if limit < 0 => return Err(PaginationError::InvalidLimit),
+            Some(0) => None,
+            Some(limit) => Some(limit),
+            None => default_limit,
+        };
+
+        Ok(Page {
+            field,
+            order,
+            after,
+            limit,
+            // A cursor already marks the position
+            offset: if query.cursor.is_some() {
+                0
+            } else {
+                offset.unwrap_or(0).max(0)
+            },
+        })
+    }
+
+    pub fn offset(&self) -> i64 {
+        self.offset
+    }
+
+    /// Rows to load, one more than the page size to know whether another page follows
+    pub fn fetch_limit(&self) -> i64 {
+        self.limit.map(|limit| limit + 1).unwrap_or(-1)
+    }
+
+    /// `ORDER BY` clause, `id_column` keeps the order stable between equal sort values
+    pub fn order_by_sql(&self, id_column: &str) -> String {
+        format!(
+            "{} {}, {} {}",
+            self.field.expression,
+            self.order.as_sql(),
+            id_column,
+            self.order.as_sql()
+        )
+    }
+
+    /// Sort value of a row as text, selected next to each row to build the next cursor
+    pub fn sort_value(&self) -> diesel::expression::SqlLiteral<Text> {
+        sql::<Text>(&format!("CAST({} AS TEXT)", self.field.expression))
+    }
+
+    /// Filter of the rows after the cursor, `None` on the first page
+    pub fn after_cursor<QS: 'static>(
+        &self,
+        id_column: &str,
+    ) -> Option<Box<dyn BoxableExpression<QS, Sqlite, SqlType = Bool>>> {
+        let cursor = self.after.as_ref()?;
+        let expression = &self.field.expression;
+        let cmp = match self.order {
+            SortOrder::Asc => ">",
+            SortOrder::Desc => "<",
+        };
+        let head = format!("({} {} ", expression, cmp);
+        let middle = format!(" OR ({} = ", expression);
+        let tail = format!(" AND {} {} ", id_column, cmp);
+        Some(match self.field.kind {
+            SortKind::Integer => {
+                let value = cursor.value.parse::<i64>().unwrap_or_default();
+                Box::new(
+                    sql::<Bool>(&head)
+                        .bind::<BigInt, _>(value)
+                        .sql(&middle)
+                        .bind::<BigInt, _>(value)
+                        .sql(&tail)
+                        .bind::<BigInt, _>(cursor.id)
+                        .sql("))"),
+                )
+            }
+            SortKind::Real => {
+                let value = cursor.value.parse::<f64>().unwrap_or_default();
+                Box::new(
+                    sql::<Bool>(&head)
+                        .bind::<Double, _>(value)
+                        .sql(&middle)
+                        .bind::<Double, _>(value)
+                        .sql(&tail)
+                        .bind::<BigInt, _>(cursor.id)
+                        .sql("))"),
+                )
+            }
+            SortKind::Text => Box::new(
+                sql::<Bool>(&head)
+                    .bind::<Text, _>(cursor.value.clone())
+                    .sql(&middle)
+                    .bind::<Text, _>(cursor.value.clone())
+                    .sql(&tail)
+                    .bind::<BigInt, _>(cursor.id)
+                    .sql("))"),
+            ),
+        })
+    }
+
+    /// Cut the extra row loaded by `fetch_limit` and build the response with its next cursor.
+    /// Each row comes with its sort value and id.
+    pub fn finish<T, R>(
+        &self,
+        mut rows: Vec<(T, String, i64)>,
+        total: i64,
+        map: impl FnMut(T) -> R,
+    ) -> ResponseData<R> {
+        let has_more = match self.limit {
+            Some(limit) if rows.len() as i64 > limit => {
+                rows.truncate(limit as usize);
+                true
+            }
+            _ => false,
+        };
+        let next_cursor = match (has_more, rows.last()) {
+            (true, Some((_, value, id))) => Some(
+                Cursor {
+                    sort: self.field.name.to_string(),
+                    order: self.order,
+                    value: value.clone(),
+                    id: *id,
+                }
+                .encode(),
+            ),
+            _ => None,
+        };
+        ResponseData {
+            data: rows.into_iter().map(|(row, _, _)| row).map(map).collect(),
+            total,
+            next_cursor,
+            has_more,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use diesel::prelude::*;
+    use diesel::sql_types::Integer;
+
+    use super::*;
+    use crate::common::database::DbPool;
+    use crate::common::test_support;
+    use crate::schema::albums;
+
+    fn fields() -> Vec<SortField> {
+        vec![
+            SortField::new(
+                "rating_count",
+                "albums.rating_count",
+                SortKind::Integer,
+                SortOrder::Desc,
+            ),
+            SortField::new("title", "albums.title", SortKind::Text, SortOrder::Asc),
+        ]
+    }
+
+    /// Albums rated 2, 2, 1, 1, 1 in insert order
+    fn pool_with_albums() -> DbPool {
+        let pool = test_support::test_pool();
+        for (index, rating_count) in [1, 2, 1, 2, 1].into_iter().enumerate() {
+            diesel::sql_query(
+                "INSERT INTO albums (uuid, category_id, title, description, images, url, \
+                 content_type, width, height, bytes, rating_count) \
+                 VALUES (?, 1, ?, '', '', '', '', 0, 0, 0, ?)",
+            )
+            .bind::<Text, _>(uuid::Uuid::new_v4().to_string())
+            .bind::<Text, _>(format!("Album {}", index + 1))
+            .bind::<Integer, _>(rating_count)
+            .execute(&mut pool.get().unwrap())
+            .unwrap();
+        }
+        pool
+    }
+
+    /// Album ids of one page, loaded the way the list repositories do
+    fn load(pool: &DbPool, query: PageQuery) -> Result<ResponseData<i64>, PaginationError> {
+        let page = Page::new(query, None, fields(), Some(20))?;
+        let mut conn = pool.get().unwrap();
+        let mut rows = albums::table.into_boxed();
+        if let Some(after) = page.after_cursor::<albums::table>("albums.id") {
+            rows = rows.filter(after);
+        }
+        let total = albums::table.count().get_result::<i64>(&mut conn)?;
+        let rows = rows
+            .select((
+                sql::<BigInt>("albums.id"),
+                page.sort_value(),
+                sql::<BigInt>("albums.id"),
+            ))
+            .order(sql::<Text>(&page.order_by_sql("albums.id")))
+            .offset(page.offset())
+            .limit(page.fetch_limit())
+            .load::<(i64, String, i64)>(&mut conn)?;
+        Ok(page.finish(rows, total, |id| id))
+    }
+
+    fn query(limit: i64, cursor: Option<String>) -> PageQuery {
+        PageQuery {
+            limit: Some(limit),
+            cursor,
+            ..PageQuery::default()
+        }
+    }
+
+    #[test]
+    fn cursor_round_trips_and_is_tied_to_its_sort() {
+        let cursor = Cursor {
+            sort: String::from("title"),
+            order: SortOrder::Asc,
+            value: String::from("Album 1"),
+            id: 7,
+        };
+        let decoded = Cursor::decode(&cursor.encode()).unwrap();
+        assert_eq!(decoded.sort, "title");
+        assert_eq!(decoded.order, SortOrder::Asc);
+        assert_eq!(decoded.value, "Album 1");
+        assert_eq!(decoded.id, 7);
+        assert!(Cursor::decode("not a cursor").is_none());
+
+        let pool = pool_with_albums();
+        let first = load(&pool, query(2, None)).unwrap();
+        let token = first.next_cursor.unwrap();
+        let other_sort = PageQuery {
+            sort: Some(String::from("title")),
+            ..query(2, Some(token.clone()))
+        };
+        assert!(matches!(
+            load(&pool, other_sort),
+            Err(PaginationError::InvalidCursor)
+        ));
+        let other_order = PageQuery {
+            order: Some(SortOrder::Asc),
+            ..query(2, Some(token.clone()))
+        };
+        assert!(matches!(
+            load(&pool, other_order),
+            Err(PaginationError::InvalidCursor)
+        ));
+        assert!(matches!(
+            load(&pool, query(2, Some(String::from("zz")))),
+            Err(PaginationError::InvalidCursor)
+        ));
+        assert!(load(&pool, query(2, Some(token))).is_ok());
+    }
+
+    #[test]
+    fn pages_follow_each_other_without_duplicates_or_gaps() {
+        let pool = pool_with_albums();
+        // Equal rating counts are ordered by id, in the sort order
+        let all = load(&pool, query(0, None)).unwrap();
+        assert_eq!(all.data, vec![4, 2, 5, 3, 1]);
+        assert_eq!(all.total, 5);
+        assert!(!all.has_more);
+        assert_eq!(all.next_cursor, None);
+
+        let mut seen = vec![];
+        let mut cursor = None;
+        let mut pages = 0;
+        loop {
+            let page = load(&pool, query(2, cursor)).unwrap();
+            pages += 1;
+            assert_eq!(page.total, 5);
+            seen.extend(page.data);
+            assert_eq!(page.has_more, page.next_cursor.is_some());
+            if !page.has_more {
+                break;
+            }
+            cursor = page.next_cursor;
+        }
+        assert_eq!(pages, 3);
+        assert_eq!(seen, all.data);
+    }
+
+    #[test]
+    fn last_page_has_no_cursor() {
+        let pool = pool_with_albums();
+        // A page filled to the limit by the last rows
+        let first = load(&pool, query(3, None)).unwrap();
+        assert!(first.has_more);
+        let last = load(&pool, query(3, first.next_cursor)).unwrap();
+        assert_eq!(last.data, vec![3, 1]);
+        assert!(!last.has_more);
+        assert_eq!(last.next_cursor, None);
+        let exact = load(&pool, query(5, None)).unwrap();
+        assert_eq!(exact.data.len(), 5);
+        assert!(!exact.has_more);
+        assert_eq!(exact.next_cursor, None);
+    }
+
+    #[test]
+    fn limits_and_sorts_are_checked() {
+        assert!(matches!(
+            Page::new(query(-1, None), None, fields(), Some(20)),
+            Err(PaginationError::InvalidLimit)
+        ));
+        let unknown = PageQuery {
+            sort: Some(String::from("views")),
+            ..PageQuery::default()
+        };
+        assert!(matches!(
+            Page::new(unknown, None, fields(), Some(20)),
+            Err(PaginationError::UnknownSort { .. })
+        ));
+        // 0 loads every row, no limit falls back to the default
+        assert_eq!(
+            Page::new(query(0, None), None, fields(), Some(20))
+                .unwrap()
+                .fetch_limit(),
+            -1
+        );
+        assert_eq!(
+            Page::new(PageQuery::default(), None, fields(), Some(20))
+                .unwrap()
+                .fetch_limit(),
+            21
+        );
+        // A cursor marks the position, the offset is ignored
+        let cursor = Cursor {
+            sort: String::from("rating_count"),
+            order: SortOrder::Desc,
+            value: String::from("2"),
+            id: 4,
+        };
+        let page = Page::new(
+            query(2, Some(cursor.encode())),
+            Some(10),
+            fields(),
+            Some(20),
+        )
+        .unwrap();
+        assert_eq!(page.offset(), 0);
+    }
+}
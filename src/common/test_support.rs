@@ -0,0 +1,208 @@
+//! Shared setup for unit tests: one configuration per test binary, a scratch database with the
+//! migrations applied, a temporary data directory and a local HTTP stand-in for external URLs.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+
+use diesel::connection::SimpleConnection;
+use uuid::Uuid;
+
+use crate::common::config::{self, Config};
+use crate::common::database::{sqlite_connection, DbPool};
+
+pub const ADMIN_API_KEY: &str = "test-admin-key";
+pub const USER_API_KEY: &str = "test-user-key";
+
+static STAND_IN: OnceLock<StandIn> = OnceLock::new();
+static SERIAL: OnceLock<tokio::sync::Mutex<()>> = OnceLock::new();
+
+/// A request received by the stand-in server
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    /// Header names are lowercased
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+/// Minimal HTTP/1.1 server on 127.0.0.1 which answers every request with the status set for
+/// its path, 200 by default, and records what it received
+pub struct StandIn {
+    pub base_url: String,
+    statuses: Arc<Mutex<HashMap<String, u16>>>,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl StandIn {
+    fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind stand-in server");
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let statuses: Arc<Mutex<HashMap<String, u16>>> = Arc::default();
+        let requests: Arc<Mutex<Vec<RecordedRequest>>> = Arc::default();
+        let (thread_statuses, thread_requests) = (statuses.clone(), requests.clone());
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let (statuses, requests) = (thread_statuses.clone(), thread_requests.clone());
+                thread::spawn(move || handle_connection(stream, &statuses, &requests));
+            }
+        });
+        StandIn {
+            base_url,
+            statuses,
+            requests,
+        }
+    }
+
+    /// Answer requests for `path` with `status` from now on
+    pub fn set_status(&self, path: &str, status: u16) {
+        self.statuses
+            .lock()
+            .unwrap()
+            .insert(path.to_string(), status);
+    }
+
+    /// Requests received for `path`, oldest first
+    pub fn requests(&self, path: &str) -> Vec<RecordedRequest> {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|request| request.path == path)
+            .cloned()
+            .collect()
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    statuses: &Mutex<HashMap<String, u16>>,
+    requests: &Mutex<Vec<RecordedRequest>>,
+) {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+    let length = headers
+        .get("content-length")
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    if reader.read_exact(&mut body).is_err() {
+        return;
+    }
+    let status = statuses.lock().unwrap().get(&path).copied().unwrap_or(200);
+    let is_head = method == "HEAD";
+    requests.lock().unwrap().push(RecordedRequest {
+        method,
+        path,
+        headers,
+        body,
+    });
+    let response = if is_head {
+        format!(
+            "HTTP/1.1 {} Stand-in\r\nContent-Length: 2\r\nConnection: close\r\n\r\n",
+            status
+        )
+    } else {
+        format!(
+            "HTTP/1.1 {} Stand-in\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+            status
+        )
+    };
+    let _ = reader.get_mut().write_all(response.as_bytes());
+}
+
+/// The stand-in server, started on first use
+pub fn stand_in() -> &'static StandIn {
+    STAND_IN.get_or_init(StandIn::start)
+}
+
+/// Initialize the configuration once for the test binary: files go to temporary data and trash
+/// directories, external URLs are probed on the stand-in and job retries back off 60 seconds
+pub fn init_config() -> &'static Config {
+    static INIT: OnceLock<()> = OnceLock::new();
+    INIT.get_or_init(|| {
+        let data_dir = std::env::temp_dir().join(format!("entmt-test-{}", Uuid::new_v4()));
+        fs::create_dir_all(&data_dir).expect("Failed to create test data directory");
+        let mut test_config = Config::default();
+        test_config.auth.admin_api_key = ADMIN_API_KEY.to_string();
+        test_config.auth.user_api_key = USER_API_KEY.to_string();
+        test_config.storage.data_dir = data_dir.to_string_lossy().to_string();
+        test_config.storage.trash_dir = std::env::temp_dir()
+            .join(format!("entmt-test-trash-{}", Uuid::new_v4()))
+            .to_string_lossy()
+            .to_string();
+        test_config.integrity.probe_external = true;
+        test_config.integrity.probe_timeout_seconds = 5;
+        test_config.integrity.probe_base_url = Some(stand_in().base_url.clone());
+        test_config.jobs.max_attempts = 3;
+        test_config.jobs.retry_backoff_seconds = 60;
+        test_config.webhooks.timeout_seconds = 5;
+        config::init(test_config);
+    });
+    config::get()
+}
+
+/// Directory under the test data directory, created empty for each call
+pub fn data_subdir() -> PathBuf {
+    let dir = PathBuf::from(&init_config().storage.data_dir).join(Uuid::new_v4().to_string());
+    fs::create_dir_all(&dir).expect("Failed to create test directory");
+    dir
+}
+
+/// A fresh database file with every migration applied
+pub fn test_pool() -> DbPool {
+    init_config();
+    let database_url = std::env::temp_dir()
+        .join(format!("entmt-test-{}.db", Uuid::new_v4()))
+        .to_string_lossy()
+        .to_string();
+    let pool = sqlite_connection::connect(database_url);
+    let mut migrations: Vec<PathBuf> =
+        fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations"))
+            .expect("Failed to read migrations")
+            .flatten()
+            .map(|entry| entry.path().join("up.sql"))
+            .filter(|path| path.is_file())
+            .collect();
+    migrations.sort();
+    let mut conn = pool.get().expect("Failed to get DB connection");
+    for migration in migrations {
+        let sql = fs::read_to_string(&migration).expect("Failed to read migration");
+        conn.batch_execute(&sql)
+            .unwrap_or_else(|e| panic!("Migration {} failed: {}", migration.display(), e));
+    }
+    pool
+}
+
+/// Held by tests which must not overlap, e.g. integrity scans which share one running flag
+pub async fn serial() -> tokio::sync::MutexGuard<'static, ()> {
+    SERIAL
+        .get_or_init(|| tokio::sync::Mutex::new(()))
+        .lock()
+        .await
+}
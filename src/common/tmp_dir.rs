@@ -0,0 +1,90 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use uuid::Uuid;
+
+use crate::common::config;
+
+/// A unique directory under the configured tmp location, removed with everything left in it
+/// when the guard is dropped, including while unwinding from a panic
+#[derive(Debug)]
+pub struct TmpDir {
+    path: String,
+}
+
+impl TmpDir {
+    pub fn new() -> io::Result<Self> {
+        let path = format!("{}/{}", config::get().storage.tmp_dir, Uuid::new_v4());
+        fs::create_dir_all(&path)?;
+        Ok(TmpDir { path })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl Drop for TmpDir {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_dir_all(&self.path) {
+            if e.kind() != io::ErrorKind::NotFound {
+                eprintln!("Failed to remove tmp directory {}: {}", self.path, e);
+            }
+        }
+    }
+}
+
+/// Remove entries of the tmp directory which were last modified before `older_than`
+pub fn sweep_tmp_directory(older_than: Duration) -> io::Result<usize> {
+    let tmp_dir = &config::get().storage.tmp_dir;
+    if !Path::new(tmp_dir).exists() {
+        return Ok(0);
+    }
+    let now = SystemTime::now();
+    let mut removed = 0;
+    for entry in fs::read_dir(tmp_dir)? {
+        let entry = entry?;
+        let modified = entry.metadata()?.modified()?;
+        if now.duration_since(modified).unwrap_or_default() < older_than {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            fs::remove_dir_all(entry.path())?;
+        } else {
+            fs::remove_file(entry.path())?;
+        }
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// Create the data and tmp directories and make sure they share a filesystem, so staged
+/// uploads are moved into `data/` with a rename instead of a copy
+pub fn prepare_storage_directories() -> io::Result<()> {
+    let storage = &config::get().storage;
+    fs::create_dir_all(&storage.data_dir)?;
+    fs::create_dir_all(&storage.tmp_dir)?;
+    same_filesystem(&storage.data_dir, &storage.tmp_dir)
+}
+
+#[cfg(unix)]
+fn same_filesystem(data_dir: &str, tmp_dir: &str) -> io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+    if fs::metadata(data_dir)?.dev() != fs::metadata(tmp_dir)?.dev() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "storage.tmp_dir {} must be on the same filesystem as storage.data_dir {}",
+                tmp_dir, data_dir
+            ),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn same_filesystem(_data_dir: &str, _tmp_dir: &str) -> io::Result<()> {
+    Ok(())
+}
@@ -1,19 +1,23 @@
 use actix_multipart::Multipart;
+use actix_web::body::BoxBody;
+use actix_web::dev::ServiceResponse;
+use actix_web::http::header;
+use actix_web::middleware::ErrorHandlerResponse;
+use actix_web::{HttpRequest, HttpResponse};
 use futures_util::StreamExt;
 use image::GenericImageView;
 use mime_guess::from_path;
 use serde_json::Value;
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::io::Write;
 use std::path::Path;
 use std::{env, fs};
-use actix_web::body::BoxBody;
-use actix_web::dev::ServiceResponse;
-use actix_web::http::header;
-use actix_web::HttpResponse;
-use actix_web::middleware::ErrorHandlerResponse;
 use uuid::Uuid;
 
+use crate::common::config;
+use crate::common::tmp_dir::TmpDir;
+use crate::common::video::get_video_metadata;
 use crate::common::enums::FileDataMap;
 use crate::common::models::file_metadata::{FileMetadata, ImageMetadata, VideoMetadata};
 
@@ -51,7 +55,12 @@ pub fn move_file_and_replace(src: &str, dest: &str) {
         }
     }
 
-    // Try to copy the file
+    // Rename when both paths are on the same filesystem
+    if fs::rename(src_path, dest_path).is_ok() {
+        return;
+    }
+
+    // Otherwise copy the file
     if let Err(e) = fs::copy(src_path, dest_path) {
         eprintln!(
             "Error copying file from {} to {}: {}",
@@ -77,7 +86,7 @@ pub fn delete_directory_if_exists(path: &str) {
     }
 }
 
-pub fn get_directory_from_file_path(file_path: &str)-> Option<&str> {
+pub fn get_directory_from_file_path(file_path: &str) -> Option<&str> {
     // Convert the file path into a Path
     let path = Path::new(file_path);
     // Get the parent directory
@@ -97,23 +106,48 @@ pub fn delete_file_if_exists(path: &str) {
 }
 
 pub fn get_data_directory() -> String {
-    // format!("{}/data", env::current_dir().expect("REASON").display(), )
-    "data".to_string()
+    config::get().storage.data_dir.clone()
+}
+
+pub fn get_tmp_directory() -> String {
+    config::get().storage.tmp_dir.clone()
+}
+
+pub fn get_trash_directory() -> String {
+    config::get().storage.trash_dir.clone()
+}
+
+/// Move a file or directory without overwriting the destination, missing sources are ignored
+pub fn move_path(src: &str, dest: &str) -> std::io::Result<()> {
+    let src_path = Path::new(src);
+    if !src_path.exists() {
+        return Ok(());
+    }
+    create_directory_if_not_exists(dest);
+    if Path::new(dest).exists() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            format!("{} already exists", dest),
+        ));
+    }
+    fs::rename(src_path, dest)
 }
 pub fn get_project_directory() -> String {
-    format!("{}", env::current_dir().expect("REASON").display(), )
+    format!("{}", env::current_dir().expect("REASON").display(),)
 }
 
 pub async fn parse_payload_data(
     mut payload: Multipart,
-) -> Result<(HashMap<String, Value>, String), String> {
-    let tmp_uuid = Uuid::new_v4().to_string();
-    let tmp_path = format!("{}/tmp/{}", get_project_directory(), tmp_uuid);
+) -> Result<(HashMap<String, Value>, TmpDir), String> {
+    let upload_config = &config::get().upload;
+    let tmp_dir = TmpDir::new().map_err(|e| format!("Failed to create tmp directory: {}", e))?;
+    let tmp_path = tmp_dir.path().to_string();
     // !dynamic mapping
     let mut form_data_map: HashMap<String, Value> = HashMap::new();
     let mut file_data_map: HashMap<String, Vec<String>> = HashMap::new();
+    let mut request_bytes: u64 = 0;
+    let mut file_count: usize = 0;
 
-    create_directory_if_not_exists(&tmp_path.clone());
     while let Some(item) = payload.next().await {
         let mut field = item.unwrap();
         let content_disposition = field.content_disposition().unwrap().clone();
@@ -121,11 +155,31 @@ pub async fn parse_payload_data(
         let name = content_disposition.get_name().unwrap().to_string();
         if content_disposition.is_form_data() {
             if let Some(filename) = content_disposition.get_filename() {
+                file_count += 1;
+                if file_count > upload_config.max_files {
+                    return Err(format!(
+                        "Too many files, maximum is {}",
+                        upload_config.max_files
+                    ));
+                }
                 let uuid_path = Uuid::new_v4().to_string();
                 let mut file_data = Vec::new();
                 while let Some(chunk) = field.next().await {
                     let chunk = chunk.unwrap();
+                    request_bytes += chunk.len() as u64;
                     file_data.extend_from_slice(&chunk);
+                    if file_data.len() as u64 > upload_config.max_file_bytes {
+                        return Err(format!(
+                            "File {} exceeds the maximum size of {} bytes",
+                            filename, upload_config.max_file_bytes
+                        ));
+                    }
+                    if request_bytes > upload_config.max_request_bytes {
+                        return Err(format!(
+                            "Request exceeds the maximum size of {} bytes",
+                            upload_config.max_request_bytes
+                        ));
+                    }
                 }
                 // Save file to disk or handle it as needed
                 let filepath = format!("{}/{}/{}", tmp_path.clone(), uuid_path, filename);
@@ -136,7 +190,14 @@ pub async fn parse_payload_data(
                 let mut value = Vec::new();
                 while let Some(chunk) = field.next().await {
                     let chunk = chunk.unwrap();
+                    request_bytes += chunk.len() as u64;
                     value.extend_from_slice(&chunk);
+                    if request_bytes > upload_config.max_request_bytes {
+                        return Err(format!(
+                            "Request exceeds the maximum size of {} bytes",
+                            upload_config.max_request_bytes
+                        ));
+                    }
                 }
                 let json_value = Value::String(String::from_utf8(value).unwrap());
                 form_data_map.insert(name.to_string(), json_value);
@@ -150,7 +211,7 @@ pub async fn parse_payload_data(
             Value::Array(paths.into_iter().map(Value::String).collect()),
         );
     }
-    Ok((form_data_map, tmp_path))
+    Ok((form_data_map, tmp_dir))
 }
 
 pub fn get_file_metadata(file_path: &str) -> FileMetadata {
@@ -160,12 +221,13 @@ pub fn get_file_metadata(file_path: &str) -> FileMetadata {
     let mime_type = from_path(file_path).first_or_octet_stream();
     let content_type = mime_type.to_string();
     let mut image_data: Option<ImageMetadata> = None;
-    let video_data: Option<VideoMetadata> = None;
+    let mut video_data: Option<VideoMetadata> = None;
     if content_type.starts_with("image/") {
         image_data = get_image_metadata(file_path);
     } else if content_type.starts_with("video/") {
-        //TODO Add video with height and generate thumbnail here
-    } else {}
+        video_data = get_video_metadata(file_path);
+    } else {
+    }
     FileMetadata {
         content_type,
         original_name,
@@ -175,6 +237,10 @@ pub fn get_file_metadata(file_path: &str) -> FileMetadata {
     }
 }
 
+pub fn get_file_name_from_path(file_path: &str) -> String {
+    get_file_name(file_path).unwrap_or_default()
+}
+
 fn get_file_name(file_path: &str) -> Option<String> {
     let path = Path::new(file_path);
 
@@ -205,10 +271,26 @@ pub fn remove_values_from_vec_string<'a>(
     original_vec
 }
 
+/// Strong ETag of a response body
+pub fn etag_of(body: &[u8]) -> String {
+    format!("\"{}\"", hex::encode(Sha256::digest(body)))
+}
+
+/// Whether the `If-None-Match` header of the request lists `etag` or `*`
+pub fn etag_matches(http_request: &HttpRequest, etag: &str) -> bool {
+    http_request
+        .headers()
+        .get_all(header::IF_NONE_MATCH)
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|tag| tag.trim())
+        // If-None-Match uses the weak comparison
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
 // Custom 404 handler for static files
 pub async fn notfound_404() -> HttpResponse {
-    HttpResponse::NotFound()
-        .content_type("text/html")
-        .body("<center><h1>404 - File Not Found</h1><p>The requested file does not exist.</p></center>")
+    HttpResponse::NotFound().content_type("text/html").body(
+        "<center><h1>404 - File Not Found</h1><p>The requested file does not exist.</p></center>",
+    )
 }
-
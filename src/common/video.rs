@@ -0,0 +1,264 @@
+use std::path::Path;
+
+use crate::common::models::file_metadata::VideoMetadata;
+
+/// Path of the poster frame generated for a video, `<dir>/<stem>.poster.jpg`
+pub fn poster_path(video_path: &str) -> String {
+    let path = Path::new(video_path);
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    match path.parent().map(|parent| parent.to_string_lossy().to_string()) {
+        Some(parent) if !parent.is_empty() => format!("{}/{}.poster.jpg", parent, stem),
+        _ => format!("{}.poster.jpg", stem),
+    }
+}
+
+/// Read duration, codec, resolution and bitrate with ffprobe and generate a poster frame with
+/// ffmpeg next to the video. Returns `None` when ffprobe fails or finds no video stream.
+#[cfg(feature = "video")]
+pub fn get_video_metadata(file_path: &str) -> Option<VideoMetadata> {
+    use std::process::Command;
+
+    use serde_json::Value;
+
+    use crate::common::config;
+
+    let video_config = &config::get().video;
+    let output = Command::new(&video_config.ffprobe_path)
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "v:0",
+            "-show_entries",
+            "stream=codec_name,width,height,bit_rate:format=duration,bit_rate",
+            "-of",
+            "json",
+            file_path,
+        ])
+        .output()
+        .map_err(|e| eprintln!("Failed to run {}: {}", video_config.ffprobe_path, e))
+        .ok()?;
+    if !output.status.success() {
+        eprintln!(
+            "ffprobe failed for {}: {}",
+            file_path,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        return None;
+    }
+    let probe: Value = serde_json::from_slice(&output.stdout).ok()?;
+    let stream = probe["streams"].as_array()?.first()?;
+    let format = &probe["format"];
+    let duration_seconds = number(&format["duration"]).unwrap_or(0.0);
+    // Streams in containers such as webm carry no bitrate, fall back to the container bitrate
+    let bitrate = number(&stream["bit_rate"])
+        .or_else(|| number(&format["bit_rate"]))
+        .unwrap_or(0.0);
+
+    let poster = poster_path(file_path);
+    let poster_at = if duration_seconds > 0.0 {
+        video_config.poster_at_seconds.min(duration_seconds / 2.0)
+    } else {
+        0.0
+    };
+    let poster_generated = Command::new(&video_config.ffmpeg_path)
+        .args(["-v", "error", "-y", "-ss", &format!("{:.3}", poster_at), "-i", file_path])
+        .args(["-frames:v", "1", &poster])
+        .status()
+        .map(|status| status.success())
+        .unwrap_or_else(|e| {
+            eprintln!("Failed to run {}: {}", video_config.ffmpeg_path, e);
+            false
+        });
+
+    Some(VideoMetadata {
+        thumbnail: if poster_generated && Path::new(&poster).is_file() {
+            Some(poster)
+        } else {
+            None
+        },
+        width: stream["width"].as_u64().unwrap_or(0) as u32,
+        height: stream["height"].as_u64().unwrap_or(0) as u32,
+        duration_ms: (duration_seconds * 1000.0).round() as u64,
+        codec: stream["codec_name"].as_str().unwrap_or_default().to_string(),
+        bitrate: bitrate.round() as u64,
+    })
+}
+
+#[cfg(not(feature = "video"))]
+pub fn get_video_metadata(_file_path: &str) -> Option<VideoMetadata> {
+    None
+}
+
+/// ffprobe prints numbers as JSON strings
+#[cfg(feature = "video")]
+fn number(value: &serde_json::Value) -> Option<f64> {
+    match value {
+        serde_json::Value::String(s) => s.parse().ok(),
+        other => other.as_f64(),
+    }
+}
+
+/// Whether the binary was built with the `video` feature needed for HLS packaging
+pub fn hls_available() -> bool {
+    cfg!(feature = "video")
+}
+
+/// Package a video into the configured HLS renditions and a `master.m3u8` inside `output_dir`,
+/// replacing any previous packaging. Returns the path of the master playlist.
+#[cfg(feature = "video")]
+pub fn package_hls(
+    input: &str,
+    output_dir: &str,
+    source_width: u32,
+    source_height: u32,
+) -> Result<String, String> {
+    use std::fs;
+    use std::process::Command;
+
+    use crate::common::config;
+
+    let video_config = &config::get().video;
+    let mut renditions: Vec<_> = video_config
+        .hls_renditions
+        .iter()
+        .filter(|rendition| source_height == 0 || rendition.height <= source_height)
+        .collect();
+    if renditions.is_empty() {
+        // Source smaller than every rendition, keep the lowest one
+        renditions = video_config
+            .hls_renditions
+            .iter()
+            .min_by_key(|rendition| rendition.height)
+            .into_iter()
+            .collect();
+    }
+    if renditions.is_empty() {
+        return Err(String::from("No HLS renditions configured"));
+    }
+
+    // Build next to the final directory and swap it in once every rendition succeeded
+    let staging_dir = format!("{}.tmp-{}", output_dir, uuid::Uuid::new_v4());
+    fs::create_dir_all(&staging_dir).map_err(|e| format!("{}: {}", staging_dir, e))?;
+    let mut master = String::from("#EXTM3U\n#EXT-X-VERSION:3\n");
+    for rendition in renditions {
+        let output = Command::new(&video_config.ffmpeg_path)
+            .args(["-v", "error", "-y", "-i", input])
+            .args(["-vf", &format!("scale=-2:{}", rendition.height)])
+            .args(["-c:v", "libx264", "-preset", "veryfast", "-profile:v", "main"])
+            .args(["-b:v", &format!("{}k", rendition.video_bitrate_kbps)])
+            .args(["-maxrate", &format!("{}k", rendition.video_bitrate_kbps * 107 / 100)])
+            .args(["-bufsize", &format!("{}k", rendition.video_bitrate_kbps * 3 / 2)])
+            .args(["-c:a", "aac", "-ac", "2"])
+            .args(["-b:a", &format!("{}k", rendition.audio_bitrate_kbps)])
+            .args(["-f", "hls", "-hls_playlist_type", "vod"])
+            .args(["-hls_time", &video_config.hls_segment_seconds.to_string()])
+            .args([
+                "-hls_segment_filename",
+                &format!("{}/{}_%04d.ts", staging_dir, rendition.name),
+                &format!("{}/{}.m3u8", staging_dir, rendition.name),
+            ])
+            .output();
+        let failure = match output {
+            Ok(output) if output.status.success() => None,
+            Ok(output) => Some(format!(
+                "ffmpeg failed for {}: {}",
+                rendition.name,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )),
+            Err(e) => Some(format!("Failed to run {}: {}", video_config.ffmpeg_path, e)),
+        };
+        if let Some(failure) = failure {
+            let _ = fs::remove_dir_all(&staging_dir);
+            return Err(failure);
+        }
+        let width = if source_width > 0 && source_height > 0 {
+            // Same rounding to an even width as `scale=-2`
+            ((rendition.height as f64 * source_width as f64 / source_height as f64 / 2.0).round()
+                as u32)
+                * 2
+        } else {
+            rendition.height * 16 / 9
+        };
+        master.push_str(&format!(
+            "#EXT-X-STREAM-INF:BANDWIDTH={},RESOLUTION={}x{},NAME=\"{}\"\n{}.m3u8\n",
+            (rendition.video_bitrate_kbps + rendition.audio_bitrate_kbps) * 1000,
+            width,
+            rendition.height,
+            rendition.name,
+            rendition.name
+        ));
+    }
+    let master_path = format!("{}/master.m3u8", staging_dir);
+    if let Err(e) = fs::write(&master_path, master) {
+        let _ = fs::remove_dir_all(&staging_dir);
+        return Err(format!("{}: {}", master_path, e));
+    }
+    if Path::new(output_dir).exists() {
+        fs::remove_dir_all(output_dir).map_err(|e| format!("{}: {}", output_dir, e))?;
+    }
+    fs::rename(&staging_dir, output_dir).map_err(|e| format!("{}: {}", output_dir, e))?;
+    Ok(format!("{}/master.m3u8", output_dir))
+}
+
+#[cfg(not(feature = "video"))]
+pub fn package_hls(
+    _input: &str,
+    _output_dir: &str,
+    _source_width: u32,
+    _source_height: u32,
+) -> Result<String, String> {
+    Err(String::from("HLS packaging requires the video feature"))
+}
+
+/// A variant stream listed in an HLS master playlist
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HlsVariant {
+    pub name: String,
+    /// Playlist of the variant, relative to the master playlist
+    pub uri: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Read the variant streams of a master playlist written by `package_hls`, empty when the
+/// playlist is missing
+pub fn read_hls_variants(master_path: &str) -> Vec<HlsVariant> {
+    let Ok(playlist) = std::fs::read_to_string(master_path) else {
+        return vec![];
+    };
+    let mut variants = vec![];
+    let mut lines = playlist.lines().map(str::trim);
+    while let Some(line) = lines.next() {
+        let Some(attributes) = line.strip_prefix("#EXT-X-STREAM-INF:") else {
+            continue;
+        };
+        let Some(uri) = lines.by_ref().find(|line| !line.is_empty() && !line.starts_with('#'))
+        else {
+            break;
+        };
+        let mut variant = HlsVariant {
+            name: uri.trim_end_matches(".m3u8").to_string(),
+            uri: uri.to_string(),
+            width: 0,
+            height: 0,
+        };
+        for attribute in attributes.split(',') {
+            match attribute.split_once('=') {
+                Some(("RESOLUTION", resolution)) => {
+                    if let Some((width, height)) = resolution.split_once('x') {
+                        variant.width = width.parse().unwrap_or_default();
+                        variant.height = height.parse().unwrap_or_default();
+                    }
+                }
+                Some(("NAME", name)) => variant.name = name.trim_matches('"').to_string(),
+                _ => {}
+            }
+        }
+        variants.push(variant);
+    }
+    variants
+}
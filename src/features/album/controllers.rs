@@ -2,15 +2,18 @@ use actix_multipart::Multipart;
 use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
 
 use crate::common::database::DbPool;
-use crate::common::enums::Role::Admin;
+use crate::common::enums::Role::{Admin, Unknown, User};
+use crate::common::i18n::{requested_locales, set_language_headers, vary_on_language};
 use crate::common::models::response_message::ResponseMessage;
-use crate::common::utils::{delete_directory_if_exists, parse_payload_data};
+use crate::common::utils::parse_payload_data;
 use crate::features::album::models::{
-    AddAlbumImagesRequest, AlbumResponse, CreateAlbumRequest, GetAlbumRequest,
-    RemoveAlbumImagesRequest, UpdateAlbumRequest,
+    AddAlbumImagesRequest, CreateAlbumRequest, GetAlbumRequest, RemoveAlbumImagesRequest,
+    TrendingAlbumsRequest, UpdateAlbumRequest,
 };
 use crate::features::album::services::Service;
-use crate::features::check_role;
+use crate::features::analytics::services::Service as AnalyticsService;
+use crate::features::creator::models::SetAlbumCreditsRequest;
+use crate::features::{album_refusal, audit_mutation, check_role, reader_age};
 
 /// Create Album
 ///
@@ -39,18 +42,29 @@ pub async fn create_album(
     http_request: HttpRequest,
     payload: Multipart,
 ) -> impl Responder {
-    if check_role(http_request) == Admin {
+    if check_role(http_request.clone()) == Admin {
         match parse_payload_data(payload).await {
-            Ok((payload_data, tmp_path)) => {
+            Ok((payload_data, _tmp_dir)) => {
                 let req_data = CreateAlbumRequest::from_payload_data(payload_data).await;
+                if let Err(e) = Service::check_categories(&pool, &req_data.categories()).await {
+                    return HttpResponse::BadRequest().json(ResponseMessage { message: e });
+                }
                 match Service::create_album(&pool, req_data).await {
                     Ok(new_album) => {
-                        delete_directory_if_exists(&tmp_path);
+                        audit_mutation(
+                            &pool,
+                            &http_request,
+                            "create",
+                            "album",
+                            new_album.uuid.clone(),
+                            None,
+                            serde_json::to_value(&new_album).ok(),
+                        )
+                        .await;
                         HttpResponse::Created().json(new_album)
                     }
                     Err(e) => {
                         println!("Failed to create album: {}", e);
-                        delete_directory_if_exists(&tmp_path);
                         HttpResponse::InternalServerError().json(ResponseMessage {
                             message: String::from("Internal Server Error"),
                         })
@@ -70,10 +84,15 @@ pub async fn create_album(
 
 /// Get Albums
 ///
-/// Get album list
+/// Get album list, titles and descriptions in the best locale of `lang` or `Accept-Language`.
+/// Albums rated above the age of the reader in `X-Reader-Profile` are left out.
 #[utoipa::path(
     post,
     path = "/albums",
+    params(
+        ("lang" = Option<String>, Query, description = "Preferred locales, comma separated, before Accept-Language", example = "pt-BR"),
+        ("X-Reader-Profile" = Option<String>, Header, description = "Reader profile the album ratings are checked against")
+    ),
     request_body = GetAlbumRequest,
     responses(
     (status = 200, description = "Request successfully", body = ResponseDataAlbum),
@@ -91,10 +110,64 @@ pub async fn get_albums(
     http_request: HttpRequest,
     req: web::Json<GetAlbumRequest>,
 ) -> impl Responder {
-    if check_role(http_request) == Admin {
+    if check_role(http_request.clone()) != Unknown {
         let album_filters = req.into_inner();
-        match Service::get_albums(&pool, album_filters).await {
-            Ok(data) => HttpResponse::Ok().json(data),
+        let locales = requested_locales(&http_request);
+        let reader_age = reader_age(&pool, &http_request).await;
+        match Service::get_albums(&pool, album_filters, &locales, reader_age).await {
+            Ok(data) => {
+                let mut builder = HttpResponse::Ok();
+                vary_on_language(&mut builder);
+                builder.json(data)
+            }
+            Err(err) => HttpResponse::BadRequest().json(ResponseMessage {
+                message: err.to_string(),
+            }),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Get Trending Albums
+///
+/// Get enabled albums ranked by album views and episode reads within a window of days. Albums
+/// rated above the age of the reader in `X-Reader-Profile` are left out.
+#[utoipa::path(
+    get,
+    path = "/albums/trending",
+    params(
+        TrendingAlbumsRequest,
+        ("X-Reader-Profile" = Option<String>, Header, description = "Reader profile the album ratings are checked against")
+    ),
+    responses(
+    (status = 200, description = "Request successfully", body = ResponseDataTrendingAlbum),
+    (status = 400, description = "Bad Request", body = ResponseMessage),
+    (status = 401, description = "Unauthorized error", body = ResponseMessage),
+    (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+    ("api_key" = [])
+    ),
+    tag = "Album",
+)]
+#[get("/albums/trending")]
+pub async fn get_trending_albums(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    req: web::Query<TrendingAlbumsRequest>,
+) -> impl Responder {
+    if check_role(http_request.clone()) != Unknown {
+        let locales = requested_locales(&http_request);
+        let reader_age = reader_age(&pool, &http_request).await;
+        match Service::get_trending_albums(&pool, req.into_inner(), &locales, reader_age).await {
+            Ok(data) => {
+                let mut builder = HttpResponse::Ok();
+                vary_on_language(&mut builder);
+                builder.json(data)
+            }
             Err(err) => HttpResponse::BadRequest().json(ResponseMessage {
                 message: err.to_string(),
             }),
@@ -108,16 +181,21 @@ pub async fn get_albums(
 
 /// Get Albums By Uuid
 ///
-/// Get album by Uuid
+/// Get album by Uuid in the best locale of `lang` or `Accept-Language`, reported in
+/// `Content-Language`. Albums rated above the age of the reader in `X-Reader-Profile` are refused.
 #[utoipa::path(
     get,
     path = "/albums/{album_uuid}",
     params(
-        ("album_uuid" = String, Path, description = "Album ID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e")
+        ("album_uuid" = String, Path, description = "Album ID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e"),
+        ("lang" = Option<String>, Query, description = "Preferred locales, comma separated, before Accept-Language", example = "pt-BR"),
+        ("X-Reader-Profile" = Option<String>, Header, description = "Reader profile the album ratings are checked against")
     ),
     responses(
     (status = 200, description = "Request successfully", body = AlbumResponse),
     (status = 401, description = "Unauthorized error", body = ResponseMessage),
+    (status = 403, description = "Rated album and the reader's age is unknown", body = AgeRestrictedResponse),
+    (status = 451, description = "Reader younger than the album rating", body = AgeRestrictedResponse),
     (status = 500, description = "Internal server error", body = ResponseMessage)
     ),
     security(
@@ -131,10 +209,28 @@ pub async fn get_album_by_uuid(
     http_request: HttpRequest,
     path: web::Path<String>,
 ) -> impl Responder {
-    if check_role(http_request) == Admin {
+    if check_role(http_request.clone()) != Unknown {
         let album_uuid = path.into_inner();
+        let reader_age = reader_age(&pool, &http_request).await;
         match Service::get_album_by_uuid(&pool, album_uuid).await {
-            Ok(data) => HttpResponse::Ok().json(AlbumResponse::from_album(data)),
+            Ok(data) => {
+                if let Some(refusal) = album_refusal(reader_age, data.enable, data.min_age) {
+                    return refusal;
+                }
+                // Only reader traffic counts, admin requests are left out
+                if check_role(http_request.clone()) == User {
+                    AnalyticsService::record_album_view(&pool, data.id).await;
+                }
+                let response = Service::localized_album_response(
+                    &pool,
+                    data,
+                    &requested_locales(&http_request),
+                )
+                .await;
+                let mut builder = HttpResponse::Ok();
+                set_language_headers(&mut builder, &response.locale);
+                builder.json(response)
+            }
             Err(err) => HttpResponse::BadRequest().json(ResponseMessage {
                 message: err.to_string(),
             }),
@@ -175,19 +271,33 @@ pub async fn update_album(
     path: web::Path<String>,
     payload: Multipart,
 ) -> impl Responder {
-    if check_role(http_request) == Admin {
+    if check_role(http_request.clone()) == Admin {
         let album_uuid = path.into_inner();
+        let before = album_snapshot(&pool, &album_uuid).await;
         match parse_payload_data(payload).await {
-            Ok((payload_data, tmp_path)) => {
+            Ok((payload_data, _tmp_dir)) => {
                 let req_data = UpdateAlbumRequest::from_payload_data(payload_data).await;
+                if let Some(category_ids) = req_data.categories() {
+                    if let Err(e) = Service::check_categories(&pool, &category_ids).await {
+                        return HttpResponse::BadRequest().json(ResponseMessage { message: e });
+                    }
+                }
                 match Service::update_album(&pool, album_uuid, req_data).await {
                     Ok(updated_album) => {
-                        delete_directory_if_exists(&tmp_path);
+                        audit_mutation(
+                            &pool,
+                            &http_request,
+                            "update",
+                            "album",
+                            updated_album.uuid.clone(),
+                            before,
+                            serde_json::to_value(&updated_album).ok(),
+                        )
+                        .await;
                         HttpResponse::Ok().json(updated_album)
                     }
                     Err(e) => {
                         println!("Failed to update album: {}", e);
-                        delete_directory_if_exists(&tmp_path);
                         HttpResponse::BadRequest().json(ResponseMessage {
                             message: String::from("Failed to update album"),
                         })
@@ -234,18 +344,27 @@ pub async fn add_album_images(
     path: web::Path<String>,
     payload: Multipart,
 ) -> impl Responder {
-    if check_role(http_request) == Admin {
+    if check_role(http_request.clone()) == Admin {
         let album_uuid = path.into_inner();
+        let before = album_snapshot(&pool, &album_uuid).await;
         match parse_payload_data(payload).await {
-            Ok((payload_data, tmp_path)) => {
+            Ok((payload_data, _tmp_dir)) => {
                 let req_data = AddAlbumImagesRequest::from_payload_data(payload_data).await;
                 match Service::add_album_images(&pool, album_uuid, req_data).await {
                     Ok(album_response) => {
-                        delete_directory_if_exists(&tmp_path);
+                        audit_mutation(
+                            &pool,
+                            &http_request,
+                            "update",
+                            "album",
+                            album_response.uuid.clone(),
+                            before,
+                            serde_json::to_value(&album_response).ok(),
+                        )
+                        .await;
                         HttpResponse::Created().json(album_response)
                     }
                     Err(e) => {
-                        delete_directory_if_exists(&tmp_path);
                         HttpResponse::BadRequest().json(ResponseMessage {
                             message: String::from(e),
                         })
@@ -288,16 +407,81 @@ pub async fn remove_album_images(
     path: web::Path<String>,
     req: web::Json<RemoveAlbumImagesRequest>,
 ) -> impl Responder {
-    if check_role(http_request) == Admin {
+    if check_role(http_request.clone()) == Admin {
         let album_uuid = path.into_inner();
         let req_rmv_album_cover = req.into_inner();
+        let before = album_snapshot(&pool, &album_uuid).await;
         match Service::remove_album_images(&pool, album_uuid, req_rmv_album_cover).await {
-            Ok(response) => HttpResponse::Ok().json(response),
-            Err(e) => {
-                HttpResponse::BadRequest().json(ResponseMessage {
-                    message: String::from(e),
-                })
+            Ok(response) => {
+                audit_mutation(
+                    &pool,
+                    &http_request,
+                    "update",
+                    "album",
+                    response.uuid.clone(),
+                    before,
+                    serde_json::to_value(&response).ok(),
+                )
+                .await;
+                HttpResponse::Ok().json(response)
+            }
+            Err(e) => HttpResponse::BadRequest().json(ResponseMessage {
+                message: String::from(e),
+            }),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Set Album Credits
+///
+/// Replace the authors, artists, translators and publishers credited on the album
+#[utoipa::path(
+    put,
+    path = "/album/{album_uuid}/credits",
+    params(
+        ("album_uuid" = String, Path, description = "Album ID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e")
+    ),
+    request_body = SetAlbumCreditsRequest,
+    responses(
+        (status = 200, description = "Update successfully", body = AlbumResponse),
+        (status = 400, description = "Invalid credits", body = ResponseMessage),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Album",
+)]
+#[put("/{album_uuid}/credits")]
+pub async fn set_album_credits(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+    req: web::Json<SetAlbumCreditsRequest>,
+) -> impl Responder {
+    if check_role(http_request.clone()) == Admin {
+        let album_uuid = path.into_inner();
+        let before = album_snapshot(&pool, &album_uuid).await;
+        match Service::set_album_credits(&pool, album_uuid, req.into_inner()).await {
+            Ok(response) => {
+                audit_mutation(
+                    &pool,
+                    &http_request,
+                    "update",
+                    "album",
+                    response.uuid.clone(),
+                    before,
+                    serde_json::to_value(&response).ok(),
+                )
+                .await;
+                HttpResponse::Ok().json(response)
             }
+            Err(e) => HttpResponse::BadRequest().json(ResponseMessage { message: e }),
         }
     } else {
         HttpResponse::Unauthorized().json(ResponseMessage {
@@ -332,10 +516,23 @@ pub async fn delete_album(
     http_request: HttpRequest,
     path: web::Path<String>,
 ) -> impl Responder {
-    if check_role(http_request) == Admin {
+    if check_role(http_request.clone()) == Admin {
         let album_uuid = path.into_inner();
-        match Service::delete_album(&pool, album_uuid).await {
-            Ok(size) if size > 0 => HttpResponse::NoContent().json(""),
+        let before = album_snapshot(&pool, &album_uuid).await;
+        match Service::delete_album(&pool, album_uuid.clone()).await {
+            Ok(size) if size > 0 => {
+                audit_mutation(
+                    &pool,
+                    &http_request,
+                    "delete",
+                    "album",
+                    album_uuid,
+                    before,
+                    None,
+                )
+                .await;
+                HttpResponse::NoContent().json("")
+            }
             Ok(_) => HttpResponse::BadRequest().json(ResponseMessage {
                 message: String::from("No album found"),
             }),
@@ -352,3 +549,10 @@ pub async fn delete_album(
         })
     }
 }
+
+async fn album_snapshot(pool: &DbPool, album_uuid: &str) -> Option<serde_json::Value> {
+    let album = Service::get_album_by_uuid(pool, album_uuid.to_string())
+        .await
+        .ok()?;
+    serde_json::to_value(Service::album_response(pool, album).await).ok()
+}
@@ -11,6 +11,8 @@ pub mod services;
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(create_album);
     cfg.service(get_albums);
+    // Registered before `/albums/{album_uuid}` so "trending" is not taken for a uuid
+    cfg.service(get_trending_albums);
     cfg.service(get_album_by_uuid);
     cfg.service(add_album_images);
 
@@ -18,6 +20,7 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         web::scope("/album")
             .service(update_album)
             .service(delete_album)
-            .service(remove_album_images),
+            .service(remove_album_images)
+            .service(set_album_credits),
     );
 }
@@ -6,10 +6,17 @@ use std::collections::HashMap;
 use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
+use crate::common::i18n::default_locale;
 use crate::common::models::file_metadata::ImageMetadata;
 use crate::common::ne_parse::NEParse;
+use crate::common::pagination::{PageQuery, SortOrder};
 use crate::common::utils::{get_data_directory, get_file_metadata};
-use crate::schema::albums;
+use crate::features::category::models::CategoryResponse;
+use crate::features::creator::models::{AlbumCreditResponse, CreditRole};
+use crate::schema::{album_categories, albums};
+
+/// Most albums returned by the trending ranking
+pub const MAX_TRENDING_ALBUMS_LIMIT: i64 = 100;
 
 #[derive(
     Debug,
@@ -23,7 +30,6 @@ use crate::schema::albums;
     Clone,
     IntoParams,
     PartialEq,
-    Eq,
 )]
 #[diesel(table_name = albums)]
 pub struct Album {
@@ -46,20 +52,40 @@ pub struct Album {
     pub broken_at: Option<NaiveDateTime>,
     pub created_at: Option<NaiveDateTime>,
     pub updated_at: Option<NaiveDateTime>,
+    pub deleted_at: Option<NaiveDateTime>,
+    /// Kept by the reviews, album updates write back the stored value
+    pub rating_average: f64,
+    pub rating_count: i32,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, ToSchema, IntoParams, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema, IntoParams, PartialEq)]
 pub struct AlbumResponse {
     pub id: i32,
     pub uuid: String,
+    /// Main category, the first of `categories`
     pub category_id: i32,
+    /// Every category of the album in order
+    pub categories: Vec<CategoryResponse>,
+    /// Authors, artists, translators and publishers of the album in order
+    pub credits: Vec<AlbumCreditResponse>,
     pub title: String,
     pub description: String,
+    /// Language of the title and description
+    #[schema(example = "en")]
+    pub locale: String,
+    /// Languages the album has a title in, the default locale first
+    #[schema(example = json!(["en", "pt-br"]))]
+    pub available_locales: Vec<String>,
     pub completed: bool,
     pub images: Vec<String>,
     pub tags: Option<String>,
     pub enable: bool,
     pub min_age: i32,
+    /// Average stars of the approved reviews, 0 without ratings
+    #[schema(example = 4.25)]
+    pub rating_average: f64,
+    /// Number of approved reviews
+    pub rating_count: i32,
     pub url: String,
     pub content_type: String,
     pub width: i32,
@@ -77,8 +103,12 @@ impl AlbumResponse {
             id: album.id,
             uuid: album.uuid,
             category_id: album.category_id,
+            categories: vec![],
+            credits: vec![],
             title: album.title,
             description: album.description,
+            locale: default_locale(),
+            available_locales: vec![default_locale()],
             completed: album.completed,
             images: album
                 .images
@@ -89,6 +119,8 @@ impl AlbumResponse {
             tags: album.tags,
             enable: album.enable,
             min_age: album.min_age,
+            rating_average: album.rating_average,
+            rating_count: album.rating_count,
             url: album.url,
             content_type: album.content_type,
             width: album.width,
@@ -126,14 +158,22 @@ pub struct CreateAlbumRequest {
     /// Minimum age of your album default is 0
     #[schema(example = 1)]
     pub min_age: Option<i32>,
-    /// Album Category
+    /// Main album category, placed first when category_ids is also given
     #[schema(example = 1)]
-    pub category_id: i32,
+    pub category_id: Option<i32>,
+    /// Album categories as comma separated IDs, e.g. 1,4,7
+    #[schema(value_type = Option < String >, example = "1,4")]
+    pub category_ids: Option<Vec<i32>>,
     /// Start released date of your album
     pub released_at: Option<NaiveDateTime>,
 }
 
 impl CreateAlbumRequest {
+    /// Requested categories in order without duplicates
+    pub fn categories(&self) -> Vec<i32> {
+        merge_category_ids(self.category_id, self.category_ids.clone())
+    }
+
     pub async fn from_payload_data(payload_data: HashMap<String, Value>) -> Self {
         let image_paths: Vec<String> = if payload_data.contains_key("cover") {
             NEParse::opt_immut_vec_serde_json_value_to_vec_string(payload_data["cover"].as_array())
@@ -171,7 +211,12 @@ impl CreateAlbumRequest {
             } else {
                 Some(0)
             },
-            category_id: NEParse::opt_immut_str_to_opt_i32(payload_data["category_id"].as_str()).unwrap(),
+            category_id: NEParse::opt_immut_str_to_opt_i32(
+                payload_data.get("category_id").and_then(|v| v.as_str()),
+            ),
+            category_ids: NEParse::opt_immut_str_to_opt_vec_i32(
+                payload_data.get("category_ids").and_then(|v| v.as_str()),
+            ),
             released_at: if payload_data.contains_key("released_at") {
                 NEParse::opt_immut_str_to_opt_naive_datetime(payload_data["released_at"].as_str())
             } else {
@@ -202,9 +247,12 @@ pub struct UpdateAlbumRequest {
     /// Minimum age of your album default is 0
     #[schema(example = 0)]
     pub min_age: Option<i32>,
-    /// Album Category
+    /// Main album category, placed first when category_ids is also given
     #[schema(example = 1)]
-    pub category_id: i32,
+    pub category_id: Option<i32>,
+    /// Replace the album categories, comma separated IDs, e.g. 1,4,7
+    #[schema(value_type = Option < String >, example = "1,4")]
+    pub category_ids: Option<Vec<i32>>,
     /// Start released date of your album
     pub released_at: Option<NaiveDateTime>,
     /// Set empty if your album fix
@@ -212,6 +260,14 @@ pub struct UpdateAlbumRequest {
 }
 
 impl UpdateAlbumRequest {
+    /// Requested categories in order without duplicates, `None` keeps the current ones
+    pub fn categories(&self) -> Option<Vec<i32>> {
+        if self.category_id.is_none() && self.category_ids.is_none() {
+            return None;
+        }
+        Some(merge_category_ids(self.category_id, self.category_ids.clone()))
+    }
+
     pub async fn from_payload_data(payload_data: HashMap<String, Value>) -> Self {
         let mut image_paths: Vec<String> = vec![];
         if !payload_data["cover"].is_null() {
@@ -235,7 +291,12 @@ impl UpdateAlbumRequest {
             tags: payload_data["tags"].as_str().map(|value| value.to_string()),
             enable: NEParse::opt_immut_str_to_option_bool(payload_data["enable"].as_str()),
             min_age: NEParse::opt_immut_str_to_opt_i32(payload_data["min_age"].as_str()),
-            category_id: NEParse::opt_immut_str_to_opt_i32(payload_data["min_age"].as_str()).unwrap(),
+            category_id: NEParse::opt_immut_str_to_opt_i32(
+                payload_data.get("category_id").and_then(|v| v.as_str()),
+            ),
+            category_ids: NEParse::opt_immut_str_to_opt_vec_i32(
+                payload_data.get("category_ids").and_then(|v| v.as_str()),
+            ),
             released_at: NEParse::opt_immut_str_to_opt_naive_datetime(
                 payload_data["released_at"].as_str(),
             ),
@@ -290,7 +351,7 @@ pub struct GetAlbumRequest {
     /// Filter tags
     #[schema(example = "")]
     pub tags: Option<String>,
-    /// Filter enable
+    /// Filter enable, readers only ever get enabled albums
     #[schema(example = "null")]
     pub enable: Option<bool>,
     #[schema(example = "null")]
@@ -298,18 +359,87 @@ pub struct GetAlbumRequest {
     /// Filter minimum age
     #[schema(example = 0)]
     pub min_age: Option<i32>,
+    /// Filter category ID, albums of its sub categories included
+    #[schema(example = 0)]
+    pub category_id: Option<i32>,
+    /// Filter category slug, albums of its sub categories included
+    #[schema(example = "")]
+    pub category: Option<String>,
+    /// Filter category IDs, albums of their sub categories included
+    #[schema(example = json!([]))]
+    pub category_ids: Option<Vec<i32>>,
+    /// Whether albums need any (default) or all of the filtered categories
+    #[schema(example = "null")]
+    pub category_match: Option<CategoryMatch>,
+    /// Filter creator UUID, albums crediting the creator in any role
+    #[schema(example = "")]
+    pub creator: Option<String>,
+    /// Only count credits of this role when filtering by creator
+    #[schema(example = "null")]
+    pub creator_role: Option<CreditRole>,
     /// Filter offset
     #[schema(example = 0)]
     pub offset: Option<i64>,
     /// Filter limit (Get all for 0)
     #[schema(example = 20)]
     pub limit: Option<i64>,
+    /// Sort field (id, title, created_at, updated_at, released_at, trending, most_read,
+    /// recently_updated, rating, rating_count), id when empty
+    #[schema(example = "null")]
+    pub sort: Option<String>,
+    /// Sort order (asc, desc), descending for dates and rankings when empty
+    #[schema(example = "null")]
+    pub order: Option<SortOrder>,
+    /// `next_cursor` of the previous page, replaces offset
+    #[schema(example = "null")]
+    pub cursor: Option<String>,
+    /// Days of views ranked by the `trending` sort, `analytics.trending_window_days` when empty
+    #[schema(example = "null")]
+    pub trending_days: Option<i64>,
+}
+
+impl GetAlbumRequest {
+    pub fn page_query(&self) -> PageQuery {
+        PageQuery {
+            sort: self.sort.clone(),
+            order: self.order,
+            cursor: self.cursor.clone(),
+            limit: self.limit,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CategoryMatch {
+    Any,
+    All,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TrendingAlbumsRequest {
+    /// Days of views counted, `analytics.trending_window_days` when empty
+    pub days: Option<i64>,
+    /// Number of albums (default 20, at most 100)
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct TrendingAlbumResponse {
+    #[serde(flatten)]
+    pub album: AlbumResponse,
+    /// Album fetches within the window
+    pub views: i64,
+    /// Episode reads within the window
+    pub reads: i64,
 }
 
 #[derive(Debug, Insertable, ToSchema, Deserialize)]
 #[diesel(table_name = albums)]
 pub struct NewAlbum {
     pub uuid: String,
+    pub category_id: i32,
     pub title: String,
     pub description: String,
     pub completed: bool,
@@ -327,6 +457,7 @@ pub struct NewAlbum {
 
 impl NewAlbum {
     pub fn from_request(req: CreateAlbumRequest) -> Self {
+        let category_id = req.categories().first().copied().unwrap_or_default();
         let album_uuid = Uuid::new_v4().to_string();
         let image_uuid = Uuid::new_v4().to_string();
         let file_metadata = get_file_metadata(&req.cover);
@@ -355,6 +486,7 @@ impl NewAlbum {
 
         NewAlbum {
             uuid: album_uuid,
+            category_id,
             title: req.title,
             description: req.description,
             completed: req.completed.unwrap_or(false),
@@ -378,3 +510,21 @@ impl NewAlbum {
         }
     }
 }
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = album_categories)]
+pub struct NewAlbumCategory {
+    pub album_id: i32,
+    pub category_id: i32,
+    pub position: i32,
+}
+
+fn merge_category_ids(category_id: Option<i32>, category_ids: Option<Vec<i32>>) -> Vec<i32> {
+    let mut merged: Vec<i32> = vec![];
+    for id in category_id.into_iter().chain(category_ids.unwrap_or_default()) {
+        if !merged.contains(&id) {
+            merged.push(id);
+        }
+    }
+    merged
+}
@@ -1,14 +1,69 @@
 use crate::common::database::DbPool;
 use crate::common::models::response_data::ResponseData;
+use crate::common::pagination::{Page, SortField, SortKind, SortOrder};
 use crate::features::album::models;
-use crate::schema::albums;
+use crate::features::category::models::Category;
+use crate::features::creator::models::CreditRole;
 use crate::schema::albums::{broken_at, id};
-use diesel::dsl::count_star;
+use crate::schema::{album_categories, album_credits, albums, category};
+use chrono::Utc;
+use diesel::dsl::{count_star, sql};
 use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Bool, Text};
+
+/// Album fetches counted since `since` (inclusive, `YYYY-MM-DD`)
+fn album_views_sql(since: &str) -> String {
+    format!(
+        "(SELECT COALESCE(SUM(album_daily_views.views), 0) FROM album_daily_views \
+         WHERE album_daily_views.album_id = albums.id AND album_daily_views.day >= '{}')",
+        since
+    )
+}
+
+/// Episode reads counted since `since`, all time for an empty `since`
+fn album_reads_sql(since: &str) -> String {
+    format!(
+        "(SELECT COALESCE(SUM(album_daily_views.reads), 0) FROM album_daily_views \
+         WHERE album_daily_views.album_id = albums.id AND album_daily_views.day >= '{}')",
+        since
+    )
+}
+
+/// Latest update of the album or one of its episodes
+const ALBUM_LAST_UPDATE_SQL: &str = "MAX(COALESCE(albums.updated_at, albums.created_at, ''), \
+     COALESCE((SELECT MAX(episodes.updated_at) FROM episodes \
+     WHERE episodes.album_id = albums.id AND episodes.deleted_at IS NULL), ''))";
 
 pub struct Repository;
 
 impl Repository {
+    /// Sort options of the album list, `trending` counts views since `trending_since`
+    pub fn sort_fields(trending_since: &str) -> Vec<SortField> {
+        use SortKind::*;
+        use SortOrder::*;
+        vec![
+            SortField::new("id", "albums.id", Integer, Asc),
+            SortField::new("title", "albums.title", Text, Asc),
+            SortField::new("created_at", "COALESCE(albums.created_at, '')", Text, Desc),
+            SortField::new("updated_at", "COALESCE(albums.updated_at, '')", Text, Desc),
+            SortField::new("released_at", "COALESCE(albums.released_at, '')", Text, Desc),
+            SortField::new(
+                "trending",
+                format!(
+                    "({} + {})",
+                    album_views_sql(trending_since),
+                    album_reads_sql(trending_since)
+                ),
+                Integer,
+                Desc,
+            ),
+            SortField::new("most_read", album_reads_sql(""), Integer, Desc),
+            SortField::new("recently_updated", ALBUM_LAST_UPDATE_SQL, Text, Desc),
+            SortField::new("rating", "albums.rating_average", Real, Desc),
+            SortField::new("rating_count", "albums.rating_count", Integer, Desc),
+        ]
+    }
+
     pub async fn create_album(
         pool: &DbPool,
         new_album: models::NewAlbum,
@@ -25,11 +80,15 @@ impl Repository {
     pub async fn get_albums(
         pool: &DbPool,
         filter_albums: models::GetAlbumRequest,
+        category_groups: Vec<Vec<i32>>,
+        creator_filter: Option<(i32, Option<CreditRole>)>,
+        max_min_age: Option<i32>,
+        page: &Page,
     ) -> Result<ResponseData<models::AlbumResponse>, diesel::result::Error> {
         use crate::schema::albums::*;
         let mut conn = pool.get().expect("Failed to get DB connection");
-        let mut query = albums::table.into_boxed();
-        let mut count_query = albums::table.into_boxed();
+        let mut query = albums::table.filter(deleted_at.is_null()).into_boxed();
+        let mut count_query = albums::table.filter(deleted_at.is_null()).into_boxed();
 
         if filter_albums.id.clone().unwrap_or(0) != 0 {
             let _id = filter_albums.id.unwrap();
@@ -84,35 +143,108 @@ impl Repository {
             count_query = count_query.filter(min_age.eq(_min_age));
         }
 
+        // Albums rated above the reader's age are hidden
+        if let Some(max_min_age) = max_min_age {
+            query = query.filter(min_age.le(max_min_age));
+            count_query = count_query.filter(min_age.le(max_min_age));
+        }
+
+        // The album needs a category of every group
+        for group in category_groups {
+            query = query.filter(
+                id.eq_any(
+                    album_categories::table
+                        .filter(album_categories::category_id.eq_any(group.clone()))
+                        .select(album_categories::album_id),
+                ),
+            );
+            count_query = count_query.filter(
+                id.eq_any(
+                    album_categories::table
+                        .filter(album_categories::category_id.eq_any(group))
+                        .select(album_categories::album_id),
+                ),
+            );
+        }
+
+        if let Some((creator_id, creator_role)) = creator_filter {
+            let mut credited = album_credits::table
+                .filter(album_credits::creator_id.eq(creator_id))
+                .select(album_credits::album_id)
+                .into_boxed();
+            let mut count_credited = album_credits::table
+                .filter(album_credits::creator_id.eq(creator_id))
+                .select(album_credits::album_id)
+                .into_boxed();
+            if let Some(creator_role) = creator_role {
+                credited = credited.filter(album_credits::role.eq(creator_role.as_str()));
+                count_credited =
+                    count_credited.filter(album_credits::role.eq(creator_role.as_str()));
+            }
+            query = query.filter(id.eq_any(credited));
+            count_query = count_query.filter(id.eq_any(count_credited));
+        }
+
+        if let Some(after) = page.after_cursor::<albums::table>("albums.id") {
+            query = query.filter(after);
+        }
+
         let total = count_query
             .select(count_star())
             .first::<i64>(&mut conn)
             .expect("failed to get total");
 
         let results = query
-            .offset(filter_albums.offset.unwrap_or(0))
-            .limit(filter_albums.limit.unwrap_or(20))
-            .load::<models::Album>(&mut conn)
-            .expect("error loading albums");
-
-        let response_data = ResponseData::<models::AlbumResponse> {
-            data: results
-                .into_iter()
-                .map(|r| models::AlbumResponse::from_album(r))
-                .collect(),
-            total,
-        };
-        Ok(response_data)
+            .select((
+                models::Album::as_select(),
+                page.sort_value(),
+                sql::<BigInt>("albums.id"),
+            ))
+            .order(sql::<Text>(&page.order_by_sql("albums.id")))
+            .offset(page.offset())
+            .limit(page.fetch_limit())
+            .load::<(models::Album, String, i64)>(&mut conn)?;
+
+        Ok(page.finish(results, total, models::AlbumResponse::from_album))
+    }
+
+    /// Enabled albums with views since `since`, most viewed first, with their views and reads
+    pub async fn get_trending_albums(
+        pool: &DbPool,
+        since: String,
+        limit: i64,
+        max_min_age: Option<i32>,
+    ) -> Result<Vec<(models::Album, i64, i64)>, diesel::result::Error> {
+        use crate::schema::albums::{deleted_at, enable, min_age};
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        let views = album_views_sql(&since);
+        let reads = album_reads_sql(&since);
+        let score = format!("{} + {}", views, reads);
+        albums::table
+            .filter(deleted_at.is_null())
+            .filter(enable.eq(true))
+            .filter(min_age.le(max_min_age.unwrap_or(i32::MAX)))
+            .filter(sql::<Bool>(&format!("{} > 0", score)))
+            .select((
+                models::Album::as_select(),
+                sql::<BigInt>(&views),
+                sql::<BigInt>(&reads),
+            ))
+            .order((sql::<BigInt>(&score).desc(), id.desc()))
+            .limit(limit)
+            .load::<(models::Album, i64, i64)>(&mut conn)
     }
 
     pub async fn get_album_by_uuid(
         pool: &DbPool,
         album_uuid: String,
     ) -> Result<models::Album, diesel::result::Error> {
-        use crate::schema::albums::uuid;
+        use crate::schema::albums::{deleted_at, uuid};
         let mut conn = pool.get().expect("Failed to get DB connection");
         let mut query = albums::table.into_boxed();
-        query = query.filter(uuid.eq(album_uuid));
+        query = query
+            .filter(uuid.eq(album_uuid))
+            .filter(deleted_at.is_null());
 
         let result = query.get_result(&mut conn)?;
         Ok(result)
@@ -125,9 +257,20 @@ impl Repository {
         use crate::schema::albums::uuid;
 
         let mut conn = pool.get().expect("Failed to get DB connection");
-        let result = diesel::update(albums::table.filter(uuid.eq(update_album.uuid.clone())))
-            .set(&update_album.clone())
-            .execute(&mut conn)?;
+        let result = conn.transaction(|conn| {
+            // Ratings are kept by the reviews, the loaded ones may be stale
+            let (rating_average, rating_count) = albums::table
+                .filter(uuid.eq(update_album.uuid.clone()))
+                .select((albums::rating_average, albums::rating_count))
+                .first::<(f64, i32)>(conn)?;
+            diesel::update(albums::table.filter(uuid.eq(update_album.uuid.clone())))
+                .set(&models::Album {
+                    rating_average,
+                    rating_count,
+                    ..update_album.clone()
+                })
+                .execute(conn)
+        })?;
         if update_album.broken_at.is_none() {
             let updated_broken_at: Option<String> = None;
             let a = diesel::update(albums::table.filter(id.eq(update_album.clone().id)))
@@ -137,6 +280,79 @@ impl Repository {
         Ok(result)
     }
 
+    /// Albums crediting the creator in any role, by title
+    pub async fn get_albums_by_creator_id(
+        pool: &DbPool,
+        creator_id: i32,
+        enabled_only: bool,
+        max_min_age: Option<i32>,
+    ) -> QueryResult<Vec<models::Album>> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        let mut query = albums::table
+            .filter(albums::deleted_at.is_null())
+            .filter(albums::min_age.le(max_min_age.unwrap_or(i32::MAX)))
+            .filter(
+                id.eq_any(
+                    album_credits::table
+                        .filter(album_credits::creator_id.eq(creator_id))
+                        .select(album_credits::album_id),
+                ),
+            )
+            .into_boxed();
+        if enabled_only {
+            query = query.filter(albums::enable.eq(true));
+        }
+        query
+            .order((albums::title.asc(), id.asc()))
+            .select(models::Album::as_select())
+            .load(&mut conn)
+    }
+
+    /// Categories of the albums in order, paired with their album id
+    pub async fn get_categories_by_album_ids(
+        pool: &DbPool,
+        album_ids: Vec<i32>,
+    ) -> QueryResult<Vec<(i32, Category)>> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        album_categories::table
+            .inner_join(category::table)
+            .filter(album_categories::album_id.eq_any(album_ids))
+            .order((album_categories::album_id, album_categories::position))
+            .select((album_categories::album_id, Category::as_select()))
+            .load(&mut conn)
+    }
+
+    /// Replace the categories of the album, the first one becomes its main category
+    pub async fn set_album_categories(
+        pool: &DbPool,
+        album_id: i32,
+        category_ids: Vec<i32>,
+    ) -> QueryResult<()> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        conn.immediate_transaction(|conn| {
+            diesel::delete(album_categories::table.filter(album_categories::album_id.eq(album_id)))
+                .execute(conn)?;
+            let links = category_ids
+                .iter()
+                .enumerate()
+                .map(|(position, category_id)| models::NewAlbumCategory {
+                    album_id,
+                    category_id: *category_id,
+                    position: position as i32,
+                })
+                .collect::<Vec<_>>();
+            diesel::insert_into(album_categories::table)
+                .values(&links)
+                .execute(conn)?;
+            if let Some(main_category_id) = category_ids.first() {
+                diesel::update(albums::table.filter(id.eq(album_id)))
+                    .set(albums::category_id.eq(main_category_id))
+                    .execute(conn)?;
+            }
+            Ok(())
+        })
+    }
+
     pub async fn delete_album(
         pool: &DbPool,
         album_uuid: String,
@@ -144,7 +360,13 @@ impl Repository {
         use crate::schema::albums::dsl::*;
         let mut conn = pool.get().expect("Failed to get DB connection");
 
-        let deleted = diesel::delete(albums.filter(uuid.eq(album_uuid))).execute(&mut conn)?;
+        let deleted = diesel::update(
+            albums
+                .filter(uuid.eq(album_uuid))
+                .filter(deleted_at.is_null()),
+        )
+        .set(deleted_at.eq(Some(Utc::now().naive_utc())))
+        .execute(&mut conn)?;
         Ok(deleted)
     }
 }
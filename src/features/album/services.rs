@@ -2,14 +2,23 @@ use chrono::Utc;
 use uuid::Uuid;
 
 use crate::common::database::DbPool;
+use crate::common::i18n::{default_locale, negotiate};
 use crate::common::models::file_metadata::ImageMetadata;
 use crate::common::models::response_data::ResponseData;
+use crate::common::pagination::{Page, PaginationError};
 use crate::common::utils::{
-    delete_directory_if_exists, delete_file_if_exists, get_data_directory, get_file_metadata,
-    get_project_directory, move_file_and_replace, remove_values_from_vec_string,
+    delete_file_if_exists, get_data_directory, get_file_metadata, get_project_directory,
+    get_trash_directory, move_file_and_replace, move_path, remove_values_from_vec_string,
 };
 use crate::features::album::models;
 use crate::features::album::repository::Repository;
+use crate::features::analytics::services::Service as AnalyticsService;
+use crate::features::category::models::CategoryResponse;
+use crate::features::category::services::Service as CategoryService;
+use crate::features::creator::models::{CreatorError, SetAlbumCreditsRequest};
+use crate::features::creator::services::Service as CreatorService;
+use crate::features::reader::models::ReaderAge;
+use crate::features::translation::services::Service as TranslationService;
 
 pub struct Service;
 
@@ -20,11 +29,17 @@ impl Service {
     ) -> Result<models::AlbumResponse, &str> {
         let cover_src_file_path = req_data.cover.clone();
         let image_src_files_paths: Vec<String> = req_data.clone().images.unwrap_or(vec![]);
+        let category_ids = req_data.categories();
         let new_album = models::NewAlbum::from_request(req_data);
         let cover_des_file_path = format!("{}/{}", get_project_directory(), new_album.url.clone());
         match Repository::create_album(pool, new_album).await {
             Ok(album) => {
-                let response = models::AlbumResponse::from_album(album);
+                if let Err(e) = Repository::set_album_categories(pool, album.id, category_ids).await
+                {
+                    eprintln!("Error: {e}");
+                    return Err("Failed to set album categories");
+                }
+                let response = Self::album_response(pool, album).await;
                 if response.images.len() == image_src_files_paths.len() {
                     move_file_and_replace(&*cover_src_file_path, &*cover_des_file_path);
                     for i in 0..image_src_files_paths.len() {
@@ -46,11 +61,108 @@ impl Service {
         }
     }
 
+    /// Albums matching the filters, those rated above the reader's age left out
     pub async fn get_albums(
         pool: &DbPool,
-        filter_albums: models::GetAlbumRequest,
-    ) -> Result<ResponseData<models::AlbumResponse>, diesel::result::Error> {
-        Repository::get_albums(pool, filter_albums).await
+        mut filter_albums: models::GetAlbumRequest,
+        locales: &[String],
+        reader_age: ReaderAge,
+    ) -> Result<ResponseData<models::AlbumResponse>, PaginationError> {
+        if reader_age != ReaderAge::Unrestricted {
+            // Disabled albums are hidden from readers
+            filter_albums.enable = Some(true);
+        }
+        let trending_days = AnalyticsService::trending_window_days(filter_albums.trending_days);
+        let page = Page::new(
+            filter_albums.page_query(),
+            filter_albums.offset,
+            Repository::sort_fields(&AnalyticsService::window_start(trending_days)),
+            Some(20),
+        )?;
+        let mut requested = filter_albums.category_ids.clone().unwrap_or_default();
+        if let Some(category_id) = filter_albums.category_id.filter(|id| *id != 0) {
+            requested.push(category_id);
+        }
+        if let Some(slug) = filter_albums.category.clone().filter(|slug| !slug.is_empty()) {
+            // An unknown slug matches no album
+            requested.push(
+                CategoryService::get_category_by_slug(pool, slug)
+                    .await?
+                    .and_then(|category| category.id)
+                    .unwrap_or_default(),
+            );
+        }
+        // Albums of sub categories belong to the filtered categories too
+        let mut category_groups = vec![];
+        for category_id in requested {
+            category_groups.push(CategoryService::get_descendant_ids(pool, category_id).await?);
+        }
+        if filter_albums.category_match != Some(models::CategoryMatch::All)
+            && !category_groups.is_empty()
+        {
+            category_groups = vec![category_groups.concat()];
+        }
+        let creator_filter = match filter_albums.creator.clone().filter(|c| !c.is_empty()) {
+            // An unknown creator matches no album
+            Some(creator_uuid) => Some((
+                CreatorService::get_creator_id(pool, creator_uuid)
+                    .await?
+                    .unwrap_or_default(),
+                filter_albums.creator_role,
+            )),
+            None => None,
+        };
+        let mut response = Repository::get_albums(
+            pool,
+            filter_albums,
+            category_groups,
+            creator_filter,
+            reader_age.max_min_age(),
+            &page,
+        )
+        .await?;
+        attach_categories(pool, &mut response.data).await;
+        attach_credits(pool, &mut response.data).await;
+        attach_translations(pool, &mut response.data, locales).await;
+        Ok(response)
+    }
+
+    pub async fn get_trending_albums(
+        pool: &DbPool,
+        req: models::TrendingAlbumsRequest,
+        locales: &[String],
+        reader_age: ReaderAge,
+    ) -> Result<ResponseData<models::TrendingAlbumResponse>, diesel::result::Error> {
+        let days = AnalyticsService::trending_window_days(req.days);
+        let limit = req
+            .limit
+            .filter(|limit| *limit > 0)
+            .unwrap_or(20)
+            .min(models::MAX_TRENDING_ALBUMS_LIMIT);
+        let albums = Repository::get_trending_albums(
+            pool,
+            AnalyticsService::window_start(days),
+            limit,
+            reader_age.max_min_age(),
+        )
+        .await?;
+        let mut data = albums
+            .into_iter()
+            .map(|(album, views, reads)| models::TrendingAlbumResponse {
+                album: models::AlbumResponse::from_album(album),
+                views,
+                reads,
+            })
+            .collect::<Vec<_>>();
+        let mut responses = data.iter().map(|t| t.album.clone()).collect::<Vec<_>>();
+        attach_categories(pool, &mut responses).await;
+        attach_credits(pool, &mut responses).await;
+        attach_translations(pool, &mut responses, locales).await;
+        for (trending, album) in data.iter_mut().zip(responses) {
+            trending.album = album;
+        }
+        let total = data.len() as i64;
+        Ok(ResponseData::new(data, total, 0))
     }
 
     pub async fn get_album_by_uuid(
@@ -60,6 +172,92 @@ impl Service {
         Repository::get_album_by_uuid(pool, album_uuid).await
     }
 
+    /// Albums crediting the creator with their categories and credits, disabled albums and those
+    /// rated above the reader's age left out for readers
+    pub async fn get_albums_by_creator_id(
+        pool: &DbPool,
+        creator_id: i32,
+        locales: &[String],
+        reader_age: ReaderAge,
+    ) -> Result<Vec<models::AlbumResponse>, diesel::result::Error> {
+        let albums =
+            Repository::get_albums_by_creator_id(
+                pool,
+                creator_id,
+                reader_age != ReaderAge::Unrestricted,
+                reader_age.max_min_age(),
+            )
+            .await?;
+        let mut responses = albums
+            .into_iter()
+            .map(models::AlbumResponse::from_album)
+            .collect::<Vec<_>>();
+        attach_categories(pool, &mut responses).await;
+        attach_credits(pool, &mut responses).await;
+        attach_translations(pool, &mut responses, locales).await;
+        Ok(responses)
+    }
+
+    /// Response of an album with its categories and credits
+    pub async fn album_response(pool: &DbPool, album: models::Album) -> models::AlbumResponse {
+        Self::localized_album_response(pool, album, &[]).await
+    }
+
+    /// Response of an album with its title and description in the best of the requested locales
+    pub async fn localized_album_response(
+        pool: &DbPool,
+        album: models::Album,
+        locales: &[String],
+    ) -> models::AlbumResponse {
+        let mut response = models::AlbumResponse::from_album(album);
+        attach_categories(pool, std::slice::from_mut(&mut response)).await;
+        attach_credits(pool, std::slice::from_mut(&mut response)).await;
+        attach_translations(pool, std::slice::from_mut(&mut response), locales).await;
+        response
+    }
+
+    /// Replace the credits of the album
+    pub async fn set_album_credits(
+        pool: &DbPool,
+        album_uuid: String,
+        req: SetAlbumCreditsRequest,
+    ) -> Result<models::AlbumResponse, String> {
+        let album = match Repository::get_album_by_uuid(pool, album_uuid).await {
+            Ok(album) => album,
+            Err(diesel::result::Error::NotFound) => return Err(String::from("Album not found")),
+            Err(e) => {
+                eprintln!("Error: {e}");
+                return Err(String::from("Failed to set album credits"));
+            }
+        };
+        match CreatorService::set_album_credits(pool, album.id, req).await {
+            Ok(_) => Ok(Self::album_response(pool, album).await),
+            Err(CreatorError::Invalid(message)) => Err(message),
+            Err(e) => {
+                eprintln!("Error: {e}");
+                Err(String::from("Failed to set album credits"))
+            }
+        }
+    }
+
+    /// The album needs at least one category and every category must exist
+    pub async fn check_categories(pool: &DbPool, category_ids: &[i32]) -> Result<(), String> {
+        if category_ids.is_empty() {
+            return Err(String::from("At least one category is required"));
+        }
+        for category_id in category_ids {
+            match CategoryService::get_category_by_id(pool, *category_id).await {
+                Ok(Some(_)) => {}
+                Ok(None) => return Err(format!("Category {} not found", category_id)),
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    return Err(String::from("Failed to check album categories"));
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub async fn update_album(
         pool: &DbPool,
         album_uuid: String,
@@ -69,6 +267,7 @@ impl Service {
             .await
             .expect("Album not found");
         let old_url = album.url.clone();
+        let category_ids = update_album.categories();
         let mut new_album = models::Album {
             id: album.id,
             uuid: album.uuid.clone(),
@@ -85,10 +284,16 @@ impl Service {
             height: album.height,
             bytes: album.bytes,
             released_at: update_album.released_at,
-            category_id: update_album.category_id,
+            category_id: category_ids
+                .as_ref()
+                .and_then(|category_ids| category_ids.first().copied())
+                .unwrap_or(album.category_id),
             broken_at: update_album.broken_at,
             created_at: album.created_at,
             updated_at: album.updated_at,
+            deleted_at: album.deleted_at,
+            rating_average: album.rating_average,
+            rating_count: album.rating_count,
         };
         if let Some(src_path) = update_album.cover.clone() {
             let file_meta_data = get_file_metadata(&src_path.clone());
@@ -119,7 +324,15 @@ impl Service {
                     let des_file_path = format!("{}/{}", get_project_directory(), new_album.url);
                     move_file_and_replace(&src_file_path, &des_file_path);
                 }
-                Ok(models::AlbumResponse::from_album(new_album))
+                if let Some(category_ids) = category_ids {
+                    if let Err(e) =
+                        Repository::set_album_categories(pool, new_album.id, category_ids).await
+                    {
+                        eprintln!("Error: {e}");
+                        return Err("Failed to set album categories");
+                    }
+                }
+                Ok(Self::album_response(pool, new_album).await)
             }
             Ok(_) => Err("Album not found"),
             Err(e) => {
@@ -176,6 +389,9 @@ impl Service {
             broken_at: album.broken_at,
             created_at: album.created_at,
             updated_at: album.updated_at,
+            deleted_at: album.deleted_at,
+            rating_average: album.rating_average,
+            rating_count: album.rating_count,
         };
         new_album.updated_at = Some(Utc::now().naive_utc());
         match Repository::update_album(pool, new_album.clone()).await {
@@ -188,7 +404,7 @@ impl Service {
                                 .as_str(),
                         )
                     }
-                    Ok(models::AlbumResponse::from_album(new_album.clone()))
+                    Ok(Self::album_response(pool, new_album.clone()).await)
                 } else {
                     Err("Some files lost on data exchanging")
                 }
@@ -238,6 +454,9 @@ impl Service {
             broken_at: album.broken_at,
             created_at: album.created_at,
             updated_at: album.updated_at,
+            deleted_at: album.deleted_at,
+            rating_average: album.rating_average,
+            rating_count: album.rating_count,
         };
         new_album.updated_at = Some(Utc::now().naive_utc());
         match Repository::update_album(pool, new_album.clone()).await {
@@ -248,7 +467,7 @@ impl Service {
                 if old_album_images.len().eq(&album_images.len()) {
                     Err("No image was removed")
                 } else {
-                    Ok(models::AlbumResponse::from_album(new_album.clone()))
+                    Ok(Self::album_response(pool, new_album.clone()).await)
                 }
             }
             Ok(_) => Err("Album not found"),
@@ -263,8 +482,88 @@ impl Service {
         pool: &DbPool,
         album_uuid: String,
     ) -> Result<usize, diesel::result::Error> {
-        let filepath = format!("{}/{}", get_data_directory(), album_uuid, );
-        delete_directory_if_exists(&filepath);
-        Repository::delete_album(pool, album_uuid).await
+        let deleted = Repository::delete_album(pool, album_uuid.clone()).await?;
+        if deleted > 0 {
+            let filepath = format!("{}/{}", get_data_directory(), album_uuid);
+            let trash_path = format!("{}/albums/{}", get_trash_directory(), album_uuid);
+            if let Err(e) = move_path(&filepath, &trash_path) {
+                eprintln!("Failed to move album {} to trash: {}", album_uuid, e);
+            }
+        }
+        Ok(deleted)
+    }
+}
+
+/// Fill the categories of each album, left empty when they cannot be loaded
+async fn attach_categories(pool: &DbPool, albums: &mut [models::AlbumResponse]) {
+    let album_ids = albums.iter().map(|album| album.id).collect();
+    let categories = match Repository::get_categories_by_album_ids(pool, album_ids).await {
+        Ok(categories) => categories,
+        Err(e) => {
+            eprintln!("Failed to load album categories: {e}");
+            return;
+        }
+    };
+    for album in albums.iter_mut() {
+        album.categories = categories
+            .iter()
+            .filter(|(album_id, _)| *album_id == album.id)
+            .map(|(_, category)| CategoryResponse::from_category(category.clone()))
+            .collect();
+    }
+}
+
+/// Show each album in the best of the requested locales and list the locales it has, left in
+/// the default locale when the translations cannot be loaded
+async fn attach_translations(
+    pool: &DbPool,
+    albums: &mut [models::AlbumResponse],
+    locales: &[String],
+) {
+    let album_ids = albums.iter().map(|album| album.id).collect();
+    let translations = match TranslationService::get_album_translations(pool, album_ids).await {
+        Ok(translations) => translations,
+        Err(e) => {
+            eprintln!("Failed to load album translations: {e}");
+            return;
+        }
+    };
+    for album in albums.iter_mut() {
+        let album_translations = translations
+            .iter()
+            .filter(|translation| translation.album_id == album.id)
+            .collect::<Vec<_>>();
+        album.available_locales = std::iter::once(default_locale())
+            .chain(album_translations.iter().map(|t| t.locale.clone()))
+            .collect();
+        let Some(locale) = negotiate(locales, &album.available_locales) else {
+            continue;
+        };
+        if let Some(translation) = album_translations.iter().find(|t| t.locale == locale) {
+            album.title = translation.title.clone();
+            if !translation.description.is_empty() {
+                album.description = translation.description.clone();
+            }
+        }
+        album.locale = locale;
+    }
+}
+
+/// Fill the credits of each album, left empty when they cannot be loaded
+async fn attach_credits(pool: &DbPool, albums: &mut [models::AlbumResponse]) {
+    let album_ids = albums.iter().map(|album| album.id).collect();
+    let credits = match CreatorService::get_album_credits(pool, album_ids).await {
+        Ok(credits) => credits,
+        Err(e) => {
+            eprintln!("Failed to load album credits: {e}");
+            return;
+        }
+    };
+    for album in albums.iter_mut() {
+        album.credits = credits
+            .iter()
+            .filter(|(album_id, _)| *album_id == album.id)
+            .map(|(_, credit)| credit.clone())
+            .collect();
     }
 }
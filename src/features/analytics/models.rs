@@ -0,0 +1,24 @@
+use diesel::prelude::*;
+
+use crate::schema::{album_daily_views, episode_daily_views};
+
+/// Album page fetches and episode reads of an album on one UTC day
+#[derive(Debug, Insertable)]
+#[diesel(table_name = album_daily_views)]
+pub struct NewAlbumDailyViews {
+    pub album_id: i32,
+    /// `YYYY-MM-DD`
+    pub day: String,
+    pub views: i32,
+    pub reads: i32,
+}
+
+/// Reads of an episode on one UTC day
+#[derive(Debug, Insertable)]
+#[diesel(table_name = episode_daily_views)]
+pub struct NewEpisodeDailyViews {
+    pub episode_id: i32,
+    /// `YYYY-MM-DD`
+    pub day: String,
+    pub reads: i32,
+}
@@ -0,0 +1,67 @@
+use diesel::prelude::*;
+
+use crate::common::database::DbPool;
+use crate::features::analytics::models::{NewAlbumDailyViews, NewEpisodeDailyViews};
+use crate::schema::{album_daily_views, episode_daily_views, episodes};
+
+pub struct Repository;
+
+impl Repository {
+    pub async fn record_album_view(pool: &DbPool, album_id: i32, day: String) -> QueryResult<()> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        diesel::insert_into(album_daily_views::table)
+            .values(&NewAlbumDailyViews {
+                album_id,
+                day,
+                views: 1,
+                reads: 0,
+            })
+            .on_conflict((album_daily_views::album_id, album_daily_views::day))
+            .do_update()
+            .set(album_daily_views::views.eq(album_daily_views::views + 1))
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    /// Count a read on the episode and on its album, false when the episode is unknown
+    pub async fn record_episode_read(
+        pool: &DbPool,
+        episode_uuid: String,
+        day: String,
+    ) -> QueryResult<bool> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        conn.immediate_transaction(|conn| {
+            let episode = episodes::table
+                .filter(episodes::uuid.eq(episode_uuid))
+                .filter(episodes::deleted_at.is_null())
+                .select((episodes::id, episodes::album_id))
+                .first::<(Option<i32>, i32)>(conn)
+                .optional()?;
+            let Some((Some(episode_id), album_id)) = episode else {
+                return Ok(false);
+            };
+            diesel::insert_into(episode_daily_views::table)
+                .values(&NewEpisodeDailyViews {
+                    episode_id,
+                    day: day.clone(),
+                    reads: 1,
+                })
+                .on_conflict((episode_daily_views::episode_id, episode_daily_views::day))
+                .do_update()
+                .set(episode_daily_views::reads.eq(episode_daily_views::reads + 1))
+                .execute(conn)?;
+            diesel::insert_into(album_daily_views::table)
+                .values(&NewAlbumDailyViews {
+                    album_id,
+                    day,
+                    views: 0,
+                    reads: 1,
+                })
+                .on_conflict((album_daily_views::album_id, album_daily_views::day))
+                .do_update()
+                .set(album_daily_views::reads.eq(album_daily_views::reads + 1))
+                .execute(conn)?;
+            Ok(true)
+        })
+    }
+}
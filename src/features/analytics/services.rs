@@ -0,0 +1,47 @@
+use chrono::{Duration, Utc};
+
+use crate::common::config;
+use crate::common::database::DbPool;
+use crate::features::analytics::repository::Repository;
+
+/// Longest window accepted for the trending sort
+pub const MAX_TRENDING_WINDOW_DAYS: i64 = 365;
+
+pub struct Service;
+
+impl Service {
+    /// Count a fetch of the album, failures are logged and never fail the request
+    pub async fn record_album_view(pool: &DbPool, album_id: i32) {
+        if let Err(e) = Repository::record_album_view(pool, album_id, Self::today()).await {
+            eprintln!("Failed to record view of album {}: {}", album_id, e);
+        }
+    }
+
+    /// Count a read of the episode contents on the episode and its album
+    pub async fn record_episode_read(pool: &DbPool, episode_uuid: String) {
+        if let Err(e) =
+            Repository::record_episode_read(pool, episode_uuid.clone(), Self::today()).await
+        {
+            eprintln!("Failed to record read of episode {}: {}", episode_uuid, e);
+        }
+    }
+
+    /// Trending window in days, the configured window when none is requested
+    pub fn trending_window_days(requested: Option<i64>) -> i64 {
+        requested
+            .filter(|days| *days > 0)
+            .unwrap_or(config::get().analytics.trending_window_days)
+            .min(MAX_TRENDING_WINDOW_DAYS)
+    }
+
+    /// First day counted by a window of `days` days ending today
+    pub fn window_start(days: i64) -> String {
+        (Utc::now().date_naive() - Duration::days(days - 1))
+            .format("%Y-%m-%d")
+            .to_string()
+    }
+
+    fn today() -> String {
+        Utc::now().date_naive().format("%Y-%m-%d").to_string()
+    }
+}
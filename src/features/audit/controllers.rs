@@ -0,0 +1,46 @@
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
+
+use crate::common::database::DbPool;
+use crate::common::enums::Role::Admin;
+use crate::common::models::response_message::ResponseMessage;
+use crate::features::audit::models::GetAuditLogRequest;
+use crate::features::audit::services::Service;
+use crate::features::check_role;
+
+/// Get Audit Logs
+///
+/// Get audit log of admin mutations
+#[utoipa::path(
+    get,
+    path = "/audit",
+    params(GetAuditLogRequest),
+    responses(
+        (status = 200, description = "Request successfully", body = ResponseDataAuditLog),
+        (status = 400, description = "Bad Request", body = ResponseMessage),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Audit",
+)]
+#[get("/audit")]
+pub async fn get_audit_logs(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    req: web::Query<GetAuditLogRequest>,
+) -> impl Responder {
+    if check_role(http_request) == Admin {
+        match Service::get_audit_logs(&pool, req.into_inner()).await {
+            Ok(data) => HttpResponse::Ok().json(data),
+            Err(err) => HttpResponse::BadRequest().json(ResponseMessage {
+                message: err.to_string(),
+            }),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
@@ -0,0 +1,108 @@
+use chrono::NaiveDateTime;
+use diesel::{Insertable, Queryable, Selectable};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::common::ne_parse::NEParse;
+use crate::schema::audit_log;
+
+/// Most audit log entries returned in one page
+pub const MAX_AUDIT_LOG_LIMIT: i64 = 500;
+
+#[derive(Debug, Queryable, Selectable, Serialize, Deserialize, ToSchema, Clone, PartialEq, Eq)]
+#[diesel(table_name = audit_log)]
+pub struct AuditLog {
+    pub id: Option<i32>,
+    pub actor: String,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_uuid: String,
+    pub changes: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = audit_log)]
+pub struct NewAuditLog {
+    pub actor: String,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_uuid: String,
+    pub changes: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, PartialEq)]
+pub struct AuditLogResponse {
+    pub id: Option<i32>,
+    /// Role and masked API key of the caller
+    pub actor: String,
+    /// create, update or delete
+    pub action: String,
+    /// album, episode, content or category
+    pub entity_type: String,
+    pub entity_uuid: String,
+    /// Changed fields as `{"field": {"before": .., "after": ..}}`
+    #[schema(value_type = Object)]
+    pub changes: Option<Value>,
+    pub ip: Option<String>,
+    pub created_at: Option<String>,
+}
+
+impl AuditLogResponse {
+    pub fn from_audit_log(log: AuditLog) -> Self {
+        AuditLogResponse {
+            id: log.id,
+            actor: log.actor,
+            action: log.action,
+            entity_type: log.entity_type,
+            entity_uuid: log.entity_uuid,
+            changes: log
+                .changes
+                .and_then(|changes| serde_json::from_str(&changes).ok()),
+            ip: log.ip,
+            created_at: NEParse::opt_naive_datetime_to_utc_opt_string(log.created_at),
+        }
+    }
+
+    pub fn from_audit_logs(logs: Vec<AuditLog>) -> Vec<Self> {
+        logs.into_iter().map(Self::from_audit_log).collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GetAuditLogRequest {
+    /// Filter actor role (admin, user)
+    pub actor: Option<String>,
+    /// Filter action (create, update, delete)
+    pub action: Option<String>,
+    /// Filter entity type (album, episode, content, category)
+    pub entity_type: Option<String>,
+    /// Filter entity UUID (Exact Value)
+    pub entity_uuid: Option<String>,
+    /// Created at or after (RFC 3339)
+    pub from: Option<String>,
+    /// Created before (RFC 3339)
+    pub to: Option<String>,
+    /// Filter offset
+    pub offset: Option<i64>,
+    /// Filter limit, 1 to 500, default 50
+    pub limit: Option<i64>,
+}
+
+impl GetAuditLogRequest {
+    /// Requested offset, never negative
+    pub fn offset(&self) -> i64 {
+        self.offset.unwrap_or(0).max(0)
+    }
+
+    /// Requested limit kept within 1 to [`MAX_AUDIT_LOG_LIMIT`], SQLite reads a negative limit as
+    /// no limit
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(50).clamp(1, MAX_AUDIT_LOG_LIMIT)
+    }
+}
@@ -0,0 +1,65 @@
+use diesel::dsl::count_star;
+use diesel::prelude::*;
+
+use crate::common::database::DbPool;
+use crate::common::ne_parse::NEParse;
+use crate::features::audit::models;
+use crate::schema::audit_log;
+
+pub struct Repository;
+
+impl Repository {
+    pub async fn create_audit_log(
+        pool: &DbPool,
+        new_log: models::NewAuditLog,
+    ) -> QueryResult<usize> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        diesel::insert_into(audit_log::table)
+            .values(&new_log)
+            .execute(&mut conn)
+    }
+
+    pub async fn get_audit_logs(
+        pool: &DbPool,
+        filters: models::GetAuditLogRequest,
+    ) -> QueryResult<(Vec<models::AuditLog>, i64)> {
+        use crate::schema::audit_log::*;
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        let (_offset, _limit) = (filters.offset(), filters.limit());
+        let mut query = audit_log::table.into_boxed();
+        let mut count_query = audit_log::table.into_boxed();
+
+        if let Some(_actor) = filters.actor.filter(|s| !s.is_empty()) {
+            query = query.filter(actor.like(format!("{}%", _actor)));
+            count_query = count_query.filter(actor.like(format!("{}%", _actor)));
+        }
+        if let Some(_action) = filters.action.filter(|s| !s.is_empty()) {
+            query = query.filter(action.eq(_action.clone()));
+            count_query = count_query.filter(action.eq(_action));
+        }
+        if let Some(_entity_type) = filters.entity_type.filter(|s| !s.is_empty()) {
+            query = query.filter(entity_type.eq(_entity_type.clone()));
+            count_query = count_query.filter(entity_type.eq(_entity_type));
+        }
+        if let Some(_entity_uuid) = filters.entity_uuid.filter(|s| !s.is_empty()) {
+            query = query.filter(entity_uuid.eq(_entity_uuid.clone()));
+            count_query = count_query.filter(entity_uuid.eq(_entity_uuid));
+        }
+        if let Some(_from) = NEParse::opt_immut_str_to_opt_naive_datetime(filters.from.as_deref()) {
+            query = query.filter(created_at.ge(_from));
+            count_query = count_query.filter(created_at.ge(_from));
+        }
+        if let Some(_to) = NEParse::opt_immut_str_to_opt_naive_datetime(filters.to.as_deref()) {
+            query = query.filter(created_at.lt(_to));
+            count_query = count_query.filter(created_at.lt(_to));
+        }
+
+        let total = count_query.select(count_star()).first::<i64>(&mut conn)?;
+        let results = query
+            .order(id.desc())
+            .offset(_offset)
+            .limit(_limit)
+            .load::<models::AuditLog>(&mut conn)?;
+        Ok((results, total))
+    }
+}
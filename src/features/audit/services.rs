@@ -0,0 +1,72 @@
+use chrono::Utc;
+use serde_json::{json, Map, Value};
+
+use crate::common::database::DbPool;
+use crate::common::models::response_data::ResponseData;
+use crate::features::audit::models;
+use crate::features::audit::repository::Repository;
+
+pub struct Service;
+
+impl Service {
+    #[allow(clippy::too_many_arguments)]
+    pub async fn record(
+        pool: &DbPool,
+        actor: String,
+        ip: Option<String>,
+        action: &str,
+        entity_type: &str,
+        entity_uuid: String,
+        before: Option<Value>,
+        after: Option<Value>,
+    ) {
+        let changes = diff_json(before.as_ref(), after.as_ref());
+        let new_log = models::NewAuditLog {
+            actor,
+            action: action.to_string(),
+            entity_type: entity_type.to_string(),
+            entity_uuid,
+            changes: Some(changes.to_string()),
+            ip,
+            created_at: Some(Utc::now().naive_utc()),
+        };
+        if let Err(e) = Repository::create_audit_log(pool, new_log).await {
+            eprintln!("Failed to write audit log: {e}");
+        }
+    }
+
+    pub async fn get_audit_logs(
+        pool: &DbPool,
+        filters: models::GetAuditLogRequest,
+    ) -> Result<ResponseData<models::AuditLogResponse>, diesel::result::Error> {
+        let offset = filters.offset();
+        let (logs, total) = Repository::get_audit_logs(pool, filters).await?;
+        Ok(ResponseData::new(
+            models::AuditLogResponse::from_audit_logs(logs),
+            total,
+            offset,
+        ))
+    }
+}
+
+/// Build `{"field": {"before": .., "after": ..}}` for every top-level field that differs
+fn diff_json(before: Option<&Value>, after: Option<&Value>) -> Value {
+    let empty = Map::new();
+    let before = before.and_then(|v| v.as_object()).unwrap_or(&empty);
+    let after = after.and_then(|v| v.as_object()).unwrap_or(&empty);
+    let mut changes = Map::new();
+    for key in before.keys().chain(after.keys()) {
+        if changes.contains_key(key) {
+            continue;
+        }
+        let old_value = before.get(key).cloned().unwrap_or(Value::Null);
+        let new_value = after.get(key).cloned().unwrap_or(Value::Null);
+        if old_value != new_value {
+            changes.insert(
+                key.clone(),
+                json!({ "before": old_value, "after": new_value }),
+            );
+        }
+    }
+    Value::Object(changes)
+}
@@ -1,26 +1,34 @@
 use crate::common::database::DbPool;
 use crate::common::enums::Role::Admin;
 use crate::common::models::response_message::ResponseMessage;
-use crate::features::check_role;
-use crate::features::category::models;
-use crate::features::category::services::Service;
-use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
 use crate::common::ne_parse::NEParse;
-use crate::features::category::models::UpdateCategoryRequest;
+use crate::common::tmp_dir::TmpDir;
+use crate::common::utils::parse_payload_data;
+use crate::features::category::models::{
+    AddCategoryRequest, CategoryError, DeleteCategoryRequest, UpdateCategoryRequest,
+};
+use crate::features::category::services::Service;
+use crate::features::{audit_mutation, check_role};
+use actix_multipart::Multipart;
+use actix_web::{
+    delete, get, post, put, web, FromRequest, HttpMessage, HttpRequest, HttpResponse, Responder,
+};
 
 /// Add Category
 ///
-/// Add category
+/// Add a category, optionally below a parent category. Send the request as multipart/form-data
+/// to upload an icon with it.
 #[utoipa::path(
     post,
     path = "/category",
     request_body(
         content = AddCategoryRequest,
-        description = "Add Episode Contents Request",
+        description = "Add Category Request, as JSON or as multipart/form-data with the icon",
+        content_type = "application/json",
     ),
     responses(
         (status = 201, description = "Add successfully", body = CategoryResponse),
-        (status = 400, description = "Update failed", body = ResponseMessage),
+        (status = 400, description = "Invalid category", body = ResponseMessage),
         (status = 401, description = "Unauthorized error", body = ResponseMessage),
         (status = 500, description = "Internal server error", body = ResponseMessage)
     ),
@@ -33,21 +41,64 @@ use crate::features::category::models::UpdateCategoryRequest;
 pub async fn add_category(
     pool: web::Data<DbPool>,
     http_request: HttpRequest,
-    req: web::Json<models::AddCategoryRequest>,
+    payload: web::Payload,
 ) -> impl Responder {
-    if check_role(http_request) == Admin {
-        let request = req.into_inner();
+    if check_role(http_request.clone()) == Admin {
+        // The staged icon lives in the tmp directory until the service has moved it
+        let (request, _tmp_dir): (AddCategoryRequest, Option<TmpDir>) =
+            if is_multipart(&http_request) {
+                let multipart = Multipart::new(http_request.headers(), payload.into_inner());
+                let (payload_data, tmp_dir) = match parse_payload_data(multipart).await {
+                    Ok(parsed) => parsed,
+                    Err(e) => {
+                        return HttpResponse::BadRequest().json(ResponseMessage { message: e })
+                    }
+                };
+                match AddCategoryRequest::from_payload_data(payload_data).await {
+                    Ok(request) => (request, Some(tmp_dir)),
+                    Err(e) => {
+                        return HttpResponse::BadRequest().json(ResponseMessage {
+                            message: String::from(e),
+                        })
+                    }
+                }
+            } else {
+                match web::Json::<AddCategoryRequest>::from_request(
+                    &http_request,
+                    &mut payload.into_inner(),
+                )
+                .await
+                {
+                    // Icons are only taken from multipart uploads, never from a path in JSON
+                    Ok(req) => (
+                        AddCategoryRequest {
+                            icon: None,
+                            ..req.into_inner()
+                        },
+                        None,
+                    ),
+                    Err(e) => {
+                        return HttpResponse::BadRequest().json(ResponseMessage {
+                            message: e.to_string(),
+                        })
+                    }
+                }
+            };
         match Service::add_category(&pool, request).await {
-            Ok(msg) => {
-                HttpResponse::Created().json(ResponseMessage {
-                    message: String::from(msg),
-                })
-            }
-            Err(e) => {
-                HttpResponse::BadRequest().json(ResponseMessage {
-                    message: String::from(e),
-                })
+            Ok(category) => {
+                audit_mutation(
+                    &pool,
+                    &http_request,
+                    "create",
+                    "category",
+                    category.id.unwrap_or_default().to_string(),
+                    None,
+                    serde_json::to_value(&category).ok(),
+                )
+                .await;
+                HttpResponse::Created().json(category)
             }
+            Err(e) => category_error_response("add", e),
         }
     } else {
         HttpResponse::Unauthorized().json(ResponseMessage {
@@ -74,10 +125,7 @@ pub async fn add_category(
     tag = "Category",
 )]
 #[get("/categories")]
-pub async fn get_categories(
-    pool: web::Data<DbPool>,
-    http_request: HttpRequest,
-) -> impl Responder {
+pub async fn get_categories(pool: web::Data<DbPool>, http_request: HttpRequest) -> impl Responder {
     if check_role(http_request) == Admin {
         match Service::get_categories(&pool).await {
             Ok(response) => HttpResponse::Ok().json(response),
@@ -92,18 +140,66 @@ pub async fn get_categories(
     }
 }
 
+/// Get Category
+///
+/// Get category by slug
+#[utoipa::path(
+    get,
+    path = "/category/{slug}",
+    params(
+        ("slug" = String, Path, description = "Category slug", style = Simple, example = "romance")
+    ),
+    responses(
+        (status = 200, description = "Get successfully", body = CategoryResponse),
+        (status = 400, description = "Category Not Found", body = ResponseMessage),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Category",
+)]
+#[get("/category/{slug}")]
+pub async fn get_category(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+) -> impl Responder {
+    if check_role(http_request) == Admin {
+        match Service::get_category_by_slug(&pool, path.into_inner()).await {
+            Ok(Some(category)) => HttpResponse::Ok().json(category),
+            Ok(None) => HttpResponse::BadRequest().json(ResponseMessage {
+                message: String::from("Category not found"),
+            }),
+            Err(e) => {
+                println!("Failed to get category: {}", e);
+                HttpResponse::InternalServerError().json(ResponseMessage {
+                    message: String::from("Internal Server Error"),
+                })
+            }
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
 /// Update Category
 ///
-/// Update category
+/// Update category, fields left empty keep their value. Send the request as multipart/form-data
+/// to replace the icon.
 #[utoipa::path(
     put,
     path = "/category",
     request_body(
         content = UpdateCategoryRequest,
-        description = "Update Content Request",
+        description = "Update Category Request, as JSON or as multipart/form-data with the icon",
+        content_type = "application/json",
     ),
     responses(
-        (status = 200, description = "Add successfully", body = CategoryResponse),
+        (status = 200, description = "Update successfully", body = CategoryResponse),
         (status = 400, description = "Update failed", body = ResponseMessage),
         (status = 401, description = "Unauthorized error", body = ResponseMessage),
         (status = 500, description = "Internal server error", body = ResponseMessage)
@@ -117,19 +213,63 @@ pub async fn get_categories(
 pub async fn update_category(
     pool: web::Data<DbPool>,
     http_request: HttpRequest,
-    req: web::Json<UpdateCategoryRequest>,
+    payload: web::Payload,
 ) -> impl Responder {
-    if check_role(http_request) == Admin {
-        let update_request = req.into_inner();
+    if check_role(http_request.clone()) == Admin {
+        let (update_request, _tmp_dir): (UpdateCategoryRequest, Option<TmpDir>) =
+            if is_multipart(&http_request) {
+                let multipart = Multipart::new(http_request.headers(), payload.into_inner());
+                let (payload_data, tmp_dir) = match parse_payload_data(multipart).await {
+                    Ok(parsed) => parsed,
+                    Err(e) => {
+                        return HttpResponse::BadRequest().json(ResponseMessage { message: e })
+                    }
+                };
+                match UpdateCategoryRequest::from_payload_data(payload_data).await {
+                    Ok(request) => (request, Some(tmp_dir)),
+                    Err(e) => {
+                        return HttpResponse::BadRequest().json(ResponseMessage {
+                            message: String::from(e),
+                        })
+                    }
+                }
+            } else {
+                match web::Json::<UpdateCategoryRequest>::from_request(
+                    &http_request,
+                    &mut payload.into_inner(),
+                )
+                .await
+                {
+                    Ok(req) => (
+                        UpdateCategoryRequest {
+                            icon: None,
+                            ..req.into_inner()
+                        },
+                        None,
+                    ),
+                    Err(e) => {
+                        return HttpResponse::BadRequest().json(ResponseMessage {
+                            message: e.to_string(),
+                        })
+                    }
+                }
+            };
+        let before = category_snapshot(&pool, update_request.id).await;
         match Service::update_category(&pool, update_request).await {
             Ok(response) => {
+                audit_mutation(
+                    &pool,
+                    &http_request,
+                    "update",
+                    "category",
+                    response.id.unwrap_or_default().to_string(),
+                    before,
+                    serde_json::to_value(&response).ok(),
+                )
+                .await;
                 HttpResponse::Ok().json(response)
             }
-            Err(_) => {
-                HttpResponse::BadRequest().json(ResponseMessage {
-                    message: String::from("Failed to update category"),
-                })
-            }
+            Err(e) => category_error_response("update", e),
         }
     } else {
         HttpResponse::Unauthorized().json(ResponseMessage {
@@ -138,20 +278,22 @@ pub async fn update_category(
     }
 }
 
-
 /// Delete Category
 ///
-/// Delete category
+/// Delete category, sub categories move up to its parent. Albums still in the category must be
+/// moved with reassign_to.
 #[utoipa::path(
     delete,
     path = "/category/{category_id}",
     params(
-        ("category_id" = String, Path, description = "Category ID", style = Simple, example = "1")
+        ("category_id" = String, Path, description = "Category ID", style = Simple, example = "1"),
+        DeleteCategoryRequest
     ),
     responses(
         (status = 204, description = "Delete successfully"),
-        (status = 400, description = "Content Not Found", body = ResponseMessage),
+        (status = 400, description = "Category Not Found", body = ResponseMessage),
         (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 409, description = "Albums still use the category", body = ResponseMessage),
         (status = 500, description = "Internal server error", body = ResponseMessage)
     ),
     security(
@@ -164,24 +306,27 @@ pub async fn delete_category(
     pool: web::Data<DbPool>,
     http_request: HttpRequest,
     path: web::Path<String>,
+    query: web::Query<DeleteCategoryRequest>,
 ) -> impl Responder {
-    if check_role(http_request) == Admin {
+    if check_role(http_request.clone()) == Admin {
         let string_id = path.into_inner();
-        let c_id : i32 = NEParse::opt_immut_str_to_opt_i32(Some(string_id.as_str())).unwrap_or(0i32);
-        match Service::delete_category(&pool, c_id).await {
-            Ok(_) => HttpResponse::NoContent().json(""),
-            Err(e) => {
-                if e == diesel::result::Error::NotFound {
-                    HttpResponse::BadRequest().json(ResponseMessage {
-                        message: String::from("Content not found"),
-                    })
-                } else {
-                    println!("Failed to delete content: {}", e);
-                    HttpResponse::InternalServerError().json(ResponseMessage {
-                        message: String::from("Internal Server Error"),
-                    })
-                }
+        let c_id: i32 = NEParse::opt_immut_str_to_opt_i32(Some(string_id.as_str())).unwrap_or(0i32);
+        let before = category_snapshot(&pool, c_id).await;
+        match Service::delete_category(&pool, c_id, query.reassign_to).await {
+            Ok(_) => {
+                audit_mutation(
+                    &pool,
+                    &http_request,
+                    "delete",
+                    "category",
+                    c_id.to_string(),
+                    before,
+                    None,
+                )
+                .await;
+                HttpResponse::NoContent().json("")
             }
+            Err(e) => category_error_response("delete", e),
         }
     } else {
         HttpResponse::Unauthorized().json(ResponseMessage {
@@ -189,3 +334,114 @@ pub async fn delete_category(
         })
     }
 }
+
+/// Multipart requests carry the icon upload, everything else is read as JSON
+fn is_multipart(http_request: &HttpRequest) -> bool {
+    http_request.content_type() == "multipart/form-data"
+}
+
+fn category_error_response(action: &str, e: CategoryError) -> HttpResponse {
+    match e {
+        CategoryError::NotFound | CategoryError::Invalid(_) => {
+            HttpResponse::BadRequest().json(ResponseMessage {
+                message: e.to_string(),
+            })
+        }
+        CategoryError::InUse(_) => HttpResponse::Conflict().json(ResponseMessage {
+            message: e.to_string(),
+        }),
+        CategoryError::Database(_) => {
+            println!("Failed to {} category: {}", action, e);
+            HttpResponse::InternalServerError().json(ResponseMessage {
+                message: String::from("Internal Server Error"),
+            })
+        }
+    }
+}
+
+async fn category_snapshot(pool: &DbPool, category_id: i32) -> Option<serde_json::Value> {
+    Service::get_category_by_id(pool, category_id)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|category| serde_json::to_value(category).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{test, web, App};
+    use serde_json::{json, Value};
+
+    use crate::common::middleware::api_key_middleware::ApiKeyMiddleware;
+    use crate::common::test_support::{self, ADMIN_API_KEY, USER_API_KEY};
+    use crate::features::category;
+
+    #[actix_web::test]
+    async fn category_is_added_and_updated_with_json_or_multipart() {
+        let pool = test_support::test_pool();
+        let app = test::init_service(
+            App::new()
+                .wrap(ApiKeyMiddleware::new(
+                    ADMIN_API_KEY.to_string(),
+                    USER_API_KEY.to_string(),
+                    vec![],
+                ))
+                .app_data(web::Data::new(pool))
+                .configure(category::configure),
+        )
+        .await;
+
+        // JSON never names a file to use as the icon
+        let request = test::TestRequest::post()
+            .uri("/category")
+            .insert_header(("X-API-KEY", ADMIN_API_KEY))
+            .set_json(json!({ "name": "Slice of Life", "icon": "/etc/hostname" }))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), 201);
+        let added: Value = test::read_body_json(response).await;
+        assert_eq!(added["slug"], "slice-of-life");
+        assert_eq!(added["icon_url"], Value::Null);
+
+        let request = test::TestRequest::put()
+            .uri("/category")
+            .insert_header(("X-API-KEY", ADMIN_API_KEY))
+            .set_json(json!({ "id": added["id"], "name": "Everyday", "description": "Calm" }))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), 200);
+        let updated: Value = test::read_body_json(response).await;
+        assert_eq!(updated["name"], "Everyday");
+        assert_eq!(updated["description"], "Calm");
+        assert_eq!(updated["slug"], "slice-of-life");
+
+        let request = test::TestRequest::post()
+            .uri("/category")
+            .insert_header(("X-API-KEY", ADMIN_API_KEY))
+            .set_json(json!({ "name": " " }))
+            .to_request();
+        assert_eq!(test::call_service(&app, request).await.status(), 400);
+
+        let boundary = "category-test-boundary";
+        let body = format!(
+            "--{b}\r\nContent-Disposition: form-data; name=\"name\"\r\n\r\nRomance\r\n\
+             --{b}\r\nContent-Disposition: form-data; name=\"parent_id\"\r\n\r\n{p}\r\n--{b}--\r\n",
+            b = boundary,
+            p = added["id"]
+        );
+        let request = test::TestRequest::post()
+            .uri("/category")
+            .insert_header(("X-API-KEY", ADMIN_API_KEY))
+            .insert_header((
+                "Content-Type",
+                format!("multipart/form-data; boundary={}", boundary),
+            ))
+            .set_payload(body)
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), 201);
+        let child: Value = test::read_body_json(response).await;
+        assert_eq!(child["slug"], "romance");
+        assert_eq!(child["parent_id"], added["id"]);
+    }
+}
@@ -4,6 +4,7 @@ use diesel::{AsChangeset, Identifiable, Insertable, Queryable, Selectable};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::fmt;
 use utoipa::{IntoParams, ToSchema};
 
 #[derive(
@@ -22,22 +23,42 @@ use utoipa::{IntoParams, ToSchema};
     Eq,
 )]
 #[diesel(table_name = category)]
+#[diesel(treat_none_as_null = true)]
 pub struct Category {
     pub id: Option<i32>,
     pub name: String,
+    pub parent_id: Option<i32>,
+    pub slug: String,
+    pub description: String,
+    pub icon_url: Option<String>,
+    pub icon_content_type: Option<String>,
+    pub sort_order: i32,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema, Clone, IntoParams, PartialEq, Eq)]
 pub struct CategoryResponse {
     pub id: Option<i32>,
     pub name: String,
+    /// Parent category, empty for top level categories
+    pub parent_id: Option<i32>,
+    /// Unique URL name of the category
+    pub slug: String,
+    pub description: String,
+    pub icon_url: Option<String>,
+    /// Position among the categories of the same parent
+    pub sort_order: i32,
 }
 
 impl CategoryResponse {
     pub fn from_category(category: Category) -> Self {
         CategoryResponse {
             id: category.id,
-            name: category.name
+            name: category.name,
+            parent_id: category.parent_id,
+            slug: category.slug,
+            description: category.description,
+            icon_url: category.icon_url,
+            sort_order: category.sort_order,
         }
     }
     pub fn from_categories(categories: Vec<Category>) -> Vec<Self> {
@@ -52,30 +73,45 @@ impl CategoryResponse {
 pub struct AddCategoryRequest {
     /// Category name
     pub name: String,
+    /// Unique URL name, generated from the name when empty
+    #[schema(example = "romance")]
+    pub slug: Option<String>,
+    pub description: Option<String>,
+    /// Parent category, top level when empty
+    pub parent_id: Option<i32>,
+    /// Position among the categories of the same parent, last when empty
+    pub sort_order: Option<i32>,
+    /// Category icon
+    #[schema(value_type = Option < String >, format = Binary)]
+    pub icon: Option<String>,
 }
 
 impl AddCategoryRequest {
     pub async fn from_payload_data<'a>(
         payload_data: HashMap<String, Value>,
     ) -> Result<Self, &'a str> {
-        let name = if payload_data.contains_key("name") {
-            NEParse::opt_immut_str_to_option_string(payload_data["name"].as_str())
-        }else{
-            None
-        };
-        if name.is_none() {
-            Err("Name cannot be empty")
-        } else {
-            if name.unwrap().trim().is_empty() {
-                Err("Name cannot be empty")
-            }else{
-                Ok(AddCategoryRequest {
-                    name: NEParse::opt_immut_str_to_option_string(payload_data["name"].as_str())
-                        .expect("Invalid name"),
-                })
-            }
-
-        }
+        let name = NEParse::opt_immut_str_to_option_string(
+            payload_data.get("name").and_then(|v| v.as_str()),
+        )
+        .filter(|name| !name.trim().is_empty())
+        .ok_or("Name cannot be empty")?;
+        Ok(AddCategoryRequest {
+            name,
+            slug: NEParse::opt_immut_str_to_option_string(
+                payload_data.get("slug").and_then(|v| v.as_str()),
+            )
+            .filter(|slug| !slug.is_empty()),
+            description: NEParse::opt_immut_str_to_option_string(
+                payload_data.get("description").and_then(|v| v.as_str()),
+            ),
+            parent_id: NEParse::opt_immut_str_to_opt_i32(
+                payload_data.get("parent_id").and_then(|v| v.as_str()),
+            ),
+            sort_order: NEParse::opt_immut_str_to_opt_i32(
+                payload_data.get("sort_order").and_then(|v| v.as_str()),
+            ),
+            icon: first_file(&payload_data, "icon"),
+        })
     }
 }
 
@@ -83,33 +119,98 @@ impl AddCategoryRequest {
 pub struct UpdateCategoryRequest {
     /// Category id
     pub id: i32,
-    pub name: String,
+    pub name: Option<String>,
+    /// Unique URL name
+    pub slug: Option<String>,
+    pub description: Option<String>,
+    /// New parent category, 0 moves the category to the top level
+    pub parent_id: Option<i32>,
+    pub sort_order: Option<i32>,
+    /// Replace the category icon
+    #[schema(value_type = Option < String >, format = Binary)]
+    pub icon: Option<String>,
+    /// Remove the category icon
+    #[schema(example = false)]
+    pub remove_icon: Option<bool>,
 }
 
 impl UpdateCategoryRequest {
     pub async fn from_payload_data<'a>(
         payload_data: HashMap<String, Value>,
     ) -> Result<Self, &'a str> {
-        let id = if payload_data.contains_key("id") {
-            NEParse::opt_immut_str_to_opt_i32(payload_data["id"].as_str())
-        } else {
-           None
-        };
-        let name = if payload_data.contains_key("name") {
-            NEParse::opt_immut_str_to_option_string(payload_data["name"].as_str())
-        } else {
-            None
-        };
+        let id = NEParse::opt_immut_str_to_opt_i32(payload_data.get("id").and_then(|v| v.as_str()))
+            .ok_or("Id cannot be empty")?;
+        let name = NEParse::opt_immut_str_to_option_string(
+            payload_data.get("name").and_then(|v| v.as_str()),
+        );
+        if name.as_ref().is_some_and(|name| name.trim().is_empty()) {
+            return Err("Name cannot be empty");
+        }
+        Ok(UpdateCategoryRequest {
+            id,
+            name,
+            slug: NEParse::opt_immut_str_to_option_string(
+                payload_data.get("slug").and_then(|v| v.as_str()),
+            )
+            .filter(|slug| !slug.is_empty()),
+            description: NEParse::opt_immut_str_to_option_string(
+                payload_data.get("description").and_then(|v| v.as_str()),
+            ),
+            parent_id: NEParse::opt_immut_str_to_opt_i32(
+                payload_data.get("parent_id").and_then(|v| v.as_str()),
+            ),
+            sort_order: NEParse::opt_immut_str_to_opt_i32(
+                payload_data.get("sort_order").and_then(|v| v.as_str()),
+            ),
+            icon: first_file(&payload_data, "icon"),
+            remove_icon: NEParse::opt_immut_str_to_option_bool(
+                payload_data.get("remove_icon").and_then(|v| v.as_str()),
+            ),
+        })
+    }
+}
 
-        if id.is_none() || name.is_none() {
-            Err("Id or name cannot be empty")
-        }else{
+#[derive(Debug, Serialize, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeleteCategoryRequest {
+    /// Move the albums of the category to this category, required when albums still use it
+    pub reassign_to: Option<i32>,
+}
 
-            Ok(UpdateCategoryRequest {
-                id: id.unwrap(),
-                name: name.unwrap(),
-            })
+#[derive(Debug)]
+pub enum CategoryError {
+    NotFound,
+    Invalid(String),
+    /// Albums still use the category and no category to reassign them to was given
+    InUse(i64),
+    Database(diesel::result::Error),
+}
+
+impl fmt::Display for CategoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CategoryError::NotFound => write!(f, "Category not found"),
+            CategoryError::Invalid(message) => write!(f, "{}", message),
+            CategoryError::InUse(albums) => write!(
+                f,
+                "Category is used by {} albums, pass reassign_to to move them",
+                albums
+            ),
+            CategoryError::Database(e) => write!(f, "{}", e),
         }
+    }
+}
 
+impl From<diesel::result::Error> for CategoryError {
+    fn from(e: diesel::result::Error) -> Self {
+        CategoryError::Database(e)
     }
 }
+
+fn first_file(payload_data: &HashMap<String, Value>, key: &str) -> Option<String> {
+    NEParse::opt_immut_vec_serde_json_value_to_vec_string(
+        payload_data.get(key).and_then(|v| v.as_array()),
+    )
+    .into_iter()
+    .next()
+}
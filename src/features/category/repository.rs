@@ -1,9 +1,10 @@
 use crate::common::database::DbPool;
 use crate::features::category::models;
-use crate::schema::category;
+use crate::schema::category::id;
+use crate::schema::{album_categories, albums, category};
+use diesel::dsl::count_star;
 use diesel::prelude::*;
 use diesel::{QueryDsl, QueryResult, RunQueryDsl};
-use crate::schema::category::id;
 
 pub struct Repository;
 
@@ -11,41 +12,128 @@ impl Repository {
     pub async fn create_category(
         pool: &DbPool,
         new_category: models::Category,
-    ) -> QueryResult<usize> {
+    ) -> QueryResult<models::Category> {
         let mut conn = pool.get().expect("Failed to get DB connection");
-        let rows_inserted = diesel::insert_into(category::table)
+        diesel::insert_into(category::table)
             .values(&new_category)
-            .execute(&mut conn)
-            .expect("Failed to create category");
-        Ok(rows_inserted)
+            .execute(&mut conn)?;
+        category::table
+            .filter(category::slug.eq(new_category.slug))
+            .first::<models::Category>(&mut conn)
+    }
+
+    pub async fn get_categories(pool: &DbPool) -> QueryResult<Vec<models::Category>> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        category::table
+            .order((category::sort_order.asc(), category::name.asc(), id.asc()))
+            .load::<models::Category>(&mut conn)
     }
 
-    pub async fn get_categories(
-        pool: &DbPool
-    ) -> QueryResult<Vec<models::Category>> {
+    pub async fn get_category_by_id(
+        pool: &DbPool,
+        category_id: i32,
+    ) -> QueryResult<Option<models::Category>> {
         let mut conn = pool.get().expect("Failed to get DB connection");
         category::table
-            .load::<models::Category>(&mut conn) // Loads all matching Content rows
+            .filter(id.eq(category_id))
+            .first::<models::Category>(&mut conn)
+            .optional()
+    }
+
+    pub async fn get_category_by_name(
+        pool: &DbPool,
+        category_name: String,
+    ) -> QueryResult<Option<models::Category>> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        category::table
+            .filter(category::name.eq(category_name))
+            .first::<models::Category>(&mut conn)
+            .optional()
+    }
+
+    pub async fn get_category_by_slug(
+        pool: &DbPool,
+        category_slug: String,
+    ) -> QueryResult<Option<models::Category>> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        category::table
+            .filter(category::slug.eq(category_slug))
+            .first::<models::Category>(&mut conn)
+            .optional()
+    }
+
+    /// Sort order placing a new category after its siblings
+    pub async fn get_next_sort_order(pool: &DbPool, parent_id: Option<i32>) -> QueryResult<i32> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        let last = match parent_id {
+            Some(parent_id) => category::table
+                .filter(category::parent_id.eq(parent_id))
+                .select(diesel::dsl::max(category::sort_order))
+                .first::<Option<i32>>(&mut conn)?,
+            None => category::table
+                .filter(category::parent_id.is_null())
+                .select(diesel::dsl::max(category::sort_order))
+                .first::<Option<i32>>(&mut conn)?,
+        };
+        Ok(last.map(|last| last + 1).unwrap_or(1))
     }
 
     pub async fn update_category(
         pool: &DbPool,
         update_category: models::Category,
-    ) -> Result<models::CategoryResponse, diesel::result::Error> {
+    ) -> Result<models::Category, diesel::result::Error> {
         let mut conn = pool.get().expect("Failed to get DB connection");
-        let result = diesel::update(category::table.filter(id.eq(update_category.id.clone())))
+        diesel::update(category::table.filter(id.eq(update_category.id)))
             .set(&update_category)
             .execute(&mut conn)?;
-        Ok(models::CategoryResponse{ id: update_category.id, name: update_category.name })
+        category::table
+            .filter(id.eq(update_category.id))
+            .first::<models::Category>(&mut conn)
+    }
+
+    /// Albums in the category, trashed albums included since they can be restored
+    pub async fn count_albums_by_category_id(pool: &DbPool, category_id: i32) -> QueryResult<i64> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        album_categories::table
+            .filter(album_categories::category_id.eq(category_id))
+            .select(count_star())
+            .first::<i64>(&mut conn)
     }
 
+    /// Delete the category, moving its albums to `reassign_to` and its children to `parent_id`
     pub async fn delete_category(
         pool: &DbPool,
         category_id: i32,
+        reassign_to: Option<i32>,
+        parent_id: Option<i32>,
     ) -> Result<usize, diesel::result::Error> {
-        use crate::schema::category::dsl::*;
         let mut conn = pool.get().expect("Failed to get DB connection");
-        let deleted = diesel::delete(category.filter(id.eq(category_id))).execute(&mut conn)?;
-        Ok(deleted)
+        conn.immediate_transaction(|conn| {
+            if let Some(reassign_to) = reassign_to {
+                // Albums already in the target category only lose the deleted one
+                let in_target = album_categories::table
+                    .filter(album_categories::category_id.eq(reassign_to))
+                    .select(album_categories::album_id)
+                    .load::<i32>(conn)?;
+                diesel::update(
+                    album_categories::table
+                        .filter(album_categories::category_id.eq(category_id))
+                        .filter(album_categories::album_id.ne_all(&in_target)),
+                )
+                .set(album_categories::category_id.eq(reassign_to))
+                .execute(conn)?;
+                diesel::update(albums::table.filter(albums::category_id.eq(category_id)))
+                    .set(albums::category_id.eq(reassign_to))
+                    .execute(conn)?;
+            }
+            diesel::delete(
+                album_categories::table.filter(album_categories::category_id.eq(category_id)),
+            )
+            .execute(conn)?;
+            diesel::update(category::table.filter(category::parent_id.eq(category_id)))
+                .set(category::parent_id.eq(parent_id))
+                .execute(conn)?;
+            diesel::delete(category::table.filter(id.eq(category_id))).execute(conn)
+        })
     }
 }
@@ -1,13 +1,12 @@
 use crate::common::database::DbPool;
-use crate::common::models::file_metadata::ImageMetadata;
 use crate::common::models::response_data::ResponseData;
 use crate::common::utils::{
     delete_file_if_exists, get_data_directory, get_file_metadata, get_project_directory,
     move_file_and_replace,
 };
 use crate::features::category::models;
+use crate::features::category::models::CategoryError;
 use crate::features::category::repository::Repository;
-use chrono::Utc;
 use uuid::Uuid;
 
 pub struct Service;
@@ -16,50 +15,323 @@ impl Service {
     pub async fn add_category(
         pool: &DbPool,
         req: models::AddCategoryRequest,
-    ) -> Result<&str, &str> {
-        match Repository::create_category(pool, models::Category{id: None, name: req.name }).await {
-            Ok(size) => if size>0 { Ok("Successfully added") }else { Err("Failed to add category") }
-            Err(_) => Err("Failed to add category"),
+    ) -> Result<models::CategoryResponse, CategoryError> {
+        let name = req.name.trim().to_string();
+        if name.is_empty() {
+            return Err(CategoryError::Invalid(String::from("Name cannot be empty")));
         }
+        if Repository::get_category_by_name(pool, name.clone())
+            .await?
+            .is_some()
+        {
+            return Err(CategoryError::Invalid(format!(
+                "Category {} already exists",
+                name
+            )));
+        }
+        let slug = match req.slug {
+            Some(slug) => {
+                check_slug_available(pool, &slug, None).await?;
+                slug
+            }
+            None => generate_slug(pool, &name).await?,
+        };
+        if let Some(parent_id) = req.parent_id {
+            check_parent(pool, None, parent_id).await?;
+        }
+        let sort_order = match req.sort_order {
+            Some(sort_order) => sort_order,
+            None => Repository::get_next_sort_order(pool, req.parent_id).await?,
+        };
+        let icon = match &req.icon {
+            Some(icon_path) => Some(icon_destination(icon_path)?),
+            None => None,
+        };
+        let category = Repository::create_category(
+            pool,
+            models::Category {
+                id: None,
+                name,
+                parent_id: req.parent_id,
+                slug,
+                description: req.description.unwrap_or_default(),
+                icon_url: icon.as_ref().map(|(url, _)| url.clone()),
+                icon_content_type: icon.as_ref().map(|(_, content_type)| content_type.clone()),
+                sort_order,
+            },
+        )
+        .await?;
+        if let (Some(icon_path), Some(icon_url)) = (&req.icon, &category.icon_url) {
+            move_file_and_replace(
+                icon_path,
+                &format!("{}/{}", get_project_directory(), icon_url),
+            );
+        }
+        Ok(models::CategoryResponse::from_category(category))
     }
 
     pub async fn get_categories(
         pool: &DbPool,
     ) -> Result<ResponseData<models::CategoryResponse>, diesel::result::Error> {
         match Repository::get_categories(pool).await {
-            Ok(categories) => {
-                Ok(ResponseData::<models::CategoryResponse> {
-                    data: models::CategoryResponse::from_categories(categories.clone()),
-                    total: categories.len() as i64,
-                })
-            }
+            Ok(categories) => Ok(ResponseData::new(
+                models::CategoryResponse::from_categories(categories.clone()),
+                categories.len() as i64,
+                0,
+            )),
             Err(e) => Err(e),
         }
     }
 
+    pub async fn get_category_by_id(
+        pool: &DbPool,
+        category_id: i32,
+    ) -> Result<Option<models::CategoryResponse>, diesel::result::Error> {
+        Repository::get_category_by_id(pool, category_id)
+            .await
+            .map(|category| category.map(models::CategoryResponse::from_category))
+    }
+
+    pub async fn get_category_by_name(
+        pool: &DbPool,
+        category_name: String,
+    ) -> Result<Option<models::CategoryResponse>, diesel::result::Error> {
+        Repository::get_category_by_name(pool, category_name)
+            .await
+            .map(|category| category.map(models::CategoryResponse::from_category))
+    }
+
+    pub async fn get_category_by_slug(
+        pool: &DbPool,
+        category_slug: String,
+    ) -> Result<Option<models::CategoryResponse>, diesel::result::Error> {
+        Repository::get_category_by_slug(pool, category_slug)
+            .await
+            .map(|category| category.map(models::CategoryResponse::from_category))
+    }
+
+    /// The category and every category below it
+    pub async fn get_descendant_ids(
+        pool: &DbPool,
+        category_id: i32,
+    ) -> Result<Vec<i32>, diesel::result::Error> {
+        let categories = Repository::get_categories(pool).await?;
+        Ok(descendant_ids(&categories, category_id))
+    }
+
     pub async fn update_category(
         pool: &DbPool,
         update_category: models::UpdateCategoryRequest,
-    ) -> Result<models::CategoryResponse, diesel::result::Error> {
-        match Repository::update_category(pool, models::Category{ id: Some(update_category.id), name: update_category.name }).await {
-            Ok(category) => Ok(category),
-            Err(e) => Err(e),
+    ) -> Result<models::CategoryResponse, CategoryError> {
+        let current = Repository::get_category_by_id(pool, update_category.id)
+            .await?
+            .ok_or(CategoryError::NotFound)?;
+        let mut category = current.clone();
+        if let Some(name) = update_category.name {
+            let name = name.trim().to_string();
+            if name.is_empty() {
+                return Err(CategoryError::Invalid(String::from("Name cannot be empty")));
+            }
+            if let Some(other) = Repository::get_category_by_name(pool, name.clone()).await? {
+                if other.id != current.id {
+                    return Err(CategoryError::Invalid(format!(
+                        "Category {} already exists",
+                        name
+                    )));
+                }
+            }
+            category.name = name;
+        }
+        if let Some(slug) = update_category.slug {
+            check_slug_available(pool, &slug, current.id).await?;
+            category.slug = slug;
+        }
+        if let Some(description) = update_category.description {
+            category.description = description;
+        }
+        match update_category.parent_id {
+            Some(0) => category.parent_id = None,
+            Some(parent_id) => {
+                check_parent(pool, current.id, parent_id).await?;
+                category.parent_id = Some(parent_id);
+            }
+            None => {}
+        }
+        if let Some(sort_order) = update_category.sort_order {
+            category.sort_order = sort_order;
+        }
+        if update_category.remove_icon == Some(true) {
+            category.icon_url = None;
+            category.icon_content_type = None;
+        }
+        if let Some(icon_path) = &update_category.icon {
+            let (icon_url, icon_content_type) = icon_destination(icon_path)?;
+            category.icon_url = Some(icon_url);
+            category.icon_content_type = Some(icon_content_type);
+        }
+        let category = Repository::update_category(pool, category).await?;
+        if let (Some(icon_path), Some(icon_url)) = (&update_category.icon, &category.icon_url) {
+            move_file_and_replace(
+                icon_path,
+                &format!("{}/{}", get_project_directory(), icon_url),
+            );
+        }
+        if current.icon_url.is_some() && current.icon_url != category.icon_url {
+            delete_icon(&current);
         }
+        Ok(models::CategoryResponse::from_category(category))
     }
 
+    /// Delete a category, albums still using it are moved to `reassign_to` and sub categories
+    /// move up to the parent of the deleted category
     pub async fn delete_category(
         pool: &DbPool,
         category_id: i32,
-    ) -> Result<&str, diesel::result::Error> {
-        match Repository::delete_category(pool, category_id).await {
-            Ok(size) => {
-                if size>0 {
-                   Ok("Successfully deleted")
-                } else {
-                    Err(diesel::result::Error::NotFound)
+        reassign_to: Option<i32>,
+    ) -> Result<(), CategoryError> {
+        let category = Repository::get_category_by_id(pool, category_id)
+            .await?
+            .ok_or(CategoryError::NotFound)?;
+        match reassign_to {
+            Some(target) if target == category_id => {
+                return Err(CategoryError::Invalid(String::from(
+                    "Cannot reassign albums to the deleted category",
+                )))
+            }
+            Some(target) => {
+                if Repository::get_category_by_id(pool, target)
+                    .await?
+                    .is_none()
+                {
+                    return Err(CategoryError::Invalid(format!(
+                        "Category {} to reassign albums to not found",
+                        target
+                    )));
                 }
             }
-            Err(e) => Err(e),
+            None => {
+                let albums = Repository::count_albums_by_category_id(pool, category_id).await?;
+                if albums > 0 {
+                    return Err(CategoryError::InUse(albums));
+                }
+            }
+        }
+        Repository::delete_category(pool, category_id, reassign_to, category.parent_id).await?;
+        delete_icon(&category);
+        Ok(())
+    }
+}
+
+/// Lowercase letters and digits separated by single dashes
+fn slugify(value: &str) -> String {
+    let mut slug = String::new();
+    for c in value.trim().chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+async fn check_slug_available(
+    pool: &DbPool,
+    slug: &str,
+    category_id: Option<i32>,
+) -> Result<(), CategoryError> {
+    if slug.is_empty() || slugify(slug) != slug {
+        return Err(CategoryError::Invalid(format!(
+            "Invalid slug {}, use lowercase letters and digits separated by dashes",
+            slug
+        )));
+    }
+    match Repository::get_category_by_slug(pool, slug.to_string()).await? {
+        Some(other) if other.id != category_id => Err(CategoryError::Invalid(format!(
+            "Slug {} is already in use",
+            slug
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Slug of the name, numbered when another category already has it
+async fn generate_slug(pool: &DbPool, name: &str) -> Result<String, CategoryError> {
+    let base = slugify(name);
+    if base.is_empty() {
+        return Err(CategoryError::Invalid(String::from(
+            "Name has no letters or digits to build a slug from, set a slug",
+        )));
+    }
+    let mut slug = base.clone();
+    let mut number = 2;
+    while Repository::get_category_by_slug(pool, slug.clone())
+        .await?
+        .is_some()
+    {
+        slug = format!("{}-{}", base, number);
+        number += 1;
+    }
+    Ok(slug)
+}
+
+/// The parent must exist and must not be the category itself or one of its descendants
+async fn check_parent(
+    pool: &DbPool,
+    category_id: Option<i32>,
+    parent_id: i32,
+) -> Result<(), CategoryError> {
+    let categories = Repository::get_categories(pool).await?;
+    if !categories.iter().any(|c| c.id == Some(parent_id)) {
+        return Err(CategoryError::Invalid(format!(
+            "Parent category {} not found",
+            parent_id
+        )));
+    }
+    if let Some(category_id) = category_id {
+        if descendant_ids(&categories, category_id).contains(&parent_id) {
+            return Err(CategoryError::Invalid(String::from(
+                "A category cannot be moved below itself",
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn descendant_ids(categories: &[models::Category], category_id: i32) -> Vec<i32> {
+    let mut ids = vec![category_id];
+    let mut index = 0;
+    while index < ids.len() {
+        let parent_id = ids[index];
+        for child in categories.iter().filter(|c| c.parent_id == Some(parent_id)) {
+            if let Some(child_id) = child.id.filter(|child_id| !ids.contains(child_id)) {
+                ids.push(child_id);
+            }
         }
+        index += 1;
+    }
+    ids
+}
+
+/// Stored url and content type of an uploaded icon
+fn icon_destination(icon_path: &str) -> Result<(String, String), CategoryError> {
+    let metadata = get_file_metadata(icon_path);
+    if !metadata.content_type.starts_with("image/") {
+        return Err(CategoryError::Invalid(String::from(
+            "Category icon must be an image",
+        )));
+    }
+    let url = format!(
+        "{}/categories/{}.{}",
+        get_data_directory(),
+        Uuid::new_v4(),
+        icon_path.rsplit('.').next().unwrap_or_default()
+    );
+    Ok((url, metadata.content_type))
+}
+
+fn delete_icon(category: &models::Category) {
+    if let Some(icon_url) = &category.icon_url {
+        delete_file_if_exists(&format!("{}/{}", get_project_directory(), icon_url));
     }
 }
@@ -1,12 +1,18 @@
 use crate::common::database::DbPool;
-use crate::common::enums::Role::Admin;
+use crate::common::i18n::{
+    default_locale, normalize_locale, requested_locales, set_language_headers,
+};
+use crate::common::enums::Role::{Admin, Unknown, User};
 use crate::common::models::response_message::ResponseMessage;
-use crate::common::utils::{delete_directory_if_exists, parse_payload_data};
-use crate::features::check_role;
+use crate::common::pagination::{PageQuery, PaginationError};
+use crate::common::utils::parse_payload_data;
+use crate::features::analytics::services::Service as AnalyticsService;
 use crate::features::content::models;
 use crate::features::content::services::Service;
+use crate::features::{audit_mutation, check_role, episode_access_refusal};
 use actix_multipart::Multipart;
 use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+use serde_json::json;
 
 /// Add Contents
 ///
@@ -36,22 +42,45 @@ pub async fn add_contents(
     http_request: HttpRequest,
     payload: Multipart,
 ) -> impl Responder {
-    if check_role(http_request) == Admin {
+    if check_role(http_request.clone()) == Admin {
         match parse_payload_data(payload).await {
-            Ok((payload_data, tmp_path)) => {
+            Ok((payload_data, _tmp_dir)) => {
                 match models::AddEpisodeContentsRequest::from_payload_data(payload_data).await {
-                    Ok(req_data) => match Service::add_episode_contents(&pool, req_data).await {
-                        Ok(response) => {
-                            delete_directory_if_exists(&tmp_path);
-                            HttpResponse::Created().json(response)
+                    Ok(req_data) => {
+                        let locale = req_data.locale.clone().unwrap_or_else(default_locale);
+                        let existing_uuids = Service::get_content_uuids_by_episode_id(
+                            &pool,
+                            req_data.episode_id,
+                            &locale,
+                        )
+                        .await
+                        .unwrap_or_default();
+                        match Service::add_episode_contents(&pool, req_data).await {
+                            Ok(response) => {
+                                for content in response
+                                    .iter()
+                                    .filter(|c| !existing_uuids.contains(&c.uuid))
+                                {
+                                    audit_mutation(
+                                        &pool,
+                                        &http_request,
+                                        "create",
+                                        "content",
+                                        content.uuid.clone(),
+                                        None,
+                                        serde_json::to_value(content).ok(),
+                                    )
+                                    .await;
+                                }
+                                HttpResponse::Created().json(response)
+                            }
+                            Err(e) => {
+                                HttpResponse::BadRequest().json(ResponseMessage {
+                                    message: String::from(e),
+                                })
+                            }
                         }
-                        Err(e) => {
-                            delete_directory_if_exists(&tmp_path);
-                            HttpResponse::BadRequest().json(ResponseMessage {
-                                message: String::from(e),
-                            })
-                        }
-                    },
+                    }
                     Err(e) => HttpResponse::BadRequest().json(ResponseMessage {
                         message: String::from(e),
                     }),
@@ -70,14 +99,24 @@ pub async fn add_contents(
 
 /// Get Contents
 ///
-/// Get contents by episode id
+/// Get contents by episode id, sort by index_no (default), id or created_at. The edition is
+/// picked from `lang` or `Accept-Language` and reported in `Content-Language`. Refused when the
+/// album is rated above the age of the reader in `X-Reader-Profile`.
 #[utoipa::path(
     get,
     path = "/contents/{episode_uuid}",
+    params(
+        ("episode_uuid" = String, Path, description = "Episode UUID", style = Simple),
+        ("lang" = Option<String>, Query, description = "Preferred locales, comma separated, before Accept-Language", example = "pt-BR"),
+        ("X-Reader-Profile" = Option<String>, Header, description = "Reader profile the album ratings are checked against"),
+        PageQuery
+    ),
     responses(
-        (status = 200, description = "Add successfully", body = ContentResponse),
+        (status = 200, description = "Request successfully", body = ResponseDataContent),
         (status = 400, description = "Update failed", body = ResponseMessage),
         (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 403, description = "Rated album and the reader's age is unknown", body = AgeRestrictedResponse),
+        (status = 451, description = "Reader younger than the album rating", body = AgeRestrictedResponse),
         (status = 500, description = "Internal server error", body = ResponseMessage)
     ),
     security(
@@ -90,14 +129,106 @@ pub async fn get_contents(
     pool: web::Data<DbPool>,
     http_request: HttpRequest,
     path: web::Path<String>,
+    query: web::Query<PageQuery>,
 ) -> impl Responder {
     let episode_uuid = path.into_inner();
-    if check_role(http_request) == Admin {
-        match Service::get_contents_by_episode_uuid(&pool, episode_uuid).await {
-            Ok(response) => HttpResponse::Ok().json(response),
-            Err(_) => HttpResponse::BadRequest().json(ResponseMessage {
+    if check_role(http_request.clone()) != Unknown {
+        if let Some(refusal) = episode_access_refusal(&pool, &http_request, &episode_uuid).await {
+            return refusal;
+        }
+        let first_page = query.cursor.is_none();
+        match Service::get_contents_by_episode_uuid(
+            &pool,
+            episode_uuid.clone(),
+            &requested_locales(&http_request),
+            query.into_inner(),
+        )
+        .await
+        {
+            Ok((locale, response)) => {
+                // Later pages belong to the same read, admin requests are not reads
+                if first_page && check_role(http_request.clone()) == User {
+                    AnalyticsService::record_episode_read(&pool, episode_uuid).await;
+                }
+                let mut builder = HttpResponse::Ok();
+                set_language_headers(&mut builder, &locale);
+                builder.json(response)
+            }
+            Err(PaginationError::Database(_)) => HttpResponse::BadRequest().json(ResponseMessage {
                 message: String::from("Failed to get contents data"),
             }),
+            Err(err) => HttpResponse::BadRequest().json(ResponseMessage {
+                message: err.to_string(),
+            }),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Reorder Contents
+///
+/// Replace the page order of an edition of an episode, every content of the edition must be
+/// listed once
+#[utoipa::path(
+    put,
+    path = "/contents/{episode_uuid}/order",
+    params(
+        ("episode_uuid" = String, Path, description = "Episode UUID", style = Simple)
+    ),
+    request_body = ReorderContentsRequest,
+    responses(
+        (status = 200, description = "Reordered successfully", body = [ContentResponse]),
+        (status = 400, description = "Invalid content list or episode not found", body = ResponseMessage),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Content",
+)]
+#[put("/contents/{episode_uuid}/order")]
+pub async fn reorder_contents(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+    req: web::Json<models::ReorderContentsRequest>,
+) -> impl Responder {
+    if check_role(http_request.clone()) == Admin {
+        let episode_uuid = path.into_inner();
+        let locale = req
+            .locale
+            .as_deref()
+            .and_then(normalize_locale)
+            .unwrap_or_else(default_locale);
+        let before = match Service::get_episode_id_by_uuid(&pool, episode_uuid.clone()).await {
+            Some(episode_id) => Service::get_content_uuids_by_episode_id(&pool, episode_id, &locale)
+                .await
+                .unwrap_or_default(),
+            None => vec![],
+        };
+        match Service::reorder_contents(&pool, episode_uuid.clone(), req.into_inner()).await {
+            Ok(response) => {
+                let after = response
+                    .iter()
+                    .map(|content| content.uuid.clone())
+                    .collect::<Vec<String>>();
+                audit_mutation(
+                    &pool,
+                    &http_request,
+                    "reorder",
+                    "episode",
+                    episode_uuid,
+                    Some(json!({ "locale": locale, "contents": before })),
+                    Some(json!({ "locale": locale, "contents": after })),
+                )
+                .await;
+                HttpResponse::Ok().json(response)
+            }
+            Err(message) => HttpResponse::BadRequest().json(ResponseMessage { message }),
         }
     } else {
         HttpResponse::Unauthorized().json(ResponseMessage {
@@ -135,23 +266,34 @@ pub async fn update_content(
     path: web::Path<String>,
     payload: Multipart,
 ) -> impl Responder {
-    if check_role(http_request) == Admin {
+    if check_role(http_request.clone()) == Admin {
         let content_uuid = path.into_inner();
+        let before = content_snapshot(&pool, &content_uuid).await;
         match parse_payload_data(payload).await {
-            Ok((payload_data, tmp_path)) => {
+            Ok((payload_data, _tmp_dir)) => {
                 match models::UpdateContentRequest::from_payload_data(payload_data).await {
-                    Ok(req_data) => match Service::update_content(&pool, content_uuid, req_data).await {
-                        Ok(response) => {
-                            delete_directory_if_exists(&tmp_path);
-                            HttpResponse::Ok().json(response)
-                        }
-                        Err(e) => {
-                            delete_directory_if_exists(&tmp_path);
-                            HttpResponse::BadRequest().json(ResponseMessage {
-                                message: String::from("Failed to update content"),
-                            })
+                    Ok(req_data) => {
+                        match Service::update_content(&pool, content_uuid.clone(), req_data).await {
+                            Ok(response) => {
+                                audit_mutation(
+                                    &pool,
+                                    &http_request,
+                                    "update",
+                                    "content",
+                                    content_uuid,
+                                    before,
+                                    serde_json::to_value(&response).ok(),
+                                )
+                                .await;
+                                HttpResponse::Ok().json(response)
+                            }
+                            Err(e) => {
+                                HttpResponse::BadRequest().json(ResponseMessage {
+                                    message: String::from("Failed to update content"),
+                                })
+                            }
                         }
-                    },
+                    }
                     Err(e) => HttpResponse::BadRequest().json(ResponseMessage {
                         message: String::from(e),
                     }),
@@ -168,7 +310,6 @@ pub async fn update_content(
     }
 }
 
-
 /// Delete Content
 ///
 /// Delete content
@@ -195,10 +336,23 @@ pub async fn delete_content(
     http_request: HttpRequest,
     path: web::Path<String>,
 ) -> impl Responder {
-    if check_role(http_request) == Admin {
+    if check_role(http_request.clone()) == Admin {
         let content_uuid = path.into_inner();
-        match Service::delete_content(&pool, content_uuid).await {
-            Ok(size) if size > 0 => HttpResponse::NoContent().json(""),
+        let before = content_snapshot(&pool, &content_uuid).await;
+        match Service::delete_content(&pool, content_uuid.clone()).await {
+            Ok(size) if size > 0 => {
+                audit_mutation(
+                    &pool,
+                    &http_request,
+                    "delete",
+                    "content",
+                    content_uuid,
+                    before,
+                    None,
+                )
+                .await;
+                HttpResponse::NoContent().json("")
+            }
             Ok(_) => HttpResponse::BadRequest().json(ResponseMessage {
                 message: String::from("Content not found"),
             }),
@@ -221,3 +375,11 @@ pub async fn delete_content(
         })
     }
 }
+
+async fn content_snapshot(pool: &DbPool, content_uuid: &str) -> Option<serde_json::Value> {
+    Service::get_content_by_uuid(pool, content_uuid.to_string())
+        .await
+        .ok()
+        .flatten()
+        .and_then(|content| serde_json::to_value(content).ok())
+}
@@ -1,3 +1,4 @@
+use crate::common::i18n::{default_locale, normalize_locale};
 use crate::common::ne_parse::NEParse;
 use crate::schema::contents;
 use chrono::NaiveDateTime;
@@ -37,6 +38,27 @@ pub struct Content {
     pub broken_at: Option<NaiveDateTime>,
     pub created_at: Option<NaiveDateTime>,
     pub updated_at: Option<NaiveDateTime>,
+    pub deleted_at: Option<NaiveDateTime>,
+    /// Empty for the pages in the default locale
+    pub locale: String,
+}
+
+/// Stored locale of an edition of the episode, empty for the default locale
+pub fn edition_locale(locale: &str) -> String {
+    if locale == default_locale() {
+        String::new()
+    } else {
+        locale.to_string()
+    }
+}
+
+/// Locale of an edition as shown to clients
+pub fn display_locale(edition: &str) -> String {
+    if edition.is_empty() {
+        default_locale()
+    } else {
+        edition.to_string()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema, Clone, IntoParams, PartialEq, Eq)]
@@ -52,6 +74,9 @@ pub struct ContentResponse {
     pub height: i32,
     pub bytes: i32,
     pub broken_at: Option<String>,
+    /// Language of the edition the page belongs to
+    #[schema(example = "en")]
+    pub locale: String,
 }
 
 impl ContentResponse {
@@ -68,6 +93,7 @@ impl ContentResponse {
             height: content.height,
             bytes: content.bytes,
             broken_at: NEParse::opt_naive_datetime_to_utc_opt_string(content.broken_at),
+            locale: display_locale(&content.locale),
         }
     }
     pub fn from_contents(contents: Vec<Content>) -> Vec<Self> {
@@ -84,6 +110,13 @@ pub struct AddEpisodeContentsRequest {
     /// Episode Content Files
     #[schema(value_type = Vec < String >, format = Binary)]
     pub files: Vec<String>,
+    /// Insert the files before the page with this index_no, the following pages shift.
+    /// Appended when empty
+    #[schema(value_type = Option<i32>)]
+    pub position: Option<i32>,
+    /// Language of the edition the files are added to, the default locale when empty
+    #[schema(value_type = Option<String>, example = "pt-BR")]
+    pub locale: Option<String>,
 }
 
 impl AddEpisodeContentsRequest {
@@ -95,6 +128,12 @@ impl AddEpisodeContentsRequest {
         } else {
             vec![]
         };
+        let locale = match payload_data.get("locale").and_then(|v| v.as_str()) {
+            Some(locale) if !locale.trim().is_empty() => {
+                Some(normalize_locale(locale).ok_or("Invalid locale")?)
+            }
+            _ => None,
+        };
         if file_paths.is_empty() {
             Err("Files cannot be empty")
         } else {
@@ -102,6 +141,10 @@ impl AddEpisodeContentsRequest {
                 episode_id: NEParse::opt_immut_str_to_opt_i32(payload_data["episode_id"].as_str())
                     .expect("Invalid episode id"),
                 files: file_paths,
+                position: NEParse::opt_immut_str_to_opt_i32(
+                    payload_data.get("position").and_then(|v| v.as_str()),
+                ),
+                locale,
             })
         }
     }
@@ -113,6 +156,7 @@ pub struct UpdateContentRequest {
     #[schema(value_type = Option < String >, format = Binary)]
     pub file: Option<String>,
     pub ads_url: Option<String>,
+    /// Move the page to this index_no, the pages in between shift
     pub index_no: Option<i32>,
 }
 
@@ -130,15 +174,29 @@ impl UpdateContentRequest {
         } else {
             None
         };
-        let index_no = if payload_data.contains_key("ads_url") {
+        let index_no = if payload_data.contains_key("index_no") {
             NEParse::opt_immut_str_to_opt_i32(payload_data["index_no"].as_str())
         } else {
             None
         };
         Ok(UpdateContentRequest {
-            file: if file_paths.is_empty() { None } else { file_paths.first().cloned() },
+            file: if file_paths.is_empty() {
+                None
+            } else {
+                file_paths.first().cloned()
+            },
             ads_url,
             index_no,
         })
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReorderContentsRequest {
+    /// Every content of the episode in the new page order
+    #[schema(example = json!(["fd2fe858-9962-404f-9174-c4f6f83cc39e"]))]
+    pub content_uuids: Vec<String>,
+    /// Edition to reorder, the default locale when empty
+    #[schema(example = "pt-BR")]
+    pub locale: Option<String>,
+}
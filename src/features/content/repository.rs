@@ -1,36 +1,224 @@
 use crate::common::database::DbPool;
+use crate::common::models::response_data::ResponseData;
+use crate::common::pagination::{Page, SortField, SortKind, SortOrder};
 use crate::features::content::models;
 use crate::schema::contents::episode_id;
 use crate::schema::{albums, contents, episodes};
-use diesel::dsl::{count_star, max};
+use chrono::Utc;
+use diesel::dsl::{count_star, sql};
 use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Integer, Text};
 use diesel::{JoinOnDsl, OptionalExtension, QueryDsl, QueryResult, RunQueryDsl};
 
+/// Live content ids of one edition of the episode in page order, `locale` is empty for the
+/// pages in the default locale
+pub(crate) fn load_page_order(
+    conn: &mut SqliteConnection,
+    episode_id_val: i32,
+    locale: &str,
+) -> QueryResult<Vec<i32>> {
+    Ok(contents::table
+        .filter(contents::episode_id.eq(episode_id_val))
+        .filter(contents::locale.eq(locale))
+        .filter(contents::deleted_at.is_null())
+        .order((contents::index_no.asc(), contents::id.asc()))
+        .select(contents::id)
+        .load::<Option<i32>>(conn)?
+        .into_iter()
+        .flatten()
+        .collect())
+}
+
+/// Number the live contents of one edition of the episode 0, 1, 2... in the given order. Every
+/// page is first parked on a negative index derived from its id, so
+/// `(episode_id, locale, index_no)` stays unique while pages move past each other.
+pub(crate) fn write_page_order(
+    conn: &mut SqliteConnection,
+    episode_id_val: i32,
+    locale: &str,
+    content_ids: &[i32],
+) -> QueryResult<()> {
+    diesel::update(
+        contents::table
+            .filter(contents::episode_id.eq(episode_id_val))
+            .filter(contents::locale.eq(locale))
+            .filter(contents::deleted_at.is_null()),
+    )
+    .set(contents::index_no.eq(sql::<Integer>("-1 - id")))
+    .execute(conn)?;
+    for (position, content_id) in content_ids.iter().enumerate() {
+        diesel::update(contents::table.filter(contents::id.eq(content_id)))
+            .set(contents::index_no.eq(position as i32))
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
 pub struct Repository;
 
 impl Repository {
-    pub async fn create_contents(
+    /// Sort options of the content list of an episode
+    pub fn sort_fields() -> Vec<SortField> {
+        use SortKind::*;
+        use SortOrder::*;
+        vec![
+            SortField::new("index_no", "contents.index_no", Integer, Asc),
+            SortField::new("id", "contents.id", Integer, Asc),
+            SortField::new("created_at", "COALESCE(contents.created_at, '')", Text, Desc),
+        ]
+    }
+
+    /// Insert the contents before the page at `position` of the edition, appended when `None` or
+    /// past the end
+    pub async fn insert_contents_at(
         pool: &DbPool,
-        new_contents: Vec<models::Content>,
+        episode_id_val: i32,
+        locale: &str,
+        mut new_contents: Vec<models::Content>,
+        position: Option<i32>,
     ) -> QueryResult<usize> {
         let mut conn = pool.get().expect("Failed to get DB connection");
-        let rows_inserted = diesel::insert_into(contents::table)
-            .values(&new_contents)
-            .execute(&mut conn)
-            .expect("Failed to create contents");
-        Ok(rows_inserted)
+        conn.transaction(|conn| {
+            let mut order = load_page_order(conn, episode_id_val, locale)?;
+            // Parked below the range `write_page_order` uses until the final numbering
+            for (offset, content) in new_contents.iter_mut().enumerate() {
+                content.index_no = i32::MIN + offset as i32;
+            }
+            let rows_inserted = diesel::insert_into(contents::table)
+                .values(&new_contents)
+                .execute(conn)?;
+            let uuids = new_contents
+                .iter()
+                .map(|content| content.uuid.clone())
+                .collect::<Vec<String>>();
+            let inserted_ids = contents::table
+                .filter(contents::uuid.eq_any(&uuids))
+                .order(contents::index_no.asc())
+                .select(contents::id)
+                .load::<Option<i32>>(conn)?
+                .into_iter()
+                .flatten();
+            let at = position
+                .map(|position| position.clamp(0, order.len() as i32) as usize)
+                .unwrap_or(order.len());
+            order.splice(at..at, inserted_ids);
+            write_page_order(conn, episode_id_val, locale, &order)?;
+            Ok(rows_inserted)
+        })
+    }
+
+    /// Move a content to `position`, clamped to the pages of its edition of the episode
+    pub async fn move_content(
+        pool: &DbPool,
+        episode_id_val: i32,
+        locale: &str,
+        content_id: i32,
+        position: i32,
+    ) -> QueryResult<()> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        conn.transaction(|conn| {
+            let mut order = load_page_order(conn, episode_id_val, locale)?;
+            order.retain(|id| *id != content_id);
+            let at = position.clamp(0, order.len() as i32) as usize;
+            order.insert(at, content_id);
+            write_page_order(conn, episode_id_val, locale, &order)
+        })
     }
 
+    /// Replace the page order of the edition with the given content uuids
+    pub async fn set_page_order(
+        pool: &DbPool,
+        episode_id_val: i32,
+        locale: &str,
+        content_uuids: &[String],
+    ) -> QueryResult<()> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        conn.transaction(|conn| {
+            let ids = contents::table
+                .filter(contents::episode_id.eq(episode_id_val))
+                .filter(contents::locale.eq(locale))
+                .filter(contents::deleted_at.is_null())
+                .select((contents::uuid, contents::id))
+                .load::<(String, Option<i32>)>(conn)?;
+            let order = content_uuids
+                .iter()
+                .filter_map(|content_uuid| {
+                    ids.iter()
+                        .find(|(uuid, _)| uuid == content_uuid)
+                        .and_then(|(_, id)| *id)
+                })
+                .collect::<Vec<i32>>();
+            write_page_order(conn, episode_id_val, locale, &order)
+        })
+    }
+
+
     pub async fn get_contents_by_episode_id(
         pool: &DbPool,
         episode_id_val: i32,
+        locale: &str,
     ) -> QueryResult<Vec<models::Content>> {
         let mut conn = pool.get().expect("Failed to get DB connection");
         contents::table
             .filter(contents::episode_id.eq(episode_id_val))
+            .filter(contents::locale.eq(locale))
+            .filter(contents::deleted_at.is_null())
+            .order((contents::index_no.asc(), contents::id.asc()))
             .load::<models::Content>(&mut conn) // Loads all matching Content rows
     }
 
+    /// Locales of the translated editions of the episodes with live pages, paired with their
+    /// episode id
+    pub async fn get_content_locales_by_episode_ids(
+        pool: &DbPool,
+        episode_ids: Vec<i32>,
+    ) -> QueryResult<Vec<(i32, String)>> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        contents::table
+            .filter(contents::episode_id.eq_any(episode_ids))
+            .filter(contents::locale.ne(""))
+            .filter(contents::deleted_at.is_null())
+            .select((contents::episode_id, contents::locale))
+            .distinct()
+            .order((contents::episode_id, contents::locale))
+            .load(&mut conn)
+    }
+
+    pub async fn get_contents_page_by_episode_id(
+        pool: &DbPool,
+        episode_id_val: i32,
+        locale: &str,
+        page: &Page,
+    ) -> QueryResult<ResponseData<models::ContentResponse>> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        let total = contents::table
+            .filter(contents::episode_id.eq(episode_id_val))
+            .filter(contents::locale.eq(locale))
+            .filter(contents::deleted_at.is_null())
+            .select(count_star())
+            .first::<i64>(&mut conn)?;
+
+        let mut query = contents::table
+            .filter(contents::episode_id.eq(episode_id_val))
+            .filter(contents::locale.eq(locale))
+            .filter(contents::deleted_at.is_null())
+            .into_boxed();
+        if let Some(after) = page.after_cursor::<contents::table>("contents.id") {
+            query = query.filter(after);
+        }
+        let result = query
+            .select((
+                models::Content::as_select(),
+                page.sort_value(),
+                sql::<BigInt>("contents.id"),
+            ))
+            .order(sql::<Text>(&page.order_by_sql("contents.id")))
+            .offset(page.offset())
+            .limit(page.fetch_limit())
+            .load::<(models::Content, String, i64)>(&mut conn)?;
+        Ok(page.finish(result, total, models::ContentResponse::from_content))
+    }
+
     pub async fn get_total_content_count_by_episode_id(
         pool: &DbPool,
         episode_id_val: i32,
@@ -65,6 +253,7 @@ impl Repository {
         let mut conn = pool.get().expect("Failed to get DB connection");
         contents::table
             .filter(contents::uuid.eq(content_uuid))
+            .filter(contents::deleted_at.is_null())
             .first::<models::Content>(&mut conn)
             .optional() // Returns `None` if no match is found
     }
@@ -80,17 +269,6 @@ impl Repository {
             .optional() // Returns `None` if no episode is found with the given ID
     }
 
-    pub async fn get_next_index_by_episode_id(
-        pool: &DbPool,
-        episode_id_val: i32,
-    ) -> QueryResult<Option<i32>> {
-        let mut conn = pool.get().expect("Failed to get DB connection");
-        contents::table
-            .select(max(contents::index_no))
-            .filter(contents::episode_id.eq(episode_id_val))
-            .first::<Option<i32>>(&mut conn)
-    }
-
     pub async fn get_episode_id_by_uuid(
         pool: &DbPool,
         episode_uuid_val: String,
@@ -99,6 +277,7 @@ impl Repository {
         episodes::table
             .select(episodes::id)
             .filter(episodes::uuid.eq(episode_uuid_val))
+            .filter(episodes::deleted_at.is_null())
             .first::<Option<i32>>(&mut conn) // Returns `None` if no episode is found with the given ID
     }
 
@@ -120,7 +299,27 @@ impl Repository {
     ) -> Result<usize, diesel::result::Error> {
         use crate::schema::contents::dsl::*;
         let mut conn = pool.get().expect("Failed to get DB connection");
-        let deleted = diesel::delete(contents.filter(uuid.eq(content_uuid))).execute(&mut conn)?;
-        Ok(deleted)
+        conn.transaction(|conn| {
+            let episode = contents
+                .filter(uuid.eq(&content_uuid))
+                .filter(deleted_at.is_null())
+                .select((episode_id, locale))
+                .first::<(i32, String)>(conn)
+                .optional()?;
+            let Some((episode, edition)) = episode else {
+                return Ok(0);
+            };
+            let deleted = diesel::update(
+                contents
+                    .filter(uuid.eq(&content_uuid))
+                    .filter(deleted_at.is_null()),
+            )
+            .set(deleted_at.eq(Some(Utc::now().naive_utc())))
+            .execute(conn)?;
+            // The following pages move up
+            let order = load_page_order(conn, episode, &edition)?;
+            write_page_order(conn, episode, &edition, &order)?;
+            Ok(deleted)
+        })
     }
 }
@@ -1,12 +1,14 @@
 use crate::common::database::DbPool;
+use crate::common::i18n::{default_locale, negotiate, normalize_locale};
 use crate::common::models::file_metadata::ImageMetadata;
 use crate::common::models::response_data::ResponseData;
+use crate::common::pagination::{Page, PageQuery, PaginationError};
 use crate::common::utils::{
-    delete_file_if_exists, get_data_directory, get_file_metadata, get_project_directory,
-    move_file_and_replace,
+    delete_file_if_exists, get_data_directory, get_file_metadata, get_file_name_from_path,
+    get_project_directory, get_trash_directory, move_file_and_replace, move_path,
 };
 use crate::features::content::models;
-use crate::features::content::models::ContentResponse;
+use crate::features::content::models::{edition_locale, ContentResponse};
 use crate::features::content::repository::Repository;
 use chrono::Utc;
 use uuid::Uuid;
@@ -18,143 +20,209 @@ impl Service {
         pool: &DbPool,
         req: models::AddEpisodeContentsRequest,
     ) -> Result<Vec<models::ContentResponse>, &str> {
-        match Repository::get_next_index_by_episode_id(pool, req.episode_id.clone()).await {
-            Ok(next_index) => {
-                let mut mut_index = if next_index.is_none() {
-                    0
-                } else {
-                    next_index.unwrap()
-                };
-                match Repository::get_album_uuid_by_episode_id(pool, req.episode_id.clone()).await {
-                    Ok(album_uuid) => {
-                        if album_uuid.clone().is_none() {
-                            Err("Album UUID not found")
-                        } else {
-                            match Repository::get_episode_uuid_by_id(pool, req.episode_id.clone())
-                                .await
-                            {
-                                Ok(episode_uuid) => {
-                                    if episode_uuid.clone().is_none() {
-                                        Err("Episode UUID not found")
-                                    } else {
-                                        let mut new_contents: Vec<models::Content> = vec![];
-                                        let mut file_paths = req.files.clone();
-                                        file_paths.retain(|s| !s.trim().is_empty());
-                                        for index in 0..req.files.clone().len() {
-                                            let file_path = file_paths[index].clone();
-                                            let uuid = Uuid::new_v4().to_string();
-                                            mut_index = mut_index + (index as i32);
-                                            let new_url = format!(
-                                                "{}/{}/{}/{}.{}",
-                                                get_data_directory(),
-                                                album_uuid.clone().unwrap(),
-                                                episode_uuid.clone().unwrap(),
-                                                uuid.clone(),
-                                                file_path.clone().split(".").last().unwrap()
-                                            );
-                                            let metadata = get_file_metadata(file_path.as_str());
-                                            new_contents.push(models::Content {
-                                                id: None,
-                                                episode_id: req.episode_id.clone(),
-                                                uuid,
-                                                index_no: mut_index,
-                                                url: new_url,
-                                                ads_url: None,
-                                                content_type: metadata.content_type,
-                                                width: metadata
-                                                    .image_data
-                                                    .clone()
-                                                    .unwrap_or(ImageMetadata::default())
-                                                    .width
-                                                    as i32,
-                                                height: metadata
-                                                    .image_data
-                                                    .clone()
-                                                    .unwrap_or(ImageMetadata::default())
-                                                    .height
-                                                    as i32,
-                                                bytes: metadata.size as i32,
-                                                broken_at: None,
-                                                created_at: None,
-                                                updated_at: None,
-                                            })
-                                        }
-                                        match Repository::create_contents(
-                                            pool,
-                                            new_contents.clone(),
-                                        )
-                                            .await
-                                        {
-                                            Ok(_) => {
-                                                if file_paths.len() == new_contents.len() {
-                                                    for i in 0..new_contents.len() {
-                                                        move_file_and_replace(
-                                                            file_paths[i].as_str(),
-                                                            format!(
-                                                                "{}/{}",
-                                                                get_project_directory(),
-                                                                new_contents[i].url.clone(),
-                                                            )
-                                                                .as_str(),
-                                                        )
-                                                    }
-                                                    match Repository::get_contents_by_episode_id(
-                                                        pool,
-                                                        req.episode_id,
-                                                    )
-                                                        .await
-                                                    {
-                                                        Ok(cs) => Ok(
-                                                            models::ContentResponse::from_contents(
-                                                                cs,
-                                                            ),
-                                                        ),
-                                                        Err(_) => {
-                                                            Err("Failed to get created contents")
-                                                        }
-                                                    }
-                                                } else {
-                                                    Err("Some files lost on data exchanging")
-                                                }
-                                            }
-                                            Err(e) => {
-                                                eprintln!("Error: {e}");
-                                                Err("Failed to add episode contents")
-                                            }
-                                        }
-                                    }
-                                }
-                                Err(_) => Err("Failed to get episode uuid"),
-                            }
-                        }
-                    }
-                    Err(_) => Err("Failed to get album uuid"),
-                }
-            }
-            Err(_) => Err("Episode not found"),
+        let album_uuid = match Repository::get_album_uuid_by_episode_id(pool, req.episode_id).await
+        {
+            Ok(Some(album_uuid)) => album_uuid,
+            Ok(None) => return Err("Album UUID not found"),
+            Err(_) => return Err("Failed to get album uuid"),
+        };
+        let episode_uuid = match Repository::get_episode_uuid_by_id(pool, req.episode_id).await {
+            Ok(Some(episode_uuid)) => episode_uuid,
+            Ok(None) => return Err("Episode UUID not found"),
+            Err(_) => return Err("Failed to get episode uuid"),
+        };
+        let edition = edition_locale(&req.locale.clone().unwrap_or_else(default_locale));
+        let mut file_paths = req.files.clone();
+        file_paths.retain(|s| !s.trim().is_empty());
+        let mut new_contents: Vec<models::Content> = vec![];
+        for file_path in &file_paths {
+            let uuid = Uuid::new_v4().to_string();
+            let new_url = format!(
+                "{}/{}/{}/{}.{}",
+                get_data_directory(),
+                album_uuid,
+                episode_uuid,
+                uuid,
+                file_path.split(".").last().unwrap()
+            );
+            let metadata = get_file_metadata(file_path.as_str());
+            let image_data = metadata.image_data.clone().unwrap_or(ImageMetadata::default());
+            new_contents.push(models::Content {
+                id: None,
+                episode_id: req.episode_id,
+                uuid,
+                // Numbered by insert_contents_at
+                index_no: 0,
+                url: new_url,
+                ads_url: None,
+                content_type: metadata.content_type,
+                width: image_data.width as i32,
+                height: image_data.height as i32,
+                bytes: metadata.size as i32,
+                broken_at: None,
+                created_at: None,
+                updated_at: None,
+                deleted_at: None,
+                locale: edition.clone(),
+            })
+        }
+        if let Err(e) = Repository::insert_contents_at(
+            pool,
+            req.episode_id,
+            &edition,
+            new_contents.clone(),
+            req.position,
+        )
+        .await
+        {
+            eprintln!("Error: {e}");
+            return Err("Failed to add episode contents");
+        }
+        for (file_path, content) in file_paths.iter().zip(new_contents.iter()) {
+            move_file_and_replace(
+                file_path.as_str(),
+                format!("{}/{}", get_project_directory(), content.url).as_str(),
+            )
+        }
+        match Repository::get_contents_by_episode_id(pool, req.episode_id, &edition).await {
+            Ok(cs) => Ok(models::ContentResponse::from_contents(cs)),
+            Err(_) => Err("Failed to get created contents"),
         }
     }
 
+    /// Pages of the edition best matching the requested locales, paired with its locale
     pub async fn get_contents_by_episode_uuid(
         pool: &DbPool,
         episode_uuid: String,
-    ) -> Result<ResponseData<models::ContentResponse>, diesel::result::Error> {
-        match Repository::get_episode_id_by_uuid(pool, episode_uuid).await {
-            Ok(ep_id) => {
-                if ep_id.is_none() {
-                    Err(diesel::result::Error::NotFound)
-                } else {
-                    match Repository::get_contents_by_episode_id(pool, ep_id.unwrap()).await {
-                        Ok(response) => Ok(ResponseData::<ContentResponse> {
-                            data: models::ContentResponse::from_contents(response.clone()),
-                            total: response.len() as i64,
-                        }),
-                        Err(e) => Err(e),
-                    }
-                }
+        requested_locales: &[String],
+        page_query: PageQuery,
+    ) -> Result<(String, ResponseData<models::ContentResponse>), PaginationError> {
+        let page = Page::new(page_query, None, Repository::sort_fields(), None)?;
+        match Repository::get_episode_id_by_uuid(pool, episode_uuid).await? {
+            Some(ep_id) => {
+                let locale = Self::negotiate_edition(pool, ep_id, requested_locales).await?;
+                let contents = Repository::get_contents_page_by_episode_id(
+                    pool,
+                    ep_id,
+                    &edition_locale(&locale),
+                    &page,
+                )
+                .await?;
+                Ok((locale, contents))
             }
-            Err(e) => Err(e),
+            None => Err(PaginationError::Database(diesel::result::Error::NotFound)),
+        }
+    }
+
+    /// Every page of an edition of the episode in reading order
+    pub async fn get_all_contents_by_episode_id(
+        pool: &DbPool,
+        episode_id: i32,
+        locale: &str,
+    ) -> Result<Vec<ContentResponse>, diesel::result::Error> {
+        Repository::get_contents_by_episode_id(pool, episode_id, &edition_locale(locale))
+            .await
+            .map(ContentResponse::from_contents)
+    }
+
+    /// Locales of the translated editions of the episodes, paired with their episode id
+    pub async fn get_content_locales(
+        pool: &DbPool,
+        episode_ids: Vec<i32>,
+    ) -> Result<Vec<(i32, String)>, diesel::result::Error> {
+        Repository::get_content_locales_by_episode_ids(pool, episode_ids).await
+    }
+
+    /// Edition of the episode to read for the requested locales, the default locale when no
+    /// translated edition matches
+    pub async fn negotiate_edition(
+        pool: &DbPool,
+        episode_id: i32,
+        requested_locales: &[String],
+    ) -> Result<String, diesel::result::Error> {
+        let mut available = vec![default_locale()];
+        available.extend(
+            Repository::get_content_locales_by_episode_ids(pool, vec![episode_id])
+                .await?
+                .into_iter()
+                .map(|(_, locale)| locale),
+        );
+        Ok(negotiate(requested_locales, &available).unwrap_or_else(default_locale))
+    }
+
+    pub async fn get_content_by_uuid(
+        pool: &DbPool,
+        content_uuid: String,
+    ) -> Result<Option<ContentResponse>, diesel::result::Error> {
+        Repository::get_content_by_uuid(pool, content_uuid)
+            .await
+            .map(|content| content.map(ContentResponse::from_content))
+    }
+
+    pub async fn get_episode_id_by_uuid(pool: &DbPool, episode_uuid: String) -> Option<i32> {
+        Repository::get_episode_id_by_uuid(pool, episode_uuid)
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Replace the page order of an edition of the episode, every content of the edition must be
+    /// listed exactly once
+    pub async fn reorder_contents(
+        pool: &DbPool,
+        episode_uuid: String,
+        req: models::ReorderContentsRequest,
+    ) -> Result<Vec<ContentResponse>, String> {
+        let episode_id = Self::get_episode_id_by_uuid(pool, episode_uuid)
+            .await
+            .ok_or_else(|| String::from("Episode not found"))?;
+        let locale = match req.locale.as_deref().filter(|l| !l.trim().is_empty()) {
+            Some(locale) => normalize_locale(locale).ok_or_else(|| String::from("Invalid locale"))?,
+            None => default_locale(),
+        };
+        let edition = edition_locale(&locale);
+        let current = Self::get_content_uuids_by_episode_id(pool, episode_id, &locale)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut listed = req.content_uuids.clone();
+        listed.sort();
+        listed.dedup();
+        if listed.len() != req.content_uuids.len() {
+            return Err(String::from("A content is listed more than once"));
         }
+        if let Some(unknown) = req
+            .content_uuids
+            .iter()
+            .find(|uuid| !current.contains(uuid))
+        {
+            return Err(format!("Content {} is not in this episode", unknown));
+        }
+        if req.content_uuids.len() != current.len() {
+            return Err(format!(
+                "Every content of the episode must be listed, {} of {} given",
+                req.content_uuids.len(),
+                current.len()
+            ));
+        }
+
+        Repository::set_page_order(pool, episode_id, &edition, &req.content_uuids)
+            .await
+            .map_err(|e| e.to_string())?;
+        Repository::get_contents_by_episode_id(pool, episode_id, &edition)
+            .await
+            .map(ContentResponse::from_contents)
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn get_content_uuids_by_episode_id(
+        pool: &DbPool,
+        episode_id: i32,
+        locale: &str,
+    ) -> Result<Vec<String>, diesel::result::Error> {
+        Repository::get_contents_by_episode_id(pool, episode_id, &edition_locale(locale))
+            .await
+            .map(|contents| contents.into_iter().map(|c| c.uuid).collect())
     }
 
     pub async fn update_content(
@@ -168,9 +236,19 @@ impl Service {
                     let old_content = content.unwrap().clone();
                     let mut update_content = old_content.clone();
                     if !update_content_request.file.is_none() {
-                        match Repository::get_episode_uuid_by_id(pool, old_content.episode_id.clone()).await {
+                        match Repository::get_episode_uuid_by_id(
+                            pool,
+                            old_content.episode_id.clone(),
+                        )
+                        .await
+                        {
                             Ok(episode_uuid) => {
-                                match Repository::get_album_uuid_by_episode_id(pool, old_content.episode_id).await {
+                                match Repository::get_album_uuid_by_episode_id(
+                                    pool,
+                                    old_content.episode_id,
+                                )
+                                .await
+                                {
                                     Ok(album_uuid) => {
                                         let new_uuid = Uuid::new_v4().to_string();
                                         let new_url = format!(
@@ -179,15 +257,39 @@ impl Service {
                                             album_uuid.clone().unwrap(),
                                             episode_uuid.clone().unwrap(),
                                             new_uuid,
-                                            update_content_request.file.clone().unwrap().split(".").last().unwrap()
+                                            update_content_request
+                                                .file
+                                                .clone()
+                                                .unwrap()
+                                                .split(".")
+                                                .last()
+                                                .unwrap()
+                                        );
+                                        let des_path = format!(
+                                            "{}/{}",
+                                            get_project_directory(),
+                                            new_url.clone()
+                                        );
+                                        move_file_and_replace(
+                                            update_content_request.file.clone().unwrap().as_str(),
+                                            des_path.as_str(),
                                         );
-                                        let des_path = format!("{}/{}", get_project_directory(), new_url.clone());
-                                        move_file_and_replace(update_content_request.file.clone().unwrap().as_str(), des_path.as_str());
                                         delete_file_if_exists(&old_content.url);
-                                        let metadata = get_file_metadata(update_content_request.file.unwrap().as_str());
+                                        let metadata = get_file_metadata(
+                                            update_content_request.file.unwrap().as_str(),
+                                        );
                                         update_content.content_type = metadata.content_type;
-                                        update_content.height = metadata.image_data.clone().unwrap_or(ImageMetadata::default()).height as i32;
-                                        update_content.width = metadata.image_data.unwrap_or(ImageMetadata::default()).width as i32;
+                                        update_content.height = metadata
+                                            .image_data
+                                            .clone()
+                                            .unwrap_or(ImageMetadata::default())
+                                            .height
+                                            as i32;
+                                        update_content.width = metadata
+                                            .image_data
+                                            .unwrap_or(ImageMetadata::default())
+                                            .width
+                                            as i32;
                                         update_content.bytes = metadata.size as i32;
                                         update_content.url = new_url;
                                         update_content.uuid = new_uuid;
@@ -201,16 +303,31 @@ impl Service {
                     if !update_content_request.ads_url.is_none() {
                         update_content.ads_url = update_content_request.ads_url;
                     }
-                    if !update_content_request.index_no.is_none() {
-                        update_content.index_no = update_content_request.index_no.unwrap();
-                    }
                     update_content.updated_at = Some(Utc::now().naive_utc());
                     match Repository::update_content(pool, update_content.clone()).await {
-                        Ok(usize) => if usize > 0 {
-                            Ok(ContentResponse::from_content(update_content))
-                        } else {
-                            Err(diesel::result::Error::NotFound)
-                        },
+                        Ok(usize) => {
+                            if usize > 0 {
+                                if let Some(index_no) = update_content_request.index_no {
+                                    Repository::move_content(
+                                        pool,
+                                        update_content.episode_id,
+                                        &update_content.locale,
+                                        update_content.id.unwrap_or_default(),
+                                        index_no,
+                                    )
+                                    .await?;
+                                    update_content = Repository::get_content_by_uuid(
+                                        pool,
+                                        update_content.uuid.clone(),
+                                    )
+                                    .await?
+                                    .unwrap_or(update_content);
+                                }
+                                Ok(ContentResponse::from_content(update_content))
+                            } else {
+                                Err(diesel::result::Error::NotFound)
+                            }
+                        }
                         Err(e) => Err(e),
                     }
                 } else {
@@ -227,9 +344,20 @@ impl Service {
     ) -> Result<usize, diesel::result::Error> {
         match Repository::get_content_by_uuid(pool, content_uuid.clone()).await {
             Ok(content) => {
-                if !content.is_none() {
-                    delete_file_if_exists(&content.unwrap().url);
-                    Repository::delete_content(pool, content_uuid).await
+                if let Some(content) = content {
+                    let deleted = Repository::delete_content(pool, content_uuid.clone()).await?;
+                    if deleted > 0 {
+                        let trash_path = format!(
+                            "{}/contents/{}/{}",
+                            get_trash_directory(),
+                            content_uuid,
+                            get_file_name_from_path(&content.url)
+                        );
+                        if let Err(e) = move_path(&content.url, &trash_path) {
+                            eprintln!("Failed to move content {} to trash: {}", content_uuid, e);
+                        }
+                    }
+                    Ok(deleted)
                 } else {
                     Err(diesel::result::Error::NotFound)
                 }
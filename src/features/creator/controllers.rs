@@ -0,0 +1,307 @@
+use actix_multipart::Multipart;
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+
+use crate::common::database::DbPool;
+use crate::common::enums::Role::{Admin, Unknown};
+use crate::common::i18n::{requested_locales, vary_on_language};
+use crate::common::models::response_message::ResponseMessage;
+use crate::common::utils::parse_payload_data;
+use crate::features::creator::models::{
+    CreateCreatorRequest, CreatorError, GetCreatorsRequest, UpdateCreatorRequest,
+};
+use crate::features::creator::services::Service;
+use crate::features::{audit_mutation, check_role, reader_age};
+
+/// Create Creator
+///
+/// Add an author, artist, translator or publisher that albums can credit
+#[utoipa::path(
+    post,
+    path = "/creator",
+    request_body(
+        content = CreateCreatorRequest,
+        description = "Create Creator Request",
+        content_type = "multipart/form-data",
+    ),
+    responses(
+        (status = 201, description = "Created successfully", body = CreatorResponse),
+        (status = 400, description = "Invalid creator", body = ResponseMessage),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Creator",
+)]
+#[post("/creator")]
+pub async fn create_creator(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    payload: Multipart,
+) -> impl Responder {
+    if check_role(http_request.clone()) == Admin {
+        let (payload_data, _tmp_dir) = match parse_payload_data(payload).await {
+            Ok(parsed) => parsed,
+            Err(e) => return HttpResponse::BadRequest().json(ResponseMessage { message: e }),
+        };
+        let request = match CreateCreatorRequest::from_payload_data(payload_data).await {
+            Ok(request) => request,
+            Err(e) => {
+                return HttpResponse::BadRequest().json(ResponseMessage {
+                    message: String::from(e),
+                })
+            }
+        };
+        match Service::add_creator(&pool, request).await {
+            Ok(creator) => {
+                audit_mutation(
+                    &pool,
+                    &http_request,
+                    "create",
+                    "creator",
+                    creator.uuid.clone(),
+                    None,
+                    serde_json::to_value(&creator).ok(),
+                )
+                .await;
+                HttpResponse::Created().json(creator)
+            }
+            Err(e) => creator_error_response("create", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Get Creators
+///
+/// Get creators by name, aliases included
+#[utoipa::path(
+    get,
+    path = "/creators",
+    params(GetCreatorsRequest),
+    responses(
+        (status = 200, description = "Get successfully", body = ResponseDataCreator),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Creator",
+)]
+#[get("/creators")]
+pub async fn get_creators(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    query: web::Query<GetCreatorsRequest>,
+) -> impl Responder {
+    if check_role(http_request) == Admin {
+        match Service::get_creators(&pool, query.into_inner()).await {
+            Ok(response) => HttpResponse::Ok().json(response),
+            Err(e) => creator_error_response("get", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Get Creator Page
+///
+/// Get a creator with every album crediting them, album titles in the best locale of `lang` or
+/// `Accept-Language`. Albums rated above the age of the reader in `X-Reader-Profile` are left
+/// out.
+#[utoipa::path(
+    get,
+    path = "/creators/{creator_uuid}",
+    params(
+        ("creator_uuid" = String, Path, description = "Creator ID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e"),
+        ("lang" = Option<String>, Query, description = "Preferred locales, comma separated, before Accept-Language", example = "pt-BR"),
+        ("X-Reader-Profile" = Option<String>, Header, description = "Reader profile the album ratings are checked against")
+    ),
+    responses(
+        (status = 200, description = "Get successfully", body = CreatorPageResponse),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 404, description = "Creator not found", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Creator",
+)]
+#[get("/creators/{creator_uuid}")]
+pub async fn get_creator(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+) -> impl Responder {
+    if check_role(http_request.clone()) != Unknown {
+        let locales = requested_locales(&http_request);
+        let reader_age = reader_age(&pool, &http_request).await;
+        match Service::get_creator_page(&pool, path.into_inner(), &locales, reader_age).await {
+            Ok(response) => {
+                let mut builder = HttpResponse::Ok();
+                vary_on_language(&mut builder);
+                builder.json(response)
+            }
+            Err(e) => creator_error_response("get", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Update Creator
+///
+/// Update creator, fields left empty keep their value
+#[utoipa::path(
+    put,
+    path = "/creator/{creator_uuid}",
+    params(
+        ("creator_uuid" = String, Path, description = "Creator ID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e")
+    ),
+    request_body(
+        content = UpdateCreatorRequest,
+        description = "Update Creator Request",
+        content_type = "multipart/form-data",
+    ),
+    responses(
+        (status = 200, description = "Update successfully", body = CreatorResponse),
+        (status = 400, description = "Invalid creator", body = ResponseMessage),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 404, description = "Creator not found", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Creator",
+)]
+#[put("/{creator_uuid}")]
+pub async fn update_creator(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+    payload: Multipart,
+) -> impl Responder {
+    if check_role(http_request.clone()) == Admin {
+        let creator_uuid = path.into_inner();
+        let (payload_data, _tmp_dir) = match parse_payload_data(payload).await {
+            Ok(parsed) => parsed,
+            Err(e) => return HttpResponse::BadRequest().json(ResponseMessage { message: e }),
+        };
+        let request = match UpdateCreatorRequest::from_payload_data(payload_data).await {
+            Ok(request) => request,
+            Err(e) => {
+                return HttpResponse::BadRequest().json(ResponseMessage {
+                    message: String::from(e),
+                })
+            }
+        };
+        let before = creator_snapshot(&pool, &creator_uuid).await;
+        match Service::update_creator(&pool, creator_uuid, request).await {
+            Ok(creator) => {
+                audit_mutation(
+                    &pool,
+                    &http_request,
+                    "update",
+                    "creator",
+                    creator.uuid.clone(),
+                    before,
+                    serde_json::to_value(&creator).ok(),
+                )
+                .await;
+                HttpResponse::Ok().json(creator)
+            }
+            Err(e) => creator_error_response("update", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Delete Creator
+///
+/// Delete creator, their credits are removed from every album
+#[utoipa::path(
+    delete,
+    path = "/creator/{creator_uuid}",
+    params(
+        ("creator_uuid" = String, Path, description = "Creator ID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e")
+    ),
+    responses(
+        (status = 204, description = "Delete successfully"),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 404, description = "Creator not found", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Creator",
+)]
+#[delete("/{creator_uuid}")]
+pub async fn delete_creator(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+) -> impl Responder {
+    if check_role(http_request.clone()) == Admin {
+        let creator_uuid = path.into_inner();
+        let before = creator_snapshot(&pool, &creator_uuid).await;
+        match Service::delete_creator(&pool, creator_uuid.clone()).await {
+            Ok(_) => {
+                audit_mutation(
+                    &pool,
+                    &http_request,
+                    "delete",
+                    "creator",
+                    creator_uuid,
+                    before,
+                    None,
+                )
+                .await;
+                HttpResponse::NoContent().json("")
+            }
+            Err(e) => creator_error_response("delete", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+fn creator_error_response(action: &str, e: CreatorError) -> HttpResponse {
+    match e {
+        CreatorError::NotFound => HttpResponse::NotFound().json(ResponseMessage {
+            message: e.to_string(),
+        }),
+        CreatorError::Invalid(_) => HttpResponse::BadRequest().json(ResponseMessage {
+            message: e.to_string(),
+        }),
+        CreatorError::Database(_) => {
+            println!("Failed to {} creator: {}", action, e);
+            HttpResponse::InternalServerError().json(ResponseMessage {
+                message: String::from("Internal Server Error"),
+            })
+        }
+    }
+}
+
+async fn creator_snapshot(pool: &DbPool, creator_uuid: &str) -> Option<serde_json::Value> {
+    Service::get_creator_by_uuid(pool, creator_uuid.to_string())
+        .await
+        .ok()
+        .and_then(|creator| serde_json::to_value(creator).ok())
+}
@@ -0,0 +1,21 @@
+use actix_web::web;
+
+use crate::features::creator::controllers::*;
+
+pub mod controllers;
+pub mod models;
+pub mod repository;
+pub mod services;
+
+// ! Register ever routes in this configure function
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(create_creator);
+    cfg.service(get_creators);
+    cfg.service(get_creator);
+
+    cfg.service(
+        web::scope("/creator")
+            .service(update_creator)
+            .service(delete_creator),
+    );
+}
@@ -0,0 +1,302 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::NaiveDateTime;
+use diesel::{Insertable, Queryable, Selectable};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::common::ne_parse::NEParse;
+use crate::features::album::models::AlbumResponse;
+use crate::schema::{album_credits, creators};
+
+/// Most creators returned in one page
+pub const MAX_CREATORS_LIMIT: i64 = 500;
+
+/// Part a creator had in an album
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CreditRole {
+    Author,
+    Artist,
+    Translator,
+    Publisher,
+}
+
+impl CreditRole {
+    pub const ALL: [CreditRole; 4] = [
+        CreditRole::Author,
+        CreditRole::Artist,
+        CreditRole::Translator,
+        CreditRole::Publisher,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CreditRole::Author => "author",
+            CreditRole::Artist => "artist",
+            CreditRole::Translator => "translator",
+            CreditRole::Publisher => "publisher",
+        }
+    }
+}
+
+impl FromStr for CreditRole {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        CreditRole::ALL
+            .into_iter()
+            .find(|role| role.as_str() == value)
+            .ok_or_else(|| {
+                format!(
+                    "Invalid credit role {:?}, expected one of {}",
+                    value,
+                    CreditRole::ALL.map(|role| role.as_str()).join(", ")
+                )
+            })
+    }
+}
+
+#[derive(Debug, Queryable, Selectable, Clone, PartialEq, Eq)]
+#[diesel(table_name = creators)]
+pub struct Creator {
+    pub id: Option<i32>,
+    pub uuid: String,
+    pub name: String,
+    /// Comma separated aliases
+    pub aliases: String,
+    pub bio: String,
+    pub avatar_url: Option<String>,
+    pub avatar_content_type: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = creators)]
+pub struct NewCreator {
+    pub uuid: String,
+    pub name: String,
+    pub aliases: String,
+    pub bio: String,
+    pub avatar_url: Option<String>,
+    pub avatar_content_type: Option<String>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = album_credits)]
+pub struct NewAlbumCredit {
+    pub album_id: i32,
+    pub creator_id: i32,
+    pub role: String,
+    pub position: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, PartialEq, Eq)]
+pub struct CreatorResponse {
+    pub uuid: String,
+    pub name: String,
+    /// Pen names and other spellings of the name
+    pub aliases: Vec<String>,
+    pub bio: String,
+    pub avatar_url: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+impl CreatorResponse {
+    pub fn from_creator(creator: Creator) -> Self {
+        CreatorResponse {
+            uuid: creator.uuid,
+            name: creator.name,
+            aliases: split_aliases(&creator.aliases),
+            bio: creator.bio,
+            avatar_url: creator.avatar_url,
+            created_at: NEParse::opt_naive_datetime_to_utc_opt_string(creator.created_at),
+            updated_at: NEParse::opt_naive_datetime_to_utc_opt_string(creator.updated_at),
+        }
+    }
+
+    pub fn from_creators(creators: Vec<Creator>) -> Vec<Self> {
+        creators.into_iter().map(Self::from_creator).collect()
+    }
+}
+
+/// Creator page, the creator with every album crediting them
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
+pub struct CreatorPageResponse {
+    #[serde(flatten)]
+    pub creator: CreatorResponse,
+    /// Albums crediting the creator in any role, trashed albums excluded
+    pub albums: Vec<AlbumResponse>,
+}
+
+/// Credit of a creator on an album
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, PartialEq, Eq)]
+pub struct AlbumCreditResponse {
+    pub creator_uuid: String,
+    pub name: String,
+    pub role: CreditRole,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
+pub struct AlbumCreditRequest {
+    #[schema(example = "fd2fe858-9962-404f-9174-c4f6f83cc39e")]
+    pub creator_uuid: String,
+    pub role: CreditRole,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
+pub struct SetAlbumCreditsRequest {
+    /// Every credit of the album in order, replaces the current credits
+    pub credits: Vec<AlbumCreditRequest>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreateCreatorRequest {
+    #[schema(example = "Name")]
+    pub name: String,
+    /// Aliases as comma separated names
+    #[schema(value_type = Option < String >, example = "Pen Name,Other Spelling")]
+    pub aliases: Option<Vec<String>>,
+    pub bio: Option<String>,
+    /// Creator avatar
+    #[schema(value_type = Option < String >, format = Binary)]
+    pub avatar: Option<String>,
+}
+
+impl CreateCreatorRequest {
+    pub async fn from_payload_data<'a>(
+        payload_data: HashMap<String, Value>,
+    ) -> Result<Self, &'a str> {
+        let name = NEParse::opt_immut_str_to_option_string(
+            payload_data.get("name").and_then(|v| v.as_str()),
+        )
+        .filter(|name| !name.trim().is_empty())
+        .ok_or("Name cannot be empty")?;
+        Ok(CreateCreatorRequest {
+            name,
+            aliases: payload_data
+                .get("aliases")
+                .and_then(|v| v.as_str())
+                .map(split_aliases),
+            bio: NEParse::opt_immut_str_to_option_string(
+                payload_data.get("bio").and_then(|v| v.as_str()),
+            ),
+            avatar: first_file(&payload_data, "avatar"),
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UpdateCreatorRequest {
+    pub name: Option<String>,
+    /// Replace the aliases, comma separated names
+    #[schema(value_type = Option < String >, example = "Pen Name,Other Spelling")]
+    pub aliases: Option<Vec<String>>,
+    pub bio: Option<String>,
+    /// Replace the creator avatar
+    #[schema(value_type = Option < String >, format = Binary)]
+    pub avatar: Option<String>,
+    /// Remove the creator avatar
+    #[schema(example = false)]
+    pub remove_avatar: Option<bool>,
+}
+
+impl UpdateCreatorRequest {
+    pub async fn from_payload_data<'a>(
+        payload_data: HashMap<String, Value>,
+    ) -> Result<Self, &'a str> {
+        let name = NEParse::opt_immut_str_to_option_string(
+            payload_data.get("name").and_then(|v| v.as_str()),
+        );
+        if name.as_ref().is_some_and(|name| name.trim().is_empty()) {
+            return Err("Name cannot be empty");
+        }
+        Ok(UpdateCreatorRequest {
+            name,
+            aliases: payload_data
+                .get("aliases")
+                .and_then(|v| v.as_str())
+                .map(split_aliases),
+            bio: NEParse::opt_immut_str_to_option_string(
+                payload_data.get("bio").and_then(|v| v.as_str()),
+            ),
+            avatar: first_file(&payload_data, "avatar"),
+            remove_avatar: NEParse::opt_immut_str_to_option_bool(
+                payload_data.get("remove_avatar").and_then(|v| v.as_str()),
+            ),
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GetCreatorsRequest {
+    /// Filter name or alias
+    pub name: Option<String>,
+    /// Filter offset
+    pub offset: Option<i64>,
+    /// Filter limit, 1 to 500, default 50
+    pub limit: Option<i64>,
+}
+
+impl GetCreatorsRequest {
+    /// Requested offset, never negative
+    pub fn offset(&self) -> i64 {
+        self.offset.unwrap_or(0).max(0)
+    }
+
+    /// Requested limit kept within 1 to [`MAX_CREATORS_LIMIT`], SQLite reads a negative limit as
+    /// no limit
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(50).clamp(1, MAX_CREATORS_LIMIT)
+    }
+}
+
+#[derive(Debug)]
+pub enum CreatorError {
+    NotFound,
+    Invalid(String),
+    Database(diesel::result::Error),
+}
+
+impl fmt::Display for CreatorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CreatorError::NotFound => write!(f, "Creator not found"),
+            CreatorError::Invalid(message) => write!(f, "{}", message),
+            CreatorError::Database(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<diesel::result::Error> for CreatorError {
+    fn from(e: diesel::result::Error) -> Self {
+        match e {
+            diesel::result::Error::NotFound => CreatorError::NotFound,
+            e => CreatorError::Database(e),
+        }
+    }
+}
+
+/// Trimmed non empty names of a comma separated list
+pub fn split_aliases(aliases: &str) -> Vec<String> {
+    aliases
+        .split(',')
+        .map(str::trim)
+        .filter(|alias| !alias.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn first_file(payload_data: &HashMap<String, Value>, key: &str) -> Option<String> {
+    NEParse::opt_immut_vec_serde_json_value_to_vec_string(
+        payload_data.get(key).and_then(|v| v.as_array()),
+    )
+    .into_iter()
+    .next()
+}
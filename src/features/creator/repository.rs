@@ -0,0 +1,138 @@
+use chrono::Utc;
+use diesel::dsl::count_star;
+use diesel::prelude::*;
+
+use crate::common::database::DbPool;
+use crate::features::creator::models::{Creator, GetCreatorsRequest, NewAlbumCredit, NewCreator};
+use crate::schema::{album_credits, creators};
+
+pub struct Repository;
+
+impl Repository {
+    pub async fn create_creator(pool: &DbPool, new_creator: NewCreator) -> QueryResult<Creator> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        let creator_uuid = new_creator.uuid.clone();
+        diesel::insert_into(creators::table)
+            .values(&new_creator)
+            .execute(&mut conn)?;
+        creators::table
+            .filter(creators::uuid.eq(creator_uuid))
+            .select(Creator::as_select())
+            .first(&mut conn)
+    }
+
+    pub async fn get_creators(
+        pool: &DbPool,
+        filters: GetCreatorsRequest,
+    ) -> QueryResult<(Vec<Creator>, i64)> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        let (offset, limit) = (filters.offset(), filters.limit());
+        let mut query = creators::table.into_boxed();
+        let mut count_query = creators::table.into_boxed();
+
+        if let Some(_name) = filters.name.filter(|s| !s.trim().is_empty()) {
+            let pattern = format!("%{}%", _name.trim());
+            query = query.filter(
+                creators::name
+                    .like(pattern.clone())
+                    .or(creators::aliases.like(pattern.clone())),
+            );
+            count_query = count_query.filter(
+                creators::name
+                    .like(pattern.clone())
+                    .or(creators::aliases.like(pattern)),
+            );
+        }
+
+        let total = count_query.select(count_star()).first::<i64>(&mut conn)?;
+        let results = query
+            .order((creators::name.asc(), creators::id.asc()))
+            .offset(offset)
+            .limit(limit)
+            .select(Creator::as_select())
+            .load(&mut conn)?;
+        Ok((results, total))
+    }
+
+    pub async fn get_creator_by_uuid(pool: &DbPool, creator_uuid: String) -> QueryResult<Creator> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        creators::table
+            .filter(creators::uuid.eq(creator_uuid))
+            .select(Creator::as_select())
+            .first(&mut conn)
+    }
+
+    pub async fn get_creators_by_uuids(
+        pool: &DbPool,
+        creator_uuids: Vec<String>,
+    ) -> QueryResult<Vec<Creator>> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        creators::table
+            .filter(creators::uuid.eq_any(creator_uuids))
+            .select(Creator::as_select())
+            .load(&mut conn)
+    }
+
+    pub async fn update_creator(pool: &DbPool, creator: Creator) -> QueryResult<Creator> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        diesel::update(creators::table.filter(creators::id.eq(creator.id)))
+            .set((
+                creators::name.eq(&creator.name),
+                creators::aliases.eq(&creator.aliases),
+                creators::bio.eq(&creator.bio),
+                creators::avatar_url.eq(&creator.avatar_url),
+                creators::avatar_content_type.eq(&creator.avatar_content_type),
+                creators::updated_at.eq(Some(Utc::now().naive_utc())),
+            ))
+            .execute(&mut conn)?;
+        creators::table
+            .filter(creators::id.eq(creator.id))
+            .select(Creator::as_select())
+            .first(&mut conn)
+    }
+
+    /// Delete the creator and their album credits
+    pub async fn delete_creator(pool: &DbPool, creator_id: i32) -> QueryResult<usize> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        conn.transaction(|conn| {
+            diesel::delete(album_credits::table.filter(album_credits::creator_id.eq(creator_id)))
+                .execute(conn)?;
+            diesel::delete(creators::table.filter(creators::id.eq(creator_id))).execute(conn)
+        })
+    }
+
+    /// Credits of the albums in order, paired with their album id and role
+    pub async fn get_credits_by_album_ids(
+        pool: &DbPool,
+        album_ids: Vec<i32>,
+    ) -> QueryResult<Vec<(i32, String, Creator)>> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        album_credits::table
+            .inner_join(creators::table)
+            .filter(album_credits::album_id.eq_any(album_ids))
+            .order((album_credits::album_id, album_credits::position))
+            .select((
+                album_credits::album_id,
+                album_credits::role,
+                Creator::as_select(),
+            ))
+            .load(&mut conn)
+    }
+
+    /// Replace the credits of the album
+    pub async fn set_album_credits(
+        pool: &DbPool,
+        album_id: i32,
+        credits: Vec<NewAlbumCredit>,
+    ) -> QueryResult<()> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        conn.immediate_transaction(|conn| {
+            diesel::delete(album_credits::table.filter(album_credits::album_id.eq(album_id)))
+                .execute(conn)?;
+            diesel::insert_into(album_credits::table)
+                .values(&credits)
+                .execute(conn)?;
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,236 @@
+use uuid::Uuid;
+
+use crate::common::database::DbPool;
+use crate::common::models::response_data::ResponseData;
+use crate::common::utils::{
+    delete_file_if_exists, get_data_directory, get_file_metadata, get_project_directory,
+    move_file_and_replace,
+};
+use crate::features::album::services::Service as AlbumService;
+use crate::features::creator::models::{
+    AlbumCreditResponse, CreateCreatorRequest, Creator, CreatorError, CreatorPageResponse,
+    CreatorResponse, CreditRole, GetCreatorsRequest, NewAlbumCredit, NewCreator,
+    SetAlbumCreditsRequest, UpdateCreatorRequest,
+};
+use crate::features::creator::repository::Repository;
+use crate::features::reader::models::ReaderAge;
+
+pub struct Service;
+
+impl Service {
+    pub async fn add_creator(
+        pool: &DbPool,
+        req: CreateCreatorRequest,
+    ) -> Result<CreatorResponse, CreatorError> {
+        let avatar = match &req.avatar {
+            Some(avatar_path) => Some(avatar_destination(avatar_path)?),
+            None => None,
+        };
+        let creator = Repository::create_creator(
+            pool,
+            NewCreator {
+                uuid: Uuid::new_v4().to_string(),
+                name: req.name.trim().to_string(),
+                aliases: req.aliases.unwrap_or_default().join(","),
+                bio: req.bio.unwrap_or_default(),
+                avatar_url: avatar.as_ref().map(|(url, _)| url.clone()),
+                avatar_content_type: avatar.map(|(_, content_type)| content_type),
+            },
+        )
+        .await?;
+        if let (Some(avatar_path), Some(avatar_url)) = (&req.avatar, &creator.avatar_url) {
+            move_file_and_replace(
+                avatar_path,
+                &format!("{}/{}", get_project_directory(), avatar_url),
+            );
+        }
+        Ok(CreatorResponse::from_creator(creator))
+    }
+
+    pub async fn get_creators(
+        pool: &DbPool,
+        filters: GetCreatorsRequest,
+    ) -> Result<ResponseData<CreatorResponse>, CreatorError> {
+        let offset = filters.offset();
+        let (creators, total) = Repository::get_creators(pool, filters).await?;
+        Ok(ResponseData::new(
+            CreatorResponse::from_creators(creators),
+            total,
+            offset,
+        ))
+    }
+
+    pub async fn get_creator_by_uuid(
+        pool: &DbPool,
+        creator_uuid: String,
+    ) -> Result<CreatorResponse, CreatorError> {
+        let creator = Repository::get_creator_by_uuid(pool, creator_uuid).await?;
+        Ok(CreatorResponse::from_creator(creator))
+    }
+
+    /// Id of the creator for filtering, `None` when no creator has the uuid
+    pub async fn get_creator_id(
+        pool: &DbPool,
+        creator_uuid: String,
+    ) -> Result<Option<i32>, diesel::result::Error> {
+        match Repository::get_creator_by_uuid(pool, creator_uuid).await {
+            Ok(creator) => Ok(creator.id),
+            Err(diesel::result::Error::NotFound) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn get_creator_page(
+        pool: &DbPool,
+        creator_uuid: String,
+        locales: &[String],
+        reader_age: ReaderAge,
+    ) -> Result<CreatorPageResponse, CreatorError> {
+        let creator = Repository::get_creator_by_uuid(pool, creator_uuid).await?;
+        let albums = AlbumService::get_albums_by_creator_id(
+            pool,
+            creator.id.unwrap_or_default(),
+            locales,
+            reader_age,
+        )
+        .await?;
+        Ok(CreatorPageResponse {
+            creator: CreatorResponse::from_creator(creator),
+            albums,
+        })
+    }
+
+    pub async fn update_creator(
+        pool: &DbPool,
+        creator_uuid: String,
+        req: UpdateCreatorRequest,
+    ) -> Result<CreatorResponse, CreatorError> {
+        let current = Repository::get_creator_by_uuid(pool, creator_uuid).await?;
+        let mut creator = current.clone();
+        if let Some(name) = req.name {
+            creator.name = name.trim().to_string();
+        }
+        if let Some(aliases) = req.aliases {
+            creator.aliases = aliases.join(",");
+        }
+        if let Some(bio) = req.bio {
+            creator.bio = bio;
+        }
+        if req.remove_avatar == Some(true) {
+            creator.avatar_url = None;
+            creator.avatar_content_type = None;
+        }
+        if let Some(avatar_path) = &req.avatar {
+            let (avatar_url, avatar_content_type) = avatar_destination(avatar_path)?;
+            creator.avatar_url = Some(avatar_url);
+            creator.avatar_content_type = Some(avatar_content_type);
+        }
+        let creator = Repository::update_creator(pool, creator).await?;
+        if let (Some(avatar_path), Some(avatar_url)) = (&req.avatar, &creator.avatar_url) {
+            move_file_and_replace(
+                avatar_path,
+                &format!("{}/{}", get_project_directory(), avatar_url),
+            );
+        }
+        if current.avatar_url.is_some() && current.avatar_url != creator.avatar_url {
+            delete_avatar(&current);
+        }
+        Ok(CreatorResponse::from_creator(creator))
+    }
+
+    /// Delete the creator, their credits are removed from every album
+    pub async fn delete_creator(pool: &DbPool, creator_uuid: String) -> Result<(), CreatorError> {
+        let creator = Repository::get_creator_by_uuid(pool, creator_uuid).await?;
+        Repository::delete_creator(pool, creator.id.unwrap_or_default()).await?;
+        delete_avatar(&creator);
+        Ok(())
+    }
+
+    /// Credits of the albums in order, paired with their album id
+    pub async fn get_album_credits(
+        pool: &DbPool,
+        album_ids: Vec<i32>,
+    ) -> Result<Vec<(i32, AlbumCreditResponse)>, diesel::result::Error> {
+        let credits = Repository::get_credits_by_album_ids(pool, album_ids).await?;
+        Ok(credits
+            .into_iter()
+            .filter_map(|(album_id, role, creator)| {
+                // Rows with a role written outside the API are skipped
+                let role = role.parse::<CreditRole>().ok()?;
+                Some((
+                    album_id,
+                    AlbumCreditResponse {
+                        creator_uuid: creator.uuid,
+                        name: creator.name,
+                        role,
+                    },
+                ))
+            })
+            .collect())
+    }
+
+    /// Replace the credits of the album, every creator must exist and be credited once per role
+    pub async fn set_album_credits(
+        pool: &DbPool,
+        album_id: i32,
+        req: SetAlbumCreditsRequest,
+    ) -> Result<(), CreatorError> {
+        let creator_uuids = req
+            .credits
+            .iter()
+            .map(|credit| credit.creator_uuid.clone())
+            .collect();
+        let creators = Repository::get_creators_by_uuids(pool, creator_uuids).await?;
+        let mut credits: Vec<NewAlbumCredit> = vec![];
+        for (position, credit) in req.credits.iter().enumerate() {
+            let creator_id = creators
+                .iter()
+                .find(|creator| creator.uuid == credit.creator_uuid)
+                .and_then(|creator| creator.id)
+                .ok_or_else(|| {
+                    CreatorError::Invalid(format!("Creator {} not found", credit.creator_uuid))
+                })?;
+            if credits
+                .iter()
+                .any(|c| c.creator_id == creator_id && c.role == credit.role.as_str())
+            {
+                return Err(CreatorError::Invalid(format!(
+                    "Creator {} is credited as {} more than once",
+                    credit.creator_uuid,
+                    credit.role.as_str()
+                )));
+            }
+            credits.push(NewAlbumCredit {
+                album_id,
+                creator_id,
+                role: credit.role.as_str().to_string(),
+                position: position as i32,
+            });
+        }
+        Repository::set_album_credits(pool, album_id, credits).await?;
+        Ok(())
+    }
+}
+
+/// Stored url and content type of an uploaded avatar
+fn avatar_destination(avatar_path: &str) -> Result<(String, String), CreatorError> {
+    let metadata = get_file_metadata(avatar_path);
+    if !metadata.content_type.starts_with("image/") {
+        return Err(CreatorError::Invalid(String::from(
+            "Creator avatar must be an image",
+        )));
+    }
+    let url = format!(
+        "{}/creators/{}.{}",
+        get_data_directory(),
+        Uuid::new_v4(),
+        avatar_path.rsplit('.').next().unwrap_or_default()
+    );
+    Ok((url, metadata.content_type))
+}
+
+fn delete_avatar(creator: &Creator) {
+    if let Some(avatar_url) = &creator.avatar_url {
+        delete_file_if_exists(&format!("{}/{}", get_project_directory(), avatar_url));
+    }
+}
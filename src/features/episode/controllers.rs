@@ -1,13 +1,20 @@
 use crate::common::database::DbPool;
-use crate::common::enums::Role::Admin;
+use crate::common::enums::Role::{Admin, Unknown, User};
+use crate::common::i18n::{requested_locales, set_language_headers, vary_on_language};
+use crate::common::models::response_data::ResponseData;
 use crate::common::models::response_message::ResponseMessage;
-use crate::common::utils::{delete_directory_if_exists, parse_payload_data};
-use crate::features::check_role;
+use crate::common::utils::{etag_matches, etag_of, parse_payload_data};
 use crate::features::episode::models;
 use crate::features::episode::models::UpdateEpisodeRequest;
+use crate::features::analytics::services::Service as AnalyticsService;
 use crate::features::episode::services::Service;
+use crate::features::{
+    album_refusal, audit_mutation, check_role, episode_access_refusal, reader_age,
+};
 use actix_multipart::Multipart;
+use actix_web::http::header;
 use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+use serde_json::json;
 
 /// Create Episode
 ///
@@ -37,9 +44,9 @@ pub async fn create_episode(
     http_request: HttpRequest,
     payload: Multipart,
 ) -> impl Responder {
-    if check_role(http_request) == Admin {
+    if check_role(http_request.clone()) == Admin {
         match parse_payload_data(payload).await {
-            Ok((payload_data, tmp_path)) => {
+            Ok((payload_data, _tmp_dir)) => {
                 let check_required_fields =
                     models::CreateEpisodeRequest::check_required_data(payload_data.clone()).await;
                 if check_required_fields {
@@ -47,11 +54,19 @@ pub async fn create_episode(
                         models::CreateEpisodeRequest::from_payload_data(payload_data).await;
                     match Service::create_episode(&pool, req_data).await {
                         Ok(new_episode) => {
-                            delete_directory_if_exists(&tmp_path);
+                            audit_mutation(
+                                &pool,
+                                &http_request,
+                                "create",
+                                "episode",
+                                new_episode.uuid.clone(),
+                                None,
+                                serde_json::to_value(&new_episode).ok(),
+                            )
+                            .await;
                             HttpResponse::Created().json(new_episode)
                         }
                         Err(e) => {
-                            delete_directory_if_exists(&tmp_path);
                             HttpResponse::BadRequest().json(ResponseMessage {
                                 message: String::from(e),
                             })
@@ -76,15 +91,23 @@ pub async fn create_episode(
 
 /// Get Episodes By Album Id
 ///
-/// Get episodes by album id
+/// Get episodes by album id in reading order, with the previous and next episode of each. Titles
+/// are in the best locale of `lang` or `Accept-Language`. Refused when the album is rated above
+/// the age of the reader in `X-Reader-Profile`.
 #[utoipa::path(
     post,
     path = "/episodes/{album_id}",
+    params(
+        ("lang" = Option<String>, Query, description = "Preferred locales, comma separated, before Accept-Language", example = "pt-BR"),
+        ("X-Reader-Profile" = Option<String>, Header, description = "Reader profile the album ratings are checked against")
+    ),
     request_body = FilterEpisodeRequest,
     responses(
         (status = 200, description = "Request successfully", body = ResponseDataEpisode),
         (status = 400, description = "Album not found", body = ResponseMessage),
         (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 403, description = "Rated album and the reader's age is unknown", body = AgeRestrictedResponse),
+        (status = 451, description = "Reader younger than the album rating", body = AgeRestrictedResponse),
         (status = 500, description = "Internal server error", body = ResponseMessage)
     ),
     security(
@@ -99,13 +122,34 @@ pub async fn get_episodes_by_album_id(
     req: web::Json<models::FilterEpisodeRequest>,
     path: web::Path<String>,
 ) -> impl Responder {
-    if check_role(http_request) == Admin {
+    if check_role(http_request.clone()) != Unknown {
         let album_id_string = path.into_inner();
         match album_id_string.parse::<i32>() {
             Ok(id) => {
+                let reader_age = reader_age(&pool, &http_request).await;
+                match Service::get_album_access(&pool, id).await {
+                    Ok(Some((enable, min_age))) => {
+                        if let Some(refusal) = album_refusal(reader_age, enable, min_age) {
+                            return refusal;
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        println!("Failed to get album rating: {}", e);
+                        return HttpResponse::InternalServerError().json(ResponseMessage {
+                            message: String::from("Internal Server Error"),
+                        });
+                    }
+                }
                 let episode_filters = req.into_inner();
-                match Service::get_episodes_by_album_id(&pool, id, episode_filters).await {
-                    Ok(data) => HttpResponse::Ok().json(data),
+                let locales = requested_locales(&http_request);
+                match Service::get_episodes_by_album_id(&pool, id, episode_filters, &locales).await
+                {
+                    Ok(data) => {
+                        let mut builder = HttpResponse::Ok();
+                        vary_on_language(&mut builder);
+                        builder.json(data)
+                    }
                     Err(err) => HttpResponse::BadRequest().json(ResponseMessage {
                         message: err.to_string(),
                     }),
@@ -122,19 +166,82 @@ pub async fn get_episodes_by_album_id(
     }
 }
 
+/// Reorder Episodes
+///
+/// Replace the reading order of an album, every episode of the album must be listed once
+#[utoipa::path(
+    put,
+    path = "/episodes/{album_id}/order",
+    params(
+        ("album_id" = i32, Path, description = "Album ID")
+    ),
+    request_body = ReorderEpisodesRequest,
+    responses(
+        (status = 200, description = "Reordered successfully", body = ResponseDataEpisode),
+        (status = 400, description = "Invalid episode list or album not found", body = ResponseMessage),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Episode",
+)]
+#[put("/episodes/{album_id}/order")]
+pub async fn reorder_episodes(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<i32>,
+    req: web::Json<models::ReorderEpisodesRequest>,
+) -> impl Responder {
+    if check_role(http_request.clone()) == Admin {
+        match Service::reorder_episodes(&pool, path.into_inner(), req.into_inner()).await {
+            Ok(reordered) => {
+                let order = reordered
+                    .episodes
+                    .iter()
+                    .map(|episode| episode.uuid.clone())
+                    .collect::<Vec<String>>();
+                audit_mutation(
+                    &pool,
+                    &http_request,
+                    "reorder",
+                    "album",
+                    reordered.album_uuid,
+                    Some(json!({ "episodes": reordered.previous_order })),
+                    Some(json!({ "episodes": order })),
+                )
+                .await;
+                let total = reordered.episodes.len() as i64;
+                HttpResponse::Ok().json(ResponseData::new(reordered.episodes, total, 0))
+            }
+            Err(message) => HttpResponse::BadRequest().json(ResponseMessage { message }),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
 /// Get Episode
 ///
-/// Get episode by episode uuid
+/// Get episode by episode uuid, the title in the best locale of `lang` or `Accept-Language`.
+/// Refused when the album is rated above the age of the reader in `X-Reader-Profile`.
 #[utoipa::path(
     get,
     path = "/episode/{episode_uuid}",
     params(
-        ("episode_uuid" = String, Path, description = "Episode UUID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e")
+        ("episode_uuid" = String, Path, description = "Episode UUID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e"),
+        ("lang" = Option<String>, Query, description = "Preferred locales, comma separated, before Accept-Language", example = "pt-BR"),
+        ("X-Reader-Profile" = Option<String>, Header, description = "Reader profile the album ratings are checked against")
     ),
     responses(
         (status = 200, description = "Get successfully", body = EpisodeResponse),
         (status = 400, description = "Episode Not Found", body = ResponseMessage),
         (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 403, description = "Rated album and the reader's age is unknown", body = AgeRestrictedResponse),
+        (status = 451, description = "Reader younger than the album rating", body = AgeRestrictedResponse),
         (status = 500, description = "Internal server error", body = ResponseMessage)
     ),
     security(
@@ -148,10 +255,18 @@ pub async fn get_episode(
     http_request: HttpRequest,
     path: web::Path<String>,
 ) -> impl Responder {
-    if check_role(http_request) == Admin {
+    if check_role(http_request.clone()) != Unknown {
         let episode_uuid = path.into_inner();
-        match Service::get_episode_by_episode_uuid(&pool, episode_uuid).await {
-            Ok(episode)=>HttpResponse::Ok().json(episode),
+        if let Some(refusal) = episode_access_refusal(&pool, &http_request, &episode_uuid).await {
+            return refusal;
+        }
+        let locales = requested_locales(&http_request);
+        match Service::get_episode_by_episode_uuid(&pool, episode_uuid, &locales).await {
+            Ok(episode) => {
+                let mut builder = HttpResponse::Ok();
+                set_language_headers(&mut builder, &episode.locale);
+                builder.json(episode)
+            }
             Err(e) => {
                 if e == diesel::result::Error::NotFound {
                     HttpResponse::BadRequest().json(ResponseMessage {
@@ -172,6 +287,99 @@ pub async fn get_episode(
     }
 }
 
+/// Get Episode Manifest
+///
+/// Get the episode, its album summary, its pages in reading order and the previous and next
+/// episodes in one request. Send the returned ETag in If-None-Match to revalidate. Titles and
+/// pages are in the best locale of `lang` or `Accept-Language`. Refused when the album is rated
+/// above the age of the reader in `X-Reader-Profile`.
+#[utoipa::path(
+    get,
+    path = "/episodes/{episode_uuid}/manifest",
+    params(
+        ("episode_uuid" = String, Path, description = "Episode UUID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e"),
+        ("lang" = Option<String>, Query, description = "Preferred locales, comma separated, before Accept-Language", example = "pt-BR"),
+        ("If-None-Match" = Option<String>, Header, description = "ETag of a cached manifest"),
+        ("X-Reader-Profile" = Option<String>, Header, description = "Reader profile the album ratings are checked against")
+    ),
+    responses(
+        (status = 200, description = "Get successfully", body = EpisodeManifest),
+        (status = 304, description = "Cached manifest is still current"),
+        (status = 400, description = "Episode Not Found", body = ResponseMessage),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 403, description = "Rated album and the reader's age is unknown", body = AgeRestrictedResponse),
+        (status = 451, description = "Reader younger than the album rating", body = AgeRestrictedResponse),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Episode",
+)]
+#[get("/episodes/{episode_uuid}/manifest")]
+pub async fn get_episode_manifest(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+) -> impl Responder {
+    if check_role(http_request.clone()) == Unknown {
+        return HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        });
+    }
+    let episode_uuid = path.into_inner();
+    if let Some(refusal) = episode_access_refusal(&pool, &http_request, &episode_uuid).await {
+        return refusal;
+    }
+    let locales = requested_locales(&http_request);
+    let manifest = match Service::get_episode_manifest(&pool, episode_uuid.clone(), &locales).await
+    {
+        Ok(manifest) => manifest,
+        Err(diesel::result::Error::NotFound) => {
+            return HttpResponse::BadRequest().json(ResponseMessage {
+                message: String::from("Episode not found"),
+            })
+        }
+        Err(e) => {
+            println!("Failed to get episode manifest: {}", e);
+            return HttpResponse::InternalServerError().json(ResponseMessage {
+                message: String::from("Internal Server Error"),
+            });
+        }
+    };
+    let body = match serde_json::to_vec(&manifest) {
+        Ok(body) => body,
+        Err(e) => {
+            println!("Failed to serialize episode manifest: {}", e);
+            return HttpResponse::InternalServerError().json(ResponseMessage {
+                message: String::from("Internal Server Error"),
+            });
+        }
+    };
+    // Only reader traffic counts, admin requests are left out
+    if check_role(http_request.clone()) == User {
+        AnalyticsService::record_episode_read(&pool, episode_uuid).await;
+    }
+    let etag = etag_of(&body);
+    // Readers must revalidate, the manifest changes with every edit of the episode
+    let cache_control = (header::CACHE_CONTROL, "private, no-cache");
+    if etag_matches(&http_request, &etag) {
+        let mut builder = HttpResponse::NotModified();
+        set_language_headers(&mut builder, &manifest.contents_locale);
+        return builder
+            .insert_header((header::ETAG, etag))
+            .insert_header(cache_control)
+            .finish();
+    }
+    let mut builder = HttpResponse::Ok();
+    set_language_headers(&mut builder, &manifest.contents_locale);
+    builder
+        .insert_header((header::ETAG, etag))
+        .insert_header(cache_control)
+        .content_type("application/json")
+        .body(body)
+}
+
 /// Update Episode
 ///
 /// Update episode
@@ -201,19 +409,28 @@ pub async fn update_episode(
     path: web::Path<String>,
     payload: Multipart,
 ) -> impl Responder {
-    if check_role(http_request) == Admin {
+    if check_role(http_request.clone()) == Admin {
         let episode_uuid = path.into_inner();
+        let before = episode_snapshot(&pool, &episode_uuid).await;
         match parse_payload_data(payload).await {
-            Ok((payload_data, tmp_path)) => {
+            Ok((payload_data, _tmp_dir)) => {
                 let req_data = UpdateEpisodeRequest::from_payload_data(payload_data).await;
                 match Service::update_episode(&pool, episode_uuid, req_data).await {
                     Ok(updated_episode) => {
-                        delete_directory_if_exists(&tmp_path);
+                        audit_mutation(
+                            &pool,
+                            &http_request,
+                            "update",
+                            "episode",
+                            updated_episode.uuid.clone(),
+                            before,
+                            serde_json::to_value(&updated_episode).ok(),
+                        )
+                        .await;
                         HttpResponse::Ok().json(updated_episode)
                     }
                     Err(e) => {
                         println!("Failed to update episode: {}", e);
-                        delete_directory_if_exists(&tmp_path);
                         HttpResponse::BadRequest().json(ResponseMessage {
                             message: String::from("Failed to update episode"),
                         })
@@ -257,10 +474,23 @@ pub async fn delete_episode(
     http_request: HttpRequest,
     path: web::Path<String>,
 ) -> impl Responder {
-    if check_role(http_request) == Admin {
+    if check_role(http_request.clone()) == Admin {
         let episode_uuid = path.into_inner();
-        match Service::delete_episode(&pool, episode_uuid).await {
-            Ok(size) if size > 0 => HttpResponse::NoContent().json(""),
+        let before = episode_snapshot(&pool, &episode_uuid).await;
+        match Service::delete_episode(&pool, episode_uuid.clone()).await {
+            Ok(size) if size > 0 => {
+                audit_mutation(
+                    &pool,
+                    &http_request,
+                    "delete",
+                    "episode",
+                    episode_uuid,
+                    before,
+                    None,
+                )
+                .await;
+                HttpResponse::NoContent().json("")
+            }
             Ok(_) => HttpResponse::BadRequest().json(ResponseMessage {
                 message: String::from("Episode not found"),
             }),
@@ -283,3 +513,63 @@ pub async fn delete_episode(
         })
     }
 }
+
+/// Package Episode HLS
+///
+/// Queue packaging of the episode video into HLS renditions and a master playlist
+#[utoipa::path(
+    post,
+    path = "/episode/{episode_uuid}/hls",
+    params(
+        ("episode_uuid" = String, Path, description = "Episode UUID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e")
+    ),
+    responses(
+        (status = 202, description = "Packaging queued", body = EpisodeResponse),
+        (status = 400, description = "Episode has no video or packaging is already queued", body = ResponseMessage),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Episode",
+)]
+#[post("/episode/{episode_uuid}/hls")]
+pub async fn package_episode_hls(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+) -> impl Responder {
+    if check_role(http_request.clone()) == Admin {
+        let episode_uuid = path.into_inner();
+        match Service::queue_hls_packaging(&pool, episode_uuid.clone()).await {
+            Ok(episode) => {
+                audit_mutation(
+                    &pool,
+                    &http_request,
+                    "package_hls",
+                    "episode",
+                    episode_uuid,
+                    None,
+                    None,
+                )
+                .await;
+                HttpResponse::Accepted().json(episode)
+            }
+            Err(e) => HttpResponse::BadRequest().json(ResponseMessage {
+                message: String::from(e),
+            }),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+async fn episode_snapshot(pool: &DbPool, episode_uuid: &str) -> Option<serde_json::Value> {
+    Service::get_episode_by_episode_uuid(pool, episode_uuid.to_string(), &[])
+        .await
+        .ok()
+        .and_then(|episode| serde_json::to_value(episode).ok())
+}
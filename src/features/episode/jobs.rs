@@ -0,0 +1,27 @@
+use futures_util::future::LocalBoxFuture;
+use serde_json::Value;
+
+use crate::common::database::DbPool;
+use crate::features::episode::services::run_hls_packaging;
+use crate::features::jobs::registry::JobHandler;
+
+pub const PACKAGE_HLS_JOB: &str = "episode.package_hls";
+
+/// Payload `{"episode_uuid": ".."}`
+pub struct PackageHlsJob;
+
+impl JobHandler for PackageHlsJob {
+    fn job_type(&self) -> &'static str {
+        PACKAGE_HLS_JOB
+    }
+
+    fn run(&self, pool: DbPool, payload: Value) -> LocalBoxFuture<'static, Result<(), String>> {
+        Box::pin(async move {
+            let episode_uuid = payload["episode_uuid"]
+                .as_str()
+                .ok_or_else(|| String::from("Missing episode_uuid in payload"))?
+                .to_string();
+            run_hls_packaging(pool, episode_uuid).await
+        })
+    }
+}
@@ -1,7 +1,14 @@
-use crate::features::episode::controllers::{create_episode, delete_episode, get_episode, get_episodes_by_album_id, update_episode};
+use crate::features::episode::controllers::{
+    create_episode, delete_episode, get_episode, get_episode_manifest, get_episodes_by_album_id,
+    package_episode_hls,
+    reorder_episodes, update_episode,
+};
+use crate::features::episode::jobs::PackageHlsJob;
+use crate::features::jobs::registry::JobRegistry;
 use actix_web::web;
 
 pub mod controllers;
+pub mod jobs;
 pub mod models;
 mod repository;
 pub mod services;
@@ -11,5 +18,12 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(update_episode);
     cfg.service(delete_episode);
     cfg.service(get_episodes_by_album_id);
+    cfg.service(reorder_episodes);
     cfg.service(get_episode);
+    cfg.service(get_episode_manifest);
+    cfg.service(package_episode_hls);
+}
+
+pub fn register_jobs(registry: &mut JobRegistry) {
+    registry.register(PackageHlsJob);
 }
@@ -1,4 +1,6 @@
+use crate::common::i18n::default_locale;
 use crate::common::ne_parse::NEParse;
+use crate::common::pagination::{PageQuery, SortOrder};
 use crate::common::utils::get_data_directory;
 use crate::schema::{albums, episodes};
 use chrono::NaiveDateTime;
@@ -9,7 +11,7 @@ use std::collections::HashMap;
 use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
-#[derive(Debug, Queryable, Selectable, ToSchema, Clone, IntoParams, PartialEq, Eq)]
+#[derive(Debug, Queryable, Selectable, ToSchema, Clone, IntoParams, PartialEq)]
 #[diesel(table_name = albums)]
 pub struct EpisodeAlbum {
     pub id: i32,
@@ -31,6 +33,9 @@ pub struct EpisodeAlbum {
     pub broken_at: Option<NaiveDateTime>,
     pub created_at: Option<NaiveDateTime>,
     pub updated_at: Option<NaiveDateTime>,
+    pub deleted_at: Option<NaiveDateTime>,
+    pub rating_average: f64,
+    pub rating_count: i32,
 }
 
 #[derive(
@@ -46,7 +51,6 @@ pub struct EpisodeAlbum {
     Clone,
     IntoParams,
     PartialEq,
-    Eq,
 )]
 #[diesel(table_name = episodes)]
 pub struct Episode {
@@ -63,6 +67,18 @@ pub struct Episode {
     pub broken_at: Option<NaiveDateTime>,
     pub created_at: Option<NaiveDateTime>,
     pub updated_at: Option<NaiveDateTime>,
+    pub deleted_at: Option<NaiveDateTime>,
+    pub duration_ms: Option<i32>,
+    pub video_codec: Option<String>,
+    pub bitrate: Option<i32>,
+    pub poster_url: Option<String>,
+    pub hls_status: Option<String>,
+    pub hls_url: Option<String>,
+    pub hls_error: Option<String>,
+    pub file_name: Option<String>,
+    pub number: Option<f64>,
+    pub volume: Option<i32>,
+    pub sort_order: i32,
 }
 
 impl Episode {
@@ -94,11 +110,23 @@ impl Episode {
             broken_at: None,
             created_at: None,
             updated_at: None,
+            deleted_at: None,
+            duration_ms: None,
+            video_codec: None,
+            bitrate: None,
+            poster_url: None,
+            hls_status: None,
+            hls_url: None,
+            hls_error: None,
+            file_name: None,
+            number: req.number,
+            volume: req.volume,
+            sort_order: req.sort_order.unwrap_or_default(),
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, ToSchema, IntoParams, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema, IntoParams, PartialEq)]
 pub struct EpisodeResponse {
     pub id: Option<i32>,
     pub album_id: i32,
@@ -113,6 +141,38 @@ pub struct EpisodeResponse {
     pub broken_at: Option<String>,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
+    /// Video duration in milliseconds
+    pub duration_ms: Option<i32>,
+    /// Video codec, e.g. h264
+    pub video_codec: Option<String>,
+    /// Video bitrate in bits per second
+    pub bitrate: Option<i32>,
+    /// Poster frame stored next to the video
+    pub poster_url: Option<String>,
+    /// HLS packaging job status: pending, processing, ready or failed
+    pub hls_status: Option<String>,
+    /// HLS master playlist, set once packaging is ready
+    pub hls_url: Option<String>,
+    /// Reason of the last failed packaging job
+    pub hls_error: Option<String>,
+    /// Name of the uploaded file, used for Content-Disposition when streaming
+    pub file_name: Option<String>,
+    /// Episode number, decimals for specials such as 10.5
+    pub number: Option<f64>,
+    pub volume: Option<i32>,
+    /// Position in the reading order of the album
+    pub sort_order: i32,
+    /// Episode before this one in the reading order, only set on episode reads, lists and manifests
+    pub previous_episode_uuid: Option<String>,
+    /// Episode after this one in the reading order, only set on episode reads, lists and manifests
+    pub next_episode_uuid: Option<String>,
+    /// Language picked for the episode, the title stays in the default locale when only the
+    /// pages are translated
+    #[schema(example = "en")]
+    pub locale: String,
+    /// Languages the episode has a title or pages in, the default locale first
+    #[schema(example = json!(["en", "pt-br"]))]
+    pub available_locales: Vec<String>,
 }
 
 impl EpisodeResponse {
@@ -131,6 +191,21 @@ impl EpisodeResponse {
             broken_at: NEParse::opt_naive_datetime_to_utc_opt_string(ep.broken_at),
             created_at: NEParse::opt_naive_datetime_to_utc_opt_string(ep.created_at),
             updated_at: NEParse::opt_naive_datetime_to_utc_opt_string(ep.updated_at),
+            duration_ms: ep.duration_ms,
+            video_codec: ep.video_codec,
+            bitrate: ep.bitrate,
+            poster_url: ep.poster_url,
+            hls_status: ep.hls_status,
+            hls_url: ep.hls_url,
+            hls_error: ep.hls_error,
+            file_name: ep.file_name,
+            number: ep.number,
+            volume: ep.volume,
+            sort_order: ep.sort_order,
+            previous_episode_uuid: None,
+            next_episode_uuid: None,
+            locale: default_locale(),
+            available_locales: vec![default_locale()],
         }
     }
 
@@ -152,13 +227,21 @@ pub struct CreateEpisodeRequest {
     /// Episode File as A URL
     #[schema(value_type = Option<String>)]
     pub file_url: Option<String>,
+    /// Episode number, decimals for specials such as 10.5
+    #[schema(value_type = Option<f64>, example = "10.5")]
+    pub number: Option<f64>,
+    #[schema(value_type = Option<i32>)]
+    pub volume: Option<i32>,
+    /// Position in the reading order, after the last episode when empty
+    #[schema(value_type = Option<i32>)]
+    pub sort_order: Option<i32>,
 }
 
 impl CreateEpisodeRequest {
     pub async fn check_required_data(payload_data: HashMap<String, Value>) -> bool {
         (payload_data.contains_key("title") && payload_data.contains_key("album_id"))
             && (!payload_data["title"].as_str().is_none()
-            && !NEParse::opt_immut_str_to_opt_i32(payload_data["album_id"].as_str()).is_none())
+                && !NEParse::opt_immut_str_to_opt_i32(payload_data["album_id"].as_str()).is_none())
     }
     pub async fn from_payload_data(payload_data: HashMap<String, Value>) -> Self {
         let file_paths: Vec<String> = if payload_data.contains_key("file") {
@@ -169,12 +252,25 @@ impl CreateEpisodeRequest {
         CreateEpisodeRequest {
             album_id: NEParse::opt_immut_str_to_opt_i32(payload_data["album_id"].as_str()).unwrap(),
             title: payload_data["title"].as_str().unwrap().to_string(),
-            file_url: if payload_data.contains_key("file_url") { NEParse::opt_immut_str_to_option_string(payload_data["file_url"].as_str()) } else { None },
+            file_url: if payload_data.contains_key("file_url") {
+                NEParse::opt_immut_str_to_option_string(payload_data["file_url"].as_str())
+            } else {
+                None
+            },
             file: if file_paths.is_empty() {
                 None
             } else {
                 file_paths.first().cloned()
             },
+            number: NEParse::opt_immut_str_to_opt_f64(
+                payload_data.get("number").and_then(|v| v.as_str()),
+            ),
+            volume: NEParse::opt_immut_str_to_opt_i32(
+                payload_data.get("volume").and_then(|v| v.as_str()),
+            ),
+            sort_order: NEParse::opt_immut_str_to_opt_i32(
+                payload_data.get("sort_order").and_then(|v| v.as_str()),
+            ),
         }
     }
 }
@@ -190,7 +286,15 @@ pub struct UpdateEpisodeRequest {
     #[schema(value_type = Option<String>)]
     pub file_url: Option<String>,
     #[schema(value_type = bool)]
-    pub remove_old_file: bool
+    pub remove_old_file: bool,
+    /// Episode number, decimals for specials such as 10.5
+    #[schema(value_type = Option<f64>, example = "10.5")]
+    pub number: Option<f64>,
+    #[schema(value_type = Option<i32>)]
+    pub volume: Option<i32>,
+    /// Position in the reading order
+    #[schema(value_type = Option<i32>)]
+    pub sort_order: Option<i32>,
 }
 
 impl UpdateEpisodeRequest {
@@ -207,8 +311,26 @@ impl UpdateEpisodeRequest {
             } else {
                 file_paths.first().cloned()
             },
-            file_url: if payload_data.contains_key("file_url") { NEParse::opt_immut_str_to_option_string(payload_data["file_url"].as_str()) } else { None },
-            remove_old_file : if payload_data.contains_key("remove_old_file") {NEParse::opt_immut_str_to_option_bool(payload_data["remove_old_file"].as_str()).unwrap_or(false)}else { false },
+            file_url: if payload_data.contains_key("file_url") {
+                NEParse::opt_immut_str_to_option_string(payload_data["file_url"].as_str())
+            } else {
+                None
+            },
+            remove_old_file: if payload_data.contains_key("remove_old_file") {
+                NEParse::opt_immut_str_to_option_bool(payload_data["remove_old_file"].as_str())
+                    .unwrap_or(false)
+            } else {
+                false
+            },
+            number: NEParse::opt_immut_str_to_opt_f64(
+                payload_data.get("number").and_then(|v| v.as_str()),
+            ),
+            volume: NEParse::opt_immut_str_to_opt_i32(
+                payload_data.get("volume").and_then(|v| v.as_str()),
+            ),
+            sort_order: NEParse::opt_immut_str_to_opt_i32(
+                payload_data.get("sort_order").and_then(|v| v.as_str()),
+            ),
         }
     }
 }
@@ -216,4 +338,112 @@ impl UpdateEpisodeRequest {
 pub struct FilterEpisodeRequest {
     #[schema(example = "")]
     pub title: String,
+    /// Sort field (sort_order, number, volume, id, title, created_at, updated_at),
+    /// sort_order when empty
+    #[schema(example = "null")]
+    pub sort: Option<String>,
+    /// Sort order (asc, desc), descending for dates when empty
+    #[schema(example = "null")]
+    pub order: Option<SortOrder>,
+    /// `next_cursor` of the previous page
+    #[schema(example = "null")]
+    pub cursor: Option<String>,
+    /// Page size, every episode when empty or 0
+    #[schema(example = "null")]
+    pub limit: Option<i64>,
+}
+
+impl FilterEpisodeRequest {
+    pub fn page_query(&self) -> PageQuery {
+        PageQuery {
+            sort: self.sort.clone(),
+            order: self.order,
+            cursor: self.cursor.clone(),
+            limit: self.limit,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReorderEpisodesRequest {
+    /// Every episode of the album in the new reading order
+    #[schema(example = json!(["fd2fe858-9962-404f-9174-c4f6f83cc39e"]))]
+    pub episode_uuids: Vec<String>,
+}
+
+/// Outcome of a reorder, the previous order is kept for the audit log
+pub struct ReorderedEpisodes {
+    pub album_uuid: String,
+    pub previous_order: Vec<String>,
+    pub episodes: Vec<EpisodeResponse>,
+}
+
+/// Everything a reader needs to render an episode and prefetch its neighbours
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct EpisodeManifest {
+    pub episode: EpisodeResponse,
+    pub album: ManifestAlbum,
+    /// Language of the pages, the default locale when the episode has no edition in the
+    /// requested locales
+    #[schema(example = "en")]
+    pub contents_locale: String,
+    /// Pages in reading order
+    pub contents: Vec<ManifestContent>,
+    /// Playable versions of the episode video, empty for episodes without a video
+    pub renditions: Vec<ManifestRendition>,
+    pub previous_episode_uuid: Option<String>,
+    pub next_episode_uuid: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct ManifestAlbum {
+    pub uuid: String,
+    /// Title in the language of the episode when the album has it
+    pub title: String,
+    pub completed: bool,
+    pub min_age: i32,
+    /// Cover image
+    pub url: String,
+    pub content_type: String,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl ManifestAlbum {
+    pub fn from_album(album: EpisodeAlbum) -> Self {
+        ManifestAlbum {
+            uuid: album.uuid,
+            title: album.title,
+            completed: album.completed,
+            min_age: album.min_age,
+            url: album.url,
+            content_type: album.content_type,
+            width: album.width,
+            height: album.height,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct ManifestContent {
+    pub uuid: String,
+    pub index_no: i32,
+    pub url: String,
+    pub ads_url: Option<String>,
+    pub content_type: String,
+    pub width: i32,
+    pub height: i32,
+    pub bytes: i32,
+    pub renditions: Vec<ManifestRendition>,
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct ManifestRendition {
+    /// `original` for the uploaded file, the rendition name for HLS variants
+    #[schema(example = "original")]
+    pub name: String,
+    pub url: String,
+    pub content_type: String,
+    pub width: i32,
+    pub height: i32,
 }
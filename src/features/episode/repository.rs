@@ -1,12 +1,32 @@
+use chrono::Utc;
+use diesel::dsl::{count_star, sql};
 use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Text};
 
 use crate::common::database::DbPool;
+use crate::common::models::response_data::ResponseData;
+use crate::common::pagination::{Page, SortField, SortKind, SortOrder};
 use crate::features::episode::models;
 use crate::schema::{albums, episodes};
 
 pub struct Repository;
 
 impl Repository {
+    /// Sort options of the episode list of an album
+    pub fn sort_fields() -> Vec<SortField> {
+        use SortKind::*;
+        use SortOrder::*;
+        vec![
+            SortField::new("sort_order", "episodes.sort_order", Integer, Asc),
+            SortField::new("number", "COALESCE(episodes.number, 0)", Real, Asc),
+            SortField::new("volume", "COALESCE(episodes.volume, 0)", Integer, Asc),
+            SortField::new("id", "episodes.id", Integer, Asc),
+            SortField::new("title", "episodes.title", Text, Asc),
+            SortField::new("created_at", "COALESCE(episodes.created_at, '')", Text, Desc),
+            SortField::new("updated_at", "COALESCE(episodes.updated_at, '')", Text, Desc),
+        ]
+    }
+
     pub async fn create_episode(
         pool: &DbPool,
         new_episode: models::Episode,
@@ -26,16 +46,38 @@ impl Repository {
         pool: &DbPool,
         id: i32,
         filters: models::FilterEpisodeRequest,
-    ) -> Result<Vec<models::Episode>, diesel::result::Error> {
+        page: &Page,
+    ) -> Result<ResponseData<models::EpisodeResponse>, diesel::result::Error> {
         use crate::schema::episodes::album_id;
         use crate::schema::episodes::title;
         let mut conn = pool.get().expect("Failed to get DB connection");
+        let title_filter = format!("%{}%", filters.title);
+        let total = episodes::table
+            .filter(album_id.eq(id))
+            .filter(episodes::deleted_at.is_null())
+            .filter(title.like(title_filter.clone()))
+            .select(count_star())
+            .first::<i64>(&mut conn)?;
+
         let mut query = episodes::table.into_boxed();
         query = query
             .filter(album_id.eq(id))
-            .filter(title.like(format!("%{}%", filters.title)));
-        let result = query.get_results(&mut conn)?;
-        Ok(result)
+            .filter(episodes::deleted_at.is_null())
+            .filter(title.like(title_filter));
+        if let Some(after) = page.after_cursor::<episodes::table>("episodes.id") {
+            query = query.filter(after);
+        }
+        let result = query
+            .select((
+                models::Episode::as_select(),
+                page.sort_value(),
+                sql::<BigInt>("episodes.id"),
+            ))
+            .order(sql::<Text>(&page.order_by_sql("episodes.id")))
+            .offset(page.offset())
+            .limit(page.fetch_limit())
+            .load::<(models::Episode, String, i64)>(&mut conn)?;
+        Ok(page.finish(result, total, models::EpisodeResponse::from_episode))
     }
 
     pub async fn get_album_uuid_by_episode_uuid(
@@ -52,6 +94,21 @@ impl Repository {
         Ok(result)
     }
 
+    /// Age rating of the album of the episode
+    pub async fn get_album_access_by_episode_uuid(
+        pool: &DbPool,
+        episode_uuid: String,
+    ) -> Result<(bool, i32), diesel::result::Error> {
+        use crate::schema::episodes::uuid;
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        albums::table
+            .inner_join(episodes::table.on(episodes::album_id.eq(albums::id)))
+            .select((albums::enable, albums::min_age))
+            .filter(uuid.eq(episode_uuid))
+            .filter(episodes::deleted_at.is_null())
+            .first(&mut conn)
+    }
+
     pub async fn get_episode_by_episode_uuid(
         pool: &DbPool,
         episode_uuid: String,
@@ -59,7 +116,9 @@ impl Repository {
         use crate::schema::episodes::uuid;
         let mut conn = pool.get().expect("Failed to get DB connection");
         let mut query = episodes::table.into_boxed();
-        query = query.filter(uuid.eq(episode_uuid));
+        query = query
+            .filter(uuid.eq(episode_uuid))
+            .filter(episodes::deleted_at.is_null());
         let result = query.get_result(&mut conn)?;
         Ok(result)
     }
@@ -71,7 +130,9 @@ impl Repository {
         use crate::schema::albums::id;
         let mut conn = pool.get().expect("Failed to get DB connection");
         let mut query = albums::table.into_boxed();
-        query = query.filter(id.eq(album_id));
+        query = query
+            .filter(id.eq(album_id))
+            .filter(albums::deleted_at.is_null());
         let result = query.get_result(&mut conn)?;
         Ok(result)
     }
@@ -90,13 +151,86 @@ impl Repository {
         Ok(result)
     }
 
+    /// Next free position at the end of the reading order of the album
+    pub async fn get_next_sort_order(
+        pool: &DbPool,
+        album_id_val: i32,
+    ) -> Result<i32, diesel::result::Error> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        let last = episodes::table
+            .filter(episodes::album_id.eq(album_id_val))
+            .select(diesel::dsl::max(episodes::sort_order))
+            .first::<Option<i32>>(&mut conn)?;
+        Ok(last.map(|last| last + 1).unwrap_or(1))
+    }
+
+    /// Uuids of the album episodes in reading order
+    pub async fn get_reading_order(
+        pool: &DbPool,
+        album_id_val: i32,
+    ) -> Result<Vec<String>, diesel::result::Error> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        episodes::table
+            .filter(episodes::album_id.eq(album_id_val))
+            .filter(episodes::deleted_at.is_null())
+            .order((episodes::sort_order.asc(), episodes::id.asc()))
+            .select(episodes::uuid)
+            .load::<String>(&mut conn)
+    }
+
+    /// Number the episodes 1, 2, 3... in the given order
+    pub async fn set_reading_order(
+        pool: &DbPool,
+        album_id_val: i32,
+        episode_uuids: &[String],
+    ) -> Result<(), diesel::result::Error> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        // Only the order changes, `updated_at` is left alone so the album keeps its place in
+        // the recently updated listing
+        conn.transaction(|conn| {
+            for (position, episode_uuid) in episode_uuids.iter().enumerate() {
+                diesel::update(
+                    episodes::table
+                        .filter(episodes::album_id.eq(album_id_val))
+                        .filter(episodes::uuid.eq(episode_uuid)),
+                )
+                .set(episodes::sort_order.eq(position as i32 + 1))
+                .execute(conn)?;
+            }
+            Ok(())
+        })
+    }
+
+    pub async fn set_hls_status(
+        pool: &DbPool,
+        episode_uuid: String,
+        status: &str,
+        hls_url: Option<String>,
+        hls_error: Option<String>,
+    ) -> Result<usize, diesel::result::Error> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        diesel::update(episodes::table.filter(episodes::uuid.eq(episode_uuid)))
+            .set((
+                episodes::hls_status.eq(Some(status)),
+                episodes::hls_url.eq(hls_url),
+                episodes::hls_error.eq(hls_error),
+            ))
+            .execute(&mut conn)
+    }
+
     pub async fn delete_episode(
         pool: &DbPool,
         episode_uuid: String,
     ) -> Result<usize, diesel::result::Error> {
         use crate::schema::episodes::dsl::*;
         let mut conn = pool.get().expect("Failed to get DB connection");
-        let deleted = diesel::delete(episodes.filter(uuid.eq(episode_uuid))).execute(&mut conn)?;
+        let deleted = diesel::update(
+            episodes
+                .filter(uuid.eq(episode_uuid))
+                .filter(deleted_at.is_null()),
+        )
+        .set(deleted_at.eq(Some(Utc::now().naive_utc())))
+        .execute(&mut conn)?;
         Ok(deleted)
     }
 }
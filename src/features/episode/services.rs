@@ -1,10 +1,24 @@
 use crate::common::database::DbPool;
-use crate::common::models::file_metadata::ImageMetadata;
+use crate::common::i18n::{default_locale, negotiate};
+use crate::common::models::file_metadata::{ImageMetadata, VideoMetadata};
 use crate::common::models::response_data::ResponseData;
-use crate::common::utils::{delete_directory_if_exists, get_data_directory, get_directory_from_file_path, get_file_metadata, get_project_directory, move_file_and_replace};
+use crate::common::pagination::{Page, PaginationError};
+use crate::common::utils::{
+    delete_directory_if_exists, get_data_directory, get_directory_from_file_path,
+    get_file_metadata, get_project_directory, get_trash_directory, move_file_and_replace,
+    move_path,
+};
+use crate::common::config;
+use crate::common::video::{hls_available, package_hls, poster_path, read_hls_variants};
+use crate::features::content::services::Service as ContentService;
+use crate::features::episode::jobs::PACKAGE_HLS_JOB;
 use crate::features::episode::models;
+use crate::features::jobs;
+use crate::features::translation::services::Service as TranslationService;
 use crate::features::episode::repository::*;
+use actix_web::web;
 use chrono::Utc;
+use serde_json::json;
 use uuid::Uuid;
 
 pub struct Service;
@@ -17,7 +31,17 @@ impl Service {
         match Repository::get_album_by_id(pool, req_data.clone().album_id).await {
             Ok(album) => {
                 let album_uuid = album.uuid;
+                let append = req_data.sort_order.is_none();
                 let mut new_episode = models::Episode::from_create_request(req_data, album_uuid);
+                if append {
+                    match Repository::get_next_sort_order(pool, album.id).await {
+                        Ok(sort_order) => new_episode.sort_order = sort_order,
+                        Err(e) => {
+                            eprintln!("Error: {e}");
+                            return Err("Failed to create episode");
+                        }
+                    }
+                }
                 if !data.is_none() {
                     let metadata = get_file_metadata(data.clone().unwrap().as_str());
                     new_episode.content_type = Some(metadata.content_type.clone());
@@ -32,6 +56,8 @@ impl Service {
                         .unwrap_or(ImageMetadata::default())
                         .height as i32;
                     new_episode.bytes = metadata.size as i32;
+                    new_episode.file_name = Some(metadata.original_name.clone());
+                    apply_video_metadata(&mut new_episode, metadata.video_data.as_ref());
                 }
                 match Repository::create_episode(pool, new_episode).await {
                     Ok(episode) => {
@@ -42,7 +68,12 @@ impl Service {
                                 get_project_directory(),
                                 response.url.clone().unwrap()
                             );
-                            move_file_and_replace(data.unwrap().as_str(), &*des_file_path);
+                            let src_file_path = data.unwrap();
+                            move_file_and_replace(src_file_path.as_str(), &*des_file_path);
+                            move_poster(&src_file_path, &response.poster_url);
+                            if should_package_hls(&response) {
+                                return Self::queue_hls_packaging(pool, response.uuid).await;
+                            }
                         }
                         Ok(response)
                     }
@@ -59,31 +90,178 @@ impl Service {
         }
     }
 
+    /// Whether the album is enabled and its age rating, `None` when the album does not exist
+    pub async fn get_album_access(
+        pool: &DbPool,
+        album_id: i32,
+    ) -> Result<Option<(bool, i32)>, diesel::result::Error> {
+        match Repository::get_album_by_id(pool, album_id).await {
+            Ok(album) => Ok(Some((album.enable, album.min_age))),
+            Err(diesel::result::Error::NotFound) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Whether the album of the episode is enabled and its age rating, `None` when the episode
+    /// does not exist
+    pub async fn get_album_access_by_episode_uuid(
+        pool: &DbPool,
+        episode_uuid: String,
+    ) -> Result<Option<(bool, i32)>, diesel::result::Error> {
+        match Repository::get_album_access_by_episode_uuid(pool, episode_uuid).await {
+            Ok(access) => Ok(Some(access)),
+            Err(diesel::result::Error::NotFound) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Episode with its title in the best of the requested locales
     pub async fn get_episode_by_episode_uuid(
         pool: &DbPool,
         episode_uuid: String,
+        locales: &[String],
     ) -> Result<models::EpisodeResponse, diesel::result::Error> {
-        match Repository::get_episode_by_episode_uuid(pool, episode_uuid).await {
-            Ok(episode) => {
-                Ok(models::EpisodeResponse::from_episode(episode))
+        let episode = Repository::get_episode_by_episode_uuid(pool, episode_uuid).await?;
+        let reading_order = Repository::get_reading_order(pool, episode.album_id).await?;
+        let mut response = models::EpisodeResponse::from_episode(episode);
+        link_neighbours(&reading_order, std::slice::from_mut(&mut response));
+        localize_episodes(pool, std::slice::from_mut(&mut response), locales).await?;
+        Ok(response)
+    }
+
+    /// Episode, album summary and ordered pages in one response for readers, each in the best of
+    /// the requested locales
+    pub async fn get_episode_manifest(
+        pool: &DbPool,
+        episode_uuid: String,
+        locales: &[String],
+    ) -> Result<models::EpisodeManifest, diesel::result::Error> {
+        let episode = Self::get_episode_by_episode_uuid(pool, episode_uuid, locales).await?;
+        let album = Repository::get_album_by_id(pool, episode.album_id).await?;
+        let album_translations =
+            TranslationService::get_album_translations(pool, vec![album.id]).await?;
+        let mut manifest_album = models::ManifestAlbum::from_album(album);
+        let album_locales = std::iter::once(default_locale())
+            .chain(album_translations.iter().map(|t| t.locale.clone()))
+            .collect::<Vec<String>>();
+        if let Some(locale) = negotiate(locales, &album_locales) {
+            if let Some(translation) = album_translations.into_iter().find(|t| t.locale == locale) {
+                manifest_album.title = translation.title;
             }
-            Err(e) => Err(e),
         }
+        let episode_id = episode.id.ok_or(diesel::result::Error::NotFound)?;
+        let contents_locale = ContentService::negotiate_edition(pool, episode_id, locales).await?;
+        let pages =
+            ContentService::get_all_contents_by_episode_id(pool, episode_id, &contents_locale)
+                .await?;
+        let contents = pages
+            .into_iter()
+            .map(|content| models::ManifestContent {
+                renditions: vec![models::ManifestRendition {
+                    name: String::from("original"),
+                    url: content.url.clone(),
+                    content_type: content.content_type.clone(),
+                    width: content.width,
+                    height: content.height,
+                }],
+                uuid: content.uuid,
+                index_no: content.index_no,
+                url: content.url,
+                ads_url: content.ads_url,
+                content_type: content.content_type,
+                width: content.width,
+                height: content.height,
+                bytes: content.bytes,
+            })
+            .collect();
+        Ok(models::EpisodeManifest {
+            renditions: video_renditions(&episode),
+            previous_episode_uuid: episode.previous_episode_uuid.clone(),
+            next_episode_uuid: episode.next_episode_uuid.clone(),
+            album: manifest_album,
+            contents_locale,
+            contents,
+            episode,
+        })
     }
+
     pub async fn get_episodes_by_album_id(
         pool: &DbPool,
         album_id: i32,
         filter_episodes: models::FilterEpisodeRequest,
-    ) -> Result<ResponseData<models::EpisodeResponse>, diesel::result::Error> {
-        match Repository::get_episodes_by_album_id(pool, album_id, filter_episodes).await {
-            Ok(episodes) => {
-                let data = models::EpisodeResponse::from_episodes(episodes);
-                let total = data.len() as i64;
-                let response = ResponseData { data, total };
-                Ok(response)
-            }
-            Err(e) => Err(e),
+        locales: &[String],
+    ) -> Result<ResponseData<models::EpisodeResponse>, PaginationError> {
+        let page = Page::new(
+            filter_episodes.page_query(),
+            None,
+            Repository::sort_fields(),
+            None,
+        )?;
+        let mut response =
+            Repository::get_episodes_by_album_id(pool, album_id, filter_episodes, &page).await?;
+        let reading_order = Repository::get_reading_order(pool, album_id).await?;
+        link_neighbours(&reading_order, &mut response.data);
+        localize_episodes(pool, &mut response.data, locales).await?;
+        Ok(response)
+    }
+
+    /// Replace the reading order of the album, every episode must be listed exactly once
+    pub async fn reorder_episodes(
+        pool: &DbPool,
+        album_id: i32,
+        req: models::ReorderEpisodesRequest,
+    ) -> Result<models::ReorderedEpisodes, String> {
+        let album = Repository::get_album_by_id(pool, album_id)
+            .await
+            .map_err(|_| String::from("Album not found"))?;
+        let previous_order = Repository::get_reading_order(pool, album_id)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut listed = req.episode_uuids.clone();
+        listed.sort();
+        listed.dedup();
+        if listed.len() != req.episode_uuids.len() {
+            return Err(String::from("An episode is listed more than once"));
+        }
+        if let Some(unknown) = req
+            .episode_uuids
+            .iter()
+            .find(|uuid| !previous_order.contains(uuid))
+        {
+            return Err(format!("Episode {} is not in this album", unknown));
+        }
+        if req.episode_uuids.len() != previous_order.len() {
+            return Err(format!(
+                "Every episode of the album must be listed, {} of {} given",
+                req.episode_uuids.len(),
+                previous_order.len()
+            ));
         }
+
+        Repository::set_reading_order(pool, album_id, &req.episode_uuids)
+            .await
+            .map_err(|e| e.to_string())?;
+        let episodes = Self::get_episodes_by_album_id(
+            pool,
+            album_id,
+            models::FilterEpisodeRequest {
+                title: String::new(),
+                sort: None,
+                order: None,
+                cursor: None,
+                limit: None,
+            },
+            &[],
+        )
+        .await
+        .map_err(|e| e.to_string())?
+        .data;
+        Ok(models::ReorderedEpisodes {
+            album_uuid: album.uuid,
+            previous_order,
+            episodes,
+        })
     }
 
     pub async fn delete_episode(
@@ -92,9 +270,16 @@ impl Service {
     ) -> Result<usize, diesel::result::Error> {
         match Repository::get_album_uuid_by_episode_uuid(pool, episode_uuid.clone()).await {
             Ok(album_uuid) => {
-                let filepath = format!("{}/{}/{}", get_data_directory(), album_uuid, episode_uuid, );
-                delete_directory_if_exists(&filepath);
-                Repository::delete_episode(pool, episode_uuid).await
+                let deleted = Repository::delete_episode(pool, episode_uuid.clone()).await?;
+                if deleted > 0 {
+                    let filepath =
+                        format!("{}/{}/{}", get_data_directory(), album_uuid, episode_uuid);
+                    let trash_path = format!("{}/episodes/{}", get_trash_directory(), episode_uuid);
+                    if let Err(e) = move_path(&filepath, &trash_path) {
+                        eprintln!("Failed to move episode {} to trash: {}", episode_uuid, e);
+                    }
+                }
+                Ok(deleted)
             }
             Err(e) => Err(e),
         }
@@ -128,12 +313,17 @@ impl Service {
 
         match Repository::update_episode(pool, new_episode.clone()).await {
             Ok(size) if size > 0 => {
-                handle_old_file_cleanup(
-                    &update_episode,
-                    &new_episode,
-                    &episode.url,
-                );
-                Ok(models::EpisodeResponse::from_episode(new_episode))
+                handle_old_file_cleanup(&update_episode, &new_episode, &episode.url);
+                let mut response = models::EpisodeResponse::from_episode(new_episode);
+                if let Err(e) =
+                    localize_episodes(pool, std::slice::from_mut(&mut response), &[]).await
+                {
+                    eprintln!("Failed to load episode locales: {e}");
+                }
+                if update_episode.file.is_some() && should_package_hls(&response) {
+                    return Self::queue_hls_packaging(pool, response.uuid).await;
+                }
+                Ok(response)
             }
             Ok(_) => Err("Album not found"),
             Err(_) => Err("Failed to update album"),
@@ -141,6 +331,102 @@ impl Service {
     }
 }
 
+impl Service {
+    /// Queue packaging of the episode video into HLS renditions, the job runs on a job worker
+    pub async fn queue_hls_packaging(
+        pool: &DbPool,
+        episode_uuid: String,
+    ) -> Result<models::EpisodeResponse, &str> {
+        if !hls_available() {
+            return Err("HLS packaging requires the video feature");
+        }
+        let episode = Repository::get_episode_by_episode_uuid(pool, episode_uuid.clone())
+            .await
+            .map_err(|_| "Episode not found")?;
+        let is_video = episode
+            .content_type
+            .as_deref()
+            .is_some_and(|content_type| content_type.starts_with("video/"));
+        if !is_video || episode.url.as_deref().unwrap_or_default().is_empty() {
+            return Err("Episode has no uploaded video");
+        }
+        if matches!(episode.hls_status.as_deref(), Some("pending") | Some("processing")) {
+            return Err("HLS packaging is already queued");
+        }
+        Repository::set_hls_status(pool, episode_uuid.clone(), "pending", None, None)
+            .await
+            .map_err(|_| "Failed to queue HLS packaging")?;
+        if let Err(e) = jobs::services::Service::enqueue_unique(
+            pool,
+            PACKAGE_HLS_JOB,
+            json!({ "episode_uuid": episode.uuid }),
+        )
+        .await
+        {
+            eprintln!("Failed to queue HLS packaging of episode {}: {}", episode_uuid, e);
+            Repository::set_hls_status(pool, episode_uuid, "failed", None, Some(e.to_string()))
+                .await
+                .ok();
+            return Err("Failed to queue HLS packaging");
+        }
+        Repository::get_episode_by_episode_uuid(pool, episode_uuid)
+            .await
+            .map(models::EpisodeResponse::from_episode)
+            .map_err(|_| "Episode not found")
+    }
+}
+
+fn should_package_hls(episode: &models::EpisodeResponse) -> bool {
+    config::get().video.hls_enabled
+        && hls_available()
+        && episode
+            .content_type
+            .as_deref()
+            .is_some_and(|content_type| content_type.starts_with("video/"))
+}
+
+/// Job handler of `episode.package_hls`, an `Err` leaves the episode failed until the retry
+pub(crate) async fn run_hls_packaging(pool: DbPool, episode_uuid: String) -> Result<(), String> {
+    let episode = match Repository::get_episode_by_episode_uuid(&pool, episode_uuid.clone()).await {
+        Ok(episode) => episode,
+        Err(diesel::result::Error::NotFound) => {
+            println!("Skipping HLS packaging of deleted episode {}", episode_uuid);
+            return Ok(());
+        }
+        Err(e) => return Err(e.to_string()),
+    };
+    Repository::set_hls_status(&pool, episode_uuid.clone(), "processing", None, None)
+        .await
+        .map_err(|e| e.to_string())?;
+    let input = episode.url.clone().unwrap_or_default();
+    let output_dir = format!(
+        "{}/hls",
+        get_directory_from_file_path(&input).unwrap_or_default()
+    );
+    let (width, height) = (episode.width.max(0) as u32, episode.height.max(0) as u32);
+    let result = web::block(move || package_hls(&input, &output_dir, width, height))
+        .await
+        .unwrap_or_else(|e| Err(e.to_string()));
+    match result {
+        Ok(master_playlist) => {
+            println!("Packaged episode {} into HLS", episode_uuid);
+            Repository::set_hls_status(&pool, episode_uuid, "ready", Some(master_playlist), None)
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        }
+        Err(e) => {
+            if let Err(update_error) =
+                Repository::set_hls_status(&pool, episode_uuid.clone(), "failed", None, Some(e.clone()))
+                    .await
+            {
+                eprintln!("Failed to update HLS status of episode {}: {}", episode_uuid, update_error);
+            }
+            Err(e)
+        }
+    }
+}
+
 fn build_updated_episode(
     episode: &models::Episode,
     update_episode: &models::UpdateEpisodeRequest,
@@ -152,7 +438,10 @@ fn build_updated_episode(
         id: episode.id,
         album_id: episode.album_id,
         uuid: episode.uuid.clone(),
-        title: update_episode.title.clone().unwrap_or_else(|| episode.title.clone()),
+        title: update_episode
+            .title
+            .clone()
+            .unwrap_or_else(|| episode.title.clone()),
         url: if should_reset_fields {
             Some(String::new())
         } else {
@@ -164,12 +453,144 @@ fn build_updated_episode(
         } else {
             episode.content_type.clone()
         },
-        width: if should_reset_fields { 0 } else { episode.width },
-        height: if should_reset_fields { 0 } else { episode.height },
-        bytes: if should_reset_fields { 0 } else { episode.bytes },
+        width: if should_reset_fields {
+            0
+        } else {
+            episode.width
+        },
+        height: if should_reset_fields {
+            0
+        } else {
+            episode.height
+        },
+        bytes: if should_reset_fields {
+            0
+        } else {
+            episode.bytes
+        },
         broken_at: episode.broken_at,
         created_at: episode.created_at,
         updated_at: episode.updated_at,
+        deleted_at: episode.deleted_at,
+        duration_ms: if should_reset_fields { None } else { episode.duration_ms },
+        video_codec: if should_reset_fields {
+            None
+        } else {
+            episode.video_codec.clone()
+        },
+        bitrate: if should_reset_fields { None } else { episode.bitrate },
+        poster_url: if should_reset_fields {
+            None
+        } else {
+            episode.poster_url.clone()
+        },
+        hls_status: if should_reset_fields {
+            None
+        } else {
+            episode.hls_status.clone()
+        },
+        hls_url: if should_reset_fields {
+            None
+        } else {
+            episode.hls_url.clone()
+        },
+        hls_error: if should_reset_fields {
+            None
+        } else {
+            episode.hls_error.clone()
+        },
+        file_name: if should_reset_fields {
+            None
+        } else {
+            episode.file_name.clone()
+        },
+        number: update_episode.number.or(episode.number),
+        volume: update_episode.volume.or(episode.volume),
+        sort_order: update_episode.sort_order.unwrap_or(episode.sort_order),
+    }
+}
+
+/// The uploaded video followed by the HLS variants once packaging is ready
+fn video_renditions(episode: &models::EpisodeResponse) -> Vec<models::ManifestRendition> {
+    let Some(url) = episode.url.clone() else {
+        return vec![];
+    };
+    let mut renditions = vec![models::ManifestRendition {
+        name: String::from("original"),
+        url,
+        content_type: episode.content_type.clone().unwrap_or_default(),
+        width: episode.width,
+        height: episode.height,
+    }];
+    if let (Some("ready"), Some(hls_url)) = (episode.hls_status.as_deref(), &episode.hls_url) {
+        let hls_directory = get_directory_from_file_path(hls_url).unwrap_or_default();
+        let master_path = format!("{}/{}", get_project_directory(), hls_url);
+        renditions.extend(read_hls_variants(&master_path).into_iter().map(|variant| {
+            models::ManifestRendition {
+                name: variant.name,
+                url: format!("{}/{}", hls_directory, variant.uri),
+                content_type: String::from("application/vnd.apple.mpegurl"),
+                width: variant.width as i32,
+                height: variant.height as i32,
+            }
+        }));
+    }
+    renditions
+}
+
+/// Show each episode title in the best of the requested locales and list the locales the
+/// episode has a title or pages in
+async fn localize_episodes(
+    pool: &DbPool,
+    episodes: &mut [models::EpisodeResponse],
+    locales: &[String],
+) -> Result<(), diesel::result::Error> {
+    let episode_ids = episodes.iter().filter_map(|episode| episode.id).collect::<Vec<i32>>();
+    let translations =
+        TranslationService::get_episode_translations(pool, episode_ids.clone()).await?;
+    let content_locales = ContentService::get_content_locales(pool, episode_ids).await?;
+    for episode in episodes.iter_mut() {
+        let Some(episode_id) = episode.id else {
+            continue;
+        };
+        let mut translated = translations
+            .iter()
+            .filter(|translation| translation.episode_id == episode_id)
+            .map(|translation| translation.locale.clone())
+            .chain(
+                content_locales
+                    .iter()
+                    .filter(|(id, _)| *id == episode_id)
+                    .map(|(_, locale)| locale.clone()),
+            )
+            .collect::<Vec<String>>();
+        translated.sort();
+        translated.dedup();
+        episode.available_locales = std::iter::once(default_locale()).chain(translated).collect();
+        let Some(locale) = negotiate(locales, &episode.available_locales) else {
+            continue;
+        };
+        if let Some(translation) = translations
+            .iter()
+            .find(|t| t.episode_id == episode_id && t.locale == locale)
+        {
+            episode.title = translation.title.clone();
+        }
+        episode.locale = locale;
+    }
+    Ok(())
+}
+
+/// Fill the previous and next episode of each episode from the album reading order
+fn link_neighbours(reading_order: &[String], episodes: &mut [models::EpisodeResponse]) {
+    for episode in episodes.iter_mut() {
+        if let Some(position) = reading_order.iter().position(|uuid| *uuid == episode.uuid) {
+            episode.previous_episode_uuid = position
+                .checked_sub(1)
+                .and_then(|previous| reading_order.get(previous))
+                .cloned();
+            episode.next_episode_uuid = reading_order.get(position + 1).cloned();
+        }
     }
 }
 
@@ -184,9 +605,18 @@ fn update_episode_with_file(
     let metadata = get_file_metadata(src_path);
 
     new_episode.content_type = Some(metadata.content_type);
-    new_episode.width = metadata.image_data.clone().unwrap_or(ImageMetadata::default()).width as i32;
-    new_episode.height = metadata.image_data.clone().unwrap_or(ImageMetadata::default()).height as i32;
+    new_episode.width = metadata
+        .image_data
+        .clone()
+        .unwrap_or(ImageMetadata::default())
+        .width as i32;
+    new_episode.height = metadata
+        .image_data
+        .clone()
+        .unwrap_or(ImageMetadata::default())
+        .height as i32;
     new_episode.bytes = metadata.size as i32;
+    new_episode.file_name = Some(metadata.original_name);
 
     new_episode.url = Some(format!(
         "{}/{}/{}/{}.{}",
@@ -196,6 +626,42 @@ fn update_episode_with_file(
         new_uuid,
         format
     ));
+    apply_video_metadata(new_episode, metadata.video_data.as_ref());
+    new_episode.hls_status = None;
+    new_episode.hls_url = None;
+    new_episode.hls_error = None;
+}
+
+/// Copy ffprobe metadata onto the episode, the poster is stored next to `episode.url`
+fn apply_video_metadata(new_episode: &mut models::Episode, video_data: Option<&VideoMetadata>) {
+    match video_data {
+        Some(video) => {
+            new_episode.width = video.width as i32;
+            new_episode.height = video.height as i32;
+            new_episode.duration_ms = Some(video.duration_ms as i32);
+            new_episode.video_codec = Some(video.codec.clone()).filter(|codec| !codec.is_empty());
+            new_episode.bitrate = Some(video.bitrate as i32);
+            new_episode.poster_url = video
+                .thumbnail
+                .as_ref()
+                .and(new_episode.url.as_deref())
+                .map(poster_path);
+        }
+        None => {
+            new_episode.duration_ms = None;
+            new_episode.video_codec = None;
+            new_episode.bitrate = None;
+            new_episode.poster_url = None;
+        }
+    }
+}
+
+/// Move the poster generated next to the uploaded video to `poster_url`
+fn move_poster(src_file_path: &str, poster_url: &Option<String>) {
+    if let Some(poster_url) = poster_url {
+        let dest_file_path = format!("{}/{}", get_project_directory(), poster_url);
+        move_file_and_replace(&poster_path(src_file_path), &dest_file_path);
+    }
 }
 
 fn handle_old_file_cleanup(
@@ -216,7 +682,7 @@ fn handle_old_file_cleanup(
         if let (Some(src_file_path), Some(new_ep_url)) = (&update_episode.file, &new_episode.url) {
             let dest_file_path = format!("{}/{}", get_project_directory(), new_ep_url);
             move_file_and_replace(src_file_path, &dest_file_path);
+            move_poster(src_file_path, &new_episode.poster_url);
         }
     }
 }
-
@@ -0,0 +1,48 @@
+use actix_web::{get, web, Either, HttpRequest, HttpResponse, Responder};
+
+use crate::common::enums::Role::Admin;
+use crate::common::models::response_message::ResponseMessage;
+use crate::features::check_role;
+use crate::features::events::models::GetEventsRequest;
+use crate::features::events::services::Service;
+
+/// Stream Events
+///
+/// Server-Sent Events of album, episode, content and category changes, send `Last-Event-ID`
+/// to replay events missed while disconnected
+#[utoipa::path(
+    get,
+    path = "/events",
+    params(
+        GetEventsRequest,
+        ("Last-Event-ID" = Option<String>, Header, description = "Id of the last received event")
+    ),
+    responses(
+        (status = 200, description = "text/event-stream of CatalogEvent", body = CatalogEvent, content_type = "text/event-stream"),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Events",
+)]
+#[get("/events")]
+pub async fn stream_events(
+    http_request: HttpRequest,
+    req: web::Query<GetEventsRequest>,
+) -> impl Responder {
+    if check_role(http_request.clone()) == Admin {
+        let filters = req.into_inner();
+        let last_event_id = http_request
+            .headers()
+            .get("Last-Event-ID")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .or(filters.last_event_id);
+        Either::Left(Service::subscribe(filters, last_event_id))
+    } else {
+        Either::Right(HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        }))
+    }
+}
@@ -0,0 +1,12 @@
+use actix_web::web;
+
+use crate::features::events::controllers::stream_events;
+
+pub mod controllers;
+pub mod models;
+pub mod repository;
+pub mod services;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(stream_events);
+}
@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::{IntoParams, ToSchema};
+
+/// Entity types broadcast on `/events`
+pub const CATALOG_ENTITY_TYPES: [&str; 4] = ["album", "episode", "content", "category"];
+
+/// Sent when `Last-Event-ID` is older than the event log, clients should reload their data
+pub const TRUNCATED_EVENT: &str = "events.truncated";
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, PartialEq)]
+pub struct CatalogEvent {
+    /// Increasing event id, also sent as the SSE `id` field
+    pub id: u64,
+    /// `{entity_type}.{created|updated|deleted|restored|purged}`, also the SSE `event` field
+    pub event_type: String,
+    /// album, episode, content or category
+    pub entity_type: String,
+    pub entity_uuid: String,
+    /// Album the entity belongs to, empty for categories
+    pub album_uuid: Option<String>,
+    /// Entity after the change, or before it for deletes
+    #[schema(value_type = Object)]
+    pub data: Option<Value>,
+    pub created_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, IntoParams, Clone, Default)]
+#[into_params(parameter_in = Query)]
+pub struct GetEventsRequest {
+    /// Filter entity type (album, episode, content, category)
+    pub entity_type: Option<String>,
+    /// Filter entity UUID (Exact Value)
+    pub entity_uuid: Option<String>,
+    /// Only events of this album and its episodes and contents
+    pub album_uuid: Option<String>,
+    /// Replay events after this id, the `Last-Event-ID` header takes precedence
+    pub last_event_id: Option<u64>,
+}
+
+impl GetEventsRequest {
+    pub fn matches(&self, event: &CatalogEvent) -> bool {
+        let matches = |filter: &Option<String>, value: Option<&str>| match filter
+            .as_deref()
+            .filter(|s| !s.is_empty())
+        {
+            Some(filter) => value == Some(filter),
+            None => true,
+        };
+        matches(&self.entity_type, Some(&event.entity_type))
+            && matches(&self.entity_uuid, Some(&event.entity_uuid))
+            && matches(&self.album_uuid, event.album_uuid.as_deref())
+    }
+}
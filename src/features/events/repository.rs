@@ -0,0 +1,34 @@
+use diesel::prelude::*;
+
+use crate::common::database::DbPool;
+use crate::schema::{albums, contents, episodes};
+
+pub struct Repository;
+
+impl Repository {
+    /// Album of an album, episode or content, soft deleted rows included
+    pub async fn get_album_uuid(
+        pool: &DbPool,
+        entity_type: &str,
+        entity_uuid: String,
+    ) -> QueryResult<Option<String>> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        match entity_type {
+            "album" => Ok(Some(entity_uuid)),
+            "episode" => episodes::table
+                .inner_join(albums::table.on(albums::id.eq(episodes::album_id)))
+                .filter(episodes::uuid.eq(entity_uuid))
+                .select(albums::uuid)
+                .first::<String>(&mut conn)
+                .optional(),
+            "content" => contents::table
+                .inner_join(episodes::table.on(episodes::id.eq(contents::episode_id.nullable())))
+                .inner_join(albums::table.on(albums::id.eq(episodes::album_id)))
+                .filter(contents::uuid.eq(entity_uuid))
+                .select(albums::uuid)
+                .first::<String>(&mut conn)
+                .optional(),
+            _ => Ok(None),
+        }
+    }
+}
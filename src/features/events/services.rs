@@ -0,0 +1,375 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use actix_web::Responder;
+use actix_web_lab::sse;
+use chrono::Utc;
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+use crate::common::config;
+use crate::common::database::DbPool;
+use crate::common::ne_parse::NEParse;
+use crate::features::events::models::{
+    CatalogEvent, GetEventsRequest, CATALOG_ENTITY_TYPES, TRUNCATED_EVENT,
+};
+use crate::features::events::repository::Repository;
+
+static BROADCASTER: OnceLock<Mutex<Broadcaster>> = OnceLock::new();
+
+struct Client {
+    sender: mpsc::Sender<sse::Event>,
+    filters: GetEventsRequest,
+}
+
+/// Bounded event log and connected clients, shared by every HTTP worker
+struct Broadcaster {
+    /// Ids start at the process start time in milliseconds so they keep increasing across restarts
+    first_id: u64,
+    next_id: u64,
+    log: VecDeque<CatalogEvent>,
+    clients: Vec<Client>,
+}
+
+impl Broadcaster {
+    fn new() -> Self {
+        let first_id = Utc::now().timestamp_millis().max(0) as u64;
+        Broadcaster {
+            first_id,
+            next_id: first_id,
+            log: VecDeque::new(),
+            clients: Vec::new(),
+        }
+    }
+
+    /// Oldest id that can still be replayed
+    fn replay_start(&self) -> u64 {
+        self.log.front().map_or(self.next_id, |event| event.id)
+    }
+
+    /// Send the event to the matching clients and log it, keeping at most `capacity` events
+    fn push(
+        &mut self,
+        event_type: String,
+        entity_type: &str,
+        entity_uuid: &str,
+        album_uuid: Option<String>,
+        data: Option<Value>,
+        capacity: usize,
+    ) {
+        let event = CatalogEvent {
+            id: self.next_id,
+            event_type,
+            entity_type: entity_type.to_string(),
+            entity_uuid: entity_uuid.to_string(),
+            album_uuid,
+            data,
+            created_at: NEParse::opt_naive_datetime_to_utc_opt_string(Some(Utc::now().naive_utc())),
+        };
+        self.next_id += 1;
+        // Clients which disconnected are dropped, clients with a full buffer miss this event
+        self.clients.retain(|client| {
+            !client.filters.matches(&event)
+                || !matches!(
+                    client.sender.try_send(to_sse_event(&event)),
+                    Err(mpsc::error::TrySendError::Closed(_))
+                )
+        });
+        if capacity > 0 {
+            while self.log.len() >= capacity {
+                self.log.pop_front();
+            }
+            self.log.push_back(event);
+        }
+    }
+
+    /// Whether events after `last_event_id` have already left the log
+    fn is_truncated(&self, last_event_id: u64) -> bool {
+        last_event_id.saturating_add(1) < self.replay_start().max(self.first_id)
+    }
+
+    /// Logged events after `last_event_id` matching the filters, oldest first
+    fn events_after(&self, last_event_id: u64, filters: &GetEventsRequest) -> Vec<&CatalogEvent> {
+        self.log
+            .iter()
+            .filter(|event| event.id > last_event_id && filters.matches(event))
+            .collect()
+    }
+}
+
+fn broadcaster() -> &'static Mutex<Broadcaster> {
+    BROADCASTER.get_or_init(|| Mutex::new(Broadcaster::new()))
+}
+
+pub struct Service;
+
+impl Service {
+    /// Broadcast an audited admin mutation of an album, episode, content or category
+    pub async fn publish_mutation(
+        pool: &DbPool,
+        action: &str,
+        entity_type: &str,
+        entity_uuid: &str,
+        before: Option<&Value>,
+        after: Option<&Value>,
+    ) {
+        let Some(event_type) = mutation_event_type(action, entity_type) else {
+            return;
+        };
+        let album_uuid = Repository::get_album_uuid(pool, entity_type, entity_uuid.to_string())
+            .await
+            .unwrap_or_else(|e| {
+                eprintln!(
+                    "Failed to resolve album of {} {}: {}",
+                    entity_type, entity_uuid, e
+                );
+                None
+            });
+        Self::publish(
+            event_type,
+            entity_type,
+            entity_uuid,
+            album_uuid,
+            after.or(before).cloned(),
+        );
+    }
+
+    pub fn publish(
+        event_type: String,
+        entity_type: &str,
+        entity_uuid: &str,
+        album_uuid: Option<String>,
+        data: Option<Value>,
+    ) {
+        let capacity = config::get().events.log_capacity;
+        broadcaster()
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(
+                event_type,
+                entity_type,
+                entity_uuid,
+                album_uuid,
+                data,
+                capacity,
+            );
+    }
+
+    /// Open a stream, replaying logged events after `last_event_id` first
+    pub fn subscribe(filters: GetEventsRequest, last_event_id: Option<u64>) -> impl Responder {
+        let events_config = &config::get().events;
+        let mut broadcaster = broadcaster().lock().unwrap_or_else(|e| e.into_inner());
+        let mut replay: Vec<sse::Event> = Vec::new();
+        if let Some(last_event_id) = last_event_id {
+            if broadcaster.is_truncated(last_event_id) {
+                replay.push(sse::Event::Data(
+                    sse::Data::new("{}")
+                        .event(TRUNCATED_EVENT)
+                        .id(last_event_id.to_string()),
+                ));
+            }
+            replay.extend(
+                broadcaster
+                    .events_after(last_event_id, &filters)
+                    .into_iter()
+                    .map(to_sse_event),
+            );
+        }
+        let (sender, receiver) = mpsc::channel(events_config.client_buffer.max(replay.len() + 1));
+        for event in replay {
+            let _ = sender.try_send(event);
+        }
+        broadcaster.clients.push(Client { sender, filters });
+        sse::Sse::from_infallible_receiver(receiver)
+            .with_keep_alive(Duration::from_secs(events_config.keep_alive_seconds))
+    }
+}
+
+fn to_sse_event(event: &CatalogEvent) -> sse::Event {
+    let data = sse::Data::new_json(event).unwrap_or_else(|_| sse::Data::new("{}"));
+    sse::Event::Data(
+        data.id(event.id.to_string())
+            .event(event.event_type.clone()),
+    )
+}
+
+/// `{entity_type}.{action in past tense}`, `None` for entities which are not broadcast
+fn mutation_event_type(action: &str, entity_type: &str) -> Option<String> {
+    CATALOG_ENTITY_TYPES
+        .contains(&entity_type)
+        .then(|| format!("{}.{}", entity_type, past_tense(action)))
+}
+
+fn past_tense(action: &str) -> String {
+    match action {
+        "create" => String::from("created"),
+        "update" => String::from("updated"),
+        "delete" => String::from("deleted"),
+        "restore" => String::from("restored"),
+        "purge" => String::from("purged"),
+        "reorder" => String::from("reordered"),
+        _ => action.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use diesel::prelude::*;
+    use diesel::sql_types::Text;
+    use serde_json::json;
+
+    use super::*;
+    use crate::common::test_support;
+
+    /// Push `count` album events, `album-0`, `album-1`, ...
+    fn push_albums(broadcaster: &mut Broadcaster, count: usize, capacity: usize) {
+        for index in 0..count {
+            broadcaster.push(
+                String::from("album.updated"),
+                "album",
+                &format!("album-{}", index),
+                Some(format!("album-{}", index)),
+                None,
+                capacity,
+            );
+        }
+    }
+
+    fn entity_uuids(events: Vec<&CatalogEvent>) -> Vec<&str> {
+        events
+            .into_iter()
+            .map(|event| event.entity_uuid.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn events_after_last_event_id_are_replayed() {
+        let mut broadcaster = Broadcaster::new();
+        let first_id = broadcaster.first_id;
+        push_albums(&mut broadcaster, 4, 10);
+        let all = GetEventsRequest::default();
+
+        assert_eq!(
+            entity_uuids(broadcaster.events_after(first_id + 1, &all)),
+            vec!["album-2", "album-3"]
+        );
+        assert!(broadcaster.events_after(first_id + 3, &all).is_empty());
+        let album_1 = GetEventsRequest {
+            album_uuid: Some(String::from("album-1")),
+            ..GetEventsRequest::default()
+        };
+        assert_eq!(
+            entity_uuids(broadcaster.events_after(first_id, &album_1)),
+            vec!["album-1"]
+        );
+        // Every event after the last one seen is still logged
+        assert!(!broadcaster.is_truncated(first_id));
+        assert!(!broadcaster.is_truncated(first_id + 3));
+    }
+
+    #[test]
+    fn ids_older_than_the_log_are_truncated() {
+        let mut broadcaster = Broadcaster::new();
+        let first_id = broadcaster.first_id;
+        // Only the last three of five events are kept
+        push_albums(&mut broadcaster, 5, 3);
+        assert_eq!(broadcaster.log.len(), 3);
+        assert_eq!(broadcaster.replay_start(), first_id + 2);
+
+        assert!(broadcaster.is_truncated(first_id));
+        // The next event wanted is the oldest one logged
+        assert!(!broadcaster.is_truncated(first_id + 1));
+        assert_eq!(
+            entity_uuids(broadcaster.events_after(first_id, &GetEventsRequest::default())),
+            vec!["album-2", "album-3", "album-4"]
+        );
+        // Ids from before the process started are always truncated
+        assert!(Broadcaster::new().is_truncated(0));
+    }
+
+    #[test]
+    fn mutations_map_to_past_tense_event_types() {
+        assert_eq!(
+            mutation_event_type("create", "album").as_deref(),
+            Some("album.created")
+        );
+        assert_eq!(
+            mutation_event_type("reorder", "content").as_deref(),
+            Some("content.reordered")
+        );
+        assert_eq!(
+            mutation_event_type("purge", "episode").as_deref(),
+            Some("episode.purged")
+        );
+        assert_eq!(mutation_event_type("create", "webhook"), None);
+    }
+
+    #[actix_web::test]
+    async fn mutations_are_logged_with_their_album() {
+        let pool = test_support::test_pool();
+        let album_uuid = uuid::Uuid::new_v4().to_string();
+        let episode_uuid = uuid::Uuid::new_v4().to_string();
+        let mut conn = pool.get().unwrap();
+        diesel::sql_query(
+            "INSERT INTO albums (uuid, category_id, title, description, images, url, \
+             content_type, width, height, bytes) VALUES (?, 1, 'Album', '', '', '', '', 0, 0, 0)",
+        )
+        .bind::<Text, _>(&album_uuid)
+        .execute(&mut conn)
+        .unwrap();
+        diesel::sql_query(
+            "INSERT INTO episodes (album_id, uuid, title, content_type, width, height, bytes) \
+             VALUES (1, ?, 'Episode', 'video/mp4', 0, 0, 0)",
+        )
+        .bind::<Text, _>(&episode_uuid)
+        .execute(&mut conn)
+        .unwrap();
+        let before = json!({ "title": "Episode" });
+        let after = json!({ "title": "Pilot" });
+
+        Service::publish_mutation(
+            &pool,
+            "update",
+            "episode",
+            &episode_uuid,
+            Some(&before),
+            Some(&after),
+        )
+        .await;
+        Service::publish_mutation(
+            &pool,
+            "delete",
+            "episode",
+            &episode_uuid,
+            Some(&before),
+            None,
+        )
+        .await;
+        Service::publish_mutation(
+            &pool,
+            "create",
+            "webhook",
+            &episode_uuid,
+            None,
+            Some(&after),
+        )
+        .await;
+
+        let broadcaster = broadcaster().lock().unwrap();
+        let logged = broadcaster
+            .log
+            .iter()
+            .filter(|event| event.entity_uuid == episode_uuid)
+            .collect::<Vec<_>>();
+        assert_eq!(logged.len(), 2);
+        assert_eq!(logged[0].event_type, "episode.updated");
+        assert_eq!(logged[0].entity_type, "episode");
+        assert_eq!(logged[0].album_uuid.as_deref(), Some(album_uuid.as_str()));
+        assert_eq!(logged[0].data, Some(after));
+        // Deletes carry the entity as it was
+        assert_eq!(logged[1].event_type, "episode.deleted");
+        assert_eq!(logged[1].data, Some(before));
+        assert!(logged[0].id < logged[1].id);
+    }
+}
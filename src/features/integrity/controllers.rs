@@ -0,0 +1,52 @@
+use actix_web::{post, web, HttpRequest, HttpResponse, Responder};
+
+use crate::common::database::DbPool;
+use crate::common::enums::Role::Admin;
+use crate::common::models::response_message::ResponseMessage;
+use crate::features::check_role;
+use crate::features::integrity::models::IntegrityError;
+use crate::features::integrity::services::Service;
+
+/// Run Integrity Scan
+///
+/// Verify stored files and external episode links, update `broken_at` and list orphaned files
+#[utoipa::path(
+    post,
+    path = "/integrity/scan",
+    responses(
+        (status = 200, description = "Scan finished", body = IntegrityReport),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 409, description = "A scan is already running", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Integrity",
+)]
+#[post("/integrity/scan")]
+pub async fn run_integrity_scan(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+) -> impl Responder {
+    if check_role(http_request) == Admin {
+        match Service::scan(&pool).await {
+            Ok(report) => HttpResponse::Ok().json(report),
+            Err(e @ IntegrityError::AlreadyRunning) => {
+                HttpResponse::Conflict().json(ResponseMessage {
+                    message: e.to_string(),
+                })
+            }
+            Err(e) => {
+                println!("Failed to run integrity scan: {}", e);
+                HttpResponse::InternalServerError().json(ResponseMessage {
+                    message: String::from("Internal Server Error"),
+                })
+            }
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
@@ -0,0 +1,36 @@
+use futures_util::future::LocalBoxFuture;
+use serde_json::Value;
+
+use crate::common::database::DbPool;
+use crate::features::integrity::models::IntegrityError;
+use crate::features::integrity::services::Service;
+use crate::features::jobs::registry::JobHandler;
+
+pub const SCAN_JOB: &str = "integrity.scan";
+
+pub struct ScanJob;
+
+impl JobHandler for ScanJob {
+    fn job_type(&self) -> &'static str {
+        SCAN_JOB
+    }
+
+    fn run(&self, pool: DbPool, _payload: Value) -> LocalBoxFuture<'static, Result<(), String>> {
+        Box::pin(async move {
+            match Service::scan(&pool).await {
+                Ok(report) => {
+                    println!(
+                        "Integrity scan finished: {} broken, {} repaired, {} orphaned files",
+                        report.broken.len(),
+                        report.repaired,
+                        report.orphaned_files.len()
+                    );
+                    Ok(())
+                }
+                // A scan started from the API already covers this run
+                Err(IntegrityError::AlreadyRunning) => Ok(()),
+                Err(e) => Err(e.to_string()),
+            }
+        })
+    }
+}
@@ -0,0 +1,19 @@
+use actix_web::web;
+
+use crate::features::integrity::controllers::run_integrity_scan;
+use crate::features::integrity::jobs::ScanJob;
+use crate::features::jobs::registry::JobRegistry;
+
+pub mod controllers;
+pub mod jobs;
+pub mod models;
+pub mod repository;
+pub mod services;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(run_integrity_scan);
+}
+
+pub fn register_jobs(registry: &mut JobRegistry) {
+    registry.register(ScanJob);
+}
@@ -0,0 +1,52 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// A file or link that failed the integrity check
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, PartialEq, Eq)]
+pub struct BrokenItemResponse {
+    /// album, episode or content
+    pub entity_type: String,
+    pub uuid: String,
+    /// Local path or external URL that failed
+    pub location: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, PartialEq, Eq, Default)]
+pub struct IntegrityReport {
+    pub checked_albums: i64,
+    pub checked_episodes: i64,
+    pub checked_contents: i64,
+    /// Items found broken in this scan
+    pub broken: Vec<BrokenItemResponse>,
+    /// Items which had `broken_at` set and are healthy again
+    pub repaired: i64,
+    /// Files in the data directory with no album, episode or content row
+    pub orphaned_files: Vec<String>,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum IntegrityError {
+    /// Another scan has not finished yet
+    AlreadyRunning,
+    Database(diesel::result::Error),
+}
+
+impl fmt::Display for IntegrityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntegrityError::AlreadyRunning => write!(f, "An integrity scan is already running"),
+            IntegrityError::Database(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<diesel::result::Error> for IntegrityError {
+    fn from(e: diesel::result::Error) -> Self {
+        IntegrityError::Database(e)
+    }
+}
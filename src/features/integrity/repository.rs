@@ -0,0 +1,85 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use crate::common::database::DbPool;
+use crate::features::album::models::Album;
+use crate::features::content::models::Content;
+use crate::features::episode::models::Episode;
+use crate::schema::{albums, category, contents, creators, episodes};
+
+pub struct Repository;
+
+impl Repository {
+    pub async fn get_albums(pool: &DbPool) -> QueryResult<Vec<Album>> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        albums::table
+            .filter(albums::deleted_at.is_null())
+            .select(Album::as_select())
+            .load(&mut conn)
+    }
+
+    pub async fn get_episodes(pool: &DbPool) -> QueryResult<Vec<Episode>> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        episodes::table
+            .filter(episodes::deleted_at.is_null())
+            .select(Episode::as_select())
+            .load(&mut conn)
+    }
+
+    pub async fn get_category_icon_urls(pool: &DbPool) -> QueryResult<Vec<String>> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        category::table
+            .filter(category::icon_url.is_not_null())
+            .select(category::icon_url.assume_not_null())
+            .load(&mut conn)
+    }
+
+    pub async fn get_creator_avatar_urls(pool: &DbPool) -> QueryResult<Vec<String>> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        creators::table
+            .filter(creators::avatar_url.is_not_null())
+            .select(creators::avatar_url.assume_not_null())
+            .load(&mut conn)
+    }
+
+    pub async fn get_contents(pool: &DbPool) -> QueryResult<Vec<Content>> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        contents::table
+            .filter(contents::deleted_at.is_null())
+            .select(Content::as_select())
+            .load(&mut conn)
+    }
+
+    pub async fn set_album_broken_at(
+        pool: &DbPool,
+        album_id: i32,
+        broken_at: Option<NaiveDateTime>,
+    ) -> QueryResult<usize> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        diesel::update(albums::table.filter(albums::id.eq(album_id)))
+            .set(albums::broken_at.eq(broken_at))
+            .execute(&mut conn)
+    }
+
+    pub async fn set_episode_broken_at(
+        pool: &DbPool,
+        episode_uuid: String,
+        broken_at: Option<NaiveDateTime>,
+    ) -> QueryResult<usize> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        diesel::update(episodes::table.filter(episodes::uuid.eq(episode_uuid)))
+            .set(episodes::broken_at.eq(broken_at))
+            .execute(&mut conn)
+    }
+
+    pub async fn set_content_broken_at(
+        pool: &DbPool,
+        content_uuid: String,
+        broken_at: Option<NaiveDateTime>,
+    ) -> QueryResult<usize> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        diesel::update(contents::table.filter(contents::uuid.eq(content_uuid)))
+            .set(contents::broken_at.eq(broken_at))
+            .execute(&mut conn)
+    }
+}
@@ -0,0 +1,514 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use actix_web::web;
+use chrono::{NaiveDateTime, Utc};
+
+use crate::common::config;
+use crate::common::database::DbPool;
+use crate::common::ne_parse::NEParse;
+use crate::common::utils::{get_data_directory, get_directory_from_file_path};
+use crate::features::integrity::models::{BrokenItemResponse, IntegrityError, IntegrityReport};
+use crate::features::integrity::repository::Repository;
+use crate::features::webhooks;
+use crate::features::webhooks::models::WebhookEvent;
+
+static SCAN_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Holds `SCAN_RUNNING` for one scan and releases it when dropped, also when the scan panics
+struct ScanGuard;
+
+impl ScanGuard {
+    fn acquire() -> Option<Self> {
+        if SCAN_RUNNING.swap(true, Ordering::SeqCst) {
+            None
+        } else {
+            Some(ScanGuard)
+        }
+    }
+}
+
+impl Drop for ScanGuard {
+    fn drop(&mut self) {
+        SCAN_RUNNING.store(false, Ordering::SeqCst);
+    }
+}
+
+pub struct Service;
+
+impl Service {
+    /// Verify every stored file and external link, update `broken_at` and list orphaned files
+    pub async fn scan(pool: &DbPool) -> Result<IntegrityReport, IntegrityError> {
+        let Some(_guard) = ScanGuard::acquire() else {
+            return Err(IntegrityError::AlreadyRunning);
+        };
+        run_scan(pool).await
+    }
+}
+
+async fn run_scan(pool: &DbPool) -> Result<IntegrityReport, IntegrityError> {
+    let integrity_config = &config::get().integrity;
+    let now = Utc::now().naive_utc();
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(integrity_config.probe_timeout_seconds))
+        .build()
+        .ok();
+    let mut report = IntegrityReport {
+        started_at: NEParse::opt_naive_datetime_to_utc_opt_string(Some(now)),
+        ..IntegrityReport::default()
+    };
+    let mut known_files: HashSet<String> = HashSet::new();
+    // HLS output of ready episodes, every playlist and segment under these belongs to the episode
+    let mut known_directories: Vec<String> = Vec::new();
+    // Items which were healthy before this scan, they are announced to webhooks
+    let mut newly_broken: HashSet<String> = HashSet::new();
+
+    for album in Repository::get_albums(pool).await? {
+        report.checked_albums += 1;
+        known_files.insert(normalize_path(&album.url));
+        let mut failure = check_local_file(&album.url, album.bytes, &album.content_type)
+            .await
+            .err()
+            .map(|reason| (album.url.clone(), reason));
+        for image in album
+            .images
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+        {
+            known_files.insert(normalize_path(image));
+            if failure.is_none() && !Path::new(image).is_file() {
+                failure = Some((image.to_string(), String::from("File not found")));
+            }
+        }
+        let broken_at = next_broken_at(
+            &mut report,
+            album.broken_at,
+            now,
+            failure,
+            "album",
+            &album.uuid,
+        );
+        if broken_at != album.broken_at {
+            Repository::set_album_broken_at(pool, album.id, broken_at).await?;
+            if album.broken_at.is_none() {
+                newly_broken.insert(album.uuid.clone());
+            }
+        }
+    }
+
+    for episode in Repository::get_episodes(pool).await? {
+        report.checked_episodes += 1;
+        let mut failure = None;
+        if let Some(poster_url) = &episode.poster_url {
+            known_files.insert(normalize_path(poster_url));
+        }
+        if let (Some("ready"), Some(hls_url)) = (episode.hls_status.as_deref(), &episode.hls_url) {
+            known_files.insert(normalize_path(hls_url));
+            if let Some(hls_directory) = get_directory_from_file_path(hls_url) {
+                known_directories.push(format!("{}/", normalize_path(hls_directory)));
+            }
+        }
+        if let Some(url) = &episode.url {
+            known_files.insert(normalize_path(url));
+            let content_type = episode.content_type.clone().unwrap_or_default();
+            if let Err(reason) = check_local_file(url, episode.bytes, &content_type).await {
+                failure = Some((url.clone(), reason));
+            }
+        }
+        if let (None, Some(file_url), Some(client)) = (&failure, &episode.file_url, &client) {
+            if integrity_config.probe_external && is_external(file_url) {
+                if let Err(reason) = probe_url(client, file_url).await {
+                    failure = Some((file_url.clone(), reason));
+                }
+            }
+        }
+        let broken_at = next_broken_at(
+            &mut report,
+            episode.broken_at,
+            now,
+            failure,
+            "episode",
+            &episode.uuid,
+        );
+        if broken_at != episode.broken_at {
+            Repository::set_episode_broken_at(pool, episode.uuid.clone(), broken_at).await?;
+            if episode.broken_at.is_none() {
+                newly_broken.insert(episode.uuid.clone());
+            }
+        }
+    }
+
+    for content in Repository::get_contents(pool).await? {
+        report.checked_contents += 1;
+        known_files.insert(normalize_path(&content.url));
+        let failure = check_local_file(&content.url, content.bytes, &content.content_type)
+            .await
+            .err()
+            .map(|reason| (content.url.clone(), reason));
+        let broken_at = next_broken_at(
+            &mut report,
+            content.broken_at,
+            now,
+            failure,
+            "content",
+            &content.uuid,
+        );
+        if broken_at != content.broken_at {
+            Repository::set_content_broken_at(pool, content.uuid.clone(), broken_at).await?;
+            if content.broken_at.is_none() {
+                newly_broken.insert(content.uuid.clone());
+            }
+        }
+    }
+
+    for icon_url in Repository::get_category_icon_urls(pool).await? {
+        known_files.insert(normalize_path(&icon_url));
+    }
+    for avatar_url in Repository::get_creator_avatar_urls(pool).await? {
+        known_files.insert(normalize_path(&avatar_url));
+    }
+
+    let data_dir = get_data_directory();
+    let files = web::block(move || list_files(&data_dir))
+        .await
+        .unwrap_or_default();
+    report.orphaned_files = files
+        .into_iter()
+        .filter(|file| {
+            let file = normalize_path(file);
+            !known_files.contains(&file)
+                && !known_directories
+                    .iter()
+                    .any(|directory| file.starts_with(directory.as_str()))
+        })
+        .collect();
+    report.orphaned_files.sort();
+    report.finished_at =
+        NEParse::opt_naive_datetime_to_utc_opt_string(Some(Utc::now().naive_utc()));
+    for item in report
+        .broken
+        .iter()
+        .filter(|item| newly_broken.contains(&item.uuid))
+    {
+        webhooks::services::Service::dispatch(
+            pool,
+            WebhookEvent::ItemBroken,
+            serde_json::to_value(item).unwrap_or_default(),
+        )
+        .await;
+    }
+    Ok(report)
+}
+
+/// Record the outcome of a check and return the `broken_at` value the row should have
+fn next_broken_at(
+    report: &mut IntegrityReport,
+    broken_at: Option<NaiveDateTime>,
+    now: NaiveDateTime,
+    failure: Option<(String, String)>,
+    entity_type: &str,
+    uuid: &str,
+) -> Option<NaiveDateTime> {
+    match failure {
+        Some((location, reason)) => {
+            report.broken.push(BrokenItemResponse {
+                entity_type: entity_type.to_string(),
+                uuid: uuid.to_string(),
+                location,
+                reason,
+            });
+            // Keep the time the item was first found broken
+            broken_at.or(Some(now))
+        }
+        None => {
+            if broken_at.is_some() {
+                report.repaired += 1;
+            }
+            None
+        }
+    }
+}
+
+/// Check that a stored file exists, has the recorded size and decodes when it is an image
+async fn check_local_file(
+    path: &str,
+    expected_bytes: i32,
+    content_type: &str,
+) -> Result<(), String> {
+    let path = path.to_string();
+    let decode_image = content_type.starts_with("image/");
+    web::block(move || {
+        let metadata = fs::metadata(&path).map_err(|_| String::from("File not found"))?;
+        if !metadata.is_file() {
+            return Err(String::from("Not a file"));
+        }
+        if expected_bytes > 0 && metadata.len() != expected_bytes as u64 {
+            return Err(format!(
+                "Size mismatch: expected {} bytes, found {} bytes",
+                expected_bytes,
+                metadata.len()
+            ));
+        }
+        if decode_image {
+            image::open(&path).map_err(|e| format!("Cannot decode image: {}", e))?;
+        }
+        Ok(())
+    })
+    .await
+    .unwrap_or_else(|e| Err(e.to_string()))
+}
+
+fn is_external(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+/// HEAD an external URL, through `probe_base_url` when configured
+async fn probe_url(client: &reqwest::Client, url: &str) -> Result<(), String> {
+    let target = match &config::get().integrity.probe_base_url {
+        Some(base_url) => {
+            let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+            let mut target = format!("{}{}", base_url.trim_end_matches('/'), parsed.path());
+            if let Some(query) = parsed.query() {
+                target.push('?');
+                target.push_str(query);
+            }
+            target
+        }
+        None => url.to_string(),
+    };
+    match client.head(&target).send().await {
+        Ok(response) if response.status().is_success() => Ok(()),
+        Ok(response) => Err(format!("HEAD returned {}", response.status())),
+        Err(e) => Err(format!("HEAD failed: {}", e)),
+    }
+}
+
+fn list_files(dir: &str) -> Vec<String> {
+    let mut files = Vec::new();
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = format!("{}/{}", dir, entry.file_name().to_string_lossy());
+            match entry.file_type() {
+                Ok(file_type) if file_type.is_dir() => files.extend(list_files(&path)),
+                Ok(file_type) if file_type.is_file() => files.push(path),
+                _ => {}
+            }
+        }
+    }
+    files
+}
+
+fn normalize_path(path: &str) -> String {
+    path.trim().trim_start_matches("./").replace("//", "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use diesel::prelude::*;
+    use diesel::sql_types::{Integer, Nullable, Text};
+
+    use super::*;
+    use crate::common::test_support;
+    use crate::schema::{contents, episodes};
+
+    fn insert_episode(
+        pool: &DbPool,
+        url: Option<&str>,
+        file_url: Option<&str>,
+        bytes: i32,
+    ) -> String {
+        let uuid = uuid::Uuid::new_v4().to_string();
+        let mut conn = pool.get().unwrap();
+        diesel::sql_query(
+            "INSERT INTO episodes (album_id, uuid, title, url, file_url, content_type, width, \
+             height, bytes) VALUES (1, ?, 'Episode', ?, ?, 'video/mp4', 0, 0, ?)",
+        )
+        .bind::<Text, _>(&uuid)
+        .bind::<Nullable<Text>, _>(url)
+        .bind::<Nullable<Text>, _>(file_url)
+        .bind::<Integer, _>(bytes)
+        .execute(&mut conn)
+        .unwrap();
+        uuid
+    }
+
+    fn set_hls(pool: &DbPool, uuid: &str, status: &str, hls_url: &str) {
+        diesel::update(episodes::table.filter(episodes::uuid.eq(uuid)))
+            .set((
+                episodes::hls_status.eq(status),
+                episodes::hls_url.eq(hls_url),
+            ))
+            .execute(&mut pool.get().unwrap())
+            .unwrap();
+    }
+
+    fn insert_content(pool: &DbPool, url: &str, bytes: i32) -> String {
+        let uuid = uuid::Uuid::new_v4().to_string();
+        let mut conn = pool.get().unwrap();
+        diesel::sql_query(
+            "INSERT INTO contents (episode_id, uuid, index_no, url, content_type, width, height, \
+             bytes) VALUES (1, ?, 1, ?, 'application/octet-stream', 0, 0, ?)",
+        )
+        .bind::<Text, _>(&uuid)
+        .bind::<Text, _>(url)
+        .bind::<Integer, _>(bytes)
+        .execute(&mut conn)
+        .unwrap();
+        uuid
+    }
+
+    fn episode_broken_at(pool: &DbPool, uuid: &str) -> Option<NaiveDateTime> {
+        episodes::table
+            .filter(episodes::uuid.eq(uuid))
+            .select(episodes::broken_at)
+            .first(&mut pool.get().unwrap())
+            .unwrap()
+    }
+
+    fn content_broken_at(pool: &DbPool, uuid: &str) -> Option<NaiveDateTime> {
+        contents::table
+            .filter(contents::uuid.eq(uuid))
+            .select(contents::broken_at)
+            .first(&mut pool.get().unwrap())
+            .unwrap()
+    }
+
+    fn broken_reason(report: &IntegrityReport, uuid: &str) -> Option<String> {
+        report
+            .broken
+            .iter()
+            .find(|item| item.uuid == uuid)
+            .map(|item| item.reason.clone())
+    }
+
+    #[actix_web::test]
+    async fn scan_marks_missing_and_truncated_files_and_clears_repaired_ones() {
+        let _serial = test_support::serial().await;
+        let pool = test_support::test_pool();
+        let dir = test_support::data_subdir();
+        let episode_path = dir.join("episode.mp4").to_string_lossy().to_string();
+        let content_path = dir.join("page.bin").to_string_lossy().to_string();
+        fs::write(&episode_path, [0u8; 5]).unwrap();
+        let episode_uuid = insert_episode(&pool, Some(&episode_path), None, 10);
+        let content_uuid = insert_content(&pool, &content_path, 4);
+
+        let report = Service::scan(&pool).await.unwrap();
+        assert_eq!(
+            broken_reason(&report, &episode_uuid).as_deref(),
+            Some("Size mismatch: expected 10 bytes, found 5 bytes")
+        );
+        assert_eq!(
+            broken_reason(&report, &content_uuid).as_deref(),
+            Some("File not found")
+        );
+        let first_broken_at = episode_broken_at(&pool, &episode_uuid);
+        assert!(first_broken_at.is_some());
+        assert!(content_broken_at(&pool, &content_uuid).is_some());
+
+        // A second scan keeps the time the item was first found broken
+        Service::scan(&pool).await.unwrap();
+        assert_eq!(episode_broken_at(&pool, &episode_uuid), first_broken_at);
+
+        fs::write(&episode_path, [0u8; 10]).unwrap();
+        fs::write(&content_path, [0u8; 4]).unwrap();
+        let report = Service::scan(&pool).await.unwrap();
+        assert!(report.broken.is_empty());
+        assert_eq!(report.repaired, 2);
+        assert_eq!(episode_broken_at(&pool, &episode_uuid), None);
+        assert_eq!(content_broken_at(&pool, &content_uuid), None);
+    }
+
+    #[actix_web::test]
+    async fn scan_marks_external_urls_answering_404_broken() {
+        let _serial = test_support::serial().await;
+        let pool = test_support::test_pool();
+        let stand_in = test_support::stand_in();
+        let missing_path = format!("/{}/missing.mp4", uuid::Uuid::new_v4());
+        let healthy_path = format!("/{}/healthy.mp4", uuid::Uuid::new_v4());
+        stand_in.set_status(&missing_path, 404);
+        let missing_uuid = insert_episode(
+            &pool,
+            None,
+            Some(&format!("https://cdn.example.com{}", missing_path)),
+            0,
+        );
+        let healthy_uuid = insert_episode(
+            &pool,
+            None,
+            Some(&format!("https://cdn.example.com{}", healthy_path)),
+            0,
+        );
+
+        let report = Service::scan(&pool).await.unwrap();
+        assert_eq!(
+            broken_reason(&report, &missing_uuid).as_deref(),
+            Some("HEAD returned 404 Not Found")
+        );
+        assert!(episode_broken_at(&pool, &missing_uuid).is_some());
+        assert_eq!(broken_reason(&report, &healthy_uuid), None);
+        assert_eq!(episode_broken_at(&pool, &healthy_uuid), None);
+        let requests = stand_in.requests(&missing_path);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "HEAD");
+    }
+
+    #[actix_web::test]
+    async fn scan_lists_files_without_a_row_as_orphaned() {
+        let _serial = test_support::serial().await;
+        let pool = test_support::test_pool();
+        let dir = test_support::data_subdir();
+        let known_path = dir.join("page.bin").to_string_lossy().to_string();
+        let orphan_path = dir.join("orphan.bin").to_string_lossy().to_string();
+        fs::write(&known_path, [0u8; 4]).unwrap();
+        fs::write(&orphan_path, [0u8; 4]).unwrap();
+        insert_content(&pool, &known_path, 4);
+
+        let report = Service::scan(&pool).await.unwrap();
+        assert!(report.orphaned_files.contains(&orphan_path));
+        assert!(!report.orphaned_files.contains(&known_path));
+    }
+
+    #[actix_web::test]
+    async fn scan_keeps_hls_output_of_ready_episodes_out_of_orphaned_files() {
+        let _serial = test_support::serial().await;
+        let pool = test_support::test_pool();
+        let ready_dir = test_support::data_subdir();
+        let failed_dir = test_support::data_subdir();
+        let mut hls_files = vec![];
+        for dir in [&ready_dir, &failed_dir] {
+            fs::write(dir.join("episode.mp4"), [0u8; 4]).unwrap();
+            fs::create_dir_all(dir.join("hls/720p")).unwrap();
+            for file in ["master.m3u8", "720p/index.m3u8", "720p/segment_000.ts"] {
+                let path = dir.join("hls").join(file);
+                fs::write(&path, [0u8; 4]).unwrap();
+                hls_files.push(path.to_string_lossy().to_string());
+            }
+            let episode_path = dir.join("episode.mp4").to_string_lossy().to_string();
+            let episode_uuid = insert_episode(&pool, Some(&episode_path), None, 4);
+            let status = if dir == &ready_dir { "ready" } else { "failed" };
+            let master_path = dir.join("hls/master.m3u8").to_string_lossy().to_string();
+            set_hls(&pool, &episode_uuid, status, &master_path);
+        }
+
+        let report = Service::scan(&pool).await.unwrap();
+        let (ready_files, failed_files) = hls_files.split_at(3);
+        for file in ready_files {
+            assert!(
+                !report.orphaned_files.contains(file),
+                "{} is orphaned",
+                file
+            );
+        }
+        for file in failed_files {
+            assert!(
+                report.orphaned_files.contains(file),
+                "{} is not orphaned",
+                file
+            );
+        }
+    }
+}
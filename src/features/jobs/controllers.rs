@@ -0,0 +1,193 @@
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+
+use crate::common::database::DbPool;
+use crate::common::enums::Role::Admin;
+use crate::common::models::response_message::ResponseMessage;
+use crate::features::jobs::models::{GetJobsRequest, JobError, JobStatus};
+use crate::features::jobs::services::Service;
+use crate::features::{audit_mutation, check_role};
+
+/// Get Jobs
+///
+/// Get background jobs, newest first
+#[utoipa::path(
+    get,
+    path = "/jobs",
+    params(GetJobsRequest),
+    responses(
+        (status = 200, description = "Request successfully", body = ResponseDataJob),
+        (status = 400, description = "Bad Request", body = ResponseMessage),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Jobs",
+)]
+#[get("/jobs")]
+pub async fn get_jobs(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    req: web::Query<GetJobsRequest>,
+) -> impl Responder {
+    if check_role(http_request) == Admin {
+        if let Some(Err(e)) = req
+            .status
+            .as_deref()
+            .filter(|s| !s.is_empty())
+            .map(str::parse::<JobStatus>)
+        {
+            return HttpResponse::BadRequest().json(ResponseMessage { message: e });
+        }
+        match Service::get_jobs(&pool, req.into_inner()).await {
+            Ok(data) => HttpResponse::Ok().json(data),
+            Err(e) => job_error_response("get", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Get Job
+///
+/// Get a background job with its last error
+#[utoipa::path(
+    get,
+    path = "/jobs/{job_uuid}",
+    params(
+        ("job_uuid" = String, Path, description = "Job UUID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e")
+    ),
+    responses(
+        (status = 200, description = "Request successfully", body = JobResponse),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 404, description = "Job Not Found", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Jobs",
+)]
+#[get("/{job_uuid}")]
+pub async fn get_job(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+) -> impl Responder {
+    if check_role(http_request) == Admin {
+        match Service::get_job(&pool, path.into_inner()).await {
+            Ok(job) => HttpResponse::Ok().json(job),
+            Err(e) => job_error_response("get", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Retry Job
+///
+/// Queue a failed or cancelled job again with a fresh set of attempts
+#[utoipa::path(
+    post,
+    path = "/jobs/{job_uuid}/retry",
+    params(
+        ("job_uuid" = String, Path, description = "Job UUID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e")
+    ),
+    responses(
+        (status = 200, description = "Job queued", body = JobResponse),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 404, description = "Job Not Found", body = ResponseMessage),
+        (status = 409, description = "Job is not failed or cancelled", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Jobs",
+)]
+#[post("/{job_uuid}/retry")]
+pub async fn retry_job(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+) -> impl Responder {
+    if check_role(http_request.clone()) == Admin {
+        let job_uuid = path.into_inner();
+        match Service::retry_job(&pool, job_uuid.clone()).await {
+            Ok(job) => {
+                audit_mutation(&pool, &http_request, "retry", "job", job_uuid, None, None).await;
+                HttpResponse::Ok().json(job)
+            }
+            Err(e) => job_error_response("retry", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Cancel Job
+///
+/// Cancel a pending or failed job, running jobs cannot be interrupted
+#[utoipa::path(
+    post,
+    path = "/jobs/{job_uuid}/cancel",
+    params(
+        ("job_uuid" = String, Path, description = "Job UUID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e")
+    ),
+    responses(
+        (status = 200, description = "Job cancelled", body = JobResponse),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 404, description = "Job Not Found", body = ResponseMessage),
+        (status = 409, description = "Job is running or already finished", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Jobs",
+)]
+#[post("/{job_uuid}/cancel")]
+pub async fn cancel_job(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+) -> impl Responder {
+    if check_role(http_request.clone()) == Admin {
+        let job_uuid = path.into_inner();
+        match Service::cancel_job(&pool, job_uuid.clone()).await {
+            Ok(job) => {
+                audit_mutation(&pool, &http_request, "cancel", "job", job_uuid, None, None).await;
+                HttpResponse::Ok().json(job)
+            }
+            Err(e) => job_error_response("cancel", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+fn job_error_response(action: &str, e: JobError) -> HttpResponse {
+    match e {
+        JobError::NotFound => HttpResponse::NotFound().json(ResponseMessage {
+            message: e.to_string(),
+        }),
+        JobError::InvalidStatus(_) => HttpResponse::Conflict().json(ResponseMessage {
+            message: e.to_string(),
+        }),
+        JobError::Database(_) => {
+            println!("Failed to {} job: {}", action, e);
+            HttpResponse::InternalServerError().json(ResponseMessage {
+                message: String::from("Internal Server Error"),
+            })
+        }
+    }
+}
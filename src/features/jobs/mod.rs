@@ -0,0 +1,20 @@
+use actix_web::web;
+
+use crate::features::jobs::controllers::*;
+
+pub mod controllers;
+pub mod models;
+pub mod registry;
+pub mod repository;
+pub mod services;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_jobs);
+
+    cfg.service(
+        web::scope("/jobs")
+            .service(get_job)
+            .service(retry_job)
+            .service(cancel_job),
+    );
+}
@@ -0,0 +1,179 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::NaiveDateTime;
+use diesel::{Insertable, Queryable, Selectable};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::common::ne_parse::NEParse;
+use crate::schema::jobs;
+
+/// Most jobs returned in one page
+pub const MAX_JOBS_LIMIT: i64 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Pending => "pending",
+            JobStatus::Running => "running",
+            JobStatus::Succeeded => "succeeded",
+            JobStatus::Failed => "failed",
+            JobStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl FromStr for JobStatus {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "pending" => Ok(JobStatus::Pending),
+            "running" => Ok(JobStatus::Running),
+            "succeeded" => Ok(JobStatus::Succeeded),
+            "failed" => Ok(JobStatus::Failed),
+            "cancelled" => Ok(JobStatus::Cancelled),
+            _ => Err(format!(
+                "Invalid status {:?}, expected pending, running, succeeded, failed or cancelled",
+                value
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Queryable, Selectable, Clone, PartialEq, Eq)]
+#[diesel(table_name = jobs)]
+pub struct Job {
+    pub id: Option<i32>,
+    pub uuid: String,
+    pub job_type: String,
+    pub payload: String,
+    pub status: String,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub last_error: Option<String>,
+    pub run_at: NaiveDateTime,
+    pub started_at: Option<NaiveDateTime>,
+    pub finished_at: Option<NaiveDateTime>,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = jobs)]
+pub struct NewJob {
+    pub uuid: String,
+    pub job_type: String,
+    pub payload: String,
+    pub status: String,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub run_at: NaiveDateTime,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, PartialEq)]
+pub struct JobResponse {
+    pub uuid: String,
+    /// Handler name, e.g. episode.package_hls
+    pub job_type: String,
+    #[schema(value_type = Object)]
+    pub payload: Value,
+    /// pending, running, succeeded, failed or cancelled
+    pub status: String,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    /// Error of the last failed attempt
+    pub last_error: Option<String>,
+    /// Earliest time the job is picked up, pushed back on every retry
+    pub run_at: Option<String>,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+impl JobResponse {
+    pub fn from_job(job: Job) -> Self {
+        JobResponse {
+            uuid: job.uuid,
+            job_type: job.job_type,
+            payload: serde_json::from_str(&job.payload).unwrap_or(Value::Null),
+            status: job.status,
+            attempts: job.attempts,
+            max_attempts: job.max_attempts,
+            last_error: job.last_error,
+            run_at: NEParse::opt_naive_datetime_to_utc_opt_string(Some(job.run_at)),
+            started_at: NEParse::opt_naive_datetime_to_utc_opt_string(job.started_at),
+            finished_at: NEParse::opt_naive_datetime_to_utc_opt_string(job.finished_at),
+            created_at: NEParse::opt_naive_datetime_to_utc_opt_string(job.created_at),
+            updated_at: NEParse::opt_naive_datetime_to_utc_opt_string(job.updated_at),
+        }
+    }
+
+    pub fn from_jobs(jobs: Vec<Job>) -> Vec<Self> {
+        jobs.into_iter().map(Self::from_job).collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GetJobsRequest {
+    /// Filter status (pending, running, succeeded, failed, cancelled)
+    pub status: Option<String>,
+    /// Filter job type (Exact Value)
+    pub job_type: Option<String>,
+    /// Filter offset
+    pub offset: Option<i64>,
+    /// Filter limit, 1 to 500, default 50
+    pub limit: Option<i64>,
+}
+
+impl GetJobsRequest {
+    /// Requested offset, never negative
+    pub fn offset(&self) -> i64 {
+        self.offset.unwrap_or(0).max(0)
+    }
+
+    /// Requested limit kept within 1 to [`MAX_JOBS_LIMIT`], SQLite reads a negative limit as no
+    /// limit
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(50).clamp(1, MAX_JOBS_LIMIT)
+    }
+}
+
+#[derive(Debug)]
+pub enum JobError {
+    NotFound,
+    /// The job is in a status which does not allow the requested change
+    InvalidStatus(String),
+    Database(diesel::result::Error),
+}
+
+impl fmt::Display for JobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobError::NotFound => write!(f, "Job not found"),
+            JobError::InvalidStatus(message) => write!(f, "{}", message),
+            JobError::Database(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<diesel::result::Error> for JobError {
+    fn from(e: diesel::result::Error) -> Self {
+        JobError::Database(e)
+    }
+}
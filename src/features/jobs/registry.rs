@@ -0,0 +1,40 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use futures_util::future::LocalBoxFuture;
+use serde_json::Value;
+
+use crate::common::database::DbPool;
+
+/// Runs one type of background job, feature modules register their handlers in `register_jobs`
+pub trait JobHandler {
+    /// Value stored in `jobs.job_type`, namespaced by feature, e.g. `episode.package_hls`
+    fn job_type(&self) -> &'static str;
+
+    /// An `Err` schedules a retry with backoff until `max_attempts` is reached
+    fn run(&self, pool: DbPool, payload: Value) -> LocalBoxFuture<'static, Result<(), String>>;
+}
+
+#[derive(Default, Clone)]
+pub struct JobRegistry {
+    handlers: HashMap<&'static str, Rc<dyn JobHandler>>,
+}
+
+impl JobRegistry {
+    pub fn register<H: JobHandler + 'static>(&mut self, handler: H) {
+        let job_type = handler.job_type();
+        if self.handlers.insert(job_type, Rc::new(handler)).is_some() {
+            eprintln!("Job handler {} was registered twice", job_type);
+        }
+    }
+
+    pub fn get(&self, job_type: &str) -> Option<Rc<dyn JobHandler>> {
+        self.handlers.get(job_type).cloned()
+    }
+
+    pub fn job_types(&self) -> Vec<&'static str> {
+        let mut job_types: Vec<&'static str> = self.handlers.keys().copied().collect();
+        job_types.sort();
+        job_types
+    }
+}
@@ -0,0 +1,206 @@
+use chrono::{NaiveDateTime, Utc};
+use diesel::dsl::count_star;
+use diesel::prelude::*;
+
+use crate::common::database::DbPool;
+use crate::features::jobs::models::{Job, JobStatus, NewJob};
+use crate::schema::jobs;
+
+pub struct Repository;
+
+impl Repository {
+    pub async fn create_job(pool: &DbPool, new_job: NewJob) -> QueryResult<Job> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        let job_uuid = new_job.uuid.clone();
+        diesel::insert_into(jobs::table)
+            .values(&new_job)
+            .execute(&mut conn)?;
+        jobs::table
+            .filter(jobs::uuid.eq(job_uuid))
+            .select(Job::as_select())
+            .first(&mut conn)
+    }
+
+    /// A pending or running job with the same type and payload
+    pub async fn get_active_job(
+        pool: &DbPool,
+        job_type: &str,
+        payload: &str,
+    ) -> QueryResult<Option<Job>> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        jobs::table
+            .filter(jobs::job_type.eq(job_type))
+            .filter(jobs::payload.eq(payload))
+            .filter(jobs::status.eq_any([JobStatus::Pending.as_str(), JobStatus::Running.as_str()]))
+            .select(Job::as_select())
+            .first(&mut conn)
+            .optional()
+    }
+
+    pub async fn get_job_by_uuid(pool: &DbPool, job_uuid: String) -> QueryResult<Job> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        jobs::table
+            .filter(jobs::uuid.eq(job_uuid))
+            .select(Job::as_select())
+            .first(&mut conn)
+    }
+
+    pub async fn get_jobs(
+        pool: &DbPool,
+        status: Option<String>,
+        job_type: Option<String>,
+        offset: i64,
+        limit: i64,
+    ) -> QueryResult<(Vec<Job>, i64)> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        let mut query = jobs::table.into_boxed();
+        let mut count_query = jobs::table.into_boxed();
+
+        if let Some(_status) = status {
+            query = query.filter(jobs::status.eq(_status.clone()));
+            count_query = count_query.filter(jobs::status.eq(_status));
+        }
+        if let Some(_job_type) = job_type {
+            query = query.filter(jobs::job_type.eq(_job_type.clone()));
+            count_query = count_query.filter(jobs::job_type.eq(_job_type));
+        }
+
+        let total = count_query.select(count_star()).first::<i64>(&mut conn)?;
+        let results = query
+            .order(jobs::id.desc())
+            .offset(offset)
+            .limit(limit)
+            .select(Job::as_select())
+            .load(&mut conn)?;
+        Ok((results, total))
+    }
+
+    /// Take the next due pending job and mark it running, the write lock keeps workers from
+    /// claiming the same row
+    pub async fn claim_next_job(pool: &DbPool) -> QueryResult<Option<Job>> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        conn.immediate_transaction(|conn| {
+            let now = Utc::now().naive_utc();
+            let job = jobs::table
+                .filter(jobs::status.eq(JobStatus::Pending.as_str()))
+                .filter(jobs::run_at.le(now))
+                .order((jobs::run_at.asc(), jobs::id.asc()))
+                .select(Job::as_select())
+                .first(conn)
+                .optional()?;
+            let Some(mut job) = job else {
+                return Ok(None);
+            };
+            job.status = JobStatus::Running.as_str().to_string();
+            job.attempts += 1;
+            job.started_at = Some(now);
+            job.updated_at = Some(now);
+            diesel::update(jobs::table.filter(jobs::id.eq(job.id)))
+                .set((
+                    jobs::status.eq(&job.status),
+                    jobs::attempts.eq(job.attempts),
+                    jobs::started_at.eq(job.started_at),
+                    jobs::updated_at.eq(job.updated_at),
+                ))
+                .execute(conn)?;
+            Ok(Some(job))
+        })
+    }
+
+    pub async fn finish_job(
+        pool: &DbPool,
+        job_id: Option<i32>,
+        status: JobStatus,
+        last_error: Option<String>,
+    ) -> QueryResult<usize> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        let now = Utc::now().naive_utc();
+        diesel::update(jobs::table.filter(jobs::id.eq(job_id)))
+            .set((
+                jobs::status.eq(status.as_str()),
+                jobs::last_error.eq(last_error),
+                jobs::finished_at.eq(Some(now)),
+                jobs::updated_at.eq(Some(now)),
+            ))
+            .execute(&mut conn)
+    }
+
+    pub async fn schedule_retry(
+        pool: &DbPool,
+        job_id: Option<i32>,
+        run_at: NaiveDateTime,
+        last_error: String,
+    ) -> QueryResult<usize> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        diesel::update(jobs::table.filter(jobs::id.eq(job_id)))
+            .set((
+                jobs::status.eq(JobStatus::Pending.as_str()),
+                jobs::last_error.eq(Some(last_error)),
+                jobs::run_at.eq(run_at),
+                jobs::updated_at.eq(Some(Utc::now().naive_utc())),
+            ))
+            .execute(&mut conn)
+    }
+
+    /// Move a job from one of `from` to `to`, returns 0 when the status changed in the meantime
+    pub async fn update_job_status(
+        pool: &DbPool,
+        job_uuid: String,
+        from: &[JobStatus],
+        to: JobStatus,
+        reset_attempts: bool,
+    ) -> QueryResult<usize> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        let now = Utc::now().naive_utc();
+        let from: Vec<&str> = from.iter().map(JobStatus::as_str).collect();
+        let target = jobs::table
+            .filter(jobs::uuid.eq(job_uuid))
+            .filter(jobs::status.eq_any(from));
+        if reset_attempts {
+            diesel::update(target)
+                .set((
+                    jobs::status.eq(to.as_str()),
+                    jobs::attempts.eq(0),
+                    jobs::run_at.eq(now),
+                    jobs::finished_at.eq(None::<NaiveDateTime>),
+                    jobs::updated_at.eq(Some(now)),
+                ))
+                .execute(&mut conn)
+        } else {
+            diesel::update(target)
+                .set((
+                    jobs::status.eq(to.as_str()),
+                    jobs::finished_at.eq(Some(now)),
+                    jobs::updated_at.eq(Some(now)),
+                ))
+                .execute(&mut conn)
+        }
+    }
+
+    /// Jobs left running by a previous process are queued again
+    pub async fn requeue_running_jobs(pool: &DbPool) -> QueryResult<usize> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        diesel::update(jobs::table.filter(jobs::status.eq(JobStatus::Running.as_str())))
+            .set((
+                jobs::status.eq(JobStatus::Pending.as_str()),
+                jobs::updated_at.eq(Some(Utc::now().naive_utc())),
+            ))
+            .execute(&mut conn)
+    }
+
+    pub async fn delete_finished_jobs(
+        pool: &DbPool,
+        finished_before: NaiveDateTime,
+    ) -> QueryResult<usize> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        diesel::delete(
+            jobs::table
+                .filter(
+                    jobs::status
+                        .eq_any([JobStatus::Succeeded.as_str(), JobStatus::Cancelled.as_str()]),
+                )
+                .filter(jobs::finished_at.lt(finished_before)),
+        )
+        .execute(&mut conn)
+    }
+}
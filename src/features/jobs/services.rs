@@ -0,0 +1,264 @@
+use std::panic::AssertUnwindSafe;
+use std::rc::Rc;
+use std::time::Duration;
+
+use chrono::Utc;
+use futures_util::FutureExt;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::common::config;
+use crate::common::database::DbPool;
+use crate::common::models::response_data::ResponseData;
+use crate::features::jobs::models::{
+    GetJobsRequest, Job, JobError, JobResponse, JobStatus, NewJob,
+};
+use crate::features::jobs::registry::JobRegistry;
+use crate::features::jobs::repository::Repository;
+
+pub struct Service;
+
+impl Service {
+    /// Queue a job, it runs on the next free worker
+    pub async fn enqueue(
+        pool: &DbPool,
+        job_type: &str,
+        payload: Value,
+    ) -> Result<Job, diesel::result::Error> {
+        let now = Utc::now().naive_utc();
+        let new_job = NewJob {
+            uuid: Uuid::new_v4().to_string(),
+            job_type: job_type.to_string(),
+            payload: payload.to_string(),
+            status: JobStatus::Pending.as_str().to_string(),
+            attempts: 0,
+            max_attempts: config::get().jobs.max_attempts,
+            run_at: now,
+            created_at: Some(now),
+            updated_at: Some(now),
+        };
+        Repository::create_job(pool, new_job).await
+    }
+
+    /// Queue a job unless one with the same type and payload is still pending or running
+    pub async fn enqueue_unique(
+        pool: &DbPool,
+        job_type: &str,
+        payload: Value,
+    ) -> Result<Job, diesel::result::Error> {
+        match Repository::get_active_job(pool, job_type, &payload.to_string()).await? {
+            Some(job) => Ok(job),
+            None => Self::enqueue(pool, job_type, payload).await,
+        }
+    }
+
+    pub async fn get_jobs(
+        pool: &DbPool,
+        filters: GetJobsRequest,
+    ) -> Result<ResponseData<JobResponse>, JobError> {
+        let (offset, limit) = (filters.offset(), filters.limit());
+        let (jobs, total) = Repository::get_jobs(
+            pool,
+            filters.status.filter(|s| !s.is_empty()),
+            filters.job_type.filter(|s| !s.is_empty()),
+            offset,
+            limit,
+        )
+        .await?;
+        Ok(ResponseData::new(JobResponse::from_jobs(jobs), total, offset))
+    }
+
+    pub async fn get_job(pool: &DbPool, job_uuid: String) -> Result<JobResponse, JobError> {
+        match Repository::get_job_by_uuid(pool, job_uuid).await {
+            Ok(job) => Ok(JobResponse::from_job(job)),
+            Err(diesel::result::Error::NotFound) => Err(JobError::NotFound),
+            Err(e) => Err(JobError::Database(e)),
+        }
+    }
+
+    /// Queue a failed or cancelled job again with a fresh set of attempts
+    pub async fn retry_job(pool: &DbPool, job_uuid: String) -> Result<JobResponse, JobError> {
+        Self::change_status(
+            pool,
+            job_uuid,
+            &[JobStatus::Failed, JobStatus::Cancelled],
+            JobStatus::Pending,
+        )
+        .await
+    }
+
+    /// Cancel a job which is not running, running jobs cannot be interrupted
+    pub async fn cancel_job(pool: &DbPool, job_uuid: String) -> Result<JobResponse, JobError> {
+        Self::change_status(
+            pool,
+            job_uuid,
+            &[JobStatus::Pending, JobStatus::Failed],
+            JobStatus::Cancelled,
+        )
+        .await
+    }
+
+    async fn change_status(
+        pool: &DbPool,
+        job_uuid: String,
+        from: &[JobStatus],
+        to: JobStatus,
+    ) -> Result<JobResponse, JobError> {
+        let job = Self::get_job(pool, job_uuid.clone()).await?;
+        let updated = Repository::update_job_status(
+            pool,
+            job_uuid.clone(),
+            from,
+            to,
+            to == JobStatus::Pending,
+        )
+        .await?;
+        if updated == 0 {
+            return Err(JobError::InvalidStatus(format!(
+                "Job is {}, expected {}",
+                job.status,
+                from.iter()
+                    .map(JobStatus::as_str)
+                    .collect::<Vec<&str>>()
+                    .join(" or ")
+            )));
+        }
+        Self::get_job(pool, job_uuid).await
+    }
+
+    /// Remove succeeded and cancelled jobs older than `jobs.retention_days`
+    pub async fn prune_finished_jobs(pool: &DbPool) -> Result<usize, diesel::result::Error> {
+        let retention_days = config::get().jobs.retention_days;
+        if retention_days == 0 {
+            return Ok(0);
+        }
+        let finished_before = Utc::now().naive_utc() - chrono::Duration::days(retention_days);
+        Repository::delete_finished_jobs(pool, finished_before).await
+    }
+
+    /// Queue `job_type` every `period`, skipped while the previous run is pending or running
+    pub fn schedule(pool: DbPool, job_type: &'static str, period: Duration, run_immediately: bool) {
+        actix_web::rt::spawn(async move {
+            let mut interval = actix_web::rt::time::interval(period);
+            if !run_immediately {
+                interval.tick().await;
+            }
+            loop {
+                interval.tick().await;
+                if let Err(e) = Self::enqueue_unique(&pool, job_type, json!({})).await {
+                    eprintln!("Failed to queue {}: {}", job_type, e);
+                }
+            }
+        });
+    }
+
+    /// Spawn `jobs.workers` workers on the current runtime
+    pub async fn start_workers(pool: DbPool, registry: JobRegistry) {
+        let jobs_config = &config::get().jobs;
+        if jobs_config.workers == 0 {
+            println!("Background job workers are disabled");
+            return;
+        }
+        match Repository::requeue_running_jobs(&pool).await {
+            Ok(requeued) if requeued > 0 => {
+                println!("Queued {} interrupted jobs again", requeued)
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("Failed to queue interrupted jobs: {}", e),
+        }
+        println!(
+            "Starting {} job workers for {}",
+            jobs_config.workers,
+            registry.job_types().join(", ")
+        );
+        let registry = Rc::new(registry);
+        for worker in 1..=jobs_config.workers {
+            actix_web::rt::spawn(run_worker(worker, pool.clone(), registry.clone()));
+        }
+    }
+}
+
+async fn run_worker(worker: usize, pool: DbPool, registry: Rc<JobRegistry>) {
+    let poll_interval = Duration::from_secs(config::get().jobs.poll_interval_seconds);
+    loop {
+        match run_next_job(worker, &pool, &registry).await {
+            Ok(true) => {}
+            Ok(false) => actix_web::rt::time::sleep(poll_interval).await,
+            Err(e) => {
+                eprintln!("Job worker {} failed to claim a job: {}", worker, e);
+                actix_web::rt::time::sleep(poll_interval).await;
+            }
+        }
+    }
+}
+
+/// Claim the next due job and run it, `false` when no job is due
+pub(crate) async fn run_next_job(
+    worker: usize,
+    pool: &DbPool,
+    registry: &JobRegistry,
+) -> Result<bool, diesel::result::Error> {
+    match Repository::claim_next_job(pool).await? {
+        Some(job) => {
+            run_job(worker, pool, registry, job).await;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+async fn run_job(worker: usize, pool: &DbPool, registry: &JobRegistry, job: Job) {
+    let result = match registry.get(&job.job_type) {
+        Some(handler) => {
+            let payload = serde_json::from_str(&job.payload).unwrap_or(Value::Null);
+            AssertUnwindSafe(handler.run(pool.clone(), payload))
+                .catch_unwind()
+                .await
+                .unwrap_or_else(|_| Err(String::from("Job handler panicked")))
+        }
+        None => Err(format!("No handler registered for {}", job.job_type)),
+    };
+    let updated = match result {
+        Ok(()) => {
+            println!(
+                "Job worker {} finished {} {}",
+                worker, job.job_type, job.uuid
+            );
+            Repository::finish_job(pool, job.id, JobStatus::Succeeded, None).await
+        }
+        Err(e) if job.attempts < job.max_attempts => {
+            let delay = retry_delay(job.attempts);
+            eprintln!(
+                "Job {} {} failed on attempt {}/{}, retrying in {}s: {}",
+                job.job_type,
+                job.uuid,
+                job.attempts,
+                job.max_attempts,
+                delay.num_seconds(),
+                e
+            );
+            Repository::schedule_retry(pool, job.id, Utc::now().naive_utc() + delay, e).await
+        }
+        Err(e) => {
+            eprintln!(
+                "Job {} {} failed after {} attempts: {}",
+                job.job_type, job.uuid, job.attempts, e
+            );
+            Repository::finish_job(pool, job.id, JobStatus::Failed, Some(e)).await
+        }
+    };
+    if let Err(e) = updated {
+        eprintln!("Failed to update job {}: {}", job.uuid, e);
+    }
+}
+
+/// `retry_backoff_seconds * 2^(attempts - 1)`, capped at `retry_backoff_max_seconds`
+fn retry_delay(attempts: i32) -> chrono::Duration {
+    let jobs_config = &config::get().jobs;
+    let exponent = attempts.saturating_sub(1).clamp(0, 30) as u32;
+    let seconds = jobs_config
+        .retry_backoff_seconds
+        .saturating_mul(2u64.saturating_pow(exponent))
+        .min(jobs_config.retry_backoff_max_seconds);
+    chrono::Duration::seconds(seconds as i64)
+}
@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+use futures_util::future::LocalBoxFuture;
+use serde_json::Value;
+
+use crate::common::config;
+use crate::common::database::DbPool;
+use crate::features::jobs::registry::JobHandler;
+use crate::features::jobs::services::Service as JobService;
+use crate::features::maintenance::services::Service;
+
+pub const PURGE_TMP_JOB: &str = "maintenance.purge_tmp";
+pub const PRUNE_JOBS_JOB: &str = "maintenance.prune_jobs";
+
+/// Remove tmp entries older than `storage.tmp_max_age_minutes`
+pub struct PurgeTmpJob;
+
+impl JobHandler for PurgeTmpJob {
+    fn job_type(&self) -> &'static str {
+        PURGE_TMP_JOB
+    }
+
+    fn run(&self, _pool: DbPool, _payload: Value) -> LocalBoxFuture<'static, Result<(), String>> {
+        Box::pin(async move {
+            let max_age = Duration::from_secs(config::get().storage.tmp_max_age_minutes * 60);
+            let removed = actix_web::web::block(move || Service::purge_tmp(max_age))
+                .await
+                .map_err(|e| e.to_string())?
+                .map_err(|e| e.to_string())?;
+            if removed > 0 {
+                println!("Removed {} stale tmp entries", removed);
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Delete succeeded and cancelled jobs older than `jobs.retention_days`
+pub struct PruneJobsJob;
+
+impl JobHandler for PruneJobsJob {
+    fn job_type(&self) -> &'static str {
+        PRUNE_JOBS_JOB
+    }
+
+    fn run(&self, pool: DbPool, _payload: Value) -> LocalBoxFuture<'static, Result<(), String>> {
+        Box::pin(async move {
+            let pruned = JobService::prune_finished_jobs(&pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            if pruned > 0 {
+                println!("Pruned {} finished jobs", pruned);
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,14 @@
+//! Maintenance tasks used by the `entmt-admin` binary and the job workers
+
+use crate::features::jobs::registry::JobRegistry;
+use crate::features::maintenance::jobs::{PruneJobsJob, PurgeTmpJob};
+
+pub mod jobs;
+pub mod models;
+pub mod repository;
+pub mod services;
+
+pub fn register_jobs(registry: &mut JobRegistry) {
+    registry.register(PurgeTmpJob);
+    registry.register(PruneJobsJob);
+}
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, PartialEq, Eq, Default)]
+pub struct ReindexReport {
+    /// Rows whose content type, size or dimensions changed
+    pub updated: i64,
+    pub unchanged: i64,
+    /// Rows whose file does not exist
+    pub missing: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, PartialEq, Eq, Default)]
+pub struct Stats {
+    pub categories: i64,
+    pub albums: i64,
+    pub episodes: i64,
+    pub contents: i64,
+    pub broken_albums: i64,
+    pub broken_episodes: i64,
+    pub broken_contents: i64,
+    pub trashed_albums: i64,
+    pub trashed_episodes: i64,
+    pub trashed_contents: i64,
+    /// Bytes of album covers, episode files and contents which are not in the trash
+    pub stored_bytes: i64,
+}
+
+/// File metadata columns shared by albums, episodes and contents
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileColumns {
+    pub content_type: String,
+    pub width: i32,
+    pub height: i32,
+    pub bytes: i32,
+}
@@ -0,0 +1,119 @@
+use diesel::dsl::count_star;
+use diesel::prelude::*;
+
+use crate::common::database::DbPool;
+use crate::features::maintenance::models::{FileColumns, Stats};
+use crate::schema::{albums, category, contents, episodes};
+
+pub struct Repository;
+
+impl Repository {
+    pub async fn update_album_file_columns(
+        pool: &DbPool,
+        album_uuid: String,
+        columns: FileColumns,
+    ) -> QueryResult<usize> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        diesel::update(albums::table.filter(albums::uuid.eq(album_uuid)))
+            .set((
+                albums::content_type.eq(columns.content_type),
+                albums::width.eq(columns.width),
+                albums::height.eq(columns.height),
+                albums::bytes.eq(columns.bytes),
+            ))
+            .execute(&mut conn)
+    }
+
+    pub async fn update_episode_file_columns(
+        pool: &DbPool,
+        episode_uuid: String,
+        columns: FileColumns,
+    ) -> QueryResult<usize> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        diesel::update(episodes::table.filter(episodes::uuid.eq(episode_uuid)))
+            .set((
+                episodes::content_type.eq(Some(columns.content_type)),
+                episodes::width.eq(columns.width),
+                episodes::height.eq(columns.height),
+                episodes::bytes.eq(columns.bytes),
+            ))
+            .execute(&mut conn)
+    }
+
+    pub async fn update_content_file_columns(
+        pool: &DbPool,
+        content_uuid: String,
+        columns: FileColumns,
+    ) -> QueryResult<usize> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        diesel::update(contents::table.filter(contents::uuid.eq(content_uuid)))
+            .set((
+                contents::content_type.eq(columns.content_type),
+                contents::width.eq(columns.width),
+                contents::height.eq(columns.height),
+                contents::bytes.eq(columns.bytes),
+            ))
+            .execute(&mut conn)
+    }
+
+    pub async fn get_stats(pool: &DbPool) -> QueryResult<Stats> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        let album_bytes = albums::table
+            .filter(albums::deleted_at.is_null())
+            .select(diesel::dsl::sum(albums::bytes))
+            .first::<Option<i64>>(&mut conn)?;
+        let episode_bytes = episodes::table
+            .filter(episodes::deleted_at.is_null())
+            .select(diesel::dsl::sum(episodes::bytes))
+            .first::<Option<i64>>(&mut conn)?;
+        let content_bytes = contents::table
+            .filter(contents::deleted_at.is_null())
+            .select(diesel::dsl::sum(contents::bytes))
+            .first::<Option<i64>>(&mut conn)?;
+        Ok(Stats {
+            categories: category::table.select(count_star()).first(&mut conn)?,
+            albums: albums::table
+                .filter(albums::deleted_at.is_null())
+                .select(count_star())
+                .first(&mut conn)?,
+            episodes: episodes::table
+                .filter(episodes::deleted_at.is_null())
+                .select(count_star())
+                .first(&mut conn)?,
+            contents: contents::table
+                .filter(contents::deleted_at.is_null())
+                .select(count_star())
+                .first(&mut conn)?,
+            broken_albums: albums::table
+                .filter(albums::deleted_at.is_null())
+                .filter(albums::broken_at.is_not_null())
+                .select(count_star())
+                .first(&mut conn)?,
+            broken_episodes: episodes::table
+                .filter(episodes::deleted_at.is_null())
+                .filter(episodes::broken_at.is_not_null())
+                .select(count_star())
+                .first(&mut conn)?,
+            broken_contents: contents::table
+                .filter(contents::deleted_at.is_null())
+                .filter(contents::broken_at.is_not_null())
+                .select(count_star())
+                .first(&mut conn)?,
+            trashed_albums: albums::table
+                .filter(albums::deleted_at.is_not_null())
+                .select(count_star())
+                .first(&mut conn)?,
+            trashed_episodes: episodes::table
+                .filter(episodes::deleted_at.is_not_null())
+                .select(count_star())
+                .first(&mut conn)?,
+            trashed_contents: contents::table
+                .filter(contents::deleted_at.is_not_null())
+                .select(count_star())
+                .first(&mut conn)?,
+            stored_bytes: album_bytes.unwrap_or(0)
+                + episode_bytes.unwrap_or(0)
+                + content_bytes.unwrap_or(0),
+        })
+    }
+}
@@ -0,0 +1,296 @@
+use std::cmp::Ordering;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use uuid::Uuid;
+
+use crate::common::database::DbPool;
+use crate::common::models::file_metadata::ImageMetadata;
+use crate::common::tmp_dir::{sweep_tmp_directory, TmpDir};
+use crate::common::utils::get_file_metadata;
+use crate::features::album::models::{AlbumResponse, CreateAlbumRequest};
+use crate::features::content::models::AddEpisodeContentsRequest;
+use crate::features::episode::models::CreateEpisodeRequest;
+use crate::features::integrity::repository::Repository as IntegrityRepository;
+use crate::features::maintenance::models::{FileColumns, ReindexReport, Stats};
+use crate::features::maintenance::repository::Repository;
+use crate::features::{album, content, episode};
+
+pub struct Service;
+
+impl Service {
+    /// Re-read content type, size and dimensions of every stored file with `get_file_metadata`
+    pub async fn reindex_metadata(pool: &DbPool) -> Result<ReindexReport, diesel::result::Error> {
+        let mut report = ReindexReport::default();
+        for album in IntegrityRepository::get_albums(pool).await? {
+            let current = FileColumns {
+                content_type: album.content_type.clone(),
+                width: album.width,
+                height: album.height,
+                bytes: album.bytes,
+            };
+            match read_file_columns(&album.url) {
+                Some(columns) if columns != current => {
+                    Repository::update_album_file_columns(pool, album.uuid, columns).await?;
+                    report.updated += 1;
+                }
+                Some(_) => report.unchanged += 1,
+                None => report.missing.push(album.url),
+            }
+        }
+        for episode in IntegrityRepository::get_episodes(pool).await? {
+            let Some(url) = episode.url.clone() else {
+                continue;
+            };
+            let current = FileColumns {
+                content_type: episode.content_type.clone().unwrap_or_default(),
+                width: episode.width,
+                height: episode.height,
+                bytes: episode.bytes,
+            };
+            match read_file_columns(&url) {
+                Some(columns) if columns != current => {
+                    Repository::update_episode_file_columns(pool, episode.uuid, columns).await?;
+                    report.updated += 1;
+                }
+                Some(_) => report.unchanged += 1,
+                None => report.missing.push(url),
+            }
+        }
+        for content in IntegrityRepository::get_contents(pool).await? {
+            let current = FileColumns {
+                content_type: content.content_type.clone(),
+                width: content.width,
+                height: content.height,
+                bytes: content.bytes,
+            };
+            match read_file_columns(&content.url) {
+                Some(columns) if columns != current => {
+                    Repository::update_content_file_columns(pool, content.uuid, columns).await?;
+                    report.updated += 1;
+                }
+                Some(_) => report.unchanged += 1,
+                None => report.missing.push(content.url),
+            }
+        }
+        Ok(report)
+    }
+
+    pub async fn get_stats(pool: &DbPool) -> Result<Stats, diesel::result::Error> {
+        Repository::get_stats(pool).await
+    }
+
+    /// Remove entries of the tmp directory which were last modified before `older_than`
+    pub fn purge_tmp(older_than: Duration) -> std::io::Result<usize> {
+        sweep_tmp_directory(older_than)
+    }
+
+    /// Import an album from a directory tree.
+    ///
+    /// The directory holds the cover (`cover.*`, otherwise the first image) and extra album
+    /// images, every sub directory becomes an episode named after it and its files become the
+    /// episode contents. Directories and files are ordered by name with numbers compared by
+    /// value, source files are copied and left untouched.
+    pub async fn import_album(
+        pool: &DbPool,
+        dir: &str,
+        category_id: i32,
+        title: Option<String>,
+        description: Option<String>,
+    ) -> Result<AlbumResponse, String> {
+        let source = Path::new(dir);
+        if !source.is_dir() {
+            return Err(format!("{} is not a directory", dir));
+        }
+        let staging =
+            TmpDir::new().map_err(|e| format!("Failed to create tmp directory: {}", e))?;
+        import_album_from(
+            pool,
+            source,
+            staging.path(),
+            category_id,
+            title,
+            description,
+        )
+        .await
+    }
+}
+
+async fn import_album_from(
+    pool: &DbPool,
+    source: &Path,
+    staging: &str,
+    category_id: i32,
+    title: Option<String>,
+    description: Option<String>,
+) -> Result<AlbumResponse, String> {
+    let (dirs, files) = read_sorted_dir(source)?;
+    let mut images: Vec<PathBuf> = files.into_iter().filter(|f| is_image(f)).collect();
+    let cover_index = images
+        .iter()
+        .position(|f| f.file_stem().is_some_and(|stem| stem == "cover"))
+        .unwrap_or(0);
+    if images.is_empty() {
+        return Err(format!("No cover image found in {}", source.display()));
+    }
+    let cover = images.remove(cover_index);
+
+    let req = CreateAlbumRequest {
+        title: title.unwrap_or_else(|| file_name(source)),
+        description: description.unwrap_or_default(),
+        cover: stage_file(&cover, staging)?,
+        images: Some(
+            images
+                .iter()
+                .map(|image| stage_file(image, staging))
+                .collect::<Result<Vec<String>, String>>()?,
+        ),
+        completed: Some(false),
+        tags: Some(String::new()),
+        enable: Some(true),
+        min_age: Some(0),
+        category_id: Some(category_id),
+        category_ids: None,
+        released_at: None,
+    };
+    let album = album::services::Service::create_album(pool, req)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    for episode_dir in dirs {
+        let episode = episode::services::Service::create_episode(
+            pool,
+            CreateEpisodeRequest {
+                album_id: album.id,
+                title: file_name(&episode_dir),
+                file: None,
+                file_url: None,
+                number: None,
+                volume: None,
+                sort_order: None,
+            },
+        )
+        .await
+        .map_err(|e| format!("{}: {}", episode_dir.display(), e))?;
+        let (_, files) = read_sorted_dir(&episode_dir)?;
+        if files.is_empty() {
+            continue;
+        }
+        let episode_id = episode
+            .id
+            .ok_or_else(|| format!("{}: episode has no id", episode_dir.display()))?;
+        let staged = files
+            .iter()
+            .map(|file| stage_file(file, staging))
+            .collect::<Result<Vec<String>, String>>()?;
+        content::services::Service::add_episode_contents(
+            pool,
+            AddEpisodeContentsRequest {
+                episode_id,
+                files: staged,
+                position: None,
+                locale: None,
+            },
+        )
+        .await
+        .map_err(|e| format!("{}: {}", episode_dir.display(), e))?;
+    }
+    Ok(album)
+}
+
+fn read_file_columns(path: &str) -> Option<FileColumns> {
+    if !Path::new(path).is_file() {
+        return None;
+    }
+    let metadata = get_file_metadata(path);
+    let image_data = metadata.image_data.unwrap_or(ImageMetadata::default());
+    Some(FileColumns {
+        content_type: metadata.content_type,
+        width: image_data.width as i32,
+        height: image_data.height as i32,
+        bytes: metadata.size as i32,
+    })
+}
+
+/// Sub directories and files of `dir`, both in natural name order, hidden entries skipped
+fn read_sorted_dir(dir: &Path) -> Result<(Vec<PathBuf>, Vec<PathBuf>), String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let mut dirs = Vec::new();
+    let mut files = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if file_name(&path).starts_with('.') {
+            continue;
+        }
+        if path.is_dir() {
+            dirs.push(path);
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+    dirs.sort_by(|a, b| natural_cmp(&file_name(a), &file_name(b)));
+    files.sort_by(|a, b| natural_cmp(&file_name(a), &file_name(b)));
+    Ok((dirs, files))
+}
+
+/// Copy a source file into the staging directory, services move staged files into `data/`
+fn stage_file(file: &Path, staging: &str) -> Result<String, String> {
+    let extension = file
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let staged = format!("{}/{}.{}", staging, Uuid::new_v4(), extension);
+    fs::copy(file, &staged).map_err(|e| format!("{}: {}", file.display(), e))?;
+    Ok(staged)
+}
+
+fn is_image(path: &Path) -> bool {
+    mime_guess::from_path(path)
+        .first()
+        .is_some_and(|mime| mime.type_() == mime_guess::mime::IMAGE)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Compare names so that `ep2` sorts before `ep10`
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+    loop {
+        match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let x_num = take_number(&mut a_chars);
+                let y_num = take_number(&mut b_chars);
+                match x_num.cmp(&y_num) {
+                    Ordering::Equal => continue,
+                    other => return other,
+                }
+            }
+            (Some(x), Some(y)) => {
+                a_chars.next();
+                b_chars.next();
+                match x.to_ascii_lowercase().cmp(&y.to_ascii_lowercase()) {
+                    Ordering::Equal => continue,
+                    other => return other,
+                }
+            }
+        }
+    }
+}
+
+fn take_number(chars: &mut std::iter::Peekable<std::str::Chars>) -> u128 {
+    let mut number: u128 = 0;
+    while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+        number = number.saturating_mul(10).saturating_add(digit as u128);
+        chars.next();
+    }
+    number
+}
@@ -0,0 +1,219 @@
+use actix_files::NamedFile;
+use actix_web::http::header::{
+    Charset, ContentDisposition, DispositionParam, DispositionType, ExtendedValue,
+};
+use actix_web::{route, web, HttpRequest, HttpResponse, Responder};
+use mime_guess::mime::Mime;
+
+use crate::common::database::DbPool;
+use crate::common::models::response_message::ResponseMessage;
+use crate::common::utils::{get_file_name_from_path, notfound_404};
+use crate::features::media::models::MediaError;
+use crate::features::media::services::Service;
+use crate::features::{album_refusal, reader_age};
+
+/// Stream Media
+///
+/// Serve a stored file from the data directory with byte range support. Files of an album are
+/// checked against the reader's age unless the admin key is sent
+#[utoipa::path(
+    get,
+    path = "/static/{path}",
+    params(
+        ("path" = String, Path, description = "File path as stored in url", style = Simple, example = "data/fd2fe858-9962-404f-9174-c4f6f83cc39e/cover.jpg"),
+        ("Range" = Option<String>, Header, description = "Byte range, e.g. bytes=0-1023"),
+        ("X-Reader-Profile" = Option<String>, Header, description = "Reader profile the album rating is checked against")
+    ),
+    responses(
+        (status = 200, description = "Whole file"),
+        (status = 206, description = "Requested byte range"),
+        (status = 403, description = "Path is outside the data directory, or the album is age rated and the reader's age is unknown", body = ResponseMessage),
+        (status = 404, description = "File not found"),
+        (status = 416, description = "Range not satisfiable"),
+        (status = 451, description = "Reader is younger than the album rating", body = AgeRestrictedResponse),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    tag = "Media",
+)]
+#[route("/static/{path:.*}", method = "GET", method = "HEAD")]
+pub async fn stream_media(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+) -> impl Responder {
+    let (file_path, media_file) = match Service::get_media_file(&pool, path.into_inner()).await {
+        Ok(media) => media,
+        Err(MediaError::NotFound) => return notfound_404().await,
+        Err(e @ MediaError::Forbidden) => {
+            return HttpResponse::Forbidden().json(ResponseMessage {
+                message: e.to_string(),
+            })
+        }
+        Err(e) => {
+            println!("Failed to look up media file: {}", e);
+            return HttpResponse::InternalServerError().json(ResponseMessage {
+                message: String::from("Internal Server Error"),
+            });
+        }
+    };
+    if let Some((enable, min_age)) = media_file.album {
+        let reader_age = reader_age(&pool, &http_request).await;
+        if let Some(refusal) = album_refusal(reader_age, enable, min_age) {
+            return refusal;
+        }
+    }
+    let mut named_file = match NamedFile::open_async(&file_path).await {
+        Ok(named_file) => named_file,
+        Err(_) => return notfound_404().await,
+    };
+    // The DB content type wins over the extension guess, empty values are left to actix-files
+    if let Some(mime) = media_file
+        .content_type
+        .as_deref()
+        .and_then(|content_type| content_type.parse::<Mime>().ok())
+    {
+        named_file = named_file.set_content_type(mime);
+    }
+    let file_name = media_file
+        .file_name
+        .filter(|file_name| !file_name.is_empty())
+        .unwrap_or_else(|| get_file_name_from_path(&file_path.to_string_lossy()));
+    named_file
+        .set_content_disposition(inline_disposition(file_name))
+        .into_response(&http_request)
+}
+
+fn inline_disposition(file_name: String) -> ContentDisposition {
+    let mut parameters = vec![DispositionParam::Filename(file_name.clone())];
+    if !file_name.is_ascii() {
+        parameters.push(DispositionParam::FilenameExt(ExtendedValue {
+            charset: Charset::Ext(String::from("UTF-8")),
+            language_tag: None,
+            value: file_name.into_bytes(),
+        }));
+    }
+    ContentDisposition {
+        disposition: DispositionType::Inline,
+        parameters,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use actix_web::{test, web, App};
+    use diesel::prelude::*;
+    use diesel::sql_types::Text;
+
+    use crate::common::middleware::api_key_middleware::ApiKeyMiddleware;
+    use crate::common::test_support::{self, ADMIN_API_KEY, USER_API_KEY};
+    use crate::features::media;
+
+    #[actix_web::test]
+    async fn age_rated_album_files_are_refused_to_younger_readers() {
+        let pool = test_support::test_pool();
+        let album_directory = test_support::data_subdir();
+        let album_uuid = album_directory
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let cover = album_directory.join("cover.jpg");
+        fs::write(&cover, b"cover").unwrap();
+        let reader_uuid = uuid::Uuid::new_v4().to_string();
+        let mut conn = pool.get().unwrap();
+        diesel::sql_query(
+            "INSERT INTO albums (uuid, category_id, title, description, images, url, \
+             content_type, width, height, bytes, min_age) \
+             VALUES (?, 1, 'Rated', '', '', '', '', 0, 0, 0, 18)",
+        )
+        .bind::<Text, _>(&album_uuid)
+        .execute(&mut conn)
+        .unwrap();
+        diesel::sql_query(
+            "INSERT INTO reader_profiles (uuid, display_name, birth_date) \
+             VALUES (?, 'Young', date('now', '-10 years'))",
+        )
+        .bind::<Text, _>(&reader_uuid)
+        .execute(&mut conn)
+        .unwrap();
+        let app = test::init_service(
+            App::new()
+                .wrap(ApiKeyMiddleware::new(
+                    ADMIN_API_KEY.to_string(),
+                    USER_API_KEY.to_string(),
+                    vec![],
+                ))
+                .app_data(web::Data::new(pool.clone()))
+                .configure(media::configure),
+        )
+        .await;
+        let uri = format!("/static/{}", cover.to_string_lossy());
+
+        let request = test::TestRequest::get()
+            .uri(&uri)
+            .insert_header(("X-API-KEY", USER_API_KEY))
+            .insert_header(("X-Reader-Profile", reader_uuid.as_str()))
+            .to_request();
+        assert_eq!(test::call_service(&app, request).await.status(), 451);
+        // Without a profile the age is unknown
+        let request = test::TestRequest::get().uri(&uri).to_request();
+        assert_eq!(test::call_service(&app, request).await.status(), 403);
+        let request = test::TestRequest::get()
+            .uri(&uri)
+            .insert_header(("X-API-KEY", ADMIN_API_KEY))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), 200);
+        assert_eq!(test::read_body(response).await, "cover");
+    }
+
+    #[actix_web::test]
+    async fn ranges_are_served_with_the_stored_content_type() {
+        let pool = test_support::test_pool();
+        let album_directory = test_support::data_subdir();
+        let page = album_directory.join("page.jpg");
+        fs::write(&page, b"0123456789abcdefghij").unwrap();
+        // Stored as WebP although the extension says JPEG
+        diesel::sql_query(
+            "INSERT INTO albums (uuid, category_id, title, description, images, url, \
+             content_type, width, height, bytes) \
+             VALUES (?, 1, 'Album', '', '', ?, 'image/webp', 0, 0, 20)",
+        )
+        .bind::<Text, _>(uuid::Uuid::new_v4().to_string())
+        .bind::<Text, _>(page.to_string_lossy().to_string())
+        .execute(&mut pool.get().unwrap())
+        .unwrap();
+        let app = test::init_service(
+            App::new()
+                .wrap(ApiKeyMiddleware::new(
+                    ADMIN_API_KEY.to_string(),
+                    USER_API_KEY.to_string(),
+                    vec![],
+                ))
+                .app_data(web::Data::new(pool.clone()))
+                .configure(media::configure),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri(&format!("/static/{}", page.to_string_lossy()))
+            .insert_header(("Range", "bytes=0-9"))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), 206);
+        let headers = response.headers();
+        assert_eq!(headers.get("Content-Range").unwrap(), "bytes 0-9/20");
+        assert_eq!(headers.get("Content-Type").unwrap(), "image/webp");
+        assert_eq!(test::read_body(response).await, "0123456789");
+
+        let request = test::TestRequest::get()
+            .uri(&format!(
+                "/static/{}",
+                album_directory.join("missing.jpg").to_string_lossy()
+            ))
+            .to_request();
+        assert_eq!(test::call_service(&app, request).await.status(), 404);
+    }
+}
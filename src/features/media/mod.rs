@@ -0,0 +1,12 @@
+use actix_web::web;
+
+use crate::features::media::controllers::stream_media;
+
+pub mod controllers;
+pub mod models;
+pub mod repository;
+pub mod services;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(stream_media);
+}
@@ -0,0 +1,36 @@
+use std::fmt;
+
+/// Stored file referenced by an album, episode or content row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaFile {
+    pub content_type: Option<String>,
+    /// Name of the uploaded file, falls back to the stored file name when unknown
+    pub file_name: Option<String>,
+    /// Whether the album the file is stored under is enabled and its age rating, `None` for
+    /// files outside album directories such as category icons
+    pub album: Option<(bool, i32)>,
+}
+
+#[derive(Debug)]
+pub enum MediaError {
+    NotFound,
+    /// The requested path resolves outside the data directory
+    Forbidden,
+    Database(diesel::result::Error),
+}
+
+impl fmt::Display for MediaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MediaError::NotFound => write!(f, "File not found"),
+            MediaError::Forbidden => write!(f, "Only files in the data directory can be served"),
+            MediaError::Database(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<diesel::result::Error> for MediaError {
+    fn from(e: diesel::result::Error) -> Self {
+        MediaError::Database(e)
+    }
+}
@@ -0,0 +1,92 @@
+use diesel::prelude::*;
+
+use crate::common::database::DbPool;
+use crate::features::media::models::MediaFile;
+use crate::schema::{albums, category, contents, creators, episodes};
+
+pub struct Repository;
+
+impl Repository {
+    /// Look up the row that owns `url`, episodes first since they carry the uploaded file name
+    pub async fn get_media_file_by_url(
+        pool: &DbPool,
+        url: String,
+    ) -> QueryResult<Option<MediaFile>> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        let episode = episodes::table
+            .filter(episodes::deleted_at.is_null())
+            .filter(episodes::url.eq(&url))
+            .select((episodes::content_type, episodes::file_name))
+            .first::<(Option<String>, Option<String>)>(&mut conn)
+            .optional()?;
+        if let Some((content_type, file_name)) = episode {
+            return Ok(Some(MediaFile {
+                content_type,
+                file_name,
+                album: None,
+            }));
+        }
+        let content = contents::table
+            .filter(contents::deleted_at.is_null())
+            .filter(contents::url.eq(&url))
+            .select(contents::content_type)
+            .first::<String>(&mut conn)
+            .optional()?;
+        if let Some(content_type) = content {
+            return Ok(Some(MediaFile {
+                content_type: Some(content_type),
+                file_name: None,
+                album: None,
+            }));
+        }
+        let album = albums::table
+            .filter(albums::deleted_at.is_null())
+            .filter(albums::url.eq(&url))
+            .select(albums::content_type)
+            .first::<String>(&mut conn)
+            .optional()?;
+        if let Some(content_type) = album {
+            return Ok(Some(MediaFile {
+                content_type: Some(content_type),
+                file_name: None,
+                album: None,
+            }));
+        }
+        let icon = category::table
+            .filter(category::icon_url.eq(&url))
+            .select(category::icon_content_type)
+            .first::<Option<String>>(&mut conn)
+            .optional()?;
+        if let Some(content_type) = icon {
+            return Ok(Some(MediaFile {
+                content_type,
+                file_name: None,
+                album: None,
+            }));
+        }
+        let avatar = creators::table
+            .filter(creators::avatar_url.eq(&url))
+            .select(creators::avatar_content_type)
+            .first::<Option<String>>(&mut conn)
+            .optional()?;
+        Ok(avatar.map(|content_type| MediaFile {
+            content_type,
+            file_name: None,
+            album: None,
+        }))
+    }
+
+    /// Whether the album is enabled and its age rating, `None` when no album has the uuid
+    pub async fn get_album_access_by_uuid(
+        pool: &DbPool,
+        album_uuid: String,
+    ) -> QueryResult<Option<(bool, i32)>> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        albums::table
+            .filter(albums::deleted_at.is_null())
+            .filter(albums::uuid.eq(album_uuid))
+            .select((albums::enable, albums::min_age))
+            .first::<(bool, i32)>(&mut conn)
+            .optional()
+    }
+}
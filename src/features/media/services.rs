@@ -0,0 +1,146 @@
+use std::path::{Path, PathBuf};
+
+use crate::common::database::DbPool;
+use crate::common::utils::{get_data_directory, get_project_directory};
+use crate::features::media::models::{MediaError, MediaFile};
+use crate::features::media::repository::Repository;
+
+pub struct Service;
+
+impl Service {
+    /// Resolve a `/static` path to a file inside the data directory and its stored metadata,
+    /// with the album owning the directory the file is stored under
+    pub async fn get_media_file(
+        pool: &DbPool,
+        path: String,
+    ) -> Result<(PathBuf, MediaFile), MediaError> {
+        let file_path = resolve_data_path(&path)?;
+        let mut media_file = Repository::get_media_file_by_url(pool, path)
+            .await?
+            .unwrap_or(MediaFile {
+                content_type: None,
+                file_name: None,
+                album: None,
+            });
+        // Album files, episodes, contents and HLS output all live under `<data>/<album_uuid>/`
+        if let Some(album_uuid) = album_directory(&file_path) {
+            media_file.album = Repository::get_album_access_by_uuid(pool, album_uuid).await?;
+        }
+        Ok((file_path, media_file))
+    }
+}
+
+/// Name of the first directory under the data directory holding `file_path`, `None` for files
+/// directly in the data directory
+fn album_directory(file_path: &Path) -> Option<String> {
+    let data_directory = PathBuf::from(get_project_directory())
+        .join(get_data_directory())
+        .canonicalize()
+        .ok()?;
+    let mut components = file_path.strip_prefix(data_directory).ok()?.components();
+    let directory = components.next()?;
+    components.next()?;
+    Some(directory.as_os_str().to_string_lossy().to_string())
+}
+
+/// Canonicalize `path` relative to the project directory and refuse anything outside data,
+/// this also rejects `..` segments and symlinks pointing out of the data directory
+fn resolve_data_path(path: &str) -> Result<PathBuf, MediaError> {
+    let project_directory = PathBuf::from(get_project_directory());
+    let data_directory = project_directory
+        .join(get_data_directory())
+        .canonicalize()
+        .map_err(|_| MediaError::NotFound)?;
+    let file_path = project_directory
+        .join(Path::new(path))
+        .canonicalize()
+        .map_err(|_| MediaError::NotFound)?;
+    if !file_path.starts_with(&data_directory) {
+        return Err(MediaError::Forbidden);
+    }
+    if !file_path.is_file() {
+        return Err(MediaError::NotFound);
+    }
+    Ok(file_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::common::test_support;
+
+    /// File next to the data directory, outside of it
+    fn outside_file(name: &str) -> PathBuf {
+        let data_directory = PathBuf::from(&test_support::init_config().storage.data_dir);
+        let directory = data_directory
+            .parent()
+            .unwrap()
+            .join(format!("entmt-outside-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&directory).unwrap();
+        let file = directory.join(name);
+        fs::write(&file, b"secret").unwrap();
+        file
+    }
+
+    #[test]
+    fn paths_outside_the_data_directory_are_forbidden() {
+        let data_directory = test_support::init_config().storage.data_dir.clone();
+        let database = outside_file("entmt.db");
+        let env = outside_file(".env");
+        let escapes = [
+            format!(
+                "{}/../{}/entmt.db",
+                data_directory,
+                database
+                    .parent()
+                    .unwrap()
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+            ),
+            format!(
+                "{}/data/../../{}/.env",
+                data_directory,
+                env.parent().unwrap().file_name().unwrap().to_string_lossy()
+            ),
+        ];
+        fs::create_dir_all(PathBuf::from(&data_directory).join("data")).unwrap();
+        for escape in escapes {
+            assert!(
+                matches!(resolve_data_path(&escape), Err(MediaError::Forbidden)),
+                "{}",
+                escape
+            );
+        }
+
+        let album_directory = test_support::data_subdir();
+        let link = album_directory.join("cover.jpg");
+        std::os::unix::fs::symlink(&database, &link).unwrap();
+        assert!(matches!(
+            resolve_data_path(&link.to_string_lossy()),
+            Err(MediaError::Forbidden)
+        ));
+    }
+
+    #[test]
+    fn files_inside_the_data_directory_are_resolved() {
+        let album_directory = test_support::data_subdir();
+        let cover = album_directory.join("cover.jpg");
+        fs::write(&cover, b"cover").unwrap();
+        assert_eq!(
+            resolve_data_path(&cover.to_string_lossy()).unwrap(),
+            cover.canonicalize().unwrap()
+        );
+        assert!(matches!(
+            resolve_data_path(&album_directory.join("missing.jpg").to_string_lossy()),
+            Err(MediaError::NotFound)
+        ));
+        // Directories are not served
+        assert!(matches!(
+            resolve_data_path(&album_directory.to_string_lossy()),
+            Err(MediaError::NotFound)
+        ));
+    }
+}
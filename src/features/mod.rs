@@ -1,25 +1,68 @@
-use actix_web::{web, HttpMessage, HttpRequest};
+use actix_web::http::StatusCode;
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use serde_json::Value;
 use utoipa::openapi::security::{ApiKey, ApiKeyValue, SecurityScheme};
 use utoipa::openapi::Components;
 use utoipa::{Modify, OpenApi};
 
+use crate::common::config;
+use crate::common::database::DbPool;
 use crate::common::enums::Role;
 use crate::common::enums::Role::*;
 use crate::common::models::response_data::*;
 use crate::common::models::response_message::*;
+use crate::common::pagination::{PageQuery, SortOrder};
+use crate::features::reader::models::{
+    AgeRestrictedResponse, AgeRestriction, AgeRestrictionReason, ReaderAge,
+};
 
-pub mod category;
 pub mod album;
+pub mod analytics;
+pub mod audit;
+pub mod category;
 pub mod content;
+pub mod creator;
 pub mod episode;
+pub mod events;
 pub mod health_check;
+pub mod integrity;
+pub mod jobs;
+pub mod maintenance;
+pub mod media;
+pub mod reader;
+pub mod review;
+pub mod translation;
+pub mod trash;
+pub mod webhooks;
 
 pub fn config_routes(cfg: &mut web::ServiceConfig) {
     category::configure(cfg);
+    // Registered before the `/album` scope, which would answer 404 for the translation and
+    // review routes
+    translation::configure(cfg);
+    review::configure(cfg);
     album::configure(cfg);
+    creator::configure(cfg);
+    reader::configure(cfg);
     episode::configure(cfg);
     content::configure(cfg);
     health_check::configure(cfg);
+    audit::configure(cfg);
+    trash::configure(cfg);
+    integrity::configure(cfg);
+    media::configure(cfg);
+    jobs::configure(cfg);
+    webhooks::configure(cfg);
+    events::configure(cfg);
+}
+
+// ! Register every background job handler in this function
+pub fn register_jobs(registry: &mut jobs::registry::JobRegistry) {
+    episode::register_jobs(registry);
+    integrity::register_jobs(registry);
+    maintenance::register_jobs(registry);
+    trash::register_jobs(registry);
+    webhooks::register_jobs(registry);
 }
 
 struct SecurityAddon;
@@ -30,25 +73,73 @@ struct SecurityAddon;
     paths(
         category::controllers::add_category,
         category::controllers::get_categories,
+        category::controllers::get_category,
         category::controllers::update_category,
         category::controllers::delete_category,
         album::controllers::create_album,
         album::controllers::get_albums,
+        album::controllers::get_trending_albums,
         album::controllers::get_album_by_uuid,
         album::controllers::update_album,
         album::controllers::delete_album,
         album::controllers::add_album_images,
         album::controllers::remove_album_images,
+        album::controllers::set_album_credits,
+        translation::controllers::get_album_translations,
+        translation::controllers::set_album_translation,
+        translation::controllers::delete_album_translation,
+        creator::controllers::create_creator,
+        creator::controllers::get_creators,
+        creator::controllers::get_creator,
+        creator::controllers::update_creator,
+        creator::controllers::delete_creator,
+        reader::controllers::create_reader_profile,
+        reader::controllers::get_reader_profile,
+        reader::controllers::update_reader_profile,
+        reader::controllers::verify_reader_age,
+        reader::controllers::delete_reader_profile,
+        review::controllers::get_album_reviews,
+        review::controllers::get_reviews,
+        review::controllers::get_review,
+        review::controllers::add_review,
+        review::controllers::update_review,
+        review::controllers::moderate_review,
+        review::controllers::delete_review,
         episode::controllers::create_episode,
         episode::controllers::update_episode,
         episode::controllers::get_episode,
+        episode::controllers::get_episode_manifest,
         episode::controllers::delete_episode,
         episode::controllers::get_episodes_by_album_id,
+        episode::controllers::reorder_episodes,
+        episode::controllers::package_episode_hls,
+        translation::controllers::get_episode_translations,
+        translation::controllers::set_episode_translation,
+        translation::controllers::delete_episode_translation,
         content::controllers::add_contents,
         content::controllers::get_contents,
         content::controllers::update_content,
+        content::controllers::reorder_contents,
         content::controllers::delete_content,
         health_check::controllers::get_health,
+        audit::controllers::get_audit_logs,
+        trash::controllers::get_trash,
+        trash::controllers::restore_trash_item,
+        trash::controllers::purge_trash_item,
+        integrity::controllers::run_integrity_scan,
+        media::controllers::stream_media,
+        jobs::controllers::get_jobs,
+        jobs::controllers::get_job,
+        jobs::controllers::retry_job,
+        jobs::controllers::cancel_job,
+        webhooks::controllers::create_webhook,
+        webhooks::controllers::get_webhooks,
+        webhooks::controllers::get_webhook,
+        webhooks::controllers::update_webhook,
+        webhooks::controllers::delete_webhook,
+        webhooks::controllers::get_webhook_deliveries,
+        webhooks::controllers::test_webhook,
+        events::controllers::stream_events,
     ),
     components(
         schemas(
@@ -56,24 +147,83 @@ struct SecurityAddon;
             category::models::AddCategoryRequest,
             category::models::UpdateCategoryRequest,
             category::models::CategoryResponse,
+            category::models::DeleteCategoryRequest,
             ResponseDataCategory,
             album::models::Album,
             album::models::AlbumResponse,
             album::models::CreateAlbumRequest,
             album::models::UpdateAlbumRequest,
             album::models::GetAlbumRequest,
+            album::models::CategoryMatch,
+            album::models::TrendingAlbumsRequest,
+            album::models::TrendingAlbumResponse,
             album::models::AddAlbumImagesRequest,
             album::models::RemoveAlbumImagesRequest,
             ResponseDataAlbum,
+            ResponseDataTrendingAlbum,
+            creator::models::CreditRole,
+            creator::models::CreatorResponse,
+            creator::models::CreatorPageResponse,
+            creator::models::CreateCreatorRequest,
+            creator::models::UpdateCreatorRequest,
+            creator::models::GetCreatorsRequest,
+            creator::models::AlbumCreditResponse,
+            creator::models::AlbumCreditRequest,
+            creator::models::SetAlbumCreditsRequest,
+            ResponseDataCreator,
+            reader::models::AgeBracket,
+            reader::models::ReaderProfileResponse,
+            reader::models::CreateReaderProfileRequest,
+            reader::models::UpdateReaderProfileRequest,
+            reader::models::VerifyReaderAgeRequest,
+            reader::models::AgeRestrictionReason,
+            reader::models::AgeRestrictedResponse,
+            review::models::ReviewStatus,
+            review::models::ReviewResponse,
+            review::models::AddReviewRequest,
+            review::models::UpdateReviewRequest,
+            review::models::ModerateReviewRequest,
+            review::models::GetReviewsRequest,
+            ResponseDataReview,
+            translation::models::AlbumTranslationResponse,
+            translation::models::EpisodeTranslationResponse,
+            translation::models::SetAlbumTranslationRequest,
+            translation::models::SetEpisodeTranslationRequest,
             episode::models::Episode,
             episode::models::EpisodeResponse,
             episode::models::CreateEpisodeRequest,
             episode::models::UpdateEpisodeRequest,
             episode::models::FilterEpisodeRequest,
+            episode::models::ReorderEpisodesRequest,
+            episode::models::EpisodeManifest,
+            episode::models::ManifestAlbum,
+            episode::models::ManifestContent,
+            episode::models::ManifestRendition,
             content::models::AddEpisodeContentsRequest,
             content::models::UpdateContentRequest,
+            content::models::ReorderContentsRequest,
             content::models::ContentResponse,
             ResponseDataContent,
+            audit::models::AuditLogResponse,
+            ResponseDataAuditLog,
+            trash::models::TrashItemResponse,
+            ResponseDataTrashItem,
+            integrity::models::IntegrityReport,
+            integrity::models::BrokenItemResponse,
+            jobs::models::JobResponse,
+            jobs::models::GetJobsRequest,
+            ResponseDataJob,
+            webhooks::models::WebhookResponse,
+            webhooks::models::CreateWebhookRequest,
+            webhooks::models::UpdateWebhookRequest,
+            webhooks::models::WebhookDeliveryResponse,
+            webhooks::models::GetWebhookDeliveriesRequest,
+            ResponseDataWebhook,
+            ResponseDataWebhookDelivery,
+            events::models::CatalogEvent,
+            events::models::GetEventsRequest,
+            PageQuery,
+            SortOrder,
             ResponseMessage
         )
     ),
@@ -81,9 +231,19 @@ struct SecurityAddon;
     tags(
         (name = "Category", description = "Category"),
         (name = "Album", description = "Album"),
+        (name = "Creator", description = "Authors, artists, translators and publishers"),
+        (name = "Reader", description = "Reader profiles and age verification"),
+        (name = "Review", description = "Album ratings and moderated reviews"),
         (name = "Episode", description = "Episode"),
         (name = "Content", description = "Content"),
         (name = "HealthCheck", description = "Service Health Checking"),
+        (name = "Audit", description = "Audit log of admin mutations"),
+        (name = "Trash", description = "Restore or purge soft deleted items"),
+        (name = "Integrity", description = "Broken link and missing file checks"),
+        (name = "Media", description = "Stream stored files with byte range support"),
+        (name = "Jobs", description = "Inspect, retry and cancel background jobs"),
+        (name = "Webhooks", description = "Signed catalog event notifications"),
+        (name = "Events", description = "Live stream of catalog changes"),
     ),
 )]
 pub struct ApiDoc;
@@ -112,3 +272,308 @@ fn check_role(http_request: HttpRequest) -> Role {
         Unknown
     }
 }
+
+/// Age the request is checked against, from the reader profile in `X-Reader-Profile`. Admin
+/// requests are never age restricted.
+async fn reader_age(pool: &DbPool, http_request: &HttpRequest) -> ReaderAge {
+    if check_role(http_request.clone()) == Admin {
+        return ReaderAge::Unrestricted;
+    }
+    reader::services::Service::reader_age(pool, reader_profile_uuid(http_request)).await
+}
+
+/// Reader profile the request is sent as, from `X-Reader-Profile`
+fn reader_profile_uuid(http_request: &HttpRequest) -> Option<String> {
+    http_request
+        .headers()
+        .get("X-Reader-Profile")
+        .and_then(|h| h.to_str().ok())
+        .map(|uuid| uuid.trim().to_string())
+        .filter(|uuid| !uuid.is_empty())
+}
+
+/// 451 when the reader is younger than the album rating, 403 when the reader's age is unknown
+fn age_restricted_response(restriction: AgeRestriction) -> HttpResponse {
+    let status = match restriction.reason {
+        AgeRestrictionReason::AgeRestricted => StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS,
+        AgeRestrictionReason::AgeUnknown => StatusCode::FORBIDDEN,
+    };
+    HttpResponse::build(status).json(AgeRestrictedResponse::from_restriction(restriction))
+}
+
+/// Refusal of an album to a reader, `None` lets the request through. Disabled albums are
+/// answered with 404 as if they did not exist, rated albums with [`age_restricted_response`].
+/// Admin requests are never refused.
+fn album_refusal(reader_age: ReaderAge, enable: bool, min_age: i32) -> Option<HttpResponse> {
+    if reader_age == ReaderAge::Unrestricted {
+        return None;
+    }
+    if !enable {
+        return Some(HttpResponse::NotFound().json(ResponseMessage {
+            message: String::from("Album not found"),
+        }));
+    }
+    reader_age.check(min_age).err().map(age_restricted_response)
+}
+
+/// Refusal when the album of the episode is disabled or rated above the age of the reader
+/// sending the request, `None` lets the request through. Unknown episodes are answered by the
+/// endpoint.
+async fn episode_access_refusal(
+    pool: &DbPool,
+    http_request: &HttpRequest,
+    episode_uuid: &str,
+) -> Option<HttpResponse> {
+    let reader_age = reader_age(pool, http_request).await;
+    if reader_age == ReaderAge::Unrestricted {
+        return None;
+    }
+    match episode::services::Service::get_album_access_by_episode_uuid(
+        pool,
+        episode_uuid.to_string(),
+    )
+    .await
+    {
+        Ok(Some((enable, min_age))) => album_refusal(reader_age, enable, min_age),
+        Ok(None) => None,
+        Err(e) => {
+            println!("Failed to get album rating: {}", e);
+            Some(HttpResponse::InternalServerError().json(ResponseMessage {
+                message: String::from("Internal Server Error"),
+            }))
+        }
+    }
+}
+
+fn client_ip(http_request: &HttpRequest) -> Option<String> {
+    if config::get().rate_limit.trust_forwarded_for {
+        http_request
+            .connection_info()
+            .realip_remote_addr()
+            .map(|ip| ip.to_string())
+    } else {
+        http_request.peer_addr().map(|addr| addr.ip().to_string())
+    }
+}
+
+fn request_actor(http_request: &HttpRequest) -> String {
+    let role = http_request
+        .extensions()
+        .get::<String>()
+        .cloned()
+        .unwrap_or_else(|| "unknown".to_string());
+    let key_hint = http_request
+        .headers()
+        .get("X-API-KEY")
+        .and_then(|h| h.to_str().ok())
+        .map(|key| key.chars().rev().take(4).collect::<Vec<char>>())
+        .map(|chars| chars.into_iter().rev().collect::<String>())
+        .unwrap_or_default();
+    format!("{}:****{}", role, key_hint)
+}
+
+/// Record an admin mutation in the audit log, notify webhooks and broadcast it on `/events`
+async fn audit_mutation(
+    pool: &DbPool,
+    http_request: &HttpRequest,
+    action: &str,
+    entity_type: &str,
+    entity_uuid: String,
+    before: Option<Value>,
+    after: Option<Value>,
+) {
+    audit::services::Service::record(
+        pool,
+        request_actor(http_request),
+        client_ip(http_request),
+        action,
+        entity_type,
+        entity_uuid.clone(),
+        before.clone(),
+        after.clone(),
+    )
+    .await;
+    webhooks::services::Service::dispatch_mutation(
+        pool,
+        action,
+        entity_type,
+        &entity_uuid,
+        before.as_ref(),
+        after.as_ref(),
+    )
+    .await;
+    events::services::Service::publish_mutation(
+        pool,
+        action,
+        entity_type,
+        &entity_uuid,
+        before.as_ref(),
+        after.as_ref(),
+    )
+    .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{test, web, App, HttpMessage};
+    use diesel::prelude::*;
+    use diesel::sql_types::{Integer, Text};
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::common::middleware::api_key_middleware::ApiKeyMiddleware;
+    use crate::common::test_support::{self, ADMIN_API_KEY, USER_API_KEY};
+
+    /// Request sent with `role` as set by the API key middleware, reading as `reader_uuid`
+    fn request_as(role: &str, reader_uuid: Option<&str>) -> HttpRequest {
+        let mut request = test::TestRequest::default();
+        if let Some(reader_uuid) = reader_uuid {
+            request = request.insert_header(("X-Reader-Profile", reader_uuid));
+        }
+        let request = request.to_http_request();
+        request.extensions_mut().insert(role.to_string());
+        request
+    }
+
+    /// Episode of a new enabled album rated `min_age`
+    fn insert_rated_episode(pool: &DbPool, min_age: i32) -> String {
+        let album_uuid = uuid::Uuid::new_v4().to_string();
+        let episode_uuid = uuid::Uuid::new_v4().to_string();
+        let mut conn = pool.get().unwrap();
+        diesel::sql_query(
+            "INSERT INTO albums (uuid, category_id, title, description, images, url, \
+             content_type, width, height, bytes, min_age) \
+             VALUES (?, 1, 'Rated', '', '', '', '', 0, 0, 0, ?)",
+        )
+        .bind::<Text, _>(&album_uuid)
+        .bind::<Integer, _>(min_age)
+        .execute(&mut conn)
+        .unwrap();
+        diesel::sql_query(
+            "INSERT INTO episodes (album_id, uuid, title, content_type, width, height, bytes) \
+             SELECT id, ?, 'Episode', 'video/mp4', 0, 0, 0 FROM albums WHERE uuid = ?",
+        )
+        .bind::<Text, _>(&episode_uuid)
+        .bind::<Text, _>(&album_uuid)
+        .execute(&mut conn)
+        .unwrap();
+        episode_uuid
+    }
+
+    #[actix_web::test]
+    async fn episodes_of_rated_albums_are_refused_by_reader_age() {
+        let pool = test_support::test_pool();
+        let reader_uuid = uuid::Uuid::new_v4().to_string();
+        diesel::sql_query(
+            "INSERT INTO reader_profiles (uuid, display_name, birth_date) \
+             VALUES (?, 'Young', date('now', '-15 years'))",
+        )
+        .bind::<Text, _>(&reader_uuid)
+        .execute(&mut pool.get().unwrap())
+        .unwrap();
+        let all_ages = insert_rated_episode(&pool, 0);
+        let adult = insert_rated_episode(&pool, 18);
+
+        let status = |refusal: Option<HttpResponse>| refusal.map(|response| response.status());
+        let young = request_as("user", Some(&reader_uuid));
+        let anonymous = request_as("user", None);
+        let admin = request_as("admin", None);
+        assert_eq!(
+            status(episode_access_refusal(&pool, &young, &adult).await),
+            Some(StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS)
+        );
+        assert_eq!(
+            status(episode_access_refusal(&pool, &young, &all_ages).await),
+            None
+        );
+        assert_eq!(
+            status(episode_access_refusal(&pool, &anonymous, &adult).await),
+            Some(StatusCode::FORBIDDEN)
+        );
+        assert_eq!(
+            status(episode_access_refusal(&pool, &anonymous, &all_ages).await),
+            None
+        );
+        assert_eq!(
+            status(episode_access_refusal(&pool, &admin, &adult).await),
+            None
+        );
+        // Unknown episodes are left to the endpoint
+        assert_eq!(
+            status(episode_access_refusal(&pool, &young, "missing").await),
+            None
+        );
+    }
+
+    #[actix_web::test]
+    async fn readers_do_not_see_disabled_albums() {
+        let pool = test_support::test_pool();
+        let album_uuid = uuid::Uuid::new_v4().to_string();
+        let episode_uuid = uuid::Uuid::new_v4().to_string();
+        let mut conn = pool.get().unwrap();
+        diesel::sql_query(
+            "INSERT INTO albums (uuid, category_id, title, description, images, url, \
+             content_type, width, height, bytes, enable) \
+             VALUES (?, 1, 'Hidden', '', '', '', '', 0, 0, 0, 0)",
+        )
+        .bind::<Text, _>(&album_uuid)
+        .execute(&mut conn)
+        .unwrap();
+        diesel::sql_query(
+            "INSERT INTO episodes (album_id, uuid, title, content_type, width, height, bytes) \
+             VALUES (1, ?, 'Episode', 'video/mp4', 0, 0, 0)",
+        )
+        .bind::<Text, _>(&episode_uuid)
+        .execute(&mut conn)
+        .unwrap();
+        let app = test::init_service(
+            App::new()
+                .wrap(ApiKeyMiddleware::new(
+                    ADMIN_API_KEY.to_string(),
+                    USER_API_KEY.to_string(),
+                    vec![],
+                ))
+                .app_data(web::Data::new(pool.clone()))
+                .configure(super::config_routes),
+        )
+        .await;
+
+        let list = |api_key: &'static str| {
+            test::TestRequest::post()
+                .uri("/albums")
+                .insert_header(("X-API-KEY", api_key))
+                .set_json(json!({ "enable": false }))
+                .to_request()
+        };
+        let response = test::call_service(&app, list(USER_API_KEY)).await;
+        assert_eq!(response.status(), 200);
+        let albums: Value = test::read_body_json(response).await;
+        assert_eq!(albums["data"].as_array().unwrap().len(), 0);
+        let albums: Value = test::call_and_read_body_json(&app, list(ADMIN_API_KEY)).await;
+        assert_eq!(albums["data"][0]["uuid"], album_uuid.as_str());
+
+        for uri in [
+            format!("/albums/{}", album_uuid),
+            String::from("/episodes/1"),
+            format!("/episode/{}", episode_uuid),
+            format!("/episodes/{}/manifest", episode_uuid),
+            format!("/contents/{}", episode_uuid),
+        ] {
+            let request = test::TestRequest::get()
+                .uri(&uri)
+                .insert_header(("X-API-KEY", USER_API_KEY))
+                .to_request();
+            assert_eq!(
+                test::call_service(&app, request).await.status(),
+                404,
+                "{}",
+                uri
+            );
+        }
+        let request = test::TestRequest::get()
+            .uri(&format!("/albums/{}", album_uuid))
+            .insert_header(("X-API-KEY", ADMIN_API_KEY))
+            .to_request();
+        assert_eq!(test::call_service(&app, request).await.status(), 200);
+    }
+}
@@ -0,0 +1,240 @@
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+
+use crate::common::database::DbPool;
+use crate::common::enums::Role::{Admin, Unknown};
+use crate::common::models::response_message::ResponseMessage;
+use crate::features::reader::models::{
+    CreateReaderProfileRequest, ReaderError, UpdateReaderProfileRequest, VerifyReaderAgeRequest,
+};
+use crate::features::reader::services::Service;
+use crate::features::{audit_mutation, check_role};
+
+/// Create Reader Profile
+///
+/// Add a reader profile, send its uuid in `X-Reader-Profile` so rated albums are checked
+/// against the reader's age
+#[utoipa::path(
+    post,
+    path = "/reader-profile",
+    request_body = CreateReaderProfileRequest,
+    responses(
+        (status = 201, description = "Created successfully", body = ReaderProfileResponse),
+        (status = 400, description = "Invalid reader profile", body = ResponseMessage),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Reader",
+)]
+#[post("/reader-profile")]
+pub async fn create_reader_profile(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    req: web::Json<CreateReaderProfileRequest>,
+) -> impl Responder {
+    if check_role(http_request) != Unknown {
+        match Service::add_profile(&pool, req.into_inner()).await {
+            Ok(profile) => HttpResponse::Created().json(profile),
+            Err(e) => reader_error_response("create", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Get Reader Profile
+///
+/// Get reader profile with the age album ratings are checked against
+#[utoipa::path(
+    get,
+    path = "/reader-profiles/{profile_uuid}",
+    params(
+        ("profile_uuid" = String, Path, description = "Reader profile ID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e")
+    ),
+    responses(
+        (status = 200, description = "Get successfully", body = ReaderProfileResponse),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 404, description = "Reader profile not found", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Reader",
+)]
+#[get("/reader-profiles/{profile_uuid}")]
+pub async fn get_reader_profile(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+) -> impl Responder {
+    if check_role(http_request) != Unknown {
+        match Service::get_profile_by_uuid(&pool, path.into_inner()).await {
+            Ok(profile) => HttpResponse::Ok().json(profile),
+            Err(e) => reader_error_response("get", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Update Reader Profile
+///
+/// Update the display name or declared birth date, fields left empty keep their value
+#[utoipa::path(
+    put,
+    path = "/reader-profile/{profile_uuid}",
+    params(
+        ("profile_uuid" = String, Path, description = "Reader profile ID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e")
+    ),
+    request_body = UpdateReaderProfileRequest,
+    responses(
+        (status = 200, description = "Update successfully", body = ReaderProfileResponse),
+        (status = 400, description = "Invalid reader profile", body = ResponseMessage),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 404, description = "Reader profile not found", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Reader",
+)]
+#[put("/{profile_uuid}")]
+pub async fn update_reader_profile(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+    req: web::Json<UpdateReaderProfileRequest>,
+) -> impl Responder {
+    if check_role(http_request) != Unknown {
+        match Service::update_profile(&pool, path.into_inner(), req.into_inner()).await {
+            Ok(profile) => HttpResponse::Ok().json(profile),
+            Err(e) => reader_error_response("update", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Verify Reader Age
+///
+/// Record the age bracket an admin checked, it takes precedence over the declared birth date.
+/// `null` withdraws the verification.
+#[utoipa::path(
+    put,
+    path = "/reader-profile/{profile_uuid}/age-verification",
+    params(
+        ("profile_uuid" = String, Path, description = "Reader profile ID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e")
+    ),
+    request_body = VerifyReaderAgeRequest,
+    responses(
+        (status = 200, description = "Verified successfully", body = ReaderProfileResponse),
+        (status = 400, description = "Invalid age bracket", body = ResponseMessage),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 404, description = "Reader profile not found", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Reader",
+)]
+#[put("/{profile_uuid}/age-verification")]
+pub async fn verify_reader_age(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+    req: web::Json<VerifyReaderAgeRequest>,
+) -> impl Responder {
+    if check_role(http_request.clone()) == Admin {
+        let profile_uuid = path.into_inner();
+        let before = Service::get_profile_by_uuid(&pool, profile_uuid.clone())
+            .await
+            .ok()
+            .and_then(|profile| serde_json::to_value(profile).ok());
+        match Service::verify_age(&pool, profile_uuid, req.into_inner()).await {
+            Ok(profile) => {
+                audit_mutation(
+                    &pool,
+                    &http_request,
+                    "verify_age",
+                    "reader_profile",
+                    profile.uuid.clone(),
+                    before,
+                    serde_json::to_value(&profile).ok(),
+                )
+                .await;
+                HttpResponse::Ok().json(profile)
+            }
+            Err(e) => reader_error_response("verify", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Delete Reader Profile
+///
+/// Delete reader profile
+#[utoipa::path(
+    delete,
+    path = "/reader-profile/{profile_uuid}",
+    params(
+        ("profile_uuid" = String, Path, description = "Reader profile ID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e")
+    ),
+    responses(
+        (status = 204, description = "Delete successfully"),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 404, description = "Reader profile not found", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Reader",
+)]
+#[delete("/{profile_uuid}")]
+pub async fn delete_reader_profile(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+) -> impl Responder {
+    if check_role(http_request) != Unknown {
+        match Service::delete_profile(&pool, path.into_inner()).await {
+            Ok(_) => HttpResponse::NoContent().json(""),
+            Err(e) => reader_error_response("delete", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+fn reader_error_response(action: &str, e: ReaderError) -> HttpResponse {
+    match e {
+        ReaderError::NotFound => HttpResponse::NotFound().json(ResponseMessage {
+            message: e.to_string(),
+        }),
+        ReaderError::Invalid(_) => HttpResponse::BadRequest().json(ResponseMessage {
+            message: e.to_string(),
+        }),
+        ReaderError::Database(_) => {
+            println!("Failed to {} reader profile: {}", action, e);
+            HttpResponse::InternalServerError().json(ResponseMessage {
+                message: String::from("Internal Server Error"),
+            })
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use actix_web::web;
+
+use crate::features::reader::controllers::*;
+
+pub mod controllers;
+pub mod models;
+pub mod repository;
+pub mod services;
+
+// ! Register ever routes in this configure function
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(create_reader_profile);
+    cfg.service(get_reader_profile);
+
+    cfg.service(
+        web::scope("/reader-profile")
+            .service(update_reader_profile)
+            .service(verify_reader_age)
+            .service(delete_reader_profile),
+    );
+}
@@ -0,0 +1,356 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Utc};
+use diesel::{Insertable, Queryable, Selectable};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::common::ne_parse::NEParse;
+use crate::schema::reader_profiles;
+
+/// Age bracket of a reader, checked by an admin
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+pub enum AgeBracket {
+    #[serde(rename = "under_13")]
+    Under13,
+    #[serde(rename = "13_to_15")]
+    From13To15,
+    #[serde(rename = "16_to_17")]
+    From16To17,
+    #[serde(rename = "18_plus")]
+    Adult,
+}
+
+impl AgeBracket {
+    pub const ALL: [AgeBracket; 4] = [
+        AgeBracket::Under13,
+        AgeBracket::From13To15,
+        AgeBracket::From16To17,
+        AgeBracket::Adult,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AgeBracket::Under13 => "under_13",
+            AgeBracket::From13To15 => "13_to_15",
+            AgeBracket::From16To17 => "16_to_17",
+            AgeBracket::Adult => "18_plus",
+        }
+    }
+
+    /// Youngest age of the bracket, the age ratings are checked against
+    pub fn min_age(&self) -> i32 {
+        match self {
+            AgeBracket::Under13 => 0,
+            AgeBracket::From13To15 => 13,
+            AgeBracket::From16To17 => 16,
+            AgeBracket::Adult => 18,
+        }
+    }
+}
+
+impl FromStr for AgeBracket {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        AgeBracket::ALL
+            .into_iter()
+            .find(|bracket| bracket.as_str() == value)
+            .ok_or_else(|| {
+                format!(
+                    "Invalid age bracket {:?}, expected one of {}",
+                    value,
+                    AgeBracket::ALL.map(|bracket| bracket.as_str()).join(", ")
+                )
+            })
+    }
+}
+
+#[derive(Debug, Queryable, Selectable, Clone, PartialEq, Eq)]
+#[diesel(table_name = reader_profiles)]
+pub struct ReaderProfile {
+    pub id: Option<i32>,
+    pub uuid: String,
+    pub display_name: String,
+    pub birth_date: Option<NaiveDate>,
+    pub age_bracket: Option<String>,
+    pub age_verified_at: Option<NaiveDateTime>,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+impl ReaderProfile {
+    /// Verified age bracket, rows with a bracket written outside the API count as unverified
+    pub fn verified_bracket(&self) -> Option<AgeBracket> {
+        self.age_bracket
+            .as_deref()
+            .and_then(|bracket| bracket.parse().ok())
+    }
+
+    /// Age the ratings are checked against, the verified bracket before the declared birth date
+    pub fn age(&self) -> Option<i32> {
+        match self.verified_bracket() {
+            Some(bracket) => Some(bracket.min_age()),
+            None => self
+                .birth_date
+                .map(|birth_date| age_on(birth_date, Utc::now().date_naive())),
+        }
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = reader_profiles)]
+pub struct NewReaderProfile {
+    pub uuid: String,
+    pub display_name: String,
+    pub birth_date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, PartialEq, Eq)]
+pub struct ReaderProfileResponse {
+    /// Sent in `X-Reader-Profile` to read as this profile
+    pub uuid: String,
+    pub display_name: String,
+    #[schema(example = "2008-05-17")]
+    pub birth_date: Option<String>,
+    /// Age bracket checked by an admin, takes precedence over the birth date
+    pub age_bracket: Option<AgeBracket>,
+    pub age_verified_at: Option<String>,
+    /// Age the album ratings are checked against, unknown without birth date or verified bracket
+    pub age: Option<i32>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+impl ReaderProfileResponse {
+    pub fn from_profile(profile: ReaderProfile) -> Self {
+        ReaderProfileResponse {
+            age: profile.age(),
+            age_bracket: profile.verified_bracket(),
+            uuid: profile.uuid,
+            display_name: profile.display_name,
+            birth_date: profile
+                .birth_date
+                .map(|birth_date| birth_date.format("%Y-%m-%d").to_string()),
+            age_verified_at: NEParse::opt_naive_datetime_to_utc_opt_string(profile.age_verified_at),
+            created_at: NEParse::opt_naive_datetime_to_utc_opt_string(profile.created_at),
+            updated_at: NEParse::opt_naive_datetime_to_utc_opt_string(profile.updated_at),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreateReaderProfileRequest {
+    #[schema(example = "Reader")]
+    pub display_name: Option<String>,
+    /// Declared birth date, `YYYY-MM-DD`
+    #[schema(example = "2008-05-17")]
+    pub birth_date: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UpdateReaderProfileRequest {
+    pub display_name: Option<String>,
+    /// Declared birth date, `YYYY-MM-DD`, an empty string removes it
+    #[schema(example = "2008-05-17")]
+    pub birth_date: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct VerifyReaderAgeRequest {
+    /// Checked age bracket, `null` withdraws the verification
+    pub age_bracket: Option<AgeBracket>,
+}
+
+/// Age rules a request is served under
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReaderAge {
+    /// Admin requests see every album
+    Unrestricted,
+    /// Age of the reader profile sent with the request
+    Known(i32),
+    /// No reader profile, or a profile without birth date or verified bracket
+    Unknown,
+}
+
+impl ReaderAge {
+    /// Highest album `min_age` the reader may see, `None` without a limit. Readers of unknown
+    /// age only see albums rated for all ages.
+    pub fn max_min_age(&self) -> Option<i32> {
+        match self {
+            ReaderAge::Unrestricted => None,
+            ReaderAge::Known(age) => Some(*age),
+            ReaderAge::Unknown => Some(0),
+        }
+    }
+
+    pub fn check(&self, min_age: i32) -> Result<(), AgeRestriction> {
+        match self {
+            ReaderAge::Unrestricted => Ok(()),
+            ReaderAge::Known(age) if *age >= min_age => Ok(()),
+            ReaderAge::Known(_) => Err(AgeRestriction {
+                reason: AgeRestrictionReason::AgeRestricted,
+                min_age,
+            }),
+            ReaderAge::Unknown if min_age <= 0 => Ok(()),
+            ReaderAge::Unknown => Err(AgeRestriction {
+                reason: AgeRestrictionReason::AgeUnknown,
+                min_age,
+            }),
+        }
+    }
+}
+
+/// Why an album was refused to the reader
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AgeRestrictionReason {
+    /// The reader is younger than the album rating, answered with 451
+    AgeRestricted,
+    /// The album is rated and the reader's age is unknown, answered with 403
+    AgeUnknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AgeRestriction {
+    pub reason: AgeRestrictionReason,
+    pub min_age: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, PartialEq, Eq)]
+pub struct AgeRestrictedResponse {
+    pub message: String,
+    pub reason: AgeRestrictionReason,
+    /// Rating of the album
+    #[schema(example = 18)]
+    pub min_age: i32,
+}
+
+impl AgeRestrictedResponse {
+    pub fn from_restriction(restriction: AgeRestriction) -> Self {
+        let message = match restriction.reason {
+            AgeRestrictionReason::AgeRestricted => format!(
+                "Rated {}+, not available at the reader's age",
+                restriction.min_age
+            ),
+            AgeRestrictionReason::AgeUnknown => format!(
+                "Rated {}+, send a reader profile with a birth date or verified age",
+                restriction.min_age
+            ),
+        };
+        AgeRestrictedResponse {
+            message,
+            reason: restriction.reason,
+            min_age: restriction.min_age,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ReaderError {
+    NotFound,
+    Invalid(String),
+    Database(diesel::result::Error),
+}
+
+impl fmt::Display for ReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReaderError::NotFound => write!(f, "Reader profile not found"),
+            ReaderError::Invalid(message) => write!(f, "{}", message),
+            ReaderError::Database(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<diesel::result::Error> for ReaderError {
+    fn from(e: diesel::result::Error) -> Self {
+        match e {
+            diesel::result::Error::NotFound => ReaderError::NotFound,
+            e => ReaderError::Database(e),
+        }
+    }
+}
+
+/// Whole years from `birth_date` to `today`
+pub fn age_on(birth_date: NaiveDate, today: NaiveDate) -> i32 {
+    let age = today.year() - birth_date.year();
+    if (today.month(), today.day()) < (birth_date.month(), birth_date.day()) {
+        age - 1
+    } else {
+        age
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn profile(birth_date: Option<&str>, age_bracket: Option<&str>) -> ReaderProfile {
+        ReaderProfile {
+            id: Some(1),
+            uuid: String::from("reader"),
+            display_name: String::from("Reader"),
+            birth_date: birth_date.map(date),
+            age_bracket: age_bracket.map(String::from),
+            age_verified_at: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn age_turns_over_on_the_birthday() {
+        let birth_date = date("2008-05-17");
+        assert_eq!(age_on(birth_date, date("2026-05-16")), 17);
+        assert_eq!(age_on(birth_date, date("2026-05-17")), 18);
+        assert_eq!(age_on(birth_date, date("2026-12-31")), 18);
+        // Born on 29 February, a year older from 1 March in other years
+        let leap_day = date("2008-02-29");
+        assert_eq!(age_on(leap_day, date("2026-02-28")), 17);
+        assert_eq!(age_on(leap_day, date("2026-03-01")), 18);
+    }
+
+    #[test]
+    fn verified_bracket_takes_precedence_over_birth_date() {
+        assert_eq!(
+            profile(Some("1990-01-01"), Some("13_to_15")).age(),
+            Some(13)
+        );
+        assert_eq!(profile(None, Some("18_plus")).age(), Some(18));
+        // A bracket not written through the API is ignored
+        let declared = profile(Some("2000-01-01"), Some("adult"));
+        assert_eq!(
+            declared.age(),
+            Some(age_on(date("2000-01-01"), Utc::now().date_naive()))
+        );
+        assert_eq!(profile(None, None).age(), None);
+    }
+
+    #[test]
+    fn ratings_are_checked_against_the_reader_age() {
+        assert_eq!(ReaderAge::Unrestricted.check(18), Ok(()));
+        assert_eq!(ReaderAge::Known(18).check(18), Ok(()));
+        assert_eq!(
+            ReaderAge::Known(17).check(18),
+            Err(AgeRestriction {
+                reason: AgeRestrictionReason::AgeRestricted,
+                min_age: 18,
+            })
+        );
+        assert_eq!(ReaderAge::Unknown.check(0), Ok(()));
+        assert_eq!(ReaderAge::Unknown.check(-1), Ok(()));
+        assert_eq!(
+            ReaderAge::Unknown.check(13),
+            Err(AgeRestriction {
+                reason: AgeRestrictionReason::AgeUnknown,
+                min_age: 13,
+            })
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use chrono::Utc;
+use diesel::prelude::*;
+
+use crate::common::database::DbPool;
+use crate::features::reader::models::{NewReaderProfile, ReaderProfile};
+use crate::schema::reader_profiles;
+
+pub struct Repository;
+
+impl Repository {
+    pub async fn create_profile(
+        pool: &DbPool,
+        new_profile: NewReaderProfile,
+    ) -> QueryResult<ReaderProfile> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        let profile_uuid = new_profile.uuid.clone();
+        diesel::insert_into(reader_profiles::table)
+            .values(&new_profile)
+            .execute(&mut conn)?;
+        reader_profiles::table
+            .filter(reader_profiles::uuid.eq(profile_uuid))
+            .select(ReaderProfile::as_select())
+            .first(&mut conn)
+    }
+
+    pub async fn get_profile_by_uuid(
+        pool: &DbPool,
+        profile_uuid: String,
+    ) -> QueryResult<ReaderProfile> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        reader_profiles::table
+            .filter(reader_profiles::uuid.eq(profile_uuid))
+            .select(ReaderProfile::as_select())
+            .first(&mut conn)
+    }
+
+    pub async fn update_profile(
+        pool: &DbPool,
+        profile: ReaderProfile,
+    ) -> QueryResult<ReaderProfile> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        diesel::update(reader_profiles::table.filter(reader_profiles::id.eq(profile.id)))
+            .set((
+                reader_profiles::display_name.eq(&profile.display_name),
+                reader_profiles::birth_date.eq(profile.birth_date),
+                reader_profiles::age_bracket.eq(&profile.age_bracket),
+                reader_profiles::age_verified_at.eq(profile.age_verified_at),
+                reader_profiles::updated_at.eq(Some(Utc::now().naive_utc())),
+            ))
+            .execute(&mut conn)?;
+        reader_profiles::table
+            .filter(reader_profiles::id.eq(profile.id))
+            .select(ReaderProfile::as_select())
+            .first(&mut conn)
+    }
+
+    pub async fn delete_profile(pool: &DbPool, profile_id: i32) -> QueryResult<usize> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        diesel::delete(reader_profiles::table.filter(reader_profiles::id.eq(profile_id)))
+            .execute(&mut conn)
+    }
+}
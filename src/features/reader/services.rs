@@ -0,0 +1,114 @@
+use chrono::{NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::common::database::DbPool;
+use crate::features::reader::models::{
+    CreateReaderProfileRequest, NewReaderProfile, ReaderAge, ReaderError, ReaderProfileResponse,
+    UpdateReaderProfileRequest, VerifyReaderAgeRequest,
+};
+use crate::features::reader::repository::Repository;
+use crate::features::review::services::Service as ReviewService;
+
+pub struct Service;
+
+impl Service {
+    pub async fn add_profile(
+        pool: &DbPool,
+        req: CreateReaderProfileRequest,
+    ) -> Result<ReaderProfileResponse, ReaderError> {
+        let birth_date = match req.birth_date.filter(|date| !date.trim().is_empty()) {
+            Some(birth_date) => Some(parse_birth_date(&birth_date)?),
+            None => None,
+        };
+        let profile = Repository::create_profile(
+            pool,
+            NewReaderProfile {
+                uuid: Uuid::new_v4().to_string(),
+                display_name: req.display_name.unwrap_or_default().trim().to_string(),
+                birth_date,
+            },
+        )
+        .await?;
+        Ok(ReaderProfileResponse::from_profile(profile))
+    }
+
+    pub async fn get_profile_by_uuid(
+        pool: &DbPool,
+        profile_uuid: String,
+    ) -> Result<ReaderProfileResponse, ReaderError> {
+        let profile = Repository::get_profile_by_uuid(pool, profile_uuid).await?;
+        Ok(ReaderProfileResponse::from_profile(profile))
+    }
+
+    pub async fn update_profile(
+        pool: &DbPool,
+        profile_uuid: String,
+        req: UpdateReaderProfileRequest,
+    ) -> Result<ReaderProfileResponse, ReaderError> {
+        let mut profile = Repository::get_profile_by_uuid(pool, profile_uuid).await?;
+        if let Some(display_name) = req.display_name {
+            profile.display_name = display_name.trim().to_string();
+        }
+        if let Some(birth_date) = req.birth_date {
+            profile.birth_date = match birth_date.trim() {
+                "" => None,
+                birth_date => Some(parse_birth_date(birth_date)?),
+            };
+        }
+        let profile = Repository::update_profile(pool, profile).await?;
+        Ok(ReaderProfileResponse::from_profile(profile))
+    }
+
+    /// Record the age bracket an admin checked, or withdraw the verification
+    pub async fn verify_age(
+        pool: &DbPool,
+        profile_uuid: String,
+        req: VerifyReaderAgeRequest,
+    ) -> Result<ReaderProfileResponse, ReaderError> {
+        let mut profile = Repository::get_profile_by_uuid(pool, profile_uuid).await?;
+        profile.age_bracket = req.age_bracket.map(|bracket| bracket.as_str().to_string());
+        profile.age_verified_at = req.age_bracket.map(|_| Utc::now().naive_utc());
+        let profile = Repository::update_profile(pool, profile).await?;
+        Ok(ReaderProfileResponse::from_profile(profile))
+    }
+
+    /// Delete the reader profile with its reviews, the ratings leave the album averages
+    pub async fn delete_profile(pool: &DbPool, profile_uuid: String) -> Result<(), ReaderError> {
+        let profile = Repository::get_profile_by_uuid(pool, profile_uuid).await?;
+        let profile_id = profile.id.unwrap_or_default();
+        ReviewService::delete_reviews_by_reader(pool, profile_id).await?;
+        Repository::delete_profile(pool, profile_id).await?;
+        Ok(())
+    }
+
+    /// Age of the reader profile, unknown without a profile or when the profile does not exist
+    pub async fn reader_age(pool: &DbPool, profile_uuid: Option<String>) -> ReaderAge {
+        let Some(profile_uuid) = profile_uuid else {
+            return ReaderAge::Unknown;
+        };
+        match Repository::get_profile_by_uuid(pool, profile_uuid).await {
+            Ok(profile) => profile.age().map_or(ReaderAge::Unknown, ReaderAge::Known),
+            Err(diesel::result::Error::NotFound) => ReaderAge::Unknown,
+            Err(e) => {
+                eprintln!("Failed to get reader profile: {}", e);
+                ReaderAge::Unknown
+            }
+        }
+    }
+}
+
+/// Birth date in `YYYY-MM-DD`, not in the future
+fn parse_birth_date(value: &str) -> Result<NaiveDate, ReaderError> {
+    let birth_date = NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").map_err(|_| {
+        ReaderError::Invalid(format!(
+            "Invalid birth date {:?}, expected YYYY-MM-DD",
+            value
+        ))
+    })?;
+    if birth_date > Utc::now().date_naive() {
+        return Err(ReaderError::Invalid(String::from(
+            "Birth date cannot be in the future",
+        )));
+    }
+    Ok(birth_date)
+}
@@ -0,0 +1,383 @@
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+
+use crate::common::database::DbPool;
+use crate::common::enums::Role::{Admin, Unknown};
+use crate::common::models::response_message::ResponseMessage;
+use crate::features::reader::models::ReaderAge;
+use crate::features::review::models::{
+    AddReviewRequest, GetReviewsRequest, ModerateReviewRequest, ReviewError, UpdateReviewRequest,
+};
+use crate::features::review::services::Service;
+use crate::features::{
+    age_restricted_response, audit_mutation, check_role, reader_age, reader_profile_uuid,
+};
+
+/// Get Album Reviews
+///
+/// Get the reviews of the album, newest first. Readers get the approved reviews, admins can
+/// filter by status.
+#[utoipa::path(
+    get,
+    path = "/albums/{album_uuid}/reviews",
+    params(
+        ("album_uuid" = String, Path, description = "Album ID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e"),
+        GetReviewsRequest
+    ),
+    responses(
+        (status = 200, description = "Get successfully", body = ResponseDataReview),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 403, description = "Album is rated and the reader's age is unknown", body = AgeRestrictedResponse),
+        (status = 404, description = "Album not found", body = ResponseMessage),
+        (status = 451, description = "Album is rated above the reader's age", body = AgeRestrictedResponse),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Review",
+)]
+#[get("/albums/{album_uuid}/reviews")]
+pub async fn get_album_reviews(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<GetReviewsRequest>,
+) -> impl Responder {
+    if check_role(http_request.clone()) != Unknown {
+        let reader_age = reader_age(&pool, &http_request).await;
+        match Service::get_album_reviews(&pool, path.into_inner(), reader_age, query.into_inner())
+            .await
+        {
+            Ok(reviews) => HttpResponse::Ok().json(reviews),
+            Err(e) => review_error_response("get", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Get Reviews
+///
+/// Get the reviews of every album, newest first. Filter by `pending` for the moderation queue.
+#[utoipa::path(
+    get,
+    path = "/reviews",
+    params(
+        GetReviewsRequest
+    ),
+    responses(
+        (status = 200, description = "Get successfully", body = ResponseDataReview),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Review",
+)]
+#[get("/reviews")]
+pub async fn get_reviews(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    query: web::Query<GetReviewsRequest>,
+) -> impl Responder {
+    if check_role(http_request) == Admin {
+        match Service::get_reviews(&pool, query.into_inner()).await {
+            Ok(reviews) => HttpResponse::Ok().json(reviews),
+            Err(e) => review_error_response("get", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Get Review
+///
+/// Get review. Reviews not approved yet are only found by their author and admins.
+#[utoipa::path(
+    get,
+    path = "/reviews/{review_uuid}",
+    params(
+        ("review_uuid" = String, Path, description = "Review ID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e")
+    ),
+    responses(
+        (status = 200, description = "Get successfully", body = ReviewResponse),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 403, description = "Album is rated and the reader's age is unknown", body = AgeRestrictedResponse),
+        (status = 404, description = "Review not found", body = ResponseMessage),
+        (status = 451, description = "Album is rated above the reader's age", body = AgeRestrictedResponse),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Review",
+)]
+#[get("/reviews/{review_uuid}")]
+pub async fn get_review(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+) -> impl Responder {
+    if check_role(http_request.clone()) != Unknown {
+        let reader_age = reader_age(&pool, &http_request).await;
+        let reader_uuid = reader_profile_uuid(&http_request);
+        match Service::get_review_by_uuid(&pool, path.into_inner(), reader_uuid, reader_age).await {
+            Ok(review) => HttpResponse::Ok().json(review),
+            Err(e) => review_error_response("get", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Add Review
+///
+/// Rate the album as the reader profile in `X-Reader-Profile`, once per album. A bare rating
+/// is approved and counts right away, a written review waits for moderation before it is listed
+/// and counted.
+#[utoipa::path(
+    post,
+    path = "/album/{album_uuid}/review",
+    params(
+        ("album_uuid" = String, Path, description = "Album ID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e"),
+        ("X-Reader-Profile" = String, Header, description = "Reader profile ID")
+    ),
+    request_body = AddReviewRequest,
+    responses(
+        (status = 201, description = "Created successfully", body = ReviewResponse),
+        (status = 400, description = "Invalid review or reader profile", body = ResponseMessage),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 403, description = "Album is rated and the reader's age is unknown", body = AgeRestrictedResponse),
+        (status = 404, description = "Album not found", body = ResponseMessage),
+        (status = 409, description = "The reader already reviewed the album", body = ResponseMessage),
+        (status = 451, description = "Album is rated above the reader's age", body = AgeRestrictedResponse),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Review",
+)]
+#[post("/album/{album_uuid}/review")]
+pub async fn add_review(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+    req: web::Json<AddReviewRequest>,
+) -> impl Responder {
+    if check_role(http_request.clone()) != Unknown {
+        let reader_age = reader_age(&pool, &http_request).await;
+        let reader_uuid = reader_profile_uuid(&http_request);
+        match Service::add_review(
+            &pool,
+            path.into_inner(),
+            reader_uuid,
+            reader_age,
+            req.into_inner(),
+        )
+        .await
+        {
+            Ok(review) => HttpResponse::Created().json(review),
+            Err(e) => review_error_response("add", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Update Review
+///
+/// Change the rating or text of the review written as the reader profile in `X-Reader-Profile`.
+/// Changed text waits for moderation again.
+#[utoipa::path(
+    put,
+    path = "/review/{review_uuid}",
+    params(
+        ("review_uuid" = String, Path, description = "Review ID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e"),
+        ("X-Reader-Profile" = String, Header, description = "Reader profile ID")
+    ),
+    request_body = UpdateReviewRequest,
+    responses(
+        (status = 200, description = "Update successfully", body = ReviewResponse),
+        (status = 400, description = "Invalid review or reader profile", body = ResponseMessage),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 403, description = "The review belongs to another reader", body = ResponseMessage),
+        (status = 404, description = "Review not found", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Review",
+)]
+#[put("/{review_uuid}")]
+pub async fn update_review(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+    req: web::Json<UpdateReviewRequest>,
+) -> impl Responder {
+    if check_role(http_request.clone()) != Unknown {
+        let reader_uuid = reader_profile_uuid(&http_request);
+        match Service::update_review(&pool, path.into_inner(), reader_uuid, req.into_inner()).await
+        {
+            Ok(review) => HttpResponse::Ok().json(review),
+            Err(e) => review_error_response("update", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Moderate Review
+///
+/// Approve or reject the review. Only approved reviews are listed to readers and count toward
+/// the album average.
+#[utoipa::path(
+    put,
+    path = "/review/{review_uuid}/moderation",
+    params(
+        ("review_uuid" = String, Path, description = "Review ID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e")
+    ),
+    request_body = ModerateReviewRequest,
+    responses(
+        (status = 200, description = "Moderated successfully", body = ReviewResponse),
+        (status = 400, description = "Invalid review status", body = ResponseMessage),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 404, description = "Review not found", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Review",
+)]
+#[put("/{review_uuid}/moderation")]
+pub async fn moderate_review(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+    req: web::Json<ModerateReviewRequest>,
+) -> impl Responder {
+    if check_role(http_request.clone()) == Admin {
+        let review_uuid = path.into_inner();
+        let before =
+            Service::get_review_by_uuid(&pool, review_uuid.clone(), None, ReaderAge::Unrestricted)
+                .await
+                .ok()
+                .and_then(|review| serde_json::to_value(review).ok());
+        match Service::moderate_review(&pool, review_uuid, req.into_inner()).await {
+            Ok(review) => {
+                audit_mutation(
+                    &pool,
+                    &http_request,
+                    "moderate",
+                    "review",
+                    review.uuid.clone(),
+                    before,
+                    serde_json::to_value(&review).ok(),
+                )
+                .await;
+                HttpResponse::Ok().json(review)
+            }
+            Err(e) => review_error_response("moderate", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Delete Review
+///
+/// Delete the review written as the reader profile in `X-Reader-Profile`, admins can delete any
+/// review
+#[utoipa::path(
+    delete,
+    path = "/review/{review_uuid}",
+    params(
+        ("review_uuid" = String, Path, description = "Review ID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e"),
+        ("X-Reader-Profile" = Option<String>, Header, description = "Reader profile ID, not needed for admins")
+    ),
+    responses(
+        (status = 204, description = "Delete successfully"),
+        (status = 400, description = "Invalid reader profile", body = ResponseMessage),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 403, description = "The review belongs to another reader", body = ResponseMessage),
+        (status = 404, description = "Review not found", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Review",
+)]
+#[delete("/{review_uuid}")]
+pub async fn delete_review(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+) -> impl Responder {
+    let role = check_role(http_request.clone());
+    if role != Unknown {
+        let reader_uuid = reader_profile_uuid(&http_request);
+        match Service::delete_review(&pool, path.into_inner(), reader_uuid, role == Admin).await {
+            Ok(review) => {
+                if role == Admin {
+                    audit_mutation(
+                        &pool,
+                        &http_request,
+                        "delete",
+                        "review",
+                        review.uuid.clone(),
+                        serde_json::to_value(&review).ok(),
+                        None,
+                    )
+                    .await;
+                }
+                HttpResponse::NoContent().json("")
+            }
+            Err(e) => review_error_response("delete", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+fn review_error_response(action: &str, e: ReviewError) -> HttpResponse {
+    match e {
+        ReviewError::NotFound(_) => HttpResponse::NotFound().json(ResponseMessage {
+            message: e.to_string(),
+        }),
+        ReviewError::Invalid(_) => HttpResponse::BadRequest().json(ResponseMessage {
+            message: e.to_string(),
+        }),
+        ReviewError::Conflict(_) => HttpResponse::Conflict().json(ResponseMessage {
+            message: e.to_string(),
+        }),
+        ReviewError::Forbidden => HttpResponse::Forbidden().json(ResponseMessage {
+            message: e.to_string(),
+        }),
+        ReviewError::AgeRestricted(restriction) => age_restricted_response(restriction),
+        ReviewError::Database(_) => {
+            println!("Failed to {} review: {}", action, e);
+            HttpResponse::InternalServerError().json(ResponseMessage {
+                message: String::from("Internal Server Error"),
+            })
+        }
+    }
+}
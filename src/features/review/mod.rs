@@ -0,0 +1,23 @@
+use actix_web::web;
+
+use crate::features::review::controllers::*;
+
+pub mod controllers;
+pub mod models;
+pub mod repository;
+pub mod services;
+
+// ! Register ever routes in this configure function
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_album_reviews);
+    cfg.service(add_review);
+    cfg.service(get_reviews);
+    cfg.service(get_review);
+
+    cfg.service(
+        web::scope("/review")
+            .service(update_review)
+            .service(moderate_review)
+            .service(delete_review),
+    );
+}
@@ -0,0 +1,220 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::NaiveDateTime;
+use diesel::{Insertable, Queryable, Selectable};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::common::ne_parse::NEParse;
+use crate::features::reader::models::AgeRestriction;
+use crate::schema::album_reviews;
+
+/// Longest review text accepted, in characters
+pub const MAX_REVIEW_LENGTH: usize = 5000;
+
+/// Most reviews returned in one page
+pub const MAX_REVIEWS_LIMIT: i64 = 100;
+
+/// Moderation state of a review
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewStatus {
+    /// Written review waiting for a moderator, only its author sees it
+    Pending,
+    /// Listed for every reader
+    Approved,
+    /// Hidden, its rating left out of the album average
+    Rejected,
+}
+
+impl ReviewStatus {
+    pub const ALL: [ReviewStatus; 3] = [
+        ReviewStatus::Pending,
+        ReviewStatus::Approved,
+        ReviewStatus::Rejected,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReviewStatus::Pending => "pending",
+            ReviewStatus::Approved => "approved",
+            ReviewStatus::Rejected => "rejected",
+        }
+    }
+
+    /// Status of a new or rewritten review, bare ratings need no moderation
+    pub fn for_body(body: &str) -> Self {
+        if body.is_empty() {
+            ReviewStatus::Approved
+        } else {
+            ReviewStatus::Pending
+        }
+    }
+}
+
+impl FromStr for ReviewStatus {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        ReviewStatus::ALL
+            .into_iter()
+            .find(|status| status.as_str() == value)
+            .ok_or_else(|| {
+                format!(
+                    "Invalid review status {:?}, expected one of {}",
+                    value,
+                    ReviewStatus::ALL.map(|status| status.as_str()).join(", ")
+                )
+            })
+    }
+}
+
+#[derive(Debug, Queryable, Selectable, Clone, PartialEq, Eq)]
+#[diesel(table_name = album_reviews)]
+pub struct AlbumReview {
+    pub id: Option<i32>,
+    pub uuid: String,
+    pub album_id: i32,
+    pub reader_profile_id: i32,
+    pub rating: i32,
+    pub body: String,
+    pub status: String,
+    pub moderated_at: Option<NaiveDateTime>,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = album_reviews)]
+pub struct NewAlbumReview {
+    pub uuid: String,
+    pub album_id: i32,
+    pub reader_profile_id: i32,
+    pub rating: i32,
+    pub body: String,
+    pub status: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, PartialEq, Eq)]
+pub struct ReviewResponse {
+    pub uuid: String,
+    pub album_uuid: String,
+    /// Display name of the reader profile
+    pub reader_name: String,
+    /// Stars from 1 to 5
+    #[schema(example = 4)]
+    pub rating: i32,
+    /// Written review, empty for a bare rating
+    pub body: String,
+    pub status: ReviewStatus,
+    pub moderated_at: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+impl ReviewResponse {
+    pub fn from_review(review: AlbumReview, album_uuid: String, reader_name: String) -> Self {
+        ReviewResponse {
+            // Rows with a status written outside the API are left for moderation
+            status: review.status.parse().unwrap_or(ReviewStatus::Pending),
+            uuid: review.uuid,
+            album_uuid,
+            reader_name,
+            rating: review.rating,
+            body: review.body,
+            moderated_at: NEParse::opt_naive_datetime_to_utc_opt_string(review.moderated_at),
+            created_at: NEParse::opt_naive_datetime_to_utc_opt_string(review.created_at),
+            updated_at: NEParse::opt_naive_datetime_to_utc_opt_string(review.updated_at),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AddReviewRequest {
+    /// Stars from 1 to 5
+    #[schema(example = 4)]
+    pub rating: i32,
+    /// Written review, waits for moderation before it is listed
+    #[schema(example = "Great art, slow start.")]
+    pub body: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UpdateReviewRequest {
+    /// Stars from 1 to 5
+    #[schema(example = 5)]
+    pub rating: Option<i32>,
+    /// Replace the written review, it waits for moderation again. An empty string leaves a bare
+    /// rating.
+    pub body: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ModerateReviewRequest {
+    pub status: ReviewStatus,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GetReviewsRequest {
+    /// Filter status, admins only. Readers see approved reviews.
+    pub status: Option<ReviewStatus>,
+    /// Filter offset
+    pub offset: Option<i64>,
+    /// Filter limit
+    pub limit: Option<i64>,
+}
+
+impl GetReviewsRequest {
+    /// Requested offset, never negative
+    pub fn offset(&self) -> i64 {
+        self.offset.unwrap_or(0).max(0)
+    }
+
+    /// Requested limit kept within 1 to [`MAX_REVIEWS_LIMIT`], SQLite reads a negative limit as no
+    /// limit
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(50).clamp(1, MAX_REVIEWS_LIMIT)
+    }
+}
+
+#[derive(Debug)]
+pub enum ReviewError {
+    /// The review or album, named in the message
+    NotFound(String),
+    Invalid(String),
+    /// The reader already reviewed the album
+    Conflict(String),
+    /// The review belongs to another reader
+    Forbidden,
+    /// The album is rated above the reader's age
+    AgeRestricted(AgeRestriction),
+    Database(diesel::result::Error),
+}
+
+impl fmt::Display for ReviewError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReviewError::NotFound(message) => write!(f, "{}", message),
+            ReviewError::Invalid(message) => write!(f, "{}", message),
+            ReviewError::Conflict(message) => write!(f, "{}", message),
+            ReviewError::Forbidden => write!(f, "The review belongs to another reader"),
+            ReviewError::AgeRestricted(restriction) => {
+                write!(f, "Album rated {}+", restriction.min_age)
+            }
+            ReviewError::Database(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<diesel::result::Error> for ReviewError {
+    fn from(e: diesel::result::Error) -> Self {
+        match e {
+            diesel::result::Error::NotFound => {
+                ReviewError::NotFound(String::from("Review not found"))
+            }
+            e => ReviewError::Database(e),
+        }
+    }
+}
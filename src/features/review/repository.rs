@@ -0,0 +1,158 @@
+use chrono::Utc;
+use diesel::dsl::count_star;
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+
+use crate::common::database::DbPool;
+use crate::features::review::models::{AlbumReview, NewAlbumReview, ReviewStatus};
+use crate::schema::{album_reviews, albums, reader_profiles};
+
+/// A review with the uuid of its album and the display name of its reader
+pub type ReviewRow = (AlbumReview, String, String);
+
+pub struct Repository;
+
+impl Repository {
+    pub async fn create_review(pool: &DbPool, new_review: NewAlbumReview) -> QueryResult<()> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        conn.transaction(|conn| {
+            diesel::insert_into(album_reviews::table)
+                .values(&new_review)
+                .execute(conn)?;
+            refresh_album_rating(conn, new_review.album_id)
+        })
+    }
+
+    pub async fn get_review_by_uuid(pool: &DbPool, review_uuid: String) -> QueryResult<ReviewRow> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        album_reviews::table
+            .inner_join(albums::table)
+            .inner_join(
+                reader_profiles::table
+                    .on(reader_profiles::id.eq(album_reviews::reader_profile_id.nullable())),
+            )
+            .filter(album_reviews::uuid.eq(review_uuid))
+            .select((
+                AlbumReview::as_select(),
+                albums::uuid,
+                reader_profiles::display_name,
+            ))
+            .first(&mut conn)
+    }
+
+    /// Reviews of the album, or of every album without one, newest first
+    pub async fn get_reviews(
+        pool: &DbPool,
+        album_id: Option<i32>,
+        status: Option<ReviewStatus>,
+        offset: i64,
+        limit: i64,
+    ) -> QueryResult<(Vec<ReviewRow>, i64)> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        let mut query = album_reviews::table
+            .inner_join(albums::table)
+            .inner_join(
+                reader_profiles::table
+                    .on(reader_profiles::id.eq(album_reviews::reader_profile_id.nullable())),
+            )
+            .filter(albums::deleted_at.is_null())
+            .into_boxed();
+        let mut count_query = album_reviews::table
+            .inner_join(albums::table)
+            .filter(albums::deleted_at.is_null())
+            .into_boxed();
+
+        if let Some(album_id) = album_id {
+            query = query.filter(album_reviews::album_id.eq(album_id));
+            count_query = count_query.filter(album_reviews::album_id.eq(album_id));
+        }
+
+        if let Some(status) = status {
+            query = query.filter(album_reviews::status.eq(status.as_str()));
+            count_query = count_query.filter(album_reviews::status.eq(status.as_str()));
+        }
+
+        let total = count_query.select(count_star()).first::<i64>(&mut conn)?;
+        let results = query
+            .order((album_reviews::created_at.desc(), album_reviews::id.desc()))
+            .offset(offset)
+            .limit(limit)
+            .select((
+                AlbumReview::as_select(),
+                albums::uuid,
+                reader_profiles::display_name,
+            ))
+            .load(&mut conn)?;
+        Ok((results, total))
+    }
+
+    /// Write the rating, text and moderation of the review
+    pub async fn update_review(pool: &DbPool, review: AlbumReview) -> QueryResult<()> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        conn.transaction(|conn| {
+            diesel::update(album_reviews::table.filter(album_reviews::id.eq(review.id)))
+                .set((
+                    album_reviews::rating.eq(review.rating),
+                    album_reviews::body.eq(&review.body),
+                    album_reviews::status.eq(&review.status),
+                    album_reviews::moderated_at.eq(review.moderated_at),
+                    album_reviews::updated_at.eq(Some(Utc::now().naive_utc())),
+                ))
+                .execute(conn)?;
+            refresh_album_rating(conn, review.album_id)
+        })
+    }
+
+    pub async fn delete_review(pool: &DbPool, review: AlbumReview) -> QueryResult<()> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        conn.transaction(|conn| {
+            diesel::delete(album_reviews::table.filter(album_reviews::id.eq(review.id)))
+                .execute(conn)?;
+            refresh_album_rating(conn, review.album_id)
+        })
+    }
+
+    /// Delete every review of the reader and refresh the ratings of the albums they reviewed
+    pub async fn delete_reviews_by_reader(
+        pool: &DbPool,
+        reader_profile_id: i32,
+    ) -> QueryResult<usize> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        conn.transaction(|conn| {
+            let album_ids = album_reviews::table
+                .filter(album_reviews::reader_profile_id.eq(reader_profile_id))
+                .select(album_reviews::album_id)
+                .load::<i32>(conn)?;
+            let deleted = diesel::delete(
+                album_reviews::table.filter(album_reviews::reader_profile_id.eq(reader_profile_id)),
+            )
+            .execute(conn)?;
+            for album_id in album_ids {
+                refresh_album_rating(conn, album_id)?;
+            }
+            Ok(deleted)
+        })
+    }
+}
+
+/// Store the average and count of the album ratings, only approved reviews count
+fn refresh_album_rating(conn: &mut SqliteConnection, album_id: i32) -> QueryResult<()> {
+    let ratings = album_reviews::table
+        .filter(album_reviews::album_id.eq(album_id))
+        .filter(album_reviews::status.eq(ReviewStatus::Approved.as_str()))
+        .select(album_reviews::rating)
+        .load::<i32>(conn)?;
+    let rating_average = if ratings.is_empty() {
+        0.0
+    } else {
+        let average = ratings.iter().sum::<i32>() as f64 / ratings.len() as f64;
+        (average * 100.0).round() / 100.0
+    };
+    diesel::update(albums::table.filter(albums::id.eq(album_id)))
+        .set((
+            albums::rating_average.eq(rating_average),
+            albums::rating_count.eq(ratings.len() as i32),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
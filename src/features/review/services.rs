@@ -0,0 +1,371 @@
+use chrono::Utc;
+use diesel::result::DatabaseErrorKind;
+use uuid::Uuid;
+
+use crate::common::database::DbPool;
+use crate::common::models::response_data::ResponseData;
+use crate::features::album::models::Album;
+use crate::features::album::services::Service as AlbumService;
+use crate::features::reader::models::{ReaderAge, ReaderProfile};
+use crate::features::reader::repository::Repository as ReaderRepository;
+use crate::features::review::models::{
+    AddReviewRequest, GetReviewsRequest, ModerateReviewRequest, NewAlbumReview, ReviewError,
+    ReviewResponse, ReviewStatus, UpdateReviewRequest, MAX_REVIEW_LENGTH,
+};
+use crate::features::review::repository::Repository;
+
+pub struct Service;
+
+impl Service {
+    /// Reviews of the album, readers only get the approved ones
+    pub async fn get_album_reviews(
+        pool: &DbPool,
+        album_uuid: String,
+        reader_age: ReaderAge,
+        filters: GetReviewsRequest,
+    ) -> Result<ResponseData<ReviewResponse>, ReviewError> {
+        let album = get_album(pool, album_uuid, reader_age).await?;
+        let status = match reader_age {
+            ReaderAge::Unrestricted => filters.status,
+            _ => Some(ReviewStatus::Approved),
+        };
+        get_reviews(pool, Some(album.id), status, filters).await
+    }
+
+    /// Reviews of every album, for moderation
+    pub async fn get_reviews(
+        pool: &DbPool,
+        filters: GetReviewsRequest,
+    ) -> Result<ResponseData<ReviewResponse>, ReviewError> {
+        get_reviews(pool, None, filters.status, filters).await
+    }
+
+    /// Review by uuid, reviews waiting for moderation or rejected are only shown to their author
+    /// and admins
+    pub async fn get_review_by_uuid(
+        pool: &DbPool,
+        review_uuid: String,
+        reader_uuid: Option<String>,
+        reader_age: ReaderAge,
+    ) -> Result<ReviewResponse, ReviewError> {
+        let (review, album_uuid, reader_name) =
+            Repository::get_review_by_uuid(pool, review_uuid).await?;
+        let album = get_album(pool, album_uuid, reader_age).await?;
+        if reader_age != ReaderAge::Unrestricted && review.status != ReviewStatus::Approved.as_str()
+        {
+            let reader = match reader_uuid {
+                Some(reader_uuid) => ReaderRepository::get_profile_by_uuid(pool, reader_uuid)
+                    .await
+                    .ok(),
+                None => None,
+            };
+            if reader.and_then(|reader| reader.id) != Some(review.reader_profile_id) {
+                return Err(ReviewError::NotFound(String::from("Review not found")));
+            }
+        }
+        Ok(ReviewResponse::from_review(review, album.uuid, reader_name))
+    }
+
+    /// Rate the album as the reader, once per album
+    pub async fn add_review(
+        pool: &DbPool,
+        album_uuid: String,
+        reader_uuid: Option<String>,
+        reader_age: ReaderAge,
+        req: AddReviewRequest,
+    ) -> Result<ReviewResponse, ReviewError> {
+        let reader = get_reader(pool, reader_uuid).await?;
+        let album = get_album(pool, album_uuid, reader_age).await?;
+        let rating = check_rating(req.rating)?;
+        let body = check_body(req.body.unwrap_or_default())?;
+        let review_uuid = Uuid::new_v4().to_string();
+        Repository::create_review(
+            pool,
+            NewAlbumReview {
+                uuid: review_uuid.clone(),
+                album_id: album.id,
+                reader_profile_id: reader.id.unwrap_or_default(),
+                rating,
+                status: ReviewStatus::for_body(&body).as_str().to_string(),
+                body,
+            },
+        )
+        .await
+        .map_err(|e| match e {
+            diesel::result::Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                ReviewError::Conflict(String::from(
+                    "The reader already reviewed this album, update the review instead",
+                ))
+            }
+            e => ReviewError::from(e),
+        })?;
+        let (review, album_uuid, reader_name) =
+            Repository::get_review_by_uuid(pool, review_uuid).await?;
+        Ok(ReviewResponse::from_review(review, album_uuid, reader_name))
+    }
+
+    /// Change the rating or text of the reader's own review, new text waits for moderation again
+    pub async fn update_review(
+        pool: &DbPool,
+        review_uuid: String,
+        reader_uuid: Option<String>,
+        req: UpdateReviewRequest,
+    ) -> Result<ReviewResponse, ReviewError> {
+        let reader = get_reader(pool, reader_uuid).await?;
+        let (mut review, _, _) = Repository::get_review_by_uuid(pool, review_uuid.clone()).await?;
+        if reader.id != Some(review.reader_profile_id) {
+            return Err(ReviewError::Forbidden);
+        }
+        if let Some(rating) = req.rating {
+            review.rating = check_rating(rating)?;
+        }
+        if let Some(body) = req.body {
+            let body = check_body(body)?;
+            if body != review.body {
+                review.status = ReviewStatus::for_body(&body).as_str().to_string();
+                review.moderated_at = None;
+                review.body = body;
+            }
+        }
+        Repository::update_review(pool, review).await?;
+        let (review, album_uuid, reader_name) =
+            Repository::get_review_by_uuid(pool, review_uuid).await?;
+        Ok(ReviewResponse::from_review(review, album_uuid, reader_name))
+    }
+
+    /// Approve or reject the review, only approved ratings count toward the album average
+    pub async fn moderate_review(
+        pool: &DbPool,
+        review_uuid: String,
+        req: ModerateReviewRequest,
+    ) -> Result<ReviewResponse, ReviewError> {
+        let (mut review, _, _) = Repository::get_review_by_uuid(pool, review_uuid.clone()).await?;
+        review.status = req.status.as_str().to_string();
+        review.moderated_at = match req.status {
+            ReviewStatus::Pending => None,
+            _ => Some(Utc::now().naive_utc()),
+        };
+        Repository::update_review(pool, review).await?;
+        let (review, album_uuid, reader_name) =
+            Repository::get_review_by_uuid(pool, review_uuid).await?;
+        Ok(ReviewResponse::from_review(review, album_uuid, reader_name))
+    }
+
+    /// Delete the review, readers can only delete their own. Returns the deleted review.
+    pub async fn delete_review(
+        pool: &DbPool,
+        review_uuid: String,
+        reader_uuid: Option<String>,
+        is_admin: bool,
+    ) -> Result<ReviewResponse, ReviewError> {
+        let (review, album_uuid, reader_name) =
+            Repository::get_review_by_uuid(pool, review_uuid).await?;
+        if !is_admin {
+            let reader = get_reader(pool, reader_uuid).await?;
+            if reader.id != Some(review.reader_profile_id) {
+                return Err(ReviewError::Forbidden);
+            }
+        }
+        Repository::delete_review(pool, review.clone()).await?;
+        Ok(ReviewResponse::from_review(review, album_uuid, reader_name))
+    }
+
+    /// Delete the reader's reviews along with the reader profile
+    pub async fn delete_reviews_by_reader(
+        pool: &DbPool,
+        reader_profile_id: i32,
+    ) -> Result<usize, diesel::result::Error> {
+        Repository::delete_reviews_by_reader(pool, reader_profile_id).await
+    }
+}
+
+async fn get_reviews(
+    pool: &DbPool,
+    album_id: Option<i32>,
+    status: Option<ReviewStatus>,
+    filters: GetReviewsRequest,
+) -> Result<ResponseData<ReviewResponse>, ReviewError> {
+    let (offset, limit) = (filters.offset(), filters.limit());
+    let (reviews, total) = Repository::get_reviews(pool, album_id, status, offset, limit).await?;
+    Ok(ResponseData::new(
+        reviews
+            .into_iter()
+            .map(|(review, album_uuid, reader_name)| {
+                ReviewResponse::from_review(review, album_uuid, reader_name)
+            })
+            .collect(),
+        total,
+        offset,
+    ))
+}
+
+/// Album the reader may see, rated albums are refused to younger readers
+async fn get_album(
+    pool: &DbPool,
+    album_uuid: String,
+    reader_age: ReaderAge,
+) -> Result<Album, ReviewError> {
+    let album = match AlbumService::get_album_by_uuid(pool, album_uuid).await {
+        Ok(album) => album,
+        Err(diesel::result::Error::NotFound) => {
+            return Err(ReviewError::NotFound(String::from("Album not found")))
+        }
+        Err(e) => return Err(ReviewError::Database(e)),
+    };
+    reader_age
+        .check(album.min_age)
+        .map_err(ReviewError::AgeRestricted)?;
+    Ok(album)
+}
+
+/// Reader profile the review is written as, sent in `X-Reader-Profile`
+async fn get_reader(
+    pool: &DbPool,
+    reader_uuid: Option<String>,
+) -> Result<ReaderProfile, ReviewError> {
+    let Some(reader_uuid) = reader_uuid else {
+        return Err(ReviewError::Invalid(String::from(
+            "Send the reader profile in X-Reader-Profile",
+        )));
+    };
+    match ReaderRepository::get_profile_by_uuid(pool, reader_uuid).await {
+        Ok(reader) => Ok(reader),
+        Err(diesel::result::Error::NotFound) => Err(ReviewError::Invalid(String::from(
+            "Reader profile not found",
+        ))),
+        Err(e) => Err(ReviewError::Database(e)),
+    }
+}
+
+fn check_rating(rating: i32) -> Result<i32, ReviewError> {
+    if (1..=5).contains(&rating) {
+        Ok(rating)
+    } else {
+        Err(ReviewError::Invalid(format!(
+            "Invalid rating {}, expected 1 to 5 stars",
+            rating
+        )))
+    }
+}
+
+fn check_body(body: String) -> Result<String, ReviewError> {
+    let body = body.trim().to_string();
+    if body.chars().count() > MAX_REVIEW_LENGTH {
+        return Err(ReviewError::Invalid(format!(
+            "Review is longer than {} characters",
+            MAX_REVIEW_LENGTH
+        )));
+    }
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use diesel::prelude::*;
+    use diesel::sql_types::Text;
+
+    use super::*;
+    use crate::common::test_support;
+    use crate::schema::albums;
+
+    fn insert_album(pool: &DbPool) -> String {
+        let album_uuid = Uuid::new_v4().to_string();
+        diesel::sql_query(
+            "INSERT INTO albums (uuid, category_id, title, description, images, url, \
+             content_type, width, height, bytes) VALUES (?, 1, 'Album', '', '', '', '', 0, 0, 0)",
+        )
+        .bind::<Text, _>(&album_uuid)
+        .execute(&mut pool.get().unwrap())
+        .unwrap();
+        album_uuid
+    }
+
+    fn insert_reader(pool: &DbPool, display_name: &str) -> String {
+        let reader_uuid = Uuid::new_v4().to_string();
+        diesel::sql_query("INSERT INTO reader_profiles (uuid, display_name) VALUES (?, ?)")
+            .bind::<Text, _>(&reader_uuid)
+            .bind::<Text, _>(display_name)
+            .execute(&mut pool.get().unwrap())
+            .unwrap();
+        reader_uuid
+    }
+
+    fn album_rating(pool: &DbPool, album_uuid: &str) -> (f64, i32) {
+        albums::table
+            .filter(albums::uuid.eq(album_uuid))
+            .select((albums::rating_average, albums::rating_count))
+            .first(&mut pool.get().unwrap())
+            .unwrap()
+    }
+
+    async fn add_review(
+        pool: &DbPool,
+        album_uuid: &str,
+        rating: i32,
+        body: &str,
+    ) -> ReviewResponse {
+        let reader_uuid = insert_reader(pool, "Reader");
+        Service::add_review(
+            pool,
+            album_uuid.to_string(),
+            Some(reader_uuid),
+            ReaderAge::Unknown,
+            AddReviewRequest {
+                rating,
+                body: Some(body.to_string()),
+            },
+        )
+        .await
+        .unwrap()
+    }
+
+    #[actix_web::test]
+    async fn only_approved_reviews_count_toward_the_album_rating() {
+        let pool = test_support::test_pool();
+        let album_uuid = insert_album(&pool);
+
+        add_review(&pool, &album_uuid, 5, "").await;
+        assert_eq!(album_rating(&pool, &album_uuid), (5.0, 1));
+
+        let written = add_review(&pool, &album_uuid, 2, "Slow start.").await;
+        assert_eq!(written.status, ReviewStatus::Pending);
+        assert_eq!(album_rating(&pool, &album_uuid), (5.0, 1));
+
+        let moderate = |status| ModerateReviewRequest { status };
+        Service::moderate_review(
+            &pool,
+            written.uuid.clone(),
+            moderate(ReviewStatus::Approved),
+        )
+        .await
+        .unwrap();
+        assert_eq!(album_rating(&pool, &album_uuid), (3.5, 2));
+
+        Service::moderate_review(&pool, written.uuid, moderate(ReviewStatus::Rejected))
+            .await
+            .unwrap();
+        assert_eq!(album_rating(&pool, &album_uuid), (5.0, 1));
+    }
+
+    #[actix_web::test]
+    async fn negative_limit_and_offset_are_clamped() {
+        let pool = test_support::test_pool();
+        let album_uuid = insert_album(&pool);
+        for _ in 0..3 {
+            add_review(&pool, &album_uuid, 4, "").await;
+        }
+
+        let page = Service::get_reviews(
+            &pool,
+            GetReviewsRequest {
+                status: None,
+                offset: Some(-5),
+                limit: Some(-1),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(page.total, 3);
+        assert_eq!(page.data.len(), 1);
+    }
+}
@@ -0,0 +1,362 @@
+use actix_web::{delete, get, put, web, HttpRequest, HttpResponse, Responder};
+
+use crate::common::database::DbPool;
+use crate::common::enums::Role::Admin;
+use crate::common::i18n::normalize_locale;
+use crate::common::models::response_message::ResponseMessage;
+use crate::features::translation::models::{
+    SetAlbumTranslationRequest, SetEpisodeTranslationRequest, TranslationError,
+};
+use crate::features::translation::services::Service;
+use crate::features::{audit_mutation, check_role};
+
+/// Get Album Translations
+///
+/// Get the titles and descriptions of the album in every translated locale
+#[utoipa::path(
+    get,
+    path = "/albums/{album_uuid}/translations",
+    params(
+        ("album_uuid" = String, Path, description = "Album ID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e")
+    ),
+    responses(
+        (status = 200, description = "Get successfully", body = [AlbumTranslationResponse]),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 404, description = "Album not found", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Album",
+)]
+#[get("/albums/{album_uuid}/translations")]
+pub async fn get_album_translations(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+) -> impl Responder {
+    if check_role(http_request) == Admin {
+        match Service::get_album_translations_by_uuid(&pool, path.into_inner()).await {
+            Ok(translations) => HttpResponse::Ok().json(translations),
+            Err(e) => translation_error_response("get", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Set Album Translation
+///
+/// Add or replace the title and description of the album in a locale other than the default
+#[utoipa::path(
+    put,
+    path = "/album/{album_uuid}/translations/{locale}",
+    params(
+        ("album_uuid" = String, Path, description = "Album ID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e"),
+        ("locale" = String, Path, description = "Language tag", style = Simple, example = "pt-BR")
+    ),
+    request_body = SetAlbumTranslationRequest,
+    responses(
+        (status = 200, description = "Update successfully", body = AlbumTranslationResponse),
+        (status = 400, description = "Invalid locale or title", body = ResponseMessage),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 404, description = "Album not found", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Album",
+)]
+#[put("/album/{album_uuid}/translations/{locale}")]
+pub async fn set_album_translation(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<(String, String)>,
+    req: web::Json<SetAlbumTranslationRequest>,
+) -> impl Responder {
+    if check_role(http_request.clone()) == Admin {
+        let (album_uuid, locale) = path.into_inner();
+        let before = album_translation_snapshot(&pool, &album_uuid, &locale).await;
+        match Service::set_album_translation(&pool, album_uuid.clone(), locale, req.into_inner())
+            .await
+        {
+            Ok(translation) => {
+                audit_mutation(
+                    &pool,
+                    &http_request,
+                    "translate",
+                    "album",
+                    album_uuid,
+                    before,
+                    serde_json::to_value(&translation).ok(),
+                )
+                .await;
+                HttpResponse::Ok().json(translation)
+            }
+            Err(e) => translation_error_response("set", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Delete Album Translation
+///
+/// Delete the album translation of a locale, readers asking for it get the default locale
+#[utoipa::path(
+    delete,
+    path = "/album/{album_uuid}/translations/{locale}",
+    params(
+        ("album_uuid" = String, Path, description = "Album ID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e"),
+        ("locale" = String, Path, description = "Language tag", style = Simple, example = "pt-BR")
+    ),
+    responses(
+        (status = 204, description = "Delete successfully"),
+        (status = 400, description = "Invalid locale", body = ResponseMessage),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 404, description = "Album or translation not found", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Album",
+)]
+#[delete("/album/{album_uuid}/translations/{locale}")]
+pub async fn delete_album_translation(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<(String, String)>,
+) -> impl Responder {
+    if check_role(http_request.clone()) == Admin {
+        let (album_uuid, locale) = path.into_inner();
+        let before = album_translation_snapshot(&pool, &album_uuid, &locale).await;
+        match Service::delete_album_translation(&pool, album_uuid.clone(), locale).await {
+            Ok(_) => {
+                audit_mutation(
+                    &pool,
+                    &http_request,
+                    "delete_translation",
+                    "album",
+                    album_uuid,
+                    before,
+                    None,
+                )
+                .await;
+                HttpResponse::NoContent().json("")
+            }
+            Err(e) => translation_error_response("delete", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Get Episode Translations
+///
+/// Get the titles of the episode in every translated locale
+#[utoipa::path(
+    get,
+    path = "/episodes/{episode_uuid}/translations",
+    params(
+        ("episode_uuid" = String, Path, description = "Episode UUID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e")
+    ),
+    responses(
+        (status = 200, description = "Get successfully", body = [EpisodeTranslationResponse]),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 404, description = "Episode not found", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Episode",
+)]
+#[get("/episodes/{episode_uuid}/translations")]
+pub async fn get_episode_translations(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+) -> impl Responder {
+    if check_role(http_request) == Admin {
+        match Service::get_episode_translations_by_uuid(&pool, path.into_inner()).await {
+            Ok(translations) => HttpResponse::Ok().json(translations),
+            Err(e) => translation_error_response("get", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Set Episode Translation
+///
+/// Add or replace the title of the episode in a locale other than the default. Translated pages
+/// are uploaded with the locale field of Add Contents.
+#[utoipa::path(
+    put,
+    path = "/episode/{episode_uuid}/translations/{locale}",
+    params(
+        ("episode_uuid" = String, Path, description = "Episode UUID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e"),
+        ("locale" = String, Path, description = "Language tag", style = Simple, example = "pt-BR")
+    ),
+    request_body = SetEpisodeTranslationRequest,
+    responses(
+        (status = 200, description = "Update successfully", body = EpisodeTranslationResponse),
+        (status = 400, description = "Invalid locale or title", body = ResponseMessage),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 404, description = "Episode not found", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Episode",
+)]
+#[put("/episode/{episode_uuid}/translations/{locale}")]
+pub async fn set_episode_translation(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<(String, String)>,
+    req: web::Json<SetEpisodeTranslationRequest>,
+) -> impl Responder {
+    if check_role(http_request.clone()) == Admin {
+        let (episode_uuid, locale) = path.into_inner();
+        let before = episode_translation_snapshot(&pool, &episode_uuid, &locale).await;
+        match Service::set_episode_translation(
+            &pool,
+            episode_uuid.clone(),
+            locale,
+            req.into_inner(),
+        )
+        .await
+        {
+            Ok(translation) => {
+                audit_mutation(
+                    &pool,
+                    &http_request,
+                    "translate",
+                    "episode",
+                    episode_uuid,
+                    before,
+                    serde_json::to_value(&translation).ok(),
+                )
+                .await;
+                HttpResponse::Ok().json(translation)
+            }
+            Err(e) => translation_error_response("set", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Delete Episode Translation
+///
+/// Delete the episode title translation of a locale, translated pages are kept
+#[utoipa::path(
+    delete,
+    path = "/episode/{episode_uuid}/translations/{locale}",
+    params(
+        ("episode_uuid" = String, Path, description = "Episode UUID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e"),
+        ("locale" = String, Path, description = "Language tag", style = Simple, example = "pt-BR")
+    ),
+    responses(
+        (status = 204, description = "Delete successfully"),
+        (status = 400, description = "Invalid locale", body = ResponseMessage),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 404, description = "Episode or translation not found", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Episode",
+)]
+#[delete("/episode/{episode_uuid}/translations/{locale}")]
+pub async fn delete_episode_translation(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<(String, String)>,
+) -> impl Responder {
+    if check_role(http_request.clone()) == Admin {
+        let (episode_uuid, locale) = path.into_inner();
+        let before = episode_translation_snapshot(&pool, &episode_uuid, &locale).await;
+        match Service::delete_episode_translation(&pool, episode_uuid.clone(), locale).await {
+            Ok(_) => {
+                audit_mutation(
+                    &pool,
+                    &http_request,
+                    "delete_translation",
+                    "episode",
+                    episode_uuid,
+                    before,
+                    None,
+                )
+                .await;
+                HttpResponse::NoContent().json("")
+            }
+            Err(e) => translation_error_response("delete", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+fn translation_error_response(action: &str, e: TranslationError) -> HttpResponse {
+    match e {
+        TranslationError::NotFound(_) => HttpResponse::NotFound().json(ResponseMessage {
+            message: e.to_string(),
+        }),
+        TranslationError::Invalid(_) => HttpResponse::BadRequest().json(ResponseMessage {
+            message: e.to_string(),
+        }),
+        TranslationError::Database(_) => {
+            println!("Failed to {} translation: {}", action, e);
+            HttpResponse::InternalServerError().json(ResponseMessage {
+                message: String::from("Internal Server Error"),
+            })
+        }
+    }
+}
+
+async fn album_translation_snapshot(
+    pool: &DbPool,
+    album_uuid: &str,
+    locale: &str,
+) -> Option<serde_json::Value> {
+    let translations = Service::get_album_translations_by_uuid(pool, album_uuid.to_string())
+        .await
+        .ok()?;
+    let translation = translations
+        .into_iter()
+        .find(|translation| Some(&translation.locale) == normalize_locale(locale).as_ref())?;
+    serde_json::to_value(translation).ok()
+}
+
+async fn episode_translation_snapshot(
+    pool: &DbPool,
+    episode_uuid: &str,
+    locale: &str,
+) -> Option<serde_json::Value> {
+    let translations = Service::get_episode_translations_by_uuid(pool, episode_uuid.to_string())
+        .await
+        .ok()?;
+    let translation = translations
+        .into_iter()
+        .find(|translation| Some(&translation.locale) == normalize_locale(locale).as_ref())?;
+    serde_json::to_value(translation).ok()
+}
@@ -0,0 +1,18 @@
+use actix_web::web;
+
+use crate::features::translation::controllers::*;
+
+pub mod controllers;
+pub mod models;
+pub mod repository;
+pub mod services;
+
+// ! Register ever routes in this configure function
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_album_translations);
+    cfg.service(set_album_translation);
+    cfg.service(delete_album_translation);
+    cfg.service(get_episode_translations);
+    cfg.service(set_episode_translation);
+    cfg.service(delete_episode_translation);
+}
@@ -0,0 +1,134 @@
+use std::fmt;
+
+use chrono::NaiveDateTime;
+use diesel::{Insertable, Queryable, Selectable};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::common::ne_parse::NEParse;
+use crate::schema::{album_translations, episode_translations};
+
+#[derive(Debug, Clone, Queryable, Selectable, PartialEq, Eq)]
+#[diesel(table_name = album_translations)]
+pub struct AlbumTranslation {
+    pub album_id: i32,
+    pub locale: String,
+    pub title: String,
+    pub description: String,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = album_translations)]
+pub struct NewAlbumTranslation {
+    pub album_id: i32,
+    pub locale: String,
+    pub title: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Queryable, Selectable, PartialEq, Eq)]
+#[diesel(table_name = episode_translations)]
+pub struct EpisodeTranslation {
+    pub episode_id: i32,
+    pub locale: String,
+    pub title: String,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = episode_translations)]
+pub struct NewEpisodeTranslation {
+    pub episode_id: i32,
+    pub locale: String,
+    pub title: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema, PartialEq, Eq)]
+pub struct AlbumTranslationResponse {
+    #[schema(example = "pt-br")]
+    pub locale: String,
+    pub title: String,
+    /// The description in the default locale is shown when empty
+    pub description: String,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+impl AlbumTranslationResponse {
+    pub fn from_translation(translation: AlbumTranslation) -> Self {
+        AlbumTranslationResponse {
+            locale: translation.locale,
+            title: translation.title,
+            description: translation.description,
+            created_at: NEParse::opt_naive_datetime_to_utc_opt_string(translation.created_at),
+            updated_at: NEParse::opt_naive_datetime_to_utc_opt_string(translation.updated_at),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema, PartialEq, Eq)]
+pub struct EpisodeTranslationResponse {
+    #[schema(example = "pt-br")]
+    pub locale: String,
+    pub title: String,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+impl EpisodeTranslationResponse {
+    pub fn from_translation(translation: EpisodeTranslation) -> Self {
+        EpisodeTranslationResponse {
+            locale: translation.locale,
+            title: translation.title,
+            created_at: NEParse::opt_naive_datetime_to_utc_opt_string(translation.created_at),
+            updated_at: NEParse::opt_naive_datetime_to_utc_opt_string(translation.updated_at),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SetAlbumTranslationRequest {
+    #[schema(example = "Meu Álbum")]
+    pub title: String,
+    /// The description in the default locale is shown when empty
+    #[schema(example = "")]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SetEpisodeTranslationRequest {
+    #[schema(example = "Episódio 1")]
+    pub title: String,
+}
+
+#[derive(Debug)]
+pub enum TranslationError {
+    /// The album, episode or translation, named in the message
+    NotFound(String),
+    Invalid(String),
+    Database(diesel::result::Error),
+}
+
+impl fmt::Display for TranslationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TranslationError::NotFound(message) => write!(f, "{}", message),
+            TranslationError::Invalid(message) => write!(f, "{}", message),
+            TranslationError::Database(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<diesel::result::Error> for TranslationError {
+    fn from(e: diesel::result::Error) -> Self {
+        match e {
+            diesel::result::Error::NotFound => {
+                TranslationError::NotFound(String::from("Translation not found"))
+            }
+            e => TranslationError::Database(e),
+        }
+    }
+}
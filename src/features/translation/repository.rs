@@ -0,0 +1,115 @@
+use chrono::Utc;
+use diesel::prelude::*;
+
+use crate::common::database::DbPool;
+use crate::features::translation::models::{
+    AlbumTranslation, EpisodeTranslation, NewAlbumTranslation, NewEpisodeTranslation,
+};
+use crate::schema::{album_translations, episode_translations};
+
+pub struct Repository;
+
+impl Repository {
+    pub async fn get_album_translations(
+        pool: &DbPool,
+        album_ids: Vec<i32>,
+    ) -> QueryResult<Vec<AlbumTranslation>> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        album_translations::table
+            .filter(album_translations::album_id.eq_any(album_ids))
+            .order((album_translations::album_id, album_translations::locale))
+            .select(AlbumTranslation::as_select())
+            .load(&mut conn)
+    }
+
+    /// Insert the translation or replace the one of the same locale
+    pub async fn set_album_translation(
+        pool: &DbPool,
+        translation: NewAlbumTranslation,
+    ) -> QueryResult<AlbumTranslation> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        diesel::insert_into(album_translations::table)
+            .values(&translation)
+            .on_conflict((album_translations::album_id, album_translations::locale))
+            .do_update()
+            .set((
+                album_translations::title.eq(&translation.title),
+                album_translations::description.eq(&translation.description),
+                album_translations::updated_at.eq(Some(Utc::now().naive_utc())),
+            ))
+            .execute(&mut conn)?;
+        album_translations::table
+            .filter(album_translations::album_id.eq(translation.album_id))
+            .filter(album_translations::locale.eq(&translation.locale))
+            .select(AlbumTranslation::as_select())
+            .first(&mut conn)
+    }
+
+    pub async fn delete_album_translation(
+        pool: &DbPool,
+        album_id: i32,
+        locale: &str,
+    ) -> QueryResult<usize> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        diesel::delete(
+            album_translations::table
+                .filter(album_translations::album_id.eq(album_id))
+                .filter(album_translations::locale.eq(locale)),
+        )
+        .execute(&mut conn)
+    }
+
+    pub async fn get_episode_translations(
+        pool: &DbPool,
+        episode_ids: Vec<i32>,
+    ) -> QueryResult<Vec<EpisodeTranslation>> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        episode_translations::table
+            .filter(episode_translations::episode_id.eq_any(episode_ids))
+            .order((
+                episode_translations::episode_id,
+                episode_translations::locale,
+            ))
+            .select(EpisodeTranslation::as_select())
+            .load(&mut conn)
+    }
+
+    /// Insert the translation or replace the one of the same locale
+    pub async fn set_episode_translation(
+        pool: &DbPool,
+        translation: NewEpisodeTranslation,
+    ) -> QueryResult<EpisodeTranslation> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        diesel::insert_into(episode_translations::table)
+            .values(&translation)
+            .on_conflict((
+                episode_translations::episode_id,
+                episode_translations::locale,
+            ))
+            .do_update()
+            .set((
+                episode_translations::title.eq(&translation.title),
+                episode_translations::updated_at.eq(Some(Utc::now().naive_utc())),
+            ))
+            .execute(&mut conn)?;
+        episode_translations::table
+            .filter(episode_translations::episode_id.eq(translation.episode_id))
+            .filter(episode_translations::locale.eq(&translation.locale))
+            .select(EpisodeTranslation::as_select())
+            .first(&mut conn)
+    }
+
+    pub async fn delete_episode_translation(
+        pool: &DbPool,
+        episode_id: i32,
+        locale: &str,
+    ) -> QueryResult<usize> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        diesel::delete(
+            episode_translations::table
+                .filter(episode_translations::episode_id.eq(episode_id))
+                .filter(episode_translations::locale.eq(locale)),
+        )
+        .execute(&mut conn)
+    }
+}
@@ -0,0 +1,158 @@
+use crate::common::database::DbPool;
+use crate::common::i18n::{default_locale, normalize_locale};
+use crate::features::album::services::Service as AlbumService;
+use crate::features::content::services::Service as ContentService;
+use crate::features::translation::models::{
+    AlbumTranslation, AlbumTranslationResponse, EpisodeTranslation, EpisodeTranslationResponse,
+    NewAlbumTranslation, NewEpisodeTranslation, SetAlbumTranslationRequest,
+    SetEpisodeTranslationRequest, TranslationError,
+};
+use crate::features::translation::repository::Repository;
+
+pub struct Service;
+
+impl Service {
+    pub async fn get_album_translations(
+        pool: &DbPool,
+        album_ids: Vec<i32>,
+    ) -> Result<Vec<AlbumTranslation>, diesel::result::Error> {
+        Repository::get_album_translations(pool, album_ids).await
+    }
+
+    pub async fn get_album_translations_by_uuid(
+        pool: &DbPool,
+        album_uuid: String,
+    ) -> Result<Vec<AlbumTranslationResponse>, TranslationError> {
+        let album_id = album_id(pool, album_uuid).await?;
+        Ok(Repository::get_album_translations(pool, vec![album_id])
+            .await?
+            .into_iter()
+            .map(AlbumTranslationResponse::from_translation)
+            .collect())
+    }
+
+    /// Add or replace the title and description of the album in a locale
+    pub async fn set_album_translation(
+        pool: &DbPool,
+        album_uuid: String,
+        locale: String,
+        req: SetAlbumTranslationRequest,
+    ) -> Result<AlbumTranslationResponse, TranslationError> {
+        let album_id = album_id(pool, album_uuid).await?;
+        let translation = Repository::set_album_translation(
+            pool,
+            NewAlbumTranslation {
+                album_id,
+                locale: translation_locale(&locale)?,
+                title: required_title(&req.title)?,
+                description: req.description.unwrap_or_default(),
+            },
+        )
+        .await?;
+        Ok(AlbumTranslationResponse::from_translation(translation))
+    }
+
+    pub async fn delete_album_translation(
+        pool: &DbPool,
+        album_uuid: String,
+        locale: String,
+    ) -> Result<(), TranslationError> {
+        let album_id = album_id(pool, album_uuid).await?;
+        let locale = translation_locale(&locale)?;
+        match Repository::delete_album_translation(pool, album_id, &locale).await? {
+            0 => Err(diesel::result::Error::NotFound.into()),
+            _ => Ok(()),
+        }
+    }
+
+    pub async fn get_episode_translations(
+        pool: &DbPool,
+        episode_ids: Vec<i32>,
+    ) -> Result<Vec<EpisodeTranslation>, diesel::result::Error> {
+        Repository::get_episode_translations(pool, episode_ids).await
+    }
+
+    pub async fn get_episode_translations_by_uuid(
+        pool: &DbPool,
+        episode_uuid: String,
+    ) -> Result<Vec<EpisodeTranslationResponse>, TranslationError> {
+        let episode_id = episode_id(pool, episode_uuid).await?;
+        Ok(Repository::get_episode_translations(pool, vec![episode_id])
+            .await?
+            .into_iter()
+            .map(EpisodeTranslationResponse::from_translation)
+            .collect())
+    }
+
+    /// Add or replace the title of the episode in a locale
+    pub async fn set_episode_translation(
+        pool: &DbPool,
+        episode_uuid: String,
+        locale: String,
+        req: SetEpisodeTranslationRequest,
+    ) -> Result<EpisodeTranslationResponse, TranslationError> {
+        let episode_id = episode_id(pool, episode_uuid).await?;
+        let translation = Repository::set_episode_translation(
+            pool,
+            NewEpisodeTranslation {
+                episode_id,
+                locale: translation_locale(&locale)?,
+                title: required_title(&req.title)?,
+            },
+        )
+        .await?;
+        Ok(EpisodeTranslationResponse::from_translation(translation))
+    }
+
+    pub async fn delete_episode_translation(
+        pool: &DbPool,
+        episode_uuid: String,
+        locale: String,
+    ) -> Result<(), TranslationError> {
+        let episode_id = episode_id(pool, episode_uuid).await?;
+        let locale = translation_locale(&locale)?;
+        match Repository::delete_episode_translation(pool, episode_id, &locale).await? {
+            0 => Err(diesel::result::Error::NotFound.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+async fn album_id(pool: &DbPool, album_uuid: String) -> Result<i32, TranslationError> {
+    match AlbumService::get_album_by_uuid(pool, album_uuid).await {
+        Ok(album) => Ok(album.id),
+        Err(diesel::result::Error::NotFound) => {
+            Err(TranslationError::NotFound(String::from("Album not found")))
+        }
+        Err(e) => Err(TranslationError::Database(e)),
+    }
+}
+
+async fn episode_id(pool: &DbPool, episode_uuid: String) -> Result<i32, TranslationError> {
+    ContentService::get_episode_id_by_uuid(pool, episode_uuid)
+        .await
+        .ok_or_else(|| TranslationError::NotFound(String::from("Episode not found")))
+}
+
+/// Normalized locale of a translation, the default locale lives on the album or episode itself
+fn translation_locale(locale: &str) -> Result<String, TranslationError> {
+    let normalized = normalize_locale(locale)
+        .ok_or_else(|| TranslationError::Invalid(format!("Invalid locale {:?}", locale)))?;
+    if normalized == default_locale() {
+        return Err(TranslationError::Invalid(format!(
+            "{} is the default locale, update the original instead",
+            normalized
+        )));
+    }
+    Ok(normalized)
+}
+
+fn required_title(title: &str) -> Result<String, TranslationError> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err(TranslationError::Invalid(String::from(
+            "Title cannot be empty",
+        )));
+    }
+    Ok(title.to_string())
+}
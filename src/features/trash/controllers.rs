@@ -0,0 +1,179 @@
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse, Responder};
+
+use crate::common::database::DbPool;
+use crate::common::enums::Role::Admin;
+use crate::common::models::response_message::ResponseMessage;
+use crate::features::trash::models::{GetTrashRequest, TrashEntity, TrashError};
+use crate::features::trash::services::Service;
+use crate::features::{audit_mutation, check_role};
+
+/// Get Trash
+///
+/// Get soft deleted albums, episodes and contents
+#[utoipa::path(
+    get,
+    path = "/trash",
+    params(GetTrashRequest),
+    responses(
+        (status = 200, description = "Request successfully", body = ResponseDataTrashItem),
+        (status = 400, description = "Bad Request", body = ResponseMessage),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Trash",
+)]
+#[get("/trash")]
+pub async fn get_trash(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    req: web::Query<GetTrashRequest>,
+) -> impl Responder {
+    if check_role(http_request) == Admin {
+        if let Some(Err(e)) = req.entity_type.as_deref().map(str::parse::<TrashEntity>) {
+            return HttpResponse::BadRequest().json(ResponseMessage { message: e });
+        }
+        match Service::get_trash_items(&pool, req.into_inner()).await {
+            Ok(data) => HttpResponse::Ok().json(data),
+            Err(err) => HttpResponse::BadRequest().json(ResponseMessage {
+                message: err.to_string(),
+            }),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Restore Trash Item
+///
+/// Restore a soft deleted album, episode or content
+#[utoipa::path(
+    post,
+    path = "/trash/{entity_type}/{uuid}/restore",
+    params(
+        ("entity_type" = String, Path, description = "album, episode or content", style = Simple, example = "album"),
+        ("uuid" = String, Path, description = "Item UUID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e")
+    ),
+    responses(
+        (status = 204, description = "Restore successfully"),
+        (status = 400, description = "Restore Not Found", body = ResponseMessage),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 409, description = "Parent is in the trash", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Trash",
+)]
+#[post("/{entity_type}/{uuid}/restore")]
+pub async fn restore_trash_item(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<(String, String)>,
+) -> impl Responder {
+    if check_role(http_request.clone()) == Admin {
+        let (entity_type, uuid) = path.into_inner();
+        let entity = match entity_type.parse::<TrashEntity>() {
+            Ok(entity) => entity,
+            Err(e) => return HttpResponse::BadRequest().json(ResponseMessage { message: e }),
+        };
+        match Service::restore(&pool, entity, uuid.clone()).await {
+            Ok(_) => {
+                audit_mutation(
+                    &pool,
+                    &http_request,
+                    "restore",
+                    entity.as_str(),
+                    uuid,
+                    None,
+                    None,
+                )
+                .await;
+                HttpResponse::NoContent().json("")
+            }
+            Err(e) => trash_error_response("restore", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Purge Trash Item
+///
+/// Permanently delete a soft deleted album, episode or content and its files
+#[utoipa::path(
+    delete,
+    path = "/trash/{entity_type}/{uuid}",
+    params(
+        ("entity_type" = String, Path, description = "album, episode or content", style = Simple, example = "album"),
+        ("uuid" = String, Path, description = "Item UUID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e")
+    ),
+    responses(
+        (status = 204, description = "Purge successfully"),
+        (status = 400, description = "Purge Not Found", body = ResponseMessage),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Trash",
+)]
+#[delete("/{entity_type}/{uuid}")]
+pub async fn purge_trash_item(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<(String, String)>,
+) -> impl Responder {
+    if check_role(http_request.clone()) == Admin {
+        let (entity_type, uuid) = path.into_inner();
+        let entity = match entity_type.parse::<TrashEntity>() {
+            Ok(entity) => entity,
+            Err(e) => return HttpResponse::BadRequest().json(ResponseMessage { message: e }),
+        };
+        match Service::purge(&pool, entity, uuid.clone()).await {
+            Ok(_) => {
+                audit_mutation(
+                    &pool,
+                    &http_request,
+                    "purge",
+                    entity.as_str(),
+                    uuid,
+                    None,
+                    None,
+                )
+                .await;
+                HttpResponse::NoContent().json("")
+            }
+            Err(e) => trash_error_response("purge", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+fn trash_error_response(action: &str, e: TrashError) -> HttpResponse {
+    match e {
+        TrashError::NotFound => HttpResponse::BadRequest().json(ResponseMessage {
+            message: e.to_string(),
+        }),
+        TrashError::ParentInTrash(_) => HttpResponse::Conflict().json(ResponseMessage {
+            message: e.to_string(),
+        }),
+        TrashError::Database(_) => {
+            println!("Failed to {} trash item: {}", action, e);
+            HttpResponse::InternalServerError().json(ResponseMessage {
+                message: String::from("Internal Server Error"),
+            })
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use futures_util::future::LocalBoxFuture;
+use serde_json::Value;
+
+use crate::common::database::DbPool;
+use crate::features::jobs::registry::JobHandler;
+use crate::features::trash::services::Service;
+
+pub const PURGE_EXPIRED_JOB: &str = "trash.purge_expired";
+
+/// Purge trash items older than `storage.trash_retention_days`
+pub struct PurgeExpiredJob;
+
+impl JobHandler for PurgeExpiredJob {
+    fn job_type(&self) -> &'static str {
+        PURGE_EXPIRED_JOB
+    }
+
+    fn run(&self, pool: DbPool, _payload: Value) -> LocalBoxFuture<'static, Result<(), String>> {
+        Box::pin(async move {
+            let purged = Service::purge_expired(&pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            if purged > 0 {
+                println!("Purged {} expired rows from trash", purged);
+            }
+            Ok(())
+        })
+    }
+}
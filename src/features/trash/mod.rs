@@ -0,0 +1,25 @@
+use actix_web::web;
+
+use crate::features::jobs::registry::JobRegistry;
+use crate::features::trash::controllers::*;
+use crate::features::trash::jobs::PurgeExpiredJob;
+
+pub mod controllers;
+pub mod jobs;
+pub mod models;
+pub mod repository;
+pub mod services;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_trash);
+
+    cfg.service(
+        web::scope("/trash")
+            .service(restore_trash_item)
+            .service(purge_trash_item),
+    );
+}
+
+pub fn register_jobs(registry: &mut JobRegistry) {
+    registry.register(PurgeExpiredJob);
+}
@@ -0,0 +1,133 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{Duration, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::common::ne_parse::NEParse;
+
+/// Most trashed items returned in one page
+pub const MAX_TRASH_LIMIT: i64 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrashEntity {
+    Album,
+    Episode,
+    Content,
+}
+
+impl TrashEntity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TrashEntity::Album => "album",
+            TrashEntity::Episode => "episode",
+            TrashEntity::Content => "content",
+        }
+    }
+}
+
+impl FromStr for TrashEntity {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "album" => Ok(TrashEntity::Album),
+            "episode" => Ok(TrashEntity::Episode),
+            "content" => Ok(TrashEntity::Content),
+            _ => Err(format!(
+                "Unknown entity type {}, expected album, episode or content",
+                value
+            )),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum TrashError {
+    NotFound,
+    /// The parent album or episode is itself in the trash
+    ParentInTrash(&'static str),
+    Database(diesel::result::Error),
+}
+
+impl fmt::Display for TrashError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrashError::NotFound => write!(f, "No item found in trash"),
+            TrashError::ParentInTrash(parent) => {
+                write!(
+                    f,
+                    "The {} of this item is in the trash, restore it first",
+                    parent
+                )
+            }
+            TrashError::Database(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<diesel::result::Error> for TrashError {
+    fn from(e: diesel::result::Error) -> Self {
+        TrashError::Database(e)
+    }
+}
+
+/// A soft deleted row as read from the database
+#[derive(Debug, Clone)]
+pub struct TrashItem {
+    pub entity_type: TrashEntity,
+    pub uuid: String,
+    pub title: String,
+    pub deleted_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, PartialEq, Eq)]
+pub struct TrashItemResponse {
+    /// album, episode or content
+    pub entity_type: String,
+    pub uuid: String,
+    /// Album or episode title, content file URL
+    pub title: String,
+    pub deleted_at: Option<String>,
+    /// When the item is purged automatically
+    pub purge_at: Option<String>,
+}
+
+impl TrashItemResponse {
+    pub fn from_trash_item(item: TrashItem, retention_days: i64) -> Self {
+        TrashItemResponse {
+            entity_type: item.entity_type.as_str().to_string(),
+            uuid: item.uuid,
+            title: item.title,
+            deleted_at: NEParse::opt_naive_datetime_to_utc_opt_string(Some(item.deleted_at)),
+            purge_at: NEParse::opt_naive_datetime_to_utc_opt_string(Some(
+                item.deleted_at + Duration::days(retention_days),
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GetTrashRequest {
+    /// Filter entity type (album, episode, content)
+    pub entity_type: Option<String>,
+    /// Filter offset
+    pub offset: Option<i64>,
+    /// Filter limit, 1 to 500, default 50
+    pub limit: Option<i64>,
+}
+
+impl GetTrashRequest {
+    /// Requested offset, never negative
+    pub fn offset(&self) -> i64 {
+        self.offset.unwrap_or(0).max(0)
+    }
+
+    /// Requested limit kept within 1 to [`MAX_TRASH_LIMIT`], SQLite reads a negative limit as no
+    /// limit
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(50).clamp(1, MAX_TRASH_LIMIT)
+    }
+}
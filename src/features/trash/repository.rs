@@ -0,0 +1,280 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Nullable, Text, Timestamp};
+
+use crate::common::database::DbPool;
+use crate::features::album::models::Album;
+use crate::features::content::models::Content;
+use crate::features::content::repository::{load_page_order, write_page_order};
+use crate::features::episode::models::Episode;
+use crate::features::trash::models::{TrashEntity, TrashItem};
+use crate::schema::{
+    album_categories, album_credits, album_daily_views, album_reviews, album_translations, albums,
+    contents, episode_daily_views, episode_translations, episodes,
+};
+
+/// Soft deleted albums, episodes and contents as `entity_type, uuid, title, deleted_at`
+const TRASH_ITEMS_SQL: &str = "\
+    SELECT 'album' AS entity_type, uuid, title, deleted_at FROM albums \
+     WHERE deleted_at IS NOT NULL \
+    UNION ALL SELECT 'episode', uuid, title, deleted_at FROM episodes \
+     WHERE deleted_at IS NOT NULL \
+    UNION ALL SELECT 'content', uuid, url, deleted_at FROM contents \
+     WHERE deleted_at IS NOT NULL";
+
+#[derive(QueryableByName)]
+struct TrashRow {
+    #[diesel(sql_type = Text)]
+    entity_type: String,
+    #[diesel(sql_type = Text)]
+    uuid: String,
+    #[diesel(sql_type = Text)]
+    title: String,
+    #[diesel(sql_type = Timestamp)]
+    deleted_at: NaiveDateTime,
+}
+
+#[derive(QueryableByName)]
+struct TrashCount {
+    #[diesel(sql_type = BigInt)]
+    total: i64,
+}
+
+pub struct Repository;
+
+impl Repository {
+    /// Page of soft deleted rows of the given type, or of every type when `entity` is `None`,
+    /// most recently deleted first, with the total count
+    pub async fn get_trash_items(
+        pool: &DbPool,
+        entity: Option<TrashEntity>,
+        offset: i64,
+        limit: i64,
+    ) -> QueryResult<(Vec<TrashItem>, i64)> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        let entity_type = entity.map(|entity| entity.as_str());
+        let total = diesel::sql_query(format!(
+            "SELECT COUNT(*) AS total FROM ({}) WHERE ? IS NULL OR entity_type = ?",
+            TRASH_ITEMS_SQL
+        ))
+        .bind::<Nullable<Text>, _>(entity_type)
+        .bind::<Nullable<Text>, _>(entity_type)
+        .get_result::<TrashCount>(&mut conn)?
+        .total;
+        let rows = diesel::sql_query(format!(
+            "SELECT entity_type, uuid, title, deleted_at FROM ({}) \
+             WHERE ? IS NULL OR entity_type = ? \
+             ORDER BY deleted_at DESC, entity_type, uuid LIMIT ? OFFSET ?",
+            TRASH_ITEMS_SQL
+        ))
+        .bind::<Nullable<Text>, _>(entity_type)
+        .bind::<Nullable<Text>, _>(entity_type)
+        .bind::<BigInt, _>(limit)
+        .bind::<BigInt, _>(offset)
+        .load::<TrashRow>(&mut conn)?;
+        Ok((to_trash_items(rows), total))
+    }
+
+    /// Soft deleted rows deleted before `deleted_before`, oldest first
+    pub async fn get_expired_items(
+        pool: &DbPool,
+        deleted_before: NaiveDateTime,
+    ) -> QueryResult<Vec<TrashItem>> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        let rows = diesel::sql_query(format!(
+            "SELECT entity_type, uuid, title, deleted_at FROM ({}) \
+             WHERE deleted_at < ? ORDER BY deleted_at, entity_type, uuid",
+            TRASH_ITEMS_SQL
+        ))
+        .bind::<Timestamp, _>(deleted_before)
+        .load::<TrashRow>(&mut conn)?;
+        Ok(to_trash_items(rows))
+    }
+
+    pub async fn get_trashed_album(pool: &DbPool, album_uuid: String) -> QueryResult<Album> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        albums::table
+            .filter(albums::uuid.eq(album_uuid))
+            .filter(albums::deleted_at.is_not_null())
+            .select(Album::as_select())
+            .first(&mut conn)
+    }
+
+    /// Trashed episode with the UUID and deleted_at of its album
+    pub async fn get_trashed_episode(
+        pool: &DbPool,
+        episode_uuid: String,
+    ) -> QueryResult<(Episode, String, Option<NaiveDateTime>)> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        episodes::table
+            .inner_join(albums::table.on(albums::id.eq(episodes::album_id)))
+            .filter(episodes::uuid.eq(episode_uuid))
+            .filter(episodes::deleted_at.is_not_null())
+            .select((Episode::as_select(), albums::uuid, albums::deleted_at))
+            .first(&mut conn)
+    }
+
+    /// Trashed content with the deleted_at of its episode and album
+    pub async fn get_trashed_content(
+        pool: &DbPool,
+        content_uuid: String,
+    ) -> QueryResult<(Content, Option<NaiveDateTime>, Option<NaiveDateTime>)> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        contents::table
+            .inner_join(episodes::table.on(episodes::id.eq(contents::episode_id.nullable())))
+            .inner_join(albums::table.on(albums::id.eq(episodes::album_id)))
+            .filter(contents::uuid.eq(content_uuid))
+            .filter(contents::deleted_at.is_not_null())
+            .select((
+                Content::as_select(),
+                episodes::deleted_at,
+                albums::deleted_at,
+            ))
+            .first(&mut conn)
+    }
+
+    pub async fn restore_album(pool: &DbPool, album_uuid: String) -> QueryResult<usize> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        diesel::update(albums::table.filter(albums::uuid.eq(album_uuid)))
+            .set(albums::deleted_at.eq(None::<NaiveDateTime>))
+            .execute(&mut conn)
+    }
+
+    pub async fn restore_episode(pool: &DbPool, episode_uuid: String) -> QueryResult<usize> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        diesel::update(episodes::table.filter(episodes::uuid.eq(episode_uuid)))
+            .set(episodes::deleted_at.eq(None::<NaiveDateTime>))
+            .execute(&mut conn)
+    }
+
+    /// Restore a content to its former page, shifting the following pages
+    pub async fn restore_content(pool: &DbPool, content: &Content) -> QueryResult<usize> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        let content_id = content.id.unwrap_or_default();
+        conn.transaction(|conn| {
+            let mut order = load_page_order(conn, content.episode_id, &content.locale)?;
+            // Parked like `write_page_order` does until the pages are renumbered
+            let restored = diesel::update(contents::table.filter(contents::uuid.eq(&content.uuid)))
+                .set((
+                    contents::deleted_at.eq(None::<NaiveDateTime>),
+                    contents::index_no.eq(-1 - content_id),
+                ))
+                .execute(conn)?;
+            let at = content.index_no.clamp(0, order.len() as i32) as usize;
+            order.insert(at, content_id);
+            write_page_order(conn, content.episode_id, &content.locale, &order)?;
+            Ok(restored)
+        })
+    }
+
+    /// UUIDs of the episodes and contents that belong to an album, trashed or not
+    pub async fn get_album_children_uuids(
+        pool: &DbPool,
+        album_id: i32,
+    ) -> QueryResult<(Vec<String>, Vec<String>)> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        let episode_uuids = episodes::table
+            .filter(episodes::album_id.eq(album_id))
+            .select(episodes::uuid)
+            .load::<String>(&mut conn)?;
+        let content_uuids = contents::table
+            .inner_join(episodes::table.on(episodes::id.eq(contents::episode_id.nullable())))
+            .filter(episodes::album_id.eq(album_id))
+            .select(contents::uuid)
+            .load::<String>(&mut conn)?;
+        Ok((episode_uuids, content_uuids))
+    }
+
+    pub async fn get_episode_content_uuids(
+        pool: &DbPool,
+        episode_id: i32,
+    ) -> QueryResult<Vec<String>> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        contents::table
+            .filter(contents::episode_id.eq(episode_id))
+            .select(contents::uuid)
+            .load::<String>(&mut conn)
+    }
+
+    /// Hard delete an album with its episodes and contents
+    pub async fn purge_album(pool: &DbPool, album_id: i32) -> QueryResult<usize> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        conn.transaction(|conn| {
+            let episode_ids = episodes::table
+                .filter(episodes::album_id.eq(album_id))
+                .select(episodes::id)
+                .load::<Option<i32>>(conn)?
+                .into_iter()
+                .flatten()
+                .collect::<Vec<i32>>();
+            diesel::delete(contents::table.filter(contents::episode_id.eq_any(&episode_ids)))
+                .execute(conn)?;
+            diesel::delete(
+                episode_daily_views::table
+                    .filter(episode_daily_views::episode_id.eq_any(&episode_ids)),
+            )
+            .execute(conn)?;
+            diesel::delete(
+                episode_translations::table
+                    .filter(episode_translations::episode_id.eq_any(&episode_ids)),
+            )
+            .execute(conn)?;
+            diesel::delete(
+                album_daily_views::table.filter(album_daily_views::album_id.eq(album_id)),
+            )
+            .execute(conn)?;
+            diesel::delete(
+                album_translations::table.filter(album_translations::album_id.eq(album_id)),
+            )
+            .execute(conn)?;
+            diesel::delete(album_categories::table.filter(album_categories::album_id.eq(album_id)))
+                .execute(conn)?;
+            diesel::delete(album_credits::table.filter(album_credits::album_id.eq(album_id)))
+                .execute(conn)?;
+            diesel::delete(album_reviews::table.filter(album_reviews::album_id.eq(album_id)))
+                .execute(conn)?;
+            diesel::delete(episodes::table.filter(episodes::album_id.eq(album_id)))
+                .execute(conn)?;
+            diesel::delete(albums::table.filter(albums::id.eq(album_id))).execute(conn)
+        })
+    }
+
+    /// Hard delete an episode with its contents
+    pub async fn purge_episode(pool: &DbPool, episode_id: i32) -> QueryResult<usize> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        conn.transaction(|conn| {
+            diesel::delete(contents::table.filter(contents::episode_id.eq(episode_id)))
+                .execute(conn)?;
+            diesel::delete(
+                episode_daily_views::table.filter(episode_daily_views::episode_id.eq(episode_id)),
+            )
+            .execute(conn)?;
+            diesel::delete(
+                episode_translations::table.filter(episode_translations::episode_id.eq(episode_id)),
+            )
+            .execute(conn)?;
+            diesel::delete(episodes::table.filter(episodes::id.eq(episode_id))).execute(conn)
+        })
+    }
+
+    pub async fn purge_content(pool: &DbPool, content_uuid: String) -> QueryResult<usize> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        diesel::delete(contents::table.filter(contents::uuid.eq(content_uuid))).execute(&mut conn)
+    }
+}
+
+fn to_trash_items(rows: Vec<TrashRow>) -> Vec<TrashItem> {
+    rows.into_iter()
+        .filter_map(|row| {
+            row.entity_type
+                .parse::<TrashEntity>()
+                .ok()
+                .map(|entity_type| TrashItem {
+                    entity_type,
+                    uuid: row.uuid,
+                    title: row.title,
+                    deleted_at: row.deleted_at,
+                })
+        })
+        .collect()
+}
@@ -0,0 +1,438 @@
+use chrono::{Duration, Utc};
+
+use crate::common::config;
+use crate::common::database::DbPool;
+use crate::common::models::response_data::ResponseData;
+use crate::common::utils::{
+    delete_directory_if_exists, get_data_directory, get_file_name_from_path, get_trash_directory,
+    move_path,
+};
+use crate::features::trash::models::{GetTrashRequest, TrashEntity, TrashError, TrashItemResponse};
+use crate::features::trash::repository::Repository;
+
+pub struct Service;
+
+impl Service {
+    pub async fn get_trash_items(
+        pool: &DbPool,
+        filters: GetTrashRequest,
+    ) -> Result<ResponseData<TrashItemResponse>, diesel::result::Error> {
+        let entity = filters
+            .entity_type
+            .as_deref()
+            .and_then(|value| value.parse::<TrashEntity>().ok());
+        let (offset, limit) = (filters.offset(), filters.limit());
+        let (items, total) = Repository::get_trash_items(pool, entity, offset, limit).await?;
+        let retention_days = config::get().storage.trash_retention_days;
+        let data = items
+            .into_iter()
+            .map(|item| TrashItemResponse::from_trash_item(item, retention_days))
+            .collect();
+        Ok(ResponseData::new(data, total, offset))
+    }
+
+    pub async fn restore(
+        pool: &DbPool,
+        entity: TrashEntity,
+        uuid: String,
+    ) -> Result<usize, TrashError> {
+        match entity {
+            TrashEntity::Album => {
+                let album = Repository::get_trashed_album(pool, uuid.clone())
+                    .await
+                    .map_err(not_found)?;
+                let restored = Repository::restore_album(pool, album.uuid.clone()).await?;
+                restore_files(
+                    &format!("{}/albums/{}", get_trash_directory(), album.uuid),
+                    &format!("{}/{}", get_data_directory(), album.uuid),
+                );
+                Ok(restored)
+            }
+            TrashEntity::Episode => {
+                let (episode, album_uuid, album_deleted_at) =
+                    Repository::get_trashed_episode(pool, uuid.clone())
+                        .await
+                        .map_err(not_found)?;
+                if album_deleted_at.is_some() {
+                    return Err(TrashError::ParentInTrash("album"));
+                }
+                let restored = Repository::restore_episode(pool, episode.uuid.clone()).await?;
+                restore_files(
+                    &format!("{}/episodes/{}", get_trash_directory(), episode.uuid),
+                    &format!("{}/{}/{}", get_data_directory(), album_uuid, episode.uuid),
+                );
+                Ok(restored)
+            }
+            TrashEntity::Content => {
+                let (content, episode_deleted_at, album_deleted_at) =
+                    Repository::get_trashed_content(pool, uuid.clone())
+                        .await
+                        .map_err(not_found)?;
+                if album_deleted_at.is_some() {
+                    return Err(TrashError::ParentInTrash("album"));
+                }
+                if episode_deleted_at.is_some() {
+                    return Err(TrashError::ParentInTrash("episode"));
+                }
+                let restored = Repository::restore_content(pool, &content).await?;
+                let trash_path = format!("{}/contents/{}", get_trash_directory(), content.uuid);
+                restore_files(
+                    &format!("{}/{}", trash_path, get_file_name_from_path(&content.url)),
+                    &content.url,
+                );
+                delete_directory_if_exists(&trash_path);
+                Ok(restored)
+            }
+        }
+    }
+
+    pub async fn purge(
+        pool: &DbPool,
+        entity: TrashEntity,
+        uuid: String,
+    ) -> Result<usize, TrashError> {
+        let trash_dir = get_trash_directory();
+        match entity {
+            TrashEntity::Album => {
+                let album = Repository::get_trashed_album(pool, uuid)
+                    .await
+                    .map_err(not_found)?;
+                let (episode_uuids, content_uuids) =
+                    Repository::get_album_children_uuids(pool, album.id).await?;
+                let purged = Repository::purge_album(pool, album.id).await?;
+                delete_directory_if_exists(&format!("{}/albums/{}", trash_dir, album.uuid));
+                for episode_uuid in episode_uuids {
+                    delete_directory_if_exists(&format!("{}/episodes/{}", trash_dir, episode_uuid));
+                }
+                for content_uuid in content_uuids {
+                    delete_directory_if_exists(&format!("{}/contents/{}", trash_dir, content_uuid));
+                }
+                Ok(purged)
+            }
+            TrashEntity::Episode => {
+                let (episode, _, _) = Repository::get_trashed_episode(pool, uuid)
+                    .await
+                    .map_err(not_found)?;
+                let episode_id = episode.id.ok_or(TrashError::NotFound)?;
+                let content_uuids = Repository::get_episode_content_uuids(pool, episode_id).await?;
+                let purged = Repository::purge_episode(pool, episode_id).await?;
+                delete_directory_if_exists(&format!("{}/episodes/{}", trash_dir, episode.uuid));
+                for content_uuid in content_uuids {
+                    delete_directory_if_exists(&format!("{}/contents/{}", trash_dir, content_uuid));
+                }
+                Ok(purged)
+            }
+            TrashEntity::Content => {
+                let (content, _, _) = Repository::get_trashed_content(pool, uuid)
+                    .await
+                    .map_err(not_found)?;
+                let purged = Repository::purge_content(pool, content.uuid.clone()).await?;
+                delete_directory_if_exists(&format!("{}/contents/{}", trash_dir, content.uuid));
+                Ok(purged)
+            }
+        }
+    }
+
+    /// Purge every item that has been in the trash longer than the retention period
+    pub async fn purge_expired(pool: &DbPool) -> Result<usize, diesel::result::Error> {
+        let retention_days = config::get().storage.trash_retention_days;
+        let deleted_before = Utc::now().naive_utc() - Duration::days(retention_days);
+        let items = Repository::get_expired_items(pool, deleted_before).await?;
+        let mut purged = 0;
+        for item in items {
+            match Self::purge(pool, item.entity_type, item.uuid.clone()).await {
+                Ok(size) => purged += size,
+                // Already removed together with its album or episode
+                Err(TrashError::NotFound) => {}
+                Err(e) => eprintln!(
+                    "Failed to purge {} {} from trash: {}",
+                    item.entity_type.as_str(),
+                    item.uuid,
+                    e
+                ),
+            }
+        }
+        Ok(purged)
+    }
+}
+
+fn not_found(e: diesel::result::Error) -> TrashError {
+    match e {
+        diesel::result::Error::NotFound => TrashError::NotFound,
+        e => TrashError::Database(e),
+    }
+}
+
+fn restore_files(trash_path: &str, data_path: &str) {
+    if let Err(e) = move_path(trash_path, data_path) {
+        eprintln!("Failed to restore {} to {}: {}", trash_path, data_path, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use diesel::prelude::*;
+    use diesel::sql_types::{Integer, Text};
+
+    use super::*;
+    use crate::common::test_support;
+    use crate::features::album::services::Service as AlbumService;
+    use crate::features::content::services::Service as ContentService;
+    use crate::schema::{
+        album_categories, album_daily_views, album_translations, albums, contents,
+        episode_daily_views, episodes,
+    };
+
+    fn insert_album(pool: &DbPool, album_uuid: &str) -> i32 {
+        let mut conn = pool.get().unwrap();
+        diesel::sql_query(
+            "INSERT INTO albums (uuid, category_id, title, description, images, url, \
+             content_type, width, height, bytes) VALUES (?, 1, 'Album', '', '', '', '', 0, 0, 0)",
+        )
+        .bind::<Text, _>(album_uuid)
+        .execute(&mut conn)
+        .unwrap();
+        albums::table
+            .filter(albums::uuid.eq(album_uuid))
+            .select(albums::id)
+            .first(&mut conn)
+            .unwrap()
+    }
+
+    fn insert_episode(pool: &DbPool, album_id: i32) -> i32 {
+        let mut conn = pool.get().unwrap();
+        let episode_uuid = uuid::Uuid::new_v4().to_string();
+        diesel::sql_query(
+            "INSERT INTO episodes (album_id, uuid, title, content_type, width, height, bytes) \
+             VALUES (?, ?, 'Episode', 'video/mp4', 0, 0, 0)",
+        )
+        .bind::<Integer, _>(album_id)
+        .bind::<Text, _>(&episode_uuid)
+        .execute(&mut conn)
+        .unwrap();
+        episodes::table
+            .filter(episodes::uuid.eq(&episode_uuid))
+            .select(episodes::id)
+            .first::<Option<i32>>(&mut conn)
+            .unwrap()
+            .unwrap()
+    }
+
+    /// Page of the episode with its file at `url`, returns the content uuid
+    fn insert_content(pool: &DbPool, episode_id: i32, index_no: i32, url: &Path) -> String {
+        let content_uuid = uuid::Uuid::new_v4().to_string();
+        fs::write(url, content_uuid.as_bytes()).unwrap();
+        diesel::sql_query(
+            "INSERT INTO contents (episode_id, uuid, index_no, url, content_type, width, height, \
+             bytes) VALUES (?, ?, ?, ?, 'image/jpeg', 0, 0, 0)",
+        )
+        .bind::<Integer, _>(episode_id)
+        .bind::<Text, _>(&content_uuid)
+        .bind::<Integer, _>(index_no)
+        .bind::<Text, _>(url.to_string_lossy().to_string())
+        .execute(&mut pool.get().unwrap())
+        .unwrap();
+        content_uuid
+    }
+
+    /// Live pages of the episode in page order
+    fn pages(pool: &DbPool, episode_id: i32) -> Vec<(String, i32)> {
+        contents::table
+            .filter(contents::episode_id.eq(episode_id))
+            .filter(contents::deleted_at.is_null())
+            .order(contents::index_no.asc())
+            .select((contents::uuid, contents::index_no))
+            .load(&mut pool.get().unwrap())
+            .unwrap()
+    }
+
+    #[actix_web::test]
+    async fn restored_content_returns_to_its_page() {
+        let pool = test_support::test_pool();
+        let album_id = insert_album(&pool, &uuid::Uuid::new_v4().to_string());
+        let episode_id = insert_episode(&pool, album_id);
+        let directory = test_support::data_subdir();
+        let urls = (0..3)
+            .map(|index| directory.join(format!("{}.jpg", index)))
+            .collect::<Vec<_>>();
+        let uuids = urls
+            .iter()
+            .enumerate()
+            .map(|(index, url)| insert_content(&pool, episode_id, index as i32, url))
+            .collect::<Vec<_>>();
+
+        ContentService::delete_content(&pool, uuids[1].clone())
+            .await
+            .unwrap();
+        assert!(!urls[1].exists());
+        let trash_path = Path::new(&get_trash_directory())
+            .join("contents")
+            .join(&uuids[1]);
+        assert!(trash_path.join("1.jpg").is_file());
+        // The following page moved up into the freed index
+        assert_eq!(
+            pages(&pool, episode_id),
+            vec![(uuids[0].clone(), 0), (uuids[2].clone(), 1)]
+        );
+
+        let restored = Service::restore(&pool, TrashEntity::Content, uuids[1].clone())
+            .await
+            .unwrap();
+        assert_eq!(restored, 1);
+        assert_eq!(
+            pages(&pool, episode_id),
+            vec![
+                (uuids[0].clone(), 0),
+                (uuids[1].clone(), 1),
+                (uuids[2].clone(), 2)
+            ]
+        );
+        assert_eq!(fs::read_to_string(&urls[1]).unwrap(), uuids[1]);
+        assert!(!trash_path.exists());
+    }
+
+    #[actix_web::test]
+    async fn restored_album_gets_its_files_back() {
+        let pool = test_support::test_pool();
+        let album_directory = test_support::data_subdir();
+        let album_uuid = album_directory
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        fs::write(album_directory.join("cover.jpg"), b"cover").unwrap();
+        insert_album(&pool, &album_uuid);
+
+        AlbumService::delete_album(&pool, album_uuid.clone())
+            .await
+            .unwrap();
+        let trash_path = Path::new(&get_trash_directory())
+            .join("albums")
+            .join(&album_uuid);
+        assert!(!album_directory.exists());
+        assert!(trash_path.join("cover.jpg").is_file());
+
+        Service::restore(&pool, TrashEntity::Album, album_uuid.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(album_directory.join("cover.jpg")).unwrap(),
+            "cover"
+        );
+        assert!(!trash_path.exists());
+        let deleted_at = albums::table
+            .filter(albums::uuid.eq(&album_uuid))
+            .select(albums::deleted_at)
+            .first::<Option<chrono::NaiveDateTime>>(&mut pool.get().unwrap())
+            .unwrap();
+        assert_eq!(deleted_at, None);
+    }
+
+    /// Album with an episode, a page and rows in the tables referencing them
+    fn insert_album_with_children(pool: &DbPool) -> (String, i32, i32) {
+        let album_uuid = uuid::Uuid::new_v4().to_string();
+        let album_id = insert_album(pool, &album_uuid);
+        let episode_id = insert_episode(pool, album_id);
+        insert_content(
+            pool,
+            episode_id,
+            0,
+            &test_support::data_subdir().join("0.jpg"),
+        );
+        let mut conn = pool.get().unwrap();
+        for statement in [
+            "INSERT INTO album_categories (album_id, category_id, position) VALUES (?, 1, 0)",
+            "INSERT INTO album_daily_views (album_id, day, views, reads) \
+             VALUES (?, '2026-10-19', 3, 1)",
+            "INSERT INTO album_translations (album_id, locale, title, description) \
+             VALUES (?, 'pt-br', 'Album', '')",
+        ] {
+            diesel::sql_query(statement)
+                .bind::<Integer, _>(album_id)
+                .execute(&mut conn)
+                .unwrap();
+        }
+        diesel::sql_query(
+            "INSERT INTO episode_daily_views (episode_id, day, reads) VALUES (?, '2026-10-19', 1)",
+        )
+        .bind::<Integer, _>(episode_id)
+        .execute(&mut conn)
+        .unwrap();
+        diesel::sql_query("UPDATE albums SET deleted_at = CURRENT_TIMESTAMP WHERE id = ?")
+            .bind::<Integer, _>(album_id)
+            .execute(&mut conn)
+            .unwrap();
+        (album_uuid, album_id, episode_id)
+    }
+
+    /// Rows left of the album in every table `purge_album` clears
+    fn remaining_rows(pool: &DbPool, album_id: i32, episode_id: i32) -> i64 {
+        let mut conn = pool.get().unwrap();
+        let count = |count: QueryResult<i64>| count.unwrap();
+        count(
+            albums::table
+                .filter(albums::id.eq(album_id))
+                .count()
+                .get_result(&mut conn),
+        ) + count(
+            episodes::table
+                .filter(episodes::album_id.eq(album_id))
+                .count()
+                .get_result(&mut conn),
+        ) + count(
+            contents::table
+                .filter(contents::episode_id.eq(episode_id))
+                .count()
+                .get_result(&mut conn),
+        ) + count(
+            album_categories::table
+                .filter(album_categories::album_id.eq(album_id))
+                .count()
+                .get_result(&mut conn),
+        ) + count(
+            album_daily_views::table
+                .filter(album_daily_views::album_id.eq(album_id))
+                .count()
+                .get_result(&mut conn),
+        ) + count(
+            album_translations::table
+                .filter(album_translations::album_id.eq(album_id))
+                .count()
+                .get_result(&mut conn),
+        ) + count(
+            episode_daily_views::table
+                .filter(episode_daily_views::episode_id.eq(episode_id))
+                .count()
+                .get_result(&mut conn),
+        )
+    }
+
+    #[actix_web::test]
+    async fn purged_album_takes_its_rows_along_or_none_at_all() {
+        let pool = test_support::test_pool();
+        let (album_uuid, album_id, episode_id) = insert_album_with_children(&pool);
+        let (kept_uuid, kept_id, kept_episode_id) = insert_album_with_children(&pool);
+        assert_eq!(remaining_rows(&pool, kept_id, kept_episode_id), 7);
+
+        Service::purge(&pool, TrashEntity::Album, album_uuid)
+            .await
+            .unwrap();
+        assert_eq!(remaining_rows(&pool, album_id, episode_id), 0);
+        assert_eq!(remaining_rows(&pool, kept_id, kept_episode_id), 7);
+
+        // A failure on the last delete rolls back the dependent rows deleted before it
+        diesel::sql_query(
+            "CREATE TRIGGER keep_albums BEFORE DELETE ON albums \
+             BEGIN SELECT RAISE(ABORT, 'albums are kept'); END",
+        )
+        .execute(&mut pool.get().unwrap())
+        .unwrap();
+        assert!(Service::purge(&pool, TrashEntity::Album, kept_uuid)
+            .await
+            .is_err());
+        assert_eq!(remaining_rows(&pool, kept_id, kept_episode_id), 7);
+    }
+}
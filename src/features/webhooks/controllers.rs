@@ -0,0 +1,428 @@
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+
+use crate::common::database::DbPool;
+use crate::common::enums::Role::Admin;
+use crate::common::models::response_message::ResponseMessage;
+use crate::features::webhooks::models::{
+    CreateWebhookRequest, GetWebhookDeliveriesRequest, UpdateWebhookRequest, WebhookError,
+    WebhookResponse,
+};
+use crate::features::webhooks::services::Service;
+use crate::features::{audit_mutation, check_role};
+
+/// Create Webhook
+///
+/// Register an endpoint for catalog events, the response is the only one containing the secret
+#[utoipa::path(
+    post,
+    path = "/webhooks",
+    request_body(
+        content = CreateWebhookRequest,
+        description = "Create Webhook",
+        content_type = "application/json",
+    ),
+    responses(
+        (status = 201, description = "Create successfully", body = WebhookResponse),
+        (status = 400, description = "Bad Request", body = ResponseMessage),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Webhooks",
+)]
+#[post("/webhooks")]
+pub async fn create_webhook(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    req: web::Json<CreateWebhookRequest>,
+) -> impl Responder {
+    if check_role(http_request.clone()) == Admin {
+        match Service::create_webhook(&pool, req.into_inner()).await {
+            Ok(webhook) => {
+                audit_mutation(
+                    &pool,
+                    &http_request,
+                    "create",
+                    "webhook",
+                    webhook.uuid.clone(),
+                    None,
+                    audit_snapshot(&webhook),
+                )
+                .await;
+                HttpResponse::Created().json(webhook)
+            }
+            Err(e) => webhook_error_response("create", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Get Webhooks
+///
+/// Get registered webhooks
+#[utoipa::path(
+    get,
+    path = "/webhooks",
+    responses(
+        (status = 200, description = "Request successfully", body = ResponseDataWebhook),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Webhooks",
+)]
+#[get("/webhooks")]
+pub async fn get_webhooks(pool: web::Data<DbPool>, http_request: HttpRequest) -> impl Responder {
+    if check_role(http_request) == Admin {
+        match Service::get_webhooks(&pool).await {
+            Ok(data) => HttpResponse::Ok().json(data),
+            Err(e) => webhook_error_response("get", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Get Webhook
+///
+/// Get a webhook by UUID
+#[utoipa::path(
+    get,
+    path = "/webhooks/{webhook_uuid}",
+    params(
+        ("webhook_uuid" = String, Path, description = "Webhook UUID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e")
+    ),
+    responses(
+        (status = 200, description = "Request successfully", body = WebhookResponse),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 404, description = "Webhook Not Found", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Webhooks",
+)]
+#[get("/{webhook_uuid}")]
+pub async fn get_webhook(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+) -> impl Responder {
+    if check_role(http_request) == Admin {
+        match Service::get_webhook(&pool, path.into_inner()).await {
+            Ok(webhook) => HttpResponse::Ok().json(webhook),
+            Err(e) => webhook_error_response("get", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Update Webhook
+///
+/// Change the endpoint, subscribed events or rotate the secret
+#[utoipa::path(
+    put,
+    path = "/webhooks/{webhook_uuid}",
+    params(
+        ("webhook_uuid" = String, Path, description = "Webhook UUID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e")
+    ),
+    request_body(
+        content = UpdateWebhookRequest,
+        description = "Update Webhook",
+        content_type = "application/json",
+    ),
+    responses(
+        (status = 200, description = "Update successfully", body = WebhookResponse),
+        (status = 400, description = "Bad Request", body = ResponseMessage),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 404, description = "Webhook Not Found", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Webhooks",
+)]
+#[put("/{webhook_uuid}")]
+pub async fn update_webhook(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+    req: web::Json<UpdateWebhookRequest>,
+) -> impl Responder {
+    if check_role(http_request.clone()) == Admin {
+        let webhook_uuid = path.into_inner();
+        let before = Service::get_webhook(&pool, webhook_uuid.clone())
+            .await
+            .ok()
+            .and_then(|webhook| audit_snapshot(&webhook));
+        match Service::update_webhook(&pool, webhook_uuid, req.into_inner()).await {
+            Ok(webhook) => {
+                audit_mutation(
+                    &pool,
+                    &http_request,
+                    "update",
+                    "webhook",
+                    webhook.uuid.clone(),
+                    before,
+                    audit_snapshot(&webhook),
+                )
+                .await;
+                HttpResponse::Ok().json(webhook)
+            }
+            Err(e) => webhook_error_response("update", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Delete Webhook
+///
+/// Delete a webhook and its delivery log
+#[utoipa::path(
+    delete,
+    path = "/webhooks/{webhook_uuid}",
+    params(
+        ("webhook_uuid" = String, Path, description = "Webhook UUID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e")
+    ),
+    responses(
+        (status = 204, description = "Delete successfully"),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 404, description = "Webhook Not Found", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Webhooks",
+)]
+#[delete("/{webhook_uuid}")]
+pub async fn delete_webhook(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+) -> impl Responder {
+    if check_role(http_request.clone()) == Admin {
+        let webhook_uuid = path.into_inner();
+        let before = Service::get_webhook(&pool, webhook_uuid.clone())
+            .await
+            .ok()
+            .and_then(|webhook| audit_snapshot(&webhook));
+        match Service::delete_webhook(&pool, webhook_uuid.clone()).await {
+            Ok(()) => {
+                audit_mutation(
+                    &pool,
+                    &http_request,
+                    "delete",
+                    "webhook",
+                    webhook_uuid,
+                    before,
+                    None,
+                )
+                .await;
+                HttpResponse::NoContent().json("")
+            }
+            Err(e) => webhook_error_response("delete", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Get Webhook Deliveries
+///
+/// Get the delivery log of a webhook, newest first
+#[utoipa::path(
+    get,
+    path = "/webhooks/{webhook_uuid}/deliveries",
+    params(
+        ("webhook_uuid" = String, Path, description = "Webhook UUID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e"),
+        GetWebhookDeliveriesRequest
+    ),
+    responses(
+        (status = 200, description = "Request successfully", body = ResponseDataWebhookDelivery),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 404, description = "Webhook Not Found", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Webhooks",
+)]
+#[get("/{webhook_uuid}/deliveries")]
+pub async fn get_webhook_deliveries(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+    req: web::Query<GetWebhookDeliveriesRequest>,
+) -> impl Responder {
+    if check_role(http_request) == Admin {
+        match Service::get_deliveries(&pool, path.into_inner(), req.into_inner()).await {
+            Ok(data) => HttpResponse::Ok().json(data),
+            Err(e) => webhook_error_response("get deliveries of", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Test Webhook
+///
+/// Send a signed `webhook.test` event right away and return the delivery result
+#[utoipa::path(
+    post,
+    path = "/webhooks/{webhook_uuid}/test",
+    params(
+        ("webhook_uuid" = String, Path, description = "Webhook UUID", style = Simple, example = "fd2fe858-9962-404f-9174-c4f6f83cc39e")
+    ),
+    responses(
+        (status = 200, description = "Delivery attempted, see status", body = WebhookDeliveryResponse),
+        (status = 401, description = "Unauthorized error", body = ResponseMessage),
+        (status = 404, description = "Webhook Not Found", body = ResponseMessage),
+        (status = 500, description = "Internal server error", body = ResponseMessage)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Webhooks",
+)]
+#[post("/{webhook_uuid}/test")]
+pub async fn test_webhook(
+    pool: web::Data<DbPool>,
+    http_request: HttpRequest,
+    path: web::Path<String>,
+) -> impl Responder {
+    if check_role(http_request) == Admin {
+        match Service::test_fire(&pool, path.into_inner()).await {
+            Ok(delivery) => HttpResponse::Ok().json(delivery),
+            Err(e) => webhook_error_response("test", e),
+        }
+    } else {
+        HttpResponse::Unauthorized().json(ResponseMessage {
+            message: String::from("Unauthorized"),
+        })
+    }
+}
+
+/// Webhook as stored in the audit log, the secret is never recorded
+fn audit_snapshot(webhook: &WebhookResponse) -> Option<serde_json::Value> {
+    serde_json::to_value(WebhookResponse {
+        secret: None,
+        ..webhook.clone()
+    })
+    .ok()
+}
+
+fn webhook_error_response(action: &str, e: WebhookError) -> HttpResponse {
+    match e {
+        WebhookError::NotFound => HttpResponse::NotFound().json(ResponseMessage {
+            message: e.to_string(),
+        }),
+        WebhookError::Invalid(_) => HttpResponse::BadRequest().json(ResponseMessage {
+            message: e.to_string(),
+        }),
+        WebhookError::Database(_) => {
+            println!("Failed to {} webhook: {}", action, e);
+            HttpResponse::InternalServerError().json(ResponseMessage {
+                message: String::from("Internal Server Error"),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{test, App};
+    use serde_json::{json, Value};
+
+    use crate::common::middleware::api_key_middleware::ApiKeyMiddleware;
+    use crate::common::test_support::{self, ADMIN_API_KEY, USER_API_KEY};
+    use crate::features::webhooks;
+    use crate::features::webhooks::services::sign;
+
+    #[actix_web::test]
+    async fn test_endpoint_delivers_a_signed_event_and_logs_it() {
+        let pool = test_support::test_pool();
+        let stand_in = test_support::stand_in();
+        let path = format!("/{}/hook", uuid::Uuid::new_v4());
+        let app = test::init_service(
+            App::new()
+                .wrap(ApiKeyMiddleware::new(
+                    ADMIN_API_KEY.to_string(),
+                    USER_API_KEY.to_string(),
+                    vec![],
+                ))
+                .app_data(actix_web::web::Data::new(pool))
+                .configure(webhooks::configure),
+        )
+        .await;
+
+        let request = test::TestRequest::post()
+            .uri("/webhooks")
+            .insert_header(("X-API-KEY", ADMIN_API_KEY))
+            .set_json(json!({ "url": stand_in.url(&path), "event_types": ["item.broken"] }))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), 201);
+        let webhook: Value = test::read_body_json(response).await;
+        let webhook_uuid = webhook["uuid"].as_str().unwrap();
+        let secret = webhook["secret"].as_str().unwrap();
+
+        let request = test::TestRequest::post()
+            .uri(&format!("/webhooks/{}/test", webhook_uuid))
+            .insert_header(("X-API-KEY", USER_API_KEY))
+            .to_request();
+        assert_eq!(test::call_service(&app, request).await.status(), 401);
+        assert!(stand_in.requests(&path).is_empty());
+
+        let request = test::TestRequest::post()
+            .uri(&format!("/webhooks/{}/test", webhook_uuid))
+            .insert_header(("X-API-KEY", ADMIN_API_KEY))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), 200);
+        let delivery: Value = test::read_body_json(response).await;
+        assert_eq!(delivery["status"], "succeeded");
+        assert_eq!(delivery["event_type"], "webhook.test");
+        let requests = stand_in.requests(&path);
+        assert_eq!(requests.len(), 1);
+        let body = String::from_utf8(requests[0].body.clone()).unwrap();
+        assert_eq!(
+            requests[0].headers.get("x-entmt-signature"),
+            Some(&format!("sha256={}", sign(secret, &body)))
+        );
+
+        let request = test::TestRequest::get()
+            .uri(&format!("/webhooks/{}/deliveries", webhook_uuid))
+            .insert_header(("X-API-KEY", ADMIN_API_KEY))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), 200);
+        let deliveries: Value = test::read_body_json(response).await;
+        assert_eq!(deliveries["total"], 1);
+        assert_eq!(deliveries["data"][0]["uuid"], delivery["uuid"]);
+        assert_eq!(deliveries["data"][0]["attempts"], 1);
+        assert_eq!(deliveries["data"][0]["response_status"], 200);
+    }
+}
@@ -0,0 +1,27 @@
+use futures_util::future::LocalBoxFuture;
+use serde_json::Value;
+
+use crate::common::database::DbPool;
+use crate::features::jobs::registry::JobHandler;
+use crate::features::webhooks::services::Service;
+
+pub const DELIVER_JOB: &str = "webhooks.deliver";
+
+/// Payload `{"delivery_uuid": ".."}`
+pub struct DeliverJob;
+
+impl JobHandler for DeliverJob {
+    fn job_type(&self) -> &'static str {
+        DELIVER_JOB
+    }
+
+    fn run(&self, pool: DbPool, payload: Value) -> LocalBoxFuture<'static, Result<(), String>> {
+        Box::pin(async move {
+            let delivery_uuid = payload["delivery_uuid"]
+                .as_str()
+                .ok_or_else(|| String::from("Missing delivery_uuid in payload"))?
+                .to_string();
+            Service::deliver(&pool, delivery_uuid).await
+        })
+    }
+}
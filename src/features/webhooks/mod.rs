@@ -0,0 +1,29 @@
+use actix_web::web;
+
+use crate::features::jobs::registry::JobRegistry;
+use crate::features::webhooks::controllers::*;
+use crate::features::webhooks::jobs::DeliverJob;
+
+pub mod controllers;
+pub mod jobs;
+pub mod models;
+pub mod repository;
+pub mod services;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(create_webhook);
+    cfg.service(get_webhooks);
+
+    cfg.service(
+        web::scope("/webhooks")
+            .service(get_webhook)
+            .service(update_webhook)
+            .service(delete_webhook)
+            .service(get_webhook_deliveries)
+            .service(test_webhook),
+    );
+}
+
+pub fn register_jobs(registry: &mut JobRegistry) {
+    registry.register(DeliverJob);
+}
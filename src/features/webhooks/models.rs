@@ -0,0 +1,308 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::NaiveDateTime;
+use diesel::{Insertable, Queryable, Selectable};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::common::ne_parse::NEParse;
+use crate::schema::{webhook_deliveries, webhooks};
+
+/// Most webhook deliveries returned in one page
+pub const MAX_WEBHOOK_DELIVERIES_LIMIT: i64 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookEvent {
+    AlbumCreated,
+    AlbumUpdated,
+    AlbumDeleted,
+    EpisodePublished,
+    ContentAdded,
+    ItemBroken,
+    /// Sent by the test-fire endpoint only, cannot be subscribed to
+    Test,
+}
+
+impl WebhookEvent {
+    pub const SUBSCRIBABLE: [WebhookEvent; 6] = [
+        WebhookEvent::AlbumCreated,
+        WebhookEvent::AlbumUpdated,
+        WebhookEvent::AlbumDeleted,
+        WebhookEvent::EpisodePublished,
+        WebhookEvent::ContentAdded,
+        WebhookEvent::ItemBroken,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::AlbumCreated => "album.created",
+            WebhookEvent::AlbumUpdated => "album.updated",
+            WebhookEvent::AlbumDeleted => "album.deleted",
+            WebhookEvent::EpisodePublished => "episode.published",
+            WebhookEvent::ContentAdded => "content.added",
+            WebhookEvent::ItemBroken => "item.broken",
+            WebhookEvent::Test => "webhook.test",
+        }
+    }
+
+    /// Event sent for an audited admin mutation
+    pub fn from_mutation(action: &str, entity_type: &str) -> Option<Self> {
+        match (entity_type, action) {
+            ("album", "create") => Some(WebhookEvent::AlbumCreated),
+            ("album", "update") => Some(WebhookEvent::AlbumUpdated),
+            ("album", "delete") => Some(WebhookEvent::AlbumDeleted),
+            ("episode", "create") => Some(WebhookEvent::EpisodePublished),
+            ("content", "create") => Some(WebhookEvent::ContentAdded),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for WebhookEvent {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        WebhookEvent::SUBSCRIBABLE
+            .into_iter()
+            .find(|event| event.as_str() == value)
+            .ok_or_else(|| {
+                format!(
+                    "Invalid event type {:?}, expected one of {}",
+                    value,
+                    WebhookEvent::SUBSCRIBABLE
+                        .map(|event| event.as_str())
+                        .join(", ")
+                )
+            })
+    }
+}
+
+#[derive(Debug, Queryable, Selectable, Clone, PartialEq, Eq)]
+#[diesel(table_name = webhooks)]
+pub struct Webhook {
+    pub id: Option<i32>,
+    pub uuid: String,
+    pub url: String,
+    pub secret: String,
+    /// Comma separated event types
+    pub event_types: String,
+    pub description: Option<String>,
+    pub enabled: bool,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+impl Webhook {
+    pub fn subscribes_to(&self, event: WebhookEvent) -> bool {
+        self.event_types
+            .split(',')
+            .any(|event_type| event_type == event.as_str())
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = webhooks)]
+pub struct NewWebhook {
+    pub uuid: String,
+    pub url: String,
+    pub secret: String,
+    pub event_types: String,
+    pub description: Option<String>,
+    pub enabled: bool,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, PartialEq, Eq)]
+pub struct WebhookResponse {
+    pub uuid: String,
+    pub url: String,
+    pub event_types: Vec<String>,
+    pub description: Option<String>,
+    pub enabled: bool,
+    /// HMAC-SHA256 key of `X-Entmt-Signature`, only returned when it is created or rotated
+    pub secret: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+impl WebhookResponse {
+    pub fn from_webhook(webhook: Webhook) -> Self {
+        WebhookResponse {
+            uuid: webhook.uuid,
+            url: webhook.url,
+            event_types: webhook
+                .event_types
+                .split(',')
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+                .collect::<Vec<String>>(),
+            description: webhook.description,
+            enabled: webhook.enabled,
+            secret: None,
+            created_at: NEParse::opt_naive_datetime_to_utc_opt_string(webhook.created_at),
+            updated_at: NEParse::opt_naive_datetime_to_utc_opt_string(webhook.updated_at),
+        }
+    }
+
+    pub fn with_secret(webhook: Webhook) -> Self {
+        let secret = webhook.secret.clone();
+        WebhookResponse {
+            secret: Some(secret),
+            ..Self::from_webhook(webhook)
+        }
+    }
+
+    pub fn from_webhooks(webhooks: Vec<Webhook>) -> Vec<Self> {
+        webhooks.into_iter().map(Self::from_webhook).collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
+pub struct CreateWebhookRequest {
+    /// http or https endpoint receiving `POST` deliveries
+    pub url: String,
+    /// album.created, album.updated, album.deleted, episode.published, content.added, item.broken
+    pub event_types: Vec<String>,
+    pub description: Option<String>,
+    /// Generated when empty
+    pub secret: Option<String>,
+    /// Default true
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
+pub struct UpdateWebhookRequest {
+    pub url: Option<String>,
+    pub event_types: Option<Vec<String>>,
+    pub description: Option<String>,
+    pub enabled: Option<bool>,
+    /// Generate a new secret, returned in the response
+    pub rotate_secret: Option<bool>,
+}
+
+#[derive(Debug, Queryable, Selectable, Clone, PartialEq, Eq)]
+#[diesel(table_name = webhook_deliveries)]
+pub struct WebhookDelivery {
+    pub id: Option<i32>,
+    pub uuid: String,
+    pub webhook_id: i32,
+    pub event_type: String,
+    /// Body exactly as signed and sent, retries send the same bytes
+    pub payload: String,
+    pub status: String,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub response_body: Option<String>,
+    pub error: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+    pub delivered_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = webhook_deliveries)]
+pub struct NewWebhookDelivery {
+    pub uuid: String,
+    pub webhook_id: i32,
+    pub event_type: String,
+    pub payload: String,
+    pub status: String,
+    pub attempts: i32,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, PartialEq)]
+pub struct WebhookDeliveryResponse {
+    pub uuid: String,
+    pub event_type: String,
+    #[schema(value_type = Object)]
+    pub payload: Value,
+    /// pending, succeeded or failed, failed deliveries are retried by the job workers
+    pub status: String,
+    pub attempts: i32,
+    /// HTTP status of the last attempt
+    pub response_status: Option<i32>,
+    /// Start of the response body of the last attempt
+    pub response_body: Option<String>,
+    /// Connection error or non-2xx status of the last attempt
+    pub error: Option<String>,
+    pub created_at: Option<String>,
+    pub delivered_at: Option<String>,
+}
+
+impl WebhookDeliveryResponse {
+    pub fn from_delivery(delivery: WebhookDelivery) -> Self {
+        WebhookDeliveryResponse {
+            uuid: delivery.uuid,
+            event_type: delivery.event_type,
+            payload: serde_json::from_str(&delivery.payload).unwrap_or(Value::Null),
+            status: delivery.status,
+            attempts: delivery.attempts,
+            response_status: delivery.response_status,
+            response_body: delivery.response_body,
+            error: delivery.error,
+            created_at: NEParse::opt_naive_datetime_to_utc_opt_string(delivery.created_at),
+            delivered_at: NEParse::opt_naive_datetime_to_utc_opt_string(delivery.delivered_at),
+        }
+    }
+
+    pub fn from_deliveries(deliveries: Vec<WebhookDelivery>) -> Vec<Self> {
+        deliveries.into_iter().map(Self::from_delivery).collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GetWebhookDeliveriesRequest {
+    /// Filter status (pending, succeeded, failed)
+    pub status: Option<String>,
+    /// Filter event type (Exact Value)
+    pub event_type: Option<String>,
+    /// Filter offset
+    pub offset: Option<i64>,
+    /// Filter limit, 1 to 500, default 50
+    pub limit: Option<i64>,
+}
+
+impl GetWebhookDeliveriesRequest {
+    /// Requested offset, never negative
+    pub fn offset(&self) -> i64 {
+        self.offset.unwrap_or(0).max(0)
+    }
+
+    /// Requested limit kept within 1 to [`MAX_WEBHOOK_DELIVERIES_LIMIT`], SQLite reads a negative
+    /// limit as no limit
+    pub fn limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(50)
+            .clamp(1, MAX_WEBHOOK_DELIVERIES_LIMIT)
+    }
+}
+
+#[derive(Debug)]
+pub enum WebhookError {
+    NotFound,
+    Invalid(String),
+    Database(diesel::result::Error),
+}
+
+impl fmt::Display for WebhookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WebhookError::NotFound => write!(f, "Webhook not found"),
+            WebhookError::Invalid(message) => write!(f, "{}", message),
+            WebhookError::Database(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<diesel::result::Error> for WebhookError {
+    fn from(e: diesel::result::Error) -> Self {
+        match e {
+            diesel::result::Error::NotFound => WebhookError::NotFound,
+            e => WebhookError::Database(e),
+        }
+    }
+}
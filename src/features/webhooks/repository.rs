@@ -0,0 +1,182 @@
+use chrono::{NaiveDateTime, Utc};
+use diesel::dsl::count_star;
+use diesel::prelude::*;
+
+use crate::common::database::DbPool;
+use crate::features::webhooks::models::{
+    GetWebhookDeliveriesRequest, NewWebhook, NewWebhookDelivery, Webhook, WebhookDelivery,
+};
+use crate::schema::{webhook_deliveries, webhooks};
+
+pub struct Repository;
+
+impl Repository {
+    pub async fn create_webhook(pool: &DbPool, new_webhook: NewWebhook) -> QueryResult<Webhook> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        let webhook_uuid = new_webhook.uuid.clone();
+        diesel::insert_into(webhooks::table)
+            .values(&new_webhook)
+            .execute(&mut conn)?;
+        webhooks::table
+            .filter(webhooks::uuid.eq(webhook_uuid))
+            .select(Webhook::as_select())
+            .first(&mut conn)
+    }
+
+    pub async fn get_webhooks(pool: &DbPool) -> QueryResult<Vec<Webhook>> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        webhooks::table
+            .order(webhooks::id.asc())
+            .select(Webhook::as_select())
+            .load(&mut conn)
+    }
+
+    pub async fn get_enabled_webhooks(pool: &DbPool) -> QueryResult<Vec<Webhook>> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        webhooks::table
+            .filter(webhooks::enabled.eq(true))
+            .select(Webhook::as_select())
+            .load(&mut conn)
+    }
+
+    pub async fn get_webhook_by_uuid(pool: &DbPool, webhook_uuid: String) -> QueryResult<Webhook> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        webhooks::table
+            .filter(webhooks::uuid.eq(webhook_uuid))
+            .select(Webhook::as_select())
+            .first(&mut conn)
+    }
+
+    pub async fn get_webhook_by_id(pool: &DbPool, webhook_id: i32) -> QueryResult<Webhook> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        webhooks::table
+            .filter(webhooks::id.eq(webhook_id))
+            .select(Webhook::as_select())
+            .first(&mut conn)
+    }
+
+    pub async fn update_webhook(pool: &DbPool, webhook: Webhook) -> QueryResult<Webhook> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        diesel::update(webhooks::table.filter(webhooks::id.eq(webhook.id)))
+            .set((
+                webhooks::url.eq(&webhook.url),
+                webhooks::secret.eq(&webhook.secret),
+                webhooks::event_types.eq(&webhook.event_types),
+                webhooks::description.eq(&webhook.description),
+                webhooks::enabled.eq(webhook.enabled),
+                webhooks::updated_at.eq(Some(Utc::now().naive_utc())),
+            ))
+            .execute(&mut conn)?;
+        webhooks::table
+            .filter(webhooks::id.eq(webhook.id))
+            .select(Webhook::as_select())
+            .first(&mut conn)
+    }
+
+    /// Delete the webhook and its delivery log
+    pub async fn delete_webhook(pool: &DbPool, webhook_uuid: String) -> QueryResult<usize> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        conn.transaction(|conn| {
+            let webhook_id = webhooks::table
+                .filter(webhooks::uuid.eq(webhook_uuid))
+                .select(webhooks::id)
+                .first::<Option<i32>>(conn)
+                .optional()?
+                .flatten();
+            let Some(webhook_id) = webhook_id else {
+                return Ok(0);
+            };
+            diesel::delete(
+                webhook_deliveries::table.filter(webhook_deliveries::webhook_id.eq(webhook_id)),
+            )
+            .execute(conn)?;
+            diesel::delete(webhooks::table.filter(webhooks::id.eq(webhook_id))).execute(conn)
+        })
+    }
+
+    pub async fn create_delivery(
+        pool: &DbPool,
+        new_delivery: NewWebhookDelivery,
+    ) -> QueryResult<WebhookDelivery> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        let delivery_uuid = new_delivery.uuid.clone();
+        diesel::insert_into(webhook_deliveries::table)
+            .values(&new_delivery)
+            .execute(&mut conn)?;
+        webhook_deliveries::table
+            .filter(webhook_deliveries::uuid.eq(delivery_uuid))
+            .select(WebhookDelivery::as_select())
+            .first(&mut conn)
+    }
+
+    pub async fn get_delivery_by_uuid(
+        pool: &DbPool,
+        delivery_uuid: String,
+    ) -> QueryResult<WebhookDelivery> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        webhook_deliveries::table
+            .filter(webhook_deliveries::uuid.eq(delivery_uuid))
+            .select(WebhookDelivery::as_select())
+            .first(&mut conn)
+    }
+
+    pub async fn get_deliveries(
+        pool: &DbPool,
+        webhook_id: i32,
+        filters: GetWebhookDeliveriesRequest,
+    ) -> QueryResult<(Vec<WebhookDelivery>, i64)> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        let (offset, limit) = (filters.offset(), filters.limit());
+        let mut query = webhook_deliveries::table
+            .filter(webhook_deliveries::webhook_id.eq(webhook_id))
+            .into_boxed();
+        let mut count_query = webhook_deliveries::table
+            .filter(webhook_deliveries::webhook_id.eq(webhook_id))
+            .into_boxed();
+
+        if let Some(_status) = filters.status.filter(|s| !s.is_empty()) {
+            query = query.filter(webhook_deliveries::status.eq(_status.clone()));
+            count_query = count_query.filter(webhook_deliveries::status.eq(_status));
+        }
+        if let Some(_event_type) = filters.event_type.filter(|s| !s.is_empty()) {
+            query = query.filter(webhook_deliveries::event_type.eq(_event_type.clone()));
+            count_query = count_query.filter(webhook_deliveries::event_type.eq(_event_type));
+        }
+
+        let total = count_query.select(count_star()).first::<i64>(&mut conn)?;
+        let results = query
+            .order(webhook_deliveries::id.desc())
+            .offset(offset)
+            .limit(limit)
+            .select(WebhookDelivery::as_select())
+            .load(&mut conn)?;
+        Ok((results, total))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn record_attempt(
+        pool: &DbPool,
+        delivery_id: Option<i32>,
+        status: &str,
+        response_status: Option<i32>,
+        response_body: Option<String>,
+        error: Option<String>,
+        delivered_at: Option<NaiveDateTime>,
+    ) -> QueryResult<WebhookDelivery> {
+        let mut conn = pool.get().expect("Failed to get DB connection");
+        diesel::update(webhook_deliveries::table.filter(webhook_deliveries::id.eq(delivery_id)))
+            .set((
+                webhook_deliveries::status.eq(status),
+                webhook_deliveries::attempts.eq(webhook_deliveries::attempts + 1),
+                webhook_deliveries::response_status.eq(response_status),
+                webhook_deliveries::response_body.eq(response_body),
+                webhook_deliveries::error.eq(error),
+                webhook_deliveries::delivered_at.eq(delivered_at),
+            ))
+            .execute(&mut conn)?;
+        webhook_deliveries::table
+            .filter(webhook_deliveries::id.eq(delivery_id))
+            .select(WebhookDelivery::as_select())
+            .first(&mut conn)
+    }
+}
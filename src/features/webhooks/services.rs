@@ -0,0 +1,542 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::common::config;
+use crate::common::database::DbPool;
+use crate::common::models::response_data::ResponseData;
+use crate::common::ne_parse::NEParse;
+use crate::features::jobs;
+use crate::features::webhooks::jobs::DELIVER_JOB;
+use crate::features::webhooks::models::{
+    CreateWebhookRequest, GetWebhookDeliveriesRequest, NewWebhook, NewWebhookDelivery,
+    UpdateWebhookRequest, Webhook, WebhookDelivery, WebhookDeliveryResponse, WebhookError,
+    WebhookEvent, WebhookResponse,
+};
+use crate::features::webhooks::repository::Repository;
+
+pub const SIGNATURE_HEADER: &str = "X-Entmt-Signature";
+pub const EVENT_HEADER: &str = "X-Entmt-Event";
+pub const DELIVERY_HEADER: &str = "X-Entmt-Delivery";
+
+pub struct Service;
+
+impl Service {
+    pub async fn create_webhook(
+        pool: &DbPool,
+        req: CreateWebhookRequest,
+    ) -> Result<WebhookResponse, WebhookError> {
+        let now = Utc::now().naive_utc();
+        let new_webhook = NewWebhook {
+            uuid: Uuid::new_v4().to_string(),
+            url: validate_url(&req.url)?,
+            secret: req
+                .secret
+                .filter(|secret| !secret.trim().is_empty())
+                .unwrap_or_else(generate_secret),
+            event_types: validate_event_types(&req.event_types)?,
+            description: req.description,
+            enabled: req.enabled.unwrap_or(true),
+            created_at: Some(now),
+            updated_at: Some(now),
+        };
+        let webhook = Repository::create_webhook(pool, new_webhook).await?;
+        Ok(WebhookResponse::with_secret(webhook))
+    }
+
+    pub async fn get_webhooks(
+        pool: &DbPool,
+    ) -> Result<ResponseData<WebhookResponse>, WebhookError> {
+        let webhooks = Repository::get_webhooks(pool).await?;
+        let total = webhooks.len() as i64;
+        Ok(ResponseData::new(
+            WebhookResponse::from_webhooks(webhooks),
+            total,
+            0,
+        ))
+    }
+
+    pub async fn get_webhook(
+        pool: &DbPool,
+        webhook_uuid: String,
+    ) -> Result<WebhookResponse, WebhookError> {
+        let webhook = Repository::get_webhook_by_uuid(pool, webhook_uuid).await?;
+        Ok(WebhookResponse::from_webhook(webhook))
+    }
+
+    pub async fn update_webhook(
+        pool: &DbPool,
+        webhook_uuid: String,
+        req: UpdateWebhookRequest,
+    ) -> Result<WebhookResponse, WebhookError> {
+        let mut webhook = Repository::get_webhook_by_uuid(pool, webhook_uuid).await?;
+        if let Some(url) = req.url {
+            webhook.url = validate_url(&url)?;
+        }
+        if let Some(event_types) = req.event_types {
+            webhook.event_types = validate_event_types(&event_types)?;
+        }
+        if req.description.is_some() {
+            webhook.description = req.description;
+        }
+        if let Some(enabled) = req.enabled {
+            webhook.enabled = enabled;
+        }
+        let rotate_secret = req.rotate_secret.unwrap_or(false);
+        if rotate_secret {
+            webhook.secret = generate_secret();
+        }
+        let webhook = Repository::update_webhook(pool, webhook).await?;
+        Ok(if rotate_secret {
+            WebhookResponse::with_secret(webhook)
+        } else {
+            WebhookResponse::from_webhook(webhook)
+        })
+    }
+
+    pub async fn delete_webhook(pool: &DbPool, webhook_uuid: String) -> Result<(), WebhookError> {
+        match Repository::delete_webhook(pool, webhook_uuid).await? {
+            0 => Err(WebhookError::NotFound),
+            _ => Ok(()),
+        }
+    }
+
+    pub async fn get_deliveries(
+        pool: &DbPool,
+        webhook_uuid: String,
+        filters: GetWebhookDeliveriesRequest,
+    ) -> Result<ResponseData<WebhookDeliveryResponse>, WebhookError> {
+        let webhook = Repository::get_webhook_by_uuid(pool, webhook_uuid).await?;
+        let offset = filters.offset();
+        let (deliveries, total) =
+            Repository::get_deliveries(pool, webhook.id.unwrap_or_default(), filters).await?;
+        Ok(ResponseData::new(
+            WebhookDeliveryResponse::from_deliveries(deliveries),
+            total,
+            offset,
+        ))
+    }
+
+    /// Send a `webhook.test` event right away and return the outcome, failures are not retried
+    pub async fn test_fire(
+        pool: &DbPool,
+        webhook_uuid: String,
+    ) -> Result<WebhookDeliveryResponse, WebhookError> {
+        let webhook = Repository::get_webhook_by_uuid(pool, webhook_uuid).await?;
+        let data = json!({ "webhook_uuid": webhook.uuid });
+        let delivery = create_delivery(pool, &webhook, WebhookEvent::Test, data).await?;
+        let delivery = send(pool, &webhook, delivery).await?;
+        Ok(WebhookDeliveryResponse::from_delivery(delivery))
+    }
+
+    /// Queue a delivery of `event` to every enabled webhook subscribed to it
+    pub async fn dispatch(pool: &DbPool, event: WebhookEvent, data: Value) {
+        let webhooks = match Repository::get_enabled_webhooks(pool).await {
+            Ok(webhooks) => webhooks,
+            Err(e) => {
+                eprintln!("Failed to load webhooks for {}: {}", event.as_str(), e);
+                return;
+            }
+        };
+        for webhook in webhooks
+            .iter()
+            .filter(|webhook| webhook.subscribes_to(event))
+        {
+            let queued = match create_delivery(pool, webhook, event, data.clone()).await {
+                Ok(delivery) => {
+                    jobs::services::Service::enqueue(
+                        pool,
+                        DELIVER_JOB,
+                        json!({ "delivery_uuid": delivery.uuid }),
+                    )
+                    .await
+                }
+                Err(e) => Err(e),
+            };
+            if let Err(e) = queued {
+                eprintln!(
+                    "Failed to queue {} for webhook {}: {}",
+                    event.as_str(),
+                    webhook.uuid,
+                    e
+                );
+            }
+        }
+    }
+
+    /// Dispatch the event matching an audited admin mutation, if any
+    pub async fn dispatch_mutation(
+        pool: &DbPool,
+        action: &str,
+        entity_type: &str,
+        entity_uuid: &str,
+        before: Option<&Value>,
+        after: Option<&Value>,
+    ) {
+        if let Some(event) = WebhookEvent::from_mutation(action, entity_type) {
+            let data = after
+                .or(before)
+                .cloned()
+                .unwrap_or_else(|| json!({ "uuid": entity_uuid }));
+            Self::dispatch(pool, event, data).await;
+        }
+    }
+
+    /// Job handler of `webhooks.deliver`, an `Err` retries the delivery with the job backoff
+    pub async fn deliver(pool: &DbPool, delivery_uuid: String) -> Result<(), String> {
+        let delivery = match Repository::get_delivery_by_uuid(pool, delivery_uuid).await {
+            Ok(delivery) => delivery,
+            Err(diesel::result::Error::NotFound) => return Ok(()),
+            Err(e) => return Err(e.to_string()),
+        };
+        let webhook = match Repository::get_webhook_by_id(pool, delivery.webhook_id).await {
+            Ok(webhook) => webhook,
+            Err(diesel::result::Error::NotFound) => return Ok(()),
+            Err(e) => return Err(e.to_string()),
+        };
+        let delivery = send(pool, &webhook, delivery)
+            .await
+            .map_err(|e| e.to_string())?;
+        match delivery.status.as_str() {
+            "succeeded" => Ok(()),
+            _ => Err(delivery
+                .error
+                .unwrap_or_else(|| String::from("Delivery failed"))),
+        }
+    }
+}
+
+/// Hex encoded HMAC-SHA256 of `body`, sent as `X-Entmt-Signature: sha256=<hex>`
+pub fn sign(secret: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+async fn create_delivery(
+    pool: &DbPool,
+    webhook: &Webhook,
+    event: WebhookEvent,
+    data: Value,
+) -> Result<WebhookDelivery, diesel::result::Error> {
+    let now = Utc::now().naive_utc();
+    let delivery_uuid = Uuid::new_v4().to_string();
+    let payload = json!({
+        "id": delivery_uuid,
+        "event": event.as_str(),
+        "created_at": NEParse::opt_naive_datetime_to_utc_opt_string(Some(now)),
+        "data": data,
+    });
+    let new_delivery = NewWebhookDelivery {
+        uuid: delivery_uuid,
+        webhook_id: webhook.id.unwrap_or_default(),
+        event_type: event.as_str().to_string(),
+        payload: payload.to_string(),
+        status: String::from("pending"),
+        attempts: 0,
+        created_at: Some(now),
+    };
+    Repository::create_delivery(pool, new_delivery).await
+}
+
+/// POST the stored payload once and record the attempt in the delivery log
+async fn send(
+    pool: &DbPool,
+    webhook: &Webhook,
+    delivery: WebhookDelivery,
+) -> Result<WebhookDelivery, diesel::result::Error> {
+    let webhooks_config = &config::get().webhooks;
+    let signature = format!("sha256={}", sign(&webhook.secret, &delivery.payload));
+    let result = match reqwest::Client::builder()
+        .timeout(Duration::from_secs(webhooks_config.timeout_seconds))
+        .build()
+    {
+        Ok(client) => client
+            .post(&webhook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(reqwest::header::USER_AGENT, "nextera-entmt-webhooks")
+            .header(EVENT_HEADER, &delivery.event_type)
+            .header(DELIVERY_HEADER, &delivery.uuid)
+            .header(SIGNATURE_HEADER, signature)
+            .body(delivery.payload.clone())
+            .send()
+            .await
+            .map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    let (status, response_status, response_body, error) = match result {
+        Ok(response) => {
+            let response_status = response.status();
+            let mut body = response.text().await.unwrap_or_default();
+            truncate_to_char_boundary(&mut body, webhooks_config.max_response_body_bytes);
+            if response_status.is_success() {
+                (
+                    "succeeded",
+                    Some(response_status.as_u16() as i32),
+                    Some(body),
+                    None,
+                )
+            } else {
+                (
+                    "failed",
+                    Some(response_status.as_u16() as i32),
+                    Some(body),
+                    Some(format!("Endpoint responded with {}", response_status)),
+                )
+            }
+        }
+        Err(e) => ("failed", None, None, Some(e)),
+    };
+    let delivered_at = (status == "succeeded").then(|| Utc::now().naive_utc());
+    Repository::record_attempt(
+        pool,
+        delivery.id,
+        status,
+        response_status,
+        response_body,
+        error,
+        delivered_at,
+    )
+    .await
+}
+
+fn truncate_to_char_boundary(value: &mut String, max_bytes: usize) {
+    if value.len() > max_bytes {
+        let mut end = max_bytes;
+        while !value.is_char_boundary(end) {
+            end -= 1;
+        }
+        value.truncate(end);
+    }
+}
+
+fn validate_url(url: &str) -> Result<String, WebhookError> {
+    let url = url.trim();
+    match reqwest::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(url.to_string()),
+        _ => Err(WebhookError::Invalid(format!(
+            "Webhook url {:?} must be an http or https URL",
+            url
+        ))),
+    }
+}
+
+/// Parse and deduplicate event types into the comma separated column value
+fn validate_event_types(event_types: &[String]) -> Result<String, WebhookError> {
+    let mut events: Vec<&'static str> = Vec::new();
+    for event_type in event_types {
+        let event = event_type
+            .trim()
+            .parse::<WebhookEvent>()
+            .map_err(WebhookError::Invalid)?;
+        if !events.contains(&event.as_str()) {
+            events.push(event.as_str());
+        }
+    }
+    if events.is_empty() {
+        return Err(WebhookError::Invalid(String::from(
+            "Webhook needs at least one event type",
+        )));
+    }
+    Ok(events.join(","))
+}
+
+fn generate_secret() -> String {
+    format!("whsec_{}", Uuid::new_v4().simple())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDateTime;
+    use diesel::prelude::*;
+
+    use super::*;
+    use crate::common::test_support;
+    use crate::features::jobs::registry::JobRegistry;
+    use crate::features::jobs::services::run_next_job;
+    use crate::schema::{jobs as jobs_table, webhook_deliveries};
+
+    async fn create_webhook(pool: &DbPool, path: &str) -> WebhookResponse {
+        Service::create_webhook(
+            pool,
+            CreateWebhookRequest {
+                url: test_support::stand_in().url(path),
+                event_types: vec![String::from("item.broken")],
+                description: None,
+                secret: Some(String::from("whsec_test")),
+                enabled: None,
+            },
+        )
+        .await
+        .unwrap()
+    }
+
+    fn deliveries(pool: &DbPool) -> Vec<WebhookDelivery> {
+        webhook_deliveries::table
+            .select(WebhookDelivery::as_select())
+            .load(&mut pool.get().unwrap())
+            .unwrap()
+    }
+
+    fn job(pool: &DbPool) -> (String, i32, Option<String>, NaiveDateTime) {
+        jobs_table::table
+            .filter(jobs_table::job_type.eq(DELIVER_JOB))
+            .select((
+                jobs_table::status,
+                jobs_table::attempts,
+                jobs_table::last_error,
+                jobs_table::run_at,
+            ))
+            .first(&mut pool.get().unwrap())
+            .unwrap()
+    }
+
+    /// Make the pending retry due now instead of waiting for the backoff
+    fn make_job_due(pool: &DbPool) {
+        diesel::update(jobs_table::table)
+            .set(jobs_table::run_at.eq(Utc::now().naive_utc()))
+            .execute(&mut pool.get().unwrap())
+            .unwrap();
+    }
+
+    fn seconds_until(run_at: NaiveDateTime) -> i64 {
+        (run_at - Utc::now().naive_utc()).num_seconds()
+    }
+
+    fn registry() -> JobRegistry {
+        let mut registry = JobRegistry::default();
+        crate::features::webhooks::register_jobs(&mut registry);
+        registry
+    }
+
+    #[test]
+    fn sign_is_hex_hmac_sha256() {
+        assert_eq!(
+            sign("key", "The quick brown fox jumps over the lazy dog"),
+            "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    #[actix_web::test]
+    async fn signature_header_is_the_hmac_of_the_exact_body() {
+        let pool = test_support::test_pool();
+        let path = format!("/{}/hook", Uuid::new_v4());
+        let webhook = create_webhook(&pool, &path).await;
+
+        let delivery = Service::test_fire(&pool, webhook.uuid).await.unwrap();
+        let requests = test_support::stand_in().requests(&path);
+        assert_eq!(requests.len(), 1);
+        let request = &requests[0];
+        let body = String::from_utf8(request.body.clone()).unwrap();
+        assert_eq!(
+            request.headers.get("x-entmt-signature"),
+            Some(&format!("sha256={}", sign("whsec_test", &body)))
+        );
+        assert_eq!(body, deliveries(&pool)[0].payload);
+        assert_eq!(
+            request.headers.get("x-entmt-event").map(String::as_str),
+            Some("webhook.test")
+        );
+        assert_eq!(
+            request.headers.get("x-entmt-delivery"),
+            Some(&delivery.uuid)
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_fire_sends_right_away_without_queueing_a_job() {
+        let pool = test_support::test_pool();
+        let stand_in = test_support::stand_in();
+        let path = format!("/{}/hook", Uuid::new_v4());
+        let webhook = create_webhook(&pool, &path).await;
+
+        let delivery = Service::test_fire(&pool, webhook.uuid.clone())
+            .await
+            .unwrap();
+        assert_eq!(stand_in.requests(&path).len(), 1);
+        assert_eq!(delivery.status, "succeeded");
+        assert_eq!(delivery.attempts, 1);
+        assert_eq!(delivery.response_status, Some(200));
+        assert!(delivery.delivered_at.is_some());
+
+        // A failed test fire is logged but not retried
+        stand_in.set_status(&path, 500);
+        let delivery = Service::test_fire(&pool, webhook.uuid).await.unwrap();
+        assert_eq!(stand_in.requests(&path).len(), 2);
+        assert_eq!(delivery.status, "failed");
+        assert_eq!(delivery.response_status, Some(500));
+        let queued: i64 = jobs_table::table
+            .count()
+            .get_result(&mut pool.get().unwrap())
+            .unwrap();
+        assert_eq!(queued, 0);
+    }
+
+    #[actix_web::test]
+    async fn failed_delivery_is_retried_with_backoff_and_every_attempt_is_logged() {
+        let pool = test_support::test_pool();
+        let stand_in = test_support::stand_in();
+        let registry = registry();
+        let path = format!("/{}/hook", Uuid::new_v4());
+        create_webhook(&pool, &path).await;
+        stand_in.set_status(&path, 500);
+
+        Service::dispatch(&pool, WebhookEvent::ItemBroken, json!({ "uuid": "item" })).await;
+        assert!(stand_in.requests(&path).is_empty());
+        let delivery = &deliveries(&pool)[0];
+        assert_eq!(
+            (delivery.status.as_str(), delivery.attempts),
+            ("pending", 0)
+        );
+
+        // First attempt fails and is retried after `retry_backoff_seconds`
+        assert!(run_next_job(1, &pool, &registry).await.unwrap());
+        assert_eq!(stand_in.requests(&path).len(), 1);
+        let delivery = &deliveries(&pool)[0];
+        assert_eq!((delivery.status.as_str(), delivery.attempts), ("failed", 1));
+        assert_eq!(delivery.response_status, Some(500));
+        assert_eq!(
+            delivery.error.as_deref(),
+            Some("Endpoint responded with 500 Internal Server Error")
+        );
+        let (status, attempts, last_error, run_at) = job(&pool);
+        assert_eq!((status.as_str(), attempts), ("pending", 1));
+        assert_eq!(last_error, delivery.error);
+        assert!((55..=60).contains(&seconds_until(run_at)));
+        assert!(!run_next_job(1, &pool, &registry).await.unwrap());
+
+        // Second attempt fails too, the backoff doubles
+        make_job_due(&pool);
+        assert!(run_next_job(1, &pool, &registry).await.unwrap());
+        assert_eq!(stand_in.requests(&path).len(), 2);
+        assert_eq!(deliveries(&pool)[0].attempts, 2);
+        let (status, attempts, _, run_at) = job(&pool);
+        assert_eq!((status.as_str(), attempts), ("pending", 2));
+        assert!((115..=120).contains(&seconds_until(run_at)));
+
+        // Third attempt succeeds
+        stand_in.set_status(&path, 200);
+        make_job_due(&pool);
+        assert!(run_next_job(1, &pool, &registry).await.unwrap());
+        let requests = stand_in.requests(&path);
+        assert_eq!(requests.len(), 3);
+        let delivery = &deliveries(&pool)[0];
+        assert_eq!(
+            (delivery.status.as_str(), delivery.attempts),
+            ("succeeded", 3)
+        );
+        assert_eq!(delivery.response_status, Some(200));
+        assert_eq!(delivery.error, None);
+        assert!(delivery.delivered_at.is_some());
+        assert_eq!(job(&pool).0, "succeeded");
+        // Every attempt sends the same signed payload
+        for request in &requests {
+            assert_eq!(request.body, delivery.payload.as_bytes());
+        }
+    }
+}
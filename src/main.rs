@@ -1,45 +1,87 @@
 extern crate core;
 
-use std::env;
+use std::io;
+use std::time::Duration;
 
-use crate::common::middleware::response_time_middleware::ResponseTime;
-use actix_cors::Cors;
-use actix_files::Files;
+use nextera_entmt::common::config::Config;
+use nextera_entmt::common::middleware::cors_middleware::CorsPolicies;
+use nextera_entmt::common::middleware::rate_limit_middleware::{RateLimitMiddleware, RateLimitState};
+use nextera_entmt::common::middleware::response_time_middleware::ResponseTime;
+use nextera_entmt::common::tmp_dir;
+use nextera_entmt::features::integrity::jobs::SCAN_JOB;
+use nextera_entmt::features::jobs::registry::JobRegistry;
+use nextera_entmt::features::jobs::services::Service as JobService;
+use nextera_entmt::features::maintenance::jobs::{PRUNE_JOBS_JOB, PURGE_TMP_JOB};
+use nextera_entmt::features::trash::jobs::PURGE_EXPIRED_JOB;
+use nextera_entmt::{common, features};
 use actix_web::{middleware, middleware::Logger, web, App, HttpServer};
 use actix_web_lab::middleware::CatchPanic;
 use dotenvy::dotenv;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
-use crate::common::utils::{notfound_404};
-
-mod common;
-mod features;
-mod schema;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     dotenv().ok();
 
+    // ! Configuration
+    let config = Config::load().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    common::config::init(config.clone());
+
+    // ! Storage
+    tmp_dir::prepare_storage_directories()?;
+    match tmp_dir::sweep_tmp_directory(Duration::ZERO) {
+        Ok(removed) if removed > 0 => println!("Removed {} leftover tmp entries", removed),
+        Ok(_) => {}
+        Err(e) => eprintln!("Failed to sweep tmp directory: {}", e),
+    }
+
     // ! Database
-    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-    let connection = common::database::sqlite_connection::connect(database_url);
+    let connection = common::database::sqlite_connection::connect(config.database.url.clone());
 
     // ! Swagger
     let openapi = features::ApiDoc::openapi();
 
     // ! API Keys
-    let admin_key = env::var("ADMIN_API_KEY").expect("Admin API Key must be set");
-    let user_key = env::var("USER_API_KEY").expect("Admin API Key must be set");
-    let public_routes = vec![String::from("/health")];
+    let admin_key = config.auth.admin_api_key.clone();
+    let user_key = config.auth.user_api_key.clone();
+    let public_routes = config.auth.public_routes.clone();
+    let cors_config = config.cors.clone();
+    let rate_limit_state = RateLimitState::default();
+    let rate_limit_config = config.rate_limit.clone();
+    let auth_config = config.auth.clone();
+    let json_limit = config.upload.max_json_bytes;
+    let (host, port) = config.bind_address();
+
+    // ! Background jobs
+    let mut job_registry = JobRegistry::default();
+    features::register_jobs(&mut job_registry);
+    JobService::start_workers(connection.clone(), job_registry).await;
+    JobService::schedule(connection.clone(), PURGE_EXPIRED_JOB, Duration::from_secs(60 * 60), true);
+    JobService::schedule(connection.clone(), PRUNE_JOBS_JOB, Duration::from_secs(60 * 60), true);
+    if config.storage.tmp_sweep_interval_minutes > 0 {
+        // The startup sweep already ran
+        JobService::schedule(
+            connection.clone(),
+            PURGE_TMP_JOB,
+            Duration::from_secs(config.storage.tmp_sweep_interval_minutes * 60),
+            false,
+        );
+    }
+    if config.integrity.scan_interval_minutes > 0 {
+        JobService::schedule(
+            connection.clone(),
+            SCAN_JOB,
+            Duration::from_secs(config.integrity.scan_interval_minutes * 60),
+            true,
+        );
+    }
+
     // Log that the API is starting
-    println!("📔API Documentation can be found at ➡️ http://localhost:8010/swagger/index.html");
-    HttpServer::new(move || {
+    println!("📔API Documentation can be found at ➡️ http://{}:{}/swagger/index.html", host, port);
+    let mut server = HttpServer::new(move || {
         // ! Configure CORS
-        let cors = Cors::default()
-            .allow_any_origin()
-            .allow_any_method()
-            .allow_any_header()
-            .supports_credentials();
+        let cors = CorsPolicies::new(&cors_config);
 
         App::new()
             .wrap(ResponseTime)
@@ -50,17 +92,23 @@ async fn main() -> std::io::Result<()> {
                     public_routes.clone(),
                 ),
             )
+            .wrap(RateLimitMiddleware::new(
+                rate_limit_state.clone(),
+                rate_limit_config.clone(),
+                auth_config.clone(),
+            ))
             .wrap(cors)
             .app_data(web::Data::new(connection.clone()))
+            .app_data(web::JsonConfig::default().limit(json_limit))
             .configure(features::config_routes)
             .wrap(CatchPanic::default())
             .wrap(Logger::default())
             .service(
                 SwaggerUi::new("/swagger/{_:.*}").url("/api-docs/openapi.json", openapi.clone()),
             )
-            .service(Files::new("/static", ".").default_handler(web::to(notfound_404)) )
-    })
-    .bind(("127.0.0.1", 8010))?
-    .run()
-    .await
+    });
+    if config.server.workers > 0 {
+        server = server.workers(config.server.workers);
+    }
+    server.bind((host, port))?.run().await
 }
@@ -1,5 +1,57 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    album_categories (album_id, category_id) {
+        album_id -> Integer,
+        category_id -> Integer,
+        position -> Integer,
+    }
+}
+
+diesel::table! {
+    album_credits (album_id, creator_id, role) {
+        album_id -> Integer,
+        creator_id -> Integer,
+        role -> Text,
+        position -> Integer,
+    }
+}
+
+diesel::table! {
+    album_daily_views (album_id, day) {
+        album_id -> Integer,
+        day -> Text,
+        views -> Integer,
+        reads -> Integer,
+    }
+}
+
+diesel::table! {
+    album_reviews (id) {
+        id -> Nullable<Integer>,
+        uuid -> Text,
+        album_id -> Integer,
+        reader_profile_id -> Integer,
+        rating -> Integer,
+        body -> Text,
+        status -> Text,
+        moderated_at -> Nullable<Timestamp>,
+        created_at -> Nullable<Timestamp>,
+        updated_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    album_translations (album_id, locale) {
+        album_id -> Integer,
+        locale -> Text,
+        title -> Text,
+        description -> Text,
+        created_at -> Nullable<Timestamp>,
+        updated_at -> Nullable<Timestamp>,
+    }
+}
+
 diesel::table! {
     albums (id) {
         id -> Integer,
@@ -21,6 +73,22 @@ diesel::table! {
         broken_at -> Nullable<Timestamp>,
         created_at -> Nullable<Timestamp>,
         updated_at -> Nullable<Timestamp>,
+        deleted_at -> Nullable<Timestamp>,
+        rating_average -> Double,
+        rating_count -> Integer,
+    }
+}
+
+diesel::table! {
+    audit_log (id) {
+        id -> Nullable<Integer>,
+        actor -> Text,
+        action -> Text,
+        entity_type -> Text,
+        entity_uuid -> Text,
+        changes -> Nullable<Text>,
+        ip -> Nullable<Text>,
+        created_at -> Nullable<Timestamp>,
     }
 }
 
@@ -28,6 +96,12 @@ diesel::table! {
     category (id) {
         id -> Nullable<Integer>,
         name -> Text,
+        parent_id -> Nullable<Integer>,
+        slug -> Text,
+        description -> Text,
+        icon_url -> Nullable<Text>,
+        icon_content_type -> Nullable<Text>,
+        sort_order -> Integer,
     }
 }
 
@@ -46,6 +120,40 @@ diesel::table! {
         broken_at -> Nullable<Timestamp>,
         created_at -> Nullable<Timestamp>,
         updated_at -> Nullable<Timestamp>,
+        deleted_at -> Nullable<Timestamp>,
+        locale -> Text,
+    }
+}
+
+diesel::table! {
+    creators (id) {
+        id -> Nullable<Integer>,
+        uuid -> Text,
+        name -> Text,
+        aliases -> Text,
+        bio -> Text,
+        avatar_url -> Nullable<Text>,
+        avatar_content_type -> Nullable<Text>,
+        created_at -> Nullable<Timestamp>,
+        updated_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    episode_daily_views (episode_id, day) {
+        episode_id -> Integer,
+        day -> Text,
+        reads -> Integer,
+    }
+}
+
+diesel::table! {
+    episode_translations (episode_id, locale) {
+        episode_id -> Integer,
+        locale -> Text,
+        title -> Text,
+        created_at -> Nullable<Timestamp>,
+        updated_at -> Nullable<Timestamp>,
     }
 }
 
@@ -64,14 +172,109 @@ diesel::table! {
         broken_at -> Nullable<Timestamp>,
         created_at -> Nullable<Timestamp>,
         updated_at -> Nullable<Timestamp>,
+        deleted_at -> Nullable<Timestamp>,
+        duration_ms -> Nullable<Integer>,
+        video_codec -> Nullable<Text>,
+        bitrate -> Nullable<Integer>,
+        poster_url -> Nullable<Text>,
+        hls_status -> Nullable<Text>,
+        hls_url -> Nullable<Text>,
+        hls_error -> Nullable<Text>,
+        file_name -> Nullable<Text>,
+        number -> Nullable<Double>,
+        volume -> Nullable<Integer>,
+        sort_order -> Integer,
+    }
+}
+
+diesel::table! {
+    jobs (id) {
+        id -> Nullable<Integer>,
+        uuid -> Text,
+        job_type -> Text,
+        payload -> Text,
+        status -> Text,
+        attempts -> Integer,
+        max_attempts -> Integer,
+        last_error -> Nullable<Text>,
+        run_at -> Timestamp,
+        started_at -> Nullable<Timestamp>,
+        finished_at -> Nullable<Timestamp>,
+        created_at -> Nullable<Timestamp>,
+        updated_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    reader_profiles (id) {
+        id -> Nullable<Integer>,
+        uuid -> Text,
+        display_name -> Text,
+        birth_date -> Nullable<Date>,
+        age_bracket -> Nullable<Text>,
+        age_verified_at -> Nullable<Timestamp>,
+        created_at -> Nullable<Timestamp>,
+        updated_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    webhook_deliveries (id) {
+        id -> Nullable<Integer>,
+        uuid -> Text,
+        webhook_id -> Integer,
+        event_type -> Text,
+        payload -> Text,
+        status -> Text,
+        attempts -> Integer,
+        response_status -> Nullable<Integer>,
+        response_body -> Nullable<Text>,
+        error -> Nullable<Text>,
+        created_at -> Nullable<Timestamp>,
+        delivered_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    webhooks (id) {
+        id -> Nullable<Integer>,
+        uuid -> Text,
+        url -> Text,
+        secret -> Text,
+        event_types -> Text,
+        description -> Nullable<Text>,
+        enabled -> Bool,
+        created_at -> Nullable<Timestamp>,
+        updated_at -> Nullable<Timestamp>,
     }
 }
 
+diesel::joinable!(album_categories -> albums (album_id));
+diesel::joinable!(album_categories -> category (category_id));
+diesel::joinable!(album_credits -> albums (album_id));
+diesel::joinable!(album_credits -> creators (creator_id));
+diesel::joinable!(album_daily_views -> albums (album_id));
+diesel::joinable!(album_reviews -> albums (album_id));
+diesel::joinable!(album_translations -> albums (album_id));
 diesel::joinable!(albums -> category (category_id));
+diesel::joinable!(webhook_deliveries -> webhooks (webhook_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    album_categories,
+    album_credits,
+    album_daily_views,
+    album_reviews,
+    album_translations,
     albums,
+    audit_log,
     category,
     contents,
+    creators,
+    episode_daily_views,
+    episode_translations,
     episodes,
+    jobs,
+    reader_profiles,
+    webhook_deliveries,
+    webhooks,
 );